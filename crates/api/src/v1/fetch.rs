@@ -1,13 +1,14 @@
 //! Types relating to the fetch API.
 
+use super::package::PackageRecord;
 use crate::Status;
 use serde::{de::Unexpected, Deserialize, Serialize, Serializer};
 use std::{borrow::Cow, collections::HashMap};
 use thiserror::Error;
 use warg_crypto::hash::AnyHash;
 use warg_protocol::{
-    registry::{LogId, RecordId, RegistryLen},
-    PublishedProtoEnvelopeBody,
+    registry::{LogId, PackageId, RecordId, RegistryLen, TimestampedCheckpoint},
+    PublishedProtoEnvelopeBody, SerdeEnvelope,
 };
 
 /// Represents a fetch logs request.
@@ -42,6 +43,79 @@ pub struct FetchLogsResponse {
     pub packages: HashMap<LogId, Vec<PublishedProtoEnvelopeBody>>,
 }
 
+/// Represents a fetch packages request.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct FetchPackagesRequest<'a> {
+    /// The prefix of the namespaces of the packages to fetch.
+    ///
+    /// If not specified, packages from all namespaces are fetched.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<Cow<'a, str>>,
+    /// The last package identifier of the previous page of packages.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<Cow<'a, PackageId>>,
+    /// The limit for the number of packages to fetch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u16>,
+}
+
+/// Represents a fetch packages response.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FetchPackagesResponse {
+    /// Whether there are more packages to fetch.
+    #[serde(default)]
+    pub more: bool,
+    /// The identifiers of the published packages, in sorted order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub packages: Vec<PackageId>,
+}
+
+/// Represents a subscribe request.
+///
+/// The request is sent as the query string of the subscribe API.
+///
+/// If a package record is specified, status changes of the record are
+/// sent until the record is either published or rejected.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SubscribeRequest {
+    /// The log of the package record to receive status changes for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_id: Option<LogId>,
+    /// The package record to receive status changes for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub record_id: Option<RecordId>,
+}
+
+/// Represents an event sent by the subscribe API.
+///
+/// Events are sent as server-sent events where the event name is
+/// given by [`SubscribeEvent::name`] and the data is the JSON
+/// serialization of the event's value.
+pub enum SubscribeEvent {
+    /// A new checkpoint was signed by the registry.
+    Checkpoint(SerdeEnvelope<TimestampedCheckpoint>),
+    /// The status of the subscribed package record changed.
+    Record(PackageRecord),
+}
+
+impl SubscribeEvent {
+    /// The name of the checkpoint event.
+    pub const CHECKPOINT: &'static str = "checkpoint";
+    /// The name of the record event.
+    pub const RECORD: &'static str = "record";
+
+    /// Gets the name of the event.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Checkpoint(_) => Self::CHECKPOINT,
+            Self::Record(_) => Self::RECORD,
+        }
+    }
+}
+
 /// Represents a fetch API error.
 #[non_exhaustive]
 #[derive(Debug, Error)]
@@ -1,6 +1,8 @@
 //! Types representing v1 of the Warg REST API.
 
 pub mod fetch;
+pub mod operator;
 pub mod package;
 pub mod paths;
 pub mod proof;
+pub mod witness;
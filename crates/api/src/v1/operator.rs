@@ -0,0 +1,162 @@
+//! Types relating to the operator API.
+
+use crate::Status;
+use serde::{de::Unexpected, Deserialize, Serialize, Serializer};
+use std::borrow::Cow;
+use thiserror::Error;
+use warg_crypto::hash::AnyHash;
+use warg_protocol::{
+    registry::{RecordId, RegistryIndex},
+    ProtoEnvelopeBody,
+};
+
+/// Represents a request to publish a record to the operator log.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishOperatorRecordRequest<'a> {
+    /// The record to add to the operator log.
+    pub record: Cow<'a, ProtoEnvelopeBody>,
+}
+
+/// Represents an operator record API entity in a registry.
+#[derive(Serialize, Deserialize)]
+pub struct OperatorRecord {
+    /// The identifier of the operator record.
+    pub id: RecordId,
+    /// The current state of the operator record.
+    pub state: OperatorRecordState,
+}
+
+/// Represents an operator record in one of the following states:
+/// * `processing` - The record is being processed.
+/// * `rejected` - The record was rejected.
+/// * `published` - The record was published to the log.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "camelCase")]
+pub enum OperatorRecordState {
+    /// The operator record is processing.
+    #[serde(rename_all = "camelCase")]
+    Processing,
+    /// The operator record is rejected.
+    #[serde(rename_all = "camelCase")]
+    Rejected {
+        /// The reason the record was rejected.
+        reason: String,
+    },
+    /// The operator record was successfully published to the log.
+    #[serde(rename_all = "camelCase")]
+    Published {
+        /// The published index of the record in the registry log.
+        registry_index: RegistryIndex,
+    },
+}
+
+/// Represents an operator API error.
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum OperatorError {
+    /// The provided record was not found.
+    #[error("record `{0}` was not found")]
+    RecordNotFound(RecordId),
+    /// The operation was not authorized by the registry.
+    #[error("unauthorized operation: {0}")]
+    Unauthorized(String),
+    /// An error with a message occurred.
+    #[error("{message}")]
+    Message {
+        /// The HTTP status code.
+        status: u16,
+        /// The error message
+        message: String,
+    },
+}
+
+impl OperatorError {
+    /// Returns the HTTP status code of the error.
+    pub fn status(&self) -> u16 {
+        match self {
+            // Note: this is 403 and not a 401 as the registry does not use
+            // HTTP authentication.
+            Self::Unauthorized(_) => 403,
+            Self::RecordNotFound(_) => 404,
+            Self::Message { status, .. } => *status,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum EntityType {
+    Record,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged, rename_all = "camelCase")]
+enum RawError<'a> {
+    Unauthorized {
+        status: Status<403>,
+        message: Cow<'a, str>,
+    },
+    NotFound {
+        status: Status<404>,
+        #[serde(rename = "type")]
+        ty: EntityType,
+        id: Cow<'a, str>,
+    },
+    Message {
+        status: u16,
+        message: Cow<'a, str>,
+    },
+}
+
+impl Serialize for OperatorError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Unauthorized(message) => RawError::Unauthorized {
+                status: Status::<403>,
+                message: Cow::Borrowed(message),
+            }
+            .serialize(serializer),
+            Self::RecordNotFound(record_id) => RawError::NotFound {
+                status: Status::<404>,
+                ty: EntityType::Record,
+                id: Cow::Owned(record_id.to_string()),
+            }
+            .serialize(serializer),
+            Self::Message { status, message } => RawError::Message {
+                status: *status,
+                message: Cow::Borrowed(message),
+            }
+            .serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for OperatorError {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        match RawError::deserialize(deserializer)? {
+            RawError::Unauthorized { status: _, message } => {
+                Ok(Self::Unauthorized(message.into_owned()))
+            }
+            RawError::NotFound { status: _, ty, id } => match ty {
+                EntityType::Record => Ok(Self::RecordNotFound(
+                    id.parse::<AnyHash>()
+                        .map_err(|_| {
+                            serde::de::Error::invalid_value(
+                                Unexpected::Str(&id),
+                                &"a valid record id",
+                            )
+                        })?
+                        .into(),
+                )),
+            },
+            RawError::Message { status, message } => Ok(Self::Message {
+                status,
+                message: message.into_owned(),
+            }),
+        }
+    }
+}
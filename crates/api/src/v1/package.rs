@@ -32,6 +32,20 @@ pub enum UploadEndpoint {
         /// The URL to POST content to.
         url: String,
     },
+    /// Content may be uploaded via HTTP PUT to the given URL.
+    ///
+    /// The request must specify the content length. Once the content is
+    /// uploaded, an empty HTTP POST must be sent to the finalize URL for
+    /// the registry to verify the content. If the finalize endpoint responds
+    /// with "201 Created" and a Location header, that header's value will be
+    /// the content source.
+    #[serde(rename_all = "camelCase")]
+    HttpPut {
+        /// The URL to PUT content to.
+        url: String,
+        /// The URL to POST to once the content has been uploaded.
+        finalize_url: String,
+    },
 }
 
 /// Information about missing content.
@@ -1,6 +1,6 @@
 //! The paths of the Warg REST API.
 
-use warg_protocol::registry::{LogId, RecordId};
+use warg_protocol::registry::{LogId, RecordId, RegistryLen};
 
 /// The path of the "fetch logs" API.
 pub fn fetch_logs() -> &'static str {
@@ -12,6 +12,21 @@ pub fn fetch_checkpoint() -> &'static str {
     "v1/fetch/checkpoint"
 }
 
+/// The path of the "fetch checkpoint" API for a specific log length.
+pub fn fetch_checkpoint_at(log_length: RegistryLen) -> String {
+    format!("v1/fetch/checkpoint/{log_length}")
+}
+
+/// The path of the "fetch packages" API.
+pub fn fetch_packages() -> &'static str {
+    "v1/fetch/packages"
+}
+
+/// The path of the "subscribe" API.
+pub fn subscribe() -> &'static str {
+    "v1/fetch/subscribe"
+}
+
 /// The path of the "publish package record" API.
 pub fn publish_package_record(log_id: &LogId) -> String {
     format!("v1/package/{log_id}/record")
@@ -22,6 +37,16 @@ pub fn package_record(log_id: &LogId, record_id: &RecordId) -> String {
     format!("v1/package/{log_id}/record/{record_id}")
 }
 
+/// The path of the "publish operator record" API.
+pub fn publish_operator_record() -> &'static str {
+    "v1/operator/record"
+}
+
+/// The path for an operator record.
+pub fn operator_record(record_id: &RecordId) -> String {
+    format!("v1/operator/record/{record_id}")
+}
+
 /// The path for proving checkpoint consistency.
 pub fn prove_consistency() -> &'static str {
     "v1/proof/consistency"
@@ -31,3 +56,18 @@ pub fn prove_consistency() -> &'static str {
 pub fn prove_inclusion() -> &'static str {
     "v1/proof/inclusion"
 }
+
+/// The path of the "publish cosignature" API.
+pub fn publish_cosignature() -> &'static str {
+    "v1/witness/cosignature"
+}
+
+/// The path of the "fetch latest cosignatures" API.
+pub fn fetch_latest_cosignatures() -> &'static str {
+    "v1/witness/cosignatures"
+}
+
+/// The path of the "fetch cosignatures" API for a specific log length.
+pub fn fetch_cosignatures(log_length: RegistryLen) -> String {
+    format!("v1/witness/cosignatures/{log_length}")
+}
@@ -0,0 +1,128 @@
+//! Types relating to the witness API.
+
+use crate::Status;
+use serde::{Deserialize, Serialize, Serializer};
+use std::borrow::Cow;
+use thiserror::Error;
+use warg_protocol::{
+    registry::{RegistryLen, WitnessedCheckpoint},
+    SerdeEnvelope,
+};
+
+/// Represents a request to publish a witness cosignature of a checkpoint.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishCosignatureRequest<'a> {
+    /// The checkpoint signed by the witness.
+    pub cosignature: Cow<'a, SerdeEnvelope<WitnessedCheckpoint>>,
+}
+
+/// Represents the witness cosignatures of a checkpoint.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CosignaturesResponse {
+    /// The cosignatures of the checkpoint, at most one per witness key.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cosignatures: Vec<SerdeEnvelope<WitnessedCheckpoint>>,
+}
+
+/// Represents a witness API error.
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum WitnessError {
+    /// The checkpoint could not be found for the provided log length.
+    #[error("checkpoint not found for log length {0}")]
+    CheckpointNotFound(RegistryLen),
+    /// The operation was not authorized by the registry.
+    #[error("unauthorized operation: {0}")]
+    Unauthorized(String),
+    /// An error with a message occurred.
+    #[error("{message}")]
+    Message {
+        /// The HTTP status code.
+        status: u16,
+        /// The error message
+        message: String,
+    },
+}
+
+impl WitnessError {
+    /// Returns the HTTP status code of the error.
+    pub fn status(&self) -> u16 {
+        match self {
+            // Note: this is 403 and not a 401 as the registry does not use
+            // HTTP authentication.
+            Self::Unauthorized(_) => 403,
+            Self::CheckpointNotFound(_) => 404,
+            Self::Message { status, .. } => *status,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum EntityType {
+    LogLength,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged, rename_all = "camelCase")]
+enum RawError<'a> {
+    Unauthorized {
+        status: Status<403>,
+        message: Cow<'a, str>,
+    },
+    NotFound {
+        status: Status<404>,
+        #[serde(rename = "type")]
+        ty: EntityType,
+        id: RegistryLen,
+    },
+    Message {
+        status: u16,
+        message: Cow<'a, str>,
+    },
+}
+
+impl Serialize for WitnessError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Unauthorized(message) => RawError::Unauthorized {
+                status: Status::<403>,
+                message: Cow::Borrowed(message),
+            }
+            .serialize(serializer),
+            Self::CheckpointNotFound(log_length) => RawError::NotFound {
+                status: Status::<404>,
+                ty: EntityType::LogLength,
+                id: *log_length,
+            }
+            .serialize(serializer),
+            Self::Message { status, message } => RawError::Message {
+                status: *status,
+                message: Cow::Borrowed(message),
+            }
+            .serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for WitnessError {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        match RawError::deserialize(deserializer)? {
+            RawError::Unauthorized { status: _, message } => {
+                Ok(Self::Unauthorized(message.into_owned()))
+            }
+            RawError::NotFound { status: _, ty, id } => match ty {
+                EntityType::LogLength => Ok(Self::CheckpointNotFound(id)),
+            },
+            RawError::Message { status, message } => Ok(Self::Message {
+                status,
+                message: message.into_owned(),
+            }),
+        }
+    }
+}
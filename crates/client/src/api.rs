@@ -2,13 +2,20 @@
 
 use anyhow::{anyhow, Result};
 use bytes::Bytes;
-use futures_util::{Stream, TryStreamExt};
-use reqwest::{Body, IntoUrl, Response, StatusCode};
+use futures_util::{stream, Stream, StreamExt, TryStreamExt};
+use reqwest::{
+    header::{CONTENT_LENGTH, IF_RANGE, RANGE},
+    Body, IntoUrl, Response, StatusCode,
+};
 use serde::de::DeserializeOwned;
 use std::borrow::Cow;
 use thiserror::Error;
 use warg_api::v1::{
-    fetch::{FetchError, FetchLogsRequest, FetchLogsResponse},
+    fetch::{
+        FetchError, FetchLogsRequest, FetchLogsResponse, FetchPackagesRequest,
+        FetchPackagesResponse, SubscribeEvent, SubscribeRequest,
+    },
+    operator::{OperatorError, OperatorRecord, PublishOperatorRecordRequest},
     package::{
         ContentSource, PackageError, PackageRecord, PackageRecordState, PublishRecordRequest,
     },
@@ -16,10 +23,14 @@ use warg_api::v1::{
     proof::{
         ConsistencyRequest, ConsistencyResponse, InclusionRequest, InclusionResponse, ProofError,
     },
+    witness::{CosignaturesResponse, PublishCosignatureRequest, WitnessError},
 };
 use warg_crypto::hash::{AnyHash, HashError, Sha256};
 use warg_protocol::{
-    registry::{Checkpoint, LogId, LogLeaf, MapLeaf, RecordId, TimestampedCheckpoint},
+    registry::{
+        Checkpoint, LogId, LogLeaf, MapLeaf, RecordId, RegistryLen, TimestampedCheckpoint,
+        WitnessedCheckpoint,
+    },
     SerdeEnvelope,
 };
 use warg_transparency::{
@@ -38,9 +49,15 @@ pub enum ClientError {
     /// An error was returned from the package API.
     #[error(transparent)]
     Package(#[from] PackageError),
+    /// An error was returned from the operator API.
+    #[error(transparent)]
+    Operator(#[from] OperatorError),
     /// An error was returned from the proof API.
     #[error(transparent)]
     Proof(#[from] ProofError),
+    /// An error was returned from the witness API.
+    #[error(transparent)]
+    Witness(#[from] WitnessError),
     /// An error occurred while communicating with the registry.
     #[error("failed to send request to registry server: {0}")]
     Communication(#[from] reqwest::Error),
@@ -131,6 +148,98 @@ async fn into_result<T: DeserializeOwned, E: DeserializeOwned + Into<ClientError
     }
 }
 
+/// Parses a single server-sent event.
+///
+/// Returns `None` for events without data (e.g. keep-alive comments) and
+/// for events with unknown names.
+fn parse_event(event: &str) -> Option<Result<SubscribeEvent, ClientError>> {
+    let mut name = None;
+    let mut data: Option<String> = None;
+    for line in event.lines() {
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "event" => name = Some(value),
+            "data" => match &mut data {
+                Some(data) => {
+                    data.push('\n');
+                    data.push_str(value);
+                }
+                None => data = Some(value.to_string()),
+            },
+            _ => {}
+        }
+    }
+
+    let data = data?;
+    let event = match name? {
+        SubscribeEvent::CHECKPOINT => serde_json::from_str(&data).map(SubscribeEvent::Checkpoint),
+        SubscribeEvent::RECORD => serde_json::from_str(&data).map(SubscribeEvent::Record),
+        _ => return None,
+    };
+
+    Some(event.map_err(|e| ClientError::UnexpectedResponse {
+        status: StatusCode::OK,
+        message: format!("failed to deserialize server-sent event: {e}"),
+    }))
+}
+
+/// Appends the given bytes to the buffer with `\r\n` and `\r` line endings
+/// converted to `\n`.
+///
+/// `cr` tracks whether the previous bytes ended with `\r`, as a `\r\n` line
+/// ending may be split between chunks.
+fn push_normalized(buffer: &mut Vec<u8>, bytes: &[u8], cr: &mut bool) {
+    for &b in bytes {
+        match b {
+            b'\r' => buffer.push(b'\n'),
+            b'\n' if *cr => {}
+            _ => buffer.push(b),
+        }
+
+        *cr = b == b'\r';
+    }
+}
+
+/// Converts a server-sent event response body into a stream of events.
+fn subscribe_events(
+    body: impl Stream<Item = reqwest::Result<Bytes>> + Unpin,
+) -> impl Stream<Item = Result<SubscribeEvent, ClientError>> {
+    stream::unfold(
+        (body, Vec::new(), false),
+        |(mut body, mut buffer, mut cr): (_, Vec<u8>, bool)| async move {
+            loop {
+                // Events are terminated by a blank line
+                if let Some(end) = buffer.windows(2).position(|w| w == b"\n\n") {
+                    let event: Vec<u8> = buffer.drain(..end + 2).collect();
+                    match parse_event(&String::from_utf8_lossy(&event)) {
+                        Some(event) => return Some((event, (body, buffer, cr))),
+                        None => continue,
+                    }
+                }
+
+                match body.next().await? {
+                    Ok(bytes) => push_normalized(&mut buffer, &bytes, &mut cr),
+                    Err(e) => return Some((Err(e.into()), (body, buffer, cr))),
+                }
+            }
+        },
+    )
+}
+
+/// Skips the given number of bytes at the start of a stream.
+fn skip_bytes<E>(
+    stream: impl Stream<Item = Result<Bytes, E>>,
+    mut skip: u64,
+) -> impl Stream<Item = Result<Bytes, E>> {
+    stream.try_filter_map(move |mut bytes| {
+        let n = skip.min(bytes.len() as u64);
+        skip -= n;
+        let bytes = bytes.split_off(n as usize);
+        futures_util::future::ok((!bytes.is_empty()).then_some(bytes))
+    })
+}
+
 /// Represents a Warg API client for communicating with
 /// a Warg registry server.
 pub struct Client {
@@ -162,6 +271,16 @@ impl Client {
         into_result::<_, FetchError>(reqwest::get(url).await?).await
     }
 
+    /// Gets the checkpoint for the given registry log length.
+    pub async fn checkpoint_at(
+        &self,
+        log_length: RegistryLen,
+    ) -> Result<SerdeEnvelope<TimestampedCheckpoint>, ClientError> {
+        let url = self.url.join(&paths::fetch_checkpoint_at(log_length));
+        tracing::debug!("getting checkpoint for log length {log_length} at `{url}`");
+        into_result::<_, FetchError>(reqwest::get(url).await?).await
+    }
+
     /// Fetches package log entries from the registry.
     pub async fn fetch_logs(
         &self,
@@ -174,6 +293,43 @@ impl Client {
         into_result::<_, FetchError>(response).await
     }
 
+    /// Fetches a page of published package identifiers from the registry.
+    pub async fn fetch_packages(
+        &self,
+        request: FetchPackagesRequest<'_>,
+    ) -> Result<FetchPackagesResponse, ClientError> {
+        let url = self.url.join(paths::fetch_packages());
+        tracing::debug!("fetching packages at `{url}`");
+
+        let response = self.client.post(url).json(&request).send().await?;
+        into_result::<_, FetchError>(response).await
+    }
+
+    /// Subscribes to new checkpoints and, optionally, the status of a package record.
+    ///
+    /// The returned stream ends when the registry closes the connection.
+    pub async fn subscribe(
+        &self,
+        request: SubscribeRequest,
+    ) -> Result<impl Stream<Item = Result<SubscribeEvent, ClientError>>, ClientError> {
+        let url = self.url.join(paths::subscribe());
+        tracing::debug!("subscribing to registry events at `{url}`");
+
+        let response = self
+            .client
+            .get(url)
+            .query(&request)
+            .header("accept", "text/event-stream")
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(deserialize::<FetchError>(response).await?.into());
+        }
+
+        Ok(subscribe_events(response.bytes_stream().boxed()))
+    }
+
     /// Publish a new record to a package log.
     pub async fn publish_package_record(
         &self,
@@ -203,12 +359,39 @@ impl Client {
         into_result::<_, PackageError>(response).await
     }
 
+    /// Publish a new record to the operator log.
+    pub async fn publish_operator_record(
+        &self,
+        request: PublishOperatorRecordRequest<'_>,
+    ) -> Result<OperatorRecord, ClientError> {
+        let url = self.url.join(paths::publish_operator_record());
+        tracing::debug!("appending record to the operator log at `{url}`");
+
+        let response = self.client.post(url).json(&request).send().await?;
+        into_result::<_, OperatorError>(response).await
+    }
+
+    /// Gets an operator record from the registry.
+    pub async fn get_operator_record(
+        &self,
+        record_id: &RecordId,
+    ) -> Result<OperatorRecord, ClientError> {
+        let url = self.url.join(&paths::operator_record(record_id));
+        tracing::debug!("getting operator record `{record_id}` at `{url}`");
+
+        let response = reqwest::get(url).await?;
+        into_result::<_, OperatorError>(response).await
+    }
+
     /// Downloads the content associated with a given record.
+    ///
+    /// The returned stream starts at the given byte `offset` of the content.
     pub async fn download_content(
         &self,
         log_id: &LogId,
         record_id: &RecordId,
         digest: &AnyHash,
+        offset: u64,
     ) -> Result<impl Stream<Item = Result<Bytes>>, ClientError> {
         tracing::debug!("fetching record `{record_id}` for package `{log_id}`");
 
@@ -229,18 +412,33 @@ impl Client {
                 ContentSource::Http { url } => url,
             };
 
-            tracing::debug!("downloading content `{digest}` from `{url}`");
+            tracing::debug!("downloading content `{digest}` from `{url}` at offset {offset}");
 
-            let response = reqwest::get(url).await?;
-            if !response.status().is_success() {
-                tracing::debug!(
-                    "failed to download content `{digest}` from `{url}`: {status}",
-                    status = response.status()
-                );
-                continue;
+            let mut request = self.client.get(url);
+            if offset > 0 {
+                request = request
+                    .header(RANGE, format!("bytes={offset}-"))
+                    .header(IF_RANGE, format!("\"{digest}\""));
             }
 
-            return Ok(response.bytes_stream().map_err(|e| anyhow!(e)));
+            let response = request.send().await?;
+            let skip = match response.status() {
+                StatusCode::PARTIAL_CONTENT if offset > 0 => 0,
+                // The source does not support ranges, so skip what was already downloaded
+                StatusCode::OK => offset,
+                // The offset is at the end of the content, so there is nothing left to download
+                StatusCode::RANGE_NOT_SATISFIABLE if offset > 0 => {
+                    return Ok(stream::empty().left_stream());
+                }
+                status => {
+                    tracing::debug!("failed to download content `{digest}` from `{url}`: {status}");
+                    continue;
+                }
+            };
+
+            return Ok(skip_bytes(response.bytes_stream(), skip)
+                .map_err(|e| anyhow!(e))
+                .right_stream());
         }
 
         Err(ClientError::AllSourcesFailed(digest.clone()))
@@ -314,6 +512,48 @@ impl Client {
         Ok(())
     }
 
+    /// Publishes a witness cosignature of a checkpoint to the registry.
+    ///
+    /// Returns the cosignatures of the checkpoint known to the registry.
+    pub async fn publish_cosignature(
+        &self,
+        cosignature: &SerdeEnvelope<WitnessedCheckpoint>,
+    ) -> Result<CosignaturesResponse, ClientError> {
+        let url = self.url.join(paths::publish_cosignature());
+        tracing::debug!(
+            "publishing cosignature for log length {log_length} at `{url}`",
+            log_length = cosignature.as_ref().checkpoint.log_length
+        );
+
+        let response = self
+            .client
+            .post(url)
+            .json(&PublishCosignatureRequest {
+                cosignature: Cow::Borrowed(cosignature),
+            })
+            .send()
+            .await?;
+        into_result::<_, WitnessError>(response).await
+    }
+
+    /// Fetches the witness cosignatures of the checkpoint for the given
+    /// registry log length.
+    pub async fn fetch_cosignatures(
+        &self,
+        log_length: RegistryLen,
+    ) -> Result<CosignaturesResponse, ClientError> {
+        let url = self.url.join(&paths::fetch_cosignatures(log_length));
+        tracing::debug!("fetching cosignatures for log length {log_length} at `{url}`");
+        into_result::<_, WitnessError>(reqwest::get(url).await?).await
+    }
+
+    /// Fetches the most recent cosignature of each witness.
+    pub async fn fetch_latest_cosignatures(&self) -> Result<CosignaturesResponse, ClientError> {
+        let url = self.url.join(paths::fetch_latest_cosignatures());
+        tracing::debug!("fetching latest cosignatures at `{url}`");
+        into_result::<_, WitnessError>(reqwest::get(url).await?).await
+    }
+
     /// Uploads package content to the registry.
     pub async fn upload_content(
         &self,
@@ -326,6 +566,50 @@ impl Client {
         tracing::debug!("uploading content to `{url}`");
 
         let response = self.client.post(url).body(content).send().await?;
+        Self::content_location(response).await
+    }
+
+    /// Uploads content via HTTP PUT to the given URL and then finalizes the
+    /// upload with the registry.
+    ///
+    /// Returns the location of the content.
+    pub async fn put_content(
+        &self,
+        url: &str,
+        finalize_url: &str,
+        content: impl Into<Body>,
+        len: u64,
+    ) -> Result<String, ClientError> {
+        let url = self.url.join(url);
+        tracing::debug!("uploading content to `{url}`");
+
+        let response = self
+            .client
+            .put(url)
+            .header(CONTENT_LENGTH, len)
+            .body(content)
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(ClientError::UnexpectedResponse {
+                status,
+                message: format!(
+                    "failed to upload content: {body}",
+                    body = response.text().await.unwrap_or_default()
+                ),
+            });
+        }
+
+        // Finalize URLs may be relative to the registry URL.
+        let url = self.url.join(finalize_url);
+        tracing::debug!("finalizing content upload at `{url}`");
+
+        let response = self.client.post(url).send().await?;
+        Self::content_location(response).await
+    }
+
+    async fn content_location(response: Response) -> Result<String, ClientError> {
         if !response.status().is_success() {
             return Err(ClientError::Package(
                 deserialize::<PackageError>(response).await?,
@@ -388,3 +672,55 @@ impl Client {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use warg_crypto::hash::HashAlgorithm;
+
+    fn assert_rejected(event: &SubscribeEvent, expected: &str) {
+        match event {
+            SubscribeEvent::Record(PackageRecord {
+                state: PackageRecordState::Rejected { reason },
+                ..
+            }) => assert_eq!(reason, expected),
+            _ => panic!("expected a rejected record event"),
+        }
+    }
+
+    #[tokio::test]
+    async fn it_parses_events_with_any_line_ending() {
+        let data = |reason: &str| {
+            serde_json::to_string(&PackageRecord {
+                id: HashAlgorithm::Sha256.digest(&[0]).into(),
+                state: PackageRecordState::Rejected {
+                    reason: reason.to_string(),
+                },
+            })
+            .unwrap()
+        };
+
+        let lf = format!("event: record\ndata: {}\n\n", data("lf"));
+        let crlf = format!(
+            ": keep-alive\r\n\r\nevent: record\r\ndata: {}\r\n\r\n",
+            data("crlf")
+        );
+        let cr = format!("event: record\rdata: {}\r\r", data("cr"));
+
+        // Split the CRLF event between its last `\r` and `\n`
+        let (crlf_start, crlf_end) = crlf.split_at(crlf.len() - 1);
+        let body = stream::iter(
+            [lf.as_str(), crlf_start, crlf_end, cr.as_str()]
+                .map(|chunk| Ok(Bytes::copy_from_slice(chunk.as_bytes()))),
+        );
+
+        let events: Vec<_> = subscribe_events(body)
+            .map(|event| event.unwrap())
+            .collect()
+            .await;
+        assert_eq!(events.len(), 3);
+        assert_rejected(&events[0], "lf");
+        assert_rejected(&events[1], "crlf");
+        assert_rejected(&events[2], "cr");
+    }
+}
@@ -10,6 +10,7 @@ use std::{
     fs::{self, File},
     path::{Component, Path, PathBuf},
 };
+use warg_crypto::signing::PublicKey;
 
 static CACHE_DIR: Lazy<Option<PathBuf>> = Lazy::new(dirs::cache_dir);
 static CONFIG_DIR: Lazy<Option<PathBuf>> = Lazy::new(dirs::config_dir);
@@ -94,9 +95,26 @@ pub struct Config {
     /// `$CACHE_DIR` is the platform-specific cache directory.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub content_dir: Option<PathBuf>,
+
+    /// The public keys of the witnesses trusted to cosign checkpoints.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub witness_keys: Vec<PublicKey>,
+
+    /// The number of trusted witness cosignatures required before a
+    /// checkpoint is accepted.
+    ///
+    /// If `None`, every trusted witness must cosign a checkpoint.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub witness_threshold: Option<usize>,
 }
 
 impl Config {
+    /// Gets the number of trusted witness cosignatures required before a
+    /// checkpoint is accepted.
+    pub fn witness_threshold(&self) -> usize {
+        self.witness_threshold.unwrap_or(self.witness_keys.len())
+    }
+
     /// Reads the client configuration from the given file path.
     pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
         let path = path.as_ref();
@@ -165,6 +183,8 @@ impl Config {
                 assert!(p.is_absolute());
                 pathdiff::diff_paths(&p, &parent).unwrap()
             }),
+            witness_keys: self.witness_keys.clone(),
+            witness_threshold: self.witness_threshold,
         };
 
         serde_json::to_writer_pretty(
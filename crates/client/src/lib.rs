@@ -4,15 +4,21 @@
 
 use crate::storage::PackageInfo;
 use anyhow::{anyhow, Context, Result};
+use futures_util::{pin_mut, Stream, StreamExt};
 use reqwest::{Body, IntoUrl};
-use std::{borrow::Cow, collections::HashMap, path::PathBuf, time::Duration};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    time::Duration,
+};
 use storage::{
-    ContentStorage, FileSystemContentStorage, FileSystemRegistryStorage, PublishInfo,
+    ContentStorage, FileSystemContentStorage, FileSystemRegistryStorage, OperatorInfo, PublishInfo,
     RegistryStorage,
 };
 use thiserror::Error;
 use warg_api::v1::{
-    fetch::{FetchError, FetchLogsRequest, FetchLogsResponse},
+    fetch::{FetchError, FetchLogsRequest, FetchLogsResponse, SubscribeEvent, SubscribeRequest},
     package::{
         MissingContent, PackageError, PackageRecord, PackageRecordState, PublishRecordRequest,
         UploadEndpoint,
@@ -21,11 +27,11 @@ use warg_api::v1::{
 };
 use warg_crypto::{
     hash::{AnyHash, Hash, Sha256},
-    signing,
+    signing::{self, KeyID, PublicKey},
 };
 use warg_protocol::{
     operator, package,
-    registry::{LogId, LogLeaf, PackageId, RecordId, TimestampedCheckpoint},
+    registry::{Checkpoint, LogId, LogLeaf, PackageId, RecordId, TimestampedCheckpoint},
     PublishedProtoEnvelope, SerdeEnvelope, Version, VersionReq,
 };
 
@@ -37,11 +43,16 @@ pub mod storage;
 pub use self::config::*;
 pub use self::registry_url::RegistryUrl;
 
+/// The maximum number of records requested per log when fetching logs.
+const FETCH_LOGS_LIMIT: u16 = 100;
+
 /// A client for a Warg registry.
 pub struct Client<R, C> {
     registry: R,
     content: C,
     api: api::Client,
+    witness_keys: HashMap<KeyID, PublicKey>,
+    witness_threshold: usize,
 }
 
 impl<R: RegistryStorage, C: ContentStorage> Client<R, C> {
@@ -52,9 +63,28 @@ impl<R: RegistryStorage, C: ContentStorage> Client<R, C> {
             registry,
             content,
             api: api::Client::new(url)?,
+            witness_keys: HashMap::new(),
+            witness_threshold: 0,
         })
     }
 
+    /// Requires checkpoints to be cosigned by at least `threshold` of the
+    /// given witness keys before they are accepted.
+    ///
+    /// A threshold of zero disables the requirement.
+    pub fn with_witnesses(
+        mut self,
+        keys: impl IntoIterator<Item = PublicKey>,
+        threshold: usize,
+    ) -> Self {
+        self.witness_keys = keys
+            .into_iter()
+            .map(|key| (key.fingerprint(), key))
+            .collect();
+        self.witness_threshold = threshold;
+        self
+    }
+
     /// Gets the URL of the client.
     pub fn url(&self) -> &RegistryUrl {
         self.api.url()
@@ -107,36 +137,14 @@ impl<R: RegistryStorage, C: ContentStorage> Client<R, C> {
             });
         }
 
-        let initializing = info.initializing();
-
         tracing::info!(
             "publishing {new}package `{id}`",
             id = info.id,
-            new = if initializing { "new " } else { "" }
+            new = if info.initializing() { "new " } else { "" }
         );
         tracing::debug!("entries: {:?}", info.entries);
 
-        let mut package = self
-            .registry
-            .load_package(&info.id)
-            .await?
-            .unwrap_or_else(|| PackageInfo::new(info.id.clone()));
-
-        // If we're not initializing the package and a head was not explicitly specified,
-        // updated to the latest checkpoint to get the latest known head.
-        if !initializing && info.head.is_none() {
-            self.update_checkpoint(&self.api.latest_checkpoint().await?, [&mut package])
-                .await?;
-
-            info.head = package.state.head().as_ref().map(|h| h.digest.clone());
-        }
-
-        match (initializing, info.head.is_some()) {
-            (true, true) => return Err(ClientError::CannotInitializePackage { id: package.id }),
-            (false, false) => return Err(ClientError::MustInitializePackage { id: package.id }),
-            _ => (),
-        }
-
+        let package = self.resolve_publish_head(&mut info).await?;
         let record = info.finalize(signing_key)?;
         let log_id = LogId::package_log::<Sha256>(&package.id);
         let record = self
@@ -162,39 +170,128 @@ impl<R: RegistryStorage, C: ContentStorage> Client<R, C> {
 
         // TODO: parallelize this
         for (digest, MissingContent { upload }) in record.missing_content() {
+            // Uploading via HTTP PUT requires the length of the content
+            let len = self
+                .content
+                .content_location(digest)
+                .and_then(|path| std::fs::metadata(path).ok())
+                .map(|metadata| metadata.len());
+
             // Upload the missing content, if the registry supports it
-            let Some(UploadEndpoint::HttpPost { url }) = upload.first() else {
+            let Some(endpoint) = upload.iter().find(|endpoint| match endpoint {
+                UploadEndpoint::HttpPost { .. } => true,
+                UploadEndpoint::HttpPut { .. } => len.is_some(),
+            }) else {
                 continue;
             };
 
-            self.api
-                .upload_content(
-                    url,
-                    Body::wrap_stream(self.content.load_content(digest).await?.ok_or_else(
-                        || ClientError::ContentNotFound {
-                            digest: digest.clone(),
-                        },
-                    )?),
-                )
-                .await
-                .map_err(|e| match e {
-                    api::ClientError::Package(PackageError::Rejection(reason)) => {
-                        ClientError::PublishRejected {
-                            id: package.id.clone(),
-                            record_id: record.id.clone(),
-                            reason,
-                        }
+            let content =
+                Body::wrap_stream(self.content.load_content(digest).await?.ok_or_else(|| {
+                    ClientError::ContentNotFound {
+                        digest: digest.clone(),
                     }
-                    _ => e.into(),
-                })?;
+                })?);
+
+            match endpoint {
+                UploadEndpoint::HttpPost { url } => self.api.upload_content(url, content).await,
+                UploadEndpoint::HttpPut { url, finalize_url } => {
+                    self.api
+                        .put_content(url, finalize_url, content, len.unwrap())
+                        .await
+                }
+            }
+            .map_err(|e| match e {
+                api::ClientError::Package(PackageError::Rejection(reason)) => {
+                    ClientError::PublishRejected {
+                        id: package.id.clone(),
+                        record_id: record.id.clone(),
+                        reason,
+                    }
+                }
+                _ => e.into(),
+            })?;
         }
 
         Ok(record.id)
     }
 
+    /// Co-signs the publish information in client storage.
+    ///
+    /// If there's no publishing information in client storage, an error is returned.
+    ///
+    /// The co-signature is stored with the publish information and is submitted
+    /// along with the record by `publish`.
+    pub async fn cosign(&self, signing_key: &signing::PrivateKey) -> ClientResult<()> {
+        let mut info = self
+            .registry
+            .load_publish()
+            .await?
+            .ok_or(ClientError::NotPublishing)?;
+
+        self.cosign_with_info(signing_key, &mut info).await?;
+        self.registry.store_publish(Some(&info)).await?;
+        Ok(())
+    }
+
+    /// Co-signs the provided publish information.
+    ///
+    /// The head of the package log is resolved and the record timestamp is fixed
+    /// so that every co-signature covers the same record.
+    pub async fn cosign_with_info(
+        &self,
+        signing_key: &signing::PrivateKey,
+        info: &mut PublishInfo,
+    ) -> ClientResult<()> {
+        if info.entries.is_empty() {
+            return Err(ClientError::NothingToPublish {
+                id: info.id.clone(),
+            });
+        }
+
+        if info.initializing() {
+            return Err(ClientError::CannotCosignInitialization {
+                id: info.id.clone(),
+            });
+        }
+
+        self.resolve_publish_head(info).await?;
+        info.cosign(signing_key)?;
+        Ok(())
+    }
+
+    /// Loads the package being published and resolves the head of its log.
+    async fn resolve_publish_head(&self, info: &mut PublishInfo) -> ClientResult<PackageInfo> {
+        let initializing = info.initializing();
+        let mut package = self
+            .registry
+            .load_package(&info.id)
+            .await?
+            .unwrap_or_else(|| PackageInfo::new(info.id.clone()));
+
+        // If we're not initializing the package and a head was not explicitly specified,
+        // updated to the latest checkpoint to get the latest known head.
+        if !initializing && info.head.is_none() {
+            self.update_checkpoint(&self.latest_checkpoint().await?, [&mut package])
+                .await?;
+
+            info.head = package.state.head().as_ref().map(|h| h.digest.clone());
+        }
+
+        match (initializing, info.head.is_some()) {
+            (true, true) => return Err(ClientError::CannotInitializePackage { id: package.id }),
+            (false, false) => return Err(ClientError::MustInitializePackage { id: package.id }),
+            _ => (),
+        }
+
+        Ok(package)
+    }
+
     /// Waits for a package record to transition to the `published` state.
     ///
-    /// The `interval` is the amount of time to wait between checks.
+    /// Record status changes are streamed from the registry; if the registry
+    /// does not support streaming, the record status is polled instead.
+    ///
+    /// The `interval` is the amount of time to wait between checks when polling.
     ///
     /// Returns an error if the package record was rejected.
     pub async fn wait_for_publish(
@@ -204,42 +301,165 @@ impl<R: RegistryStorage, C: ContentStorage> Client<R, C> {
         interval: Duration,
     ) -> ClientResult<()> {
         let log_id = LogId::package_log::<Sha256>(package);
-        let mut current = self.get_package_record(package, &log_id, record_id).await?;
 
-        loop {
-            match current.state {
-                PackageRecordState::Sourcing { .. } => {
-                    return Err(ClientError::PackageMissingContent);
-                }
-                PackageRecordState::Published { .. } => {
-                    return Ok(());
-                }
-                PackageRecordState::Rejected { reason } => {
-                    return Err(ClientError::PublishRejected {
-                        id: package.clone(),
-                        record_id: record_id.clone(),
-                        reason,
-                    });
-                }
-                PackageRecordState::Processing => {
-                    tokio::time::sleep(interval).await;
-                    current = self.get_package_record(package, &log_id, record_id).await?;
+        match self
+            .api
+            .subscribe(SubscribeRequest {
+                log_id: Some(log_id.clone()),
+                record_id: Some(record_id.clone()),
+            })
+            .await
+        {
+            Ok(events) => {
+                pin_mut!(events);
+                while let Some(event) = events.next().await {
+                    match event {
+                        Ok(SubscribeEvent::Record(record)) => {
+                            if let Some(result) =
+                                Self::publish_result(package, record_id, record.state)
+                            {
+                                return result;
+                            }
+                        }
+                        Ok(SubscribeEvent::Checkpoint(_)) => {}
+                        Err(e) => {
+                            tracing::debug!("subscription failed: {e}");
+                            break;
+                        }
+                    }
                 }
+
+                tracing::debug!("subscription ended; polling record status instead");
+            }
+            Err(e) => {
+                tracing::debug!("failed to subscribe to record status: {e}; polling instead")
+            }
+        }
+
+        loop {
+            let current = self.get_package_record(package, &log_id, record_id).await?;
+            if let Some(result) = Self::publish_result(package, record_id, current.state) {
+                return result;
+            }
+
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    /// Gets the result of publishing a package record given its state.
+    ///
+    /// Returns `None` if the record is still processing.
+    fn publish_result(
+        package: &PackageId,
+        record_id: &RecordId,
+        state: PackageRecordState,
+    ) -> Option<ClientResult<()>> {
+        match state {
+            PackageRecordState::Sourcing { .. } => Some(Err(ClientError::PackageMissingContent)),
+            PackageRecordState::Published { .. } => Some(Ok(())),
+            PackageRecordState::Rejected { reason } => Some(Err(ClientError::PublishRejected {
+                id: package.clone(),
+                record_id: record_id.clone(),
+                reason,
+            })),
+            PackageRecordState::Processing => None,
+        }
+    }
+
+    /// Gets the latest registry checkpoint trusted by the client.
+    ///
+    /// If checkpoints must be cosigned by witnesses, this is the latest
+    /// checkpoint cosigned by enough trusted witnesses, which may precede
+    /// the latest checkpoint signed by the registry.
+    pub async fn latest_checkpoint(&self) -> ClientResult<SerdeEnvelope<TimestampedCheckpoint>> {
+        let latest = self.api.latest_checkpoint().await?;
+        if self.witness_threshold == 0 {
+            return Ok(latest);
+        }
+
+        let checkpoint = &latest.as_ref().checkpoint;
+        let found = self.count_cosignatures(checkpoint).await?;
+        if found >= self.witness_threshold {
+            return Ok(latest);
+        }
+
+        // Witnesses may not have cosigned the latest checkpoint yet, so fall
+        // back to the checkpoints most recently cosigned by trusted witnesses
+        let mut log_lengths = self
+            .api
+            .fetch_latest_cosignatures()
+            .await?
+            .cosignatures
+            .iter()
+            .filter(|c| self.witness_keys.contains_key(c.key_id()))
+            .map(|c| c.as_ref().checkpoint.log_length)
+            .filter(|log_length| *log_length < checkpoint.log_length)
+            .collect::<Vec<_>>();
+        log_lengths.sort_unstable_by(|a, b| b.cmp(a));
+        log_lengths.dedup();
+
+        for log_length in log_lengths {
+            let cosigned = self.api.checkpoint_at(log_length).await?;
+            if self
+                .count_cosignatures(&cosigned.as_ref().checkpoint)
+                .await?
+                >= self.witness_threshold
+            {
+                tracing::info!(
+                    "using checkpoint with log length {log_length} as the latest checkpoint \
+                     with log length {latest} is not sufficiently cosigned",
+                    latest = checkpoint.log_length
+                );
+                return Ok(cosigned);
             }
         }
+
+        Err(ClientError::InsufficientCosignatures {
+            checkpoint_id: Hash::<Sha256>::of(checkpoint).into(),
+            required: self.witness_threshold,
+            found,
+        })
     }
 
     /// Updates every package log in client storage to the latest registry checkpoint.
     pub async fn update(&self) -> ClientResult<()> {
-        tracing::info!("updating all packages to latest checkpoint");
+        self.update_to(&self.latest_checkpoint().await?).await
+    }
+
+    /// Updates every package log in client storage to the given registry checkpoint.
+    pub async fn update_to(
+        &self,
+        checkpoint: &SerdeEnvelope<TimestampedCheckpoint>,
+    ) -> ClientResult<()> {
+        tracing::info!(
+            "updating all packages to checkpoint with log length {len}",
+            len = checkpoint.as_ref().checkpoint.log_length
+        );
 
         let mut updating = self.registry.load_packages().await?;
-        self.update_checkpoint(&self.api.latest_checkpoint().await?, &mut updating)
-            .await?;
+        self.update_checkpoint(checkpoint, &mut updating).await?;
 
         Ok(())
     }
 
+    /// Subscribes to new checkpoints signed by the registry.
+    ///
+    /// The latest checkpoint is the first item of the returned stream.
+    ///
+    /// The stream ends when the registry closes the connection.
+    pub async fn subscribe_checkpoints(
+        &self,
+    ) -> ClientResult<impl Stream<Item = ClientResult<SerdeEnvelope<TimestampedCheckpoint>>>> {
+        let events = self.api.subscribe(SubscribeRequest::default()).await?;
+        Ok(events.filter_map(|event| async move {
+            match event {
+                Ok(SubscribeEvent::Checkpoint(checkpoint)) => Some(Ok(checkpoint)),
+                Ok(SubscribeEvent::Record(_)) => None,
+                Err(e) => Some(Err(e.into())),
+            }
+        }))
+    }
+
     /// Inserts or updates the logs of the specified packages in client storage to
     /// the latest registry checkpoint.
     pub async fn upsert<'a, I>(&self, packages: I) -> Result<(), ClientError>
@@ -260,7 +480,7 @@ impl<R: RegistryStorage, C: ContentStorage> Client<R, C> {
             );
         }
 
-        self.update_checkpoint(&self.api.latest_checkpoint().await?, &mut updating)
+        self.update_checkpoint(&self.latest_checkpoint().await?, &mut updating)
             .await?;
 
         Ok(())
@@ -394,7 +614,7 @@ impl<R: RegistryStorage, C: ContentStorage> Client<R, C> {
                         .head()
                         .as_ref()
                         .map(|h| Cow::Borrowed(&h.digest)),
-                    limit: None,
+                    limit: Some(FETCH_LOGS_LIMIT),
                     packages: Cow::Borrowed(&last_known),
                 })
                 .await
@@ -404,13 +624,34 @@ impl<R: RegistryStorage, C: ContentStorage> Client<R, C> {
                     })
                 })?;
 
+            // Package records are only validated once the operator log is
+            // complete so that each is validated against the namespaces
+            // defined before it
+            let operator_complete = response.operator.len() < FETCH_LOGS_LIMIT as usize;
+
             for record in response.operator {
                 let record: PublishedProtoEnvelope<operator::OperatorRecord> = record.try_into()?;
+
                 operator
                     .state
                     .validate(&record.envelope)
                     .map_err(|inner| ClientError::OperatorValidationFailed { inner })?;
                 operator.head_registry_index = Some(record.registry_index);
+
+                for entry in &record.envelope.as_ref().entries {
+                    if let operator::OperatorEntry::DefineNamespace { namespace, .. }
+                    | operator::OperatorEntry::ImportNamespace { namespace, .. } = entry
+                    {
+                        operator
+                            .namespace_indices
+                            .insert(namespace.clone(), record.registry_index);
+                    }
+                }
+            }
+
+            if !operator_complete {
+                // The package records will be fetched again with the next page
+                continue;
             }
 
             for (log_id, records) in response.packages {
@@ -421,11 +662,24 @@ impl<R: RegistryStorage, C: ContentStorage> Client<R, C> {
                 for record in records {
                     let record: PublishedProtoEnvelope<package::PackageRecord> =
                         record.try_into()?;
-                    package.state.validate(&record.envelope).map_err(|inner| {
-                        ClientError::PackageValidationFailed {
-                            id: package.id.clone(),
-                            inner,
-                        }
+
+                    // A namespace only restricts records committed after it was defined
+                    let restricted = operator
+                        .namespace_indices
+                        .get(package.id.namespace())
+                        .map_or(true, |index| *index < record.registry_index);
+                    let result = if restricted {
+                        package.state.validate_with_operator(
+                            &package.id,
+                            &operator.state,
+                            &record.envelope,
+                        )
+                    } else {
+                        package.state.validate(&record.envelope)
+                    };
+                    result.map_err(|inner| ClientError::PackageValidationFailed {
+                        id: package.id.clone(),
+                        inner,
                     })?;
                     package.head_registry_index = Some(record.registry_index);
                 }
@@ -448,6 +702,9 @@ impl<R: RegistryStorage, C: ContentStorage> Client<R, C> {
             }
         }
 
+        Self::verify_checkpoint_signature(&mut operator, &checkpoint_id, ts_checkpoint)?;
+        self.verify_cosignatures(&checkpoint_id, checkpoint).await?;
+
         // Prove inclusion for the current log heads
         let mut leaf_indices = Vec::with_capacity(packages.len() + 1 /* for operator */);
         let mut leafs = Vec::with_capacity(leaf_indices.len());
@@ -507,6 +764,86 @@ impl<R: RegistryStorage, C: ContentStorage> Client<R, C> {
         Ok(())
     }
 
+    /// Verifies that the checkpoint was signed by the pinned operator key.
+    ///
+    /// The key that signs the first checkpoint is pinned; another key with
+    /// commit permission is only trusted (and pinned) once the operator log
+    /// has revoked the commit permission of the pinned key.
+    fn verify_checkpoint_signature(
+        operator: &mut OperatorInfo,
+        checkpoint_id: &AnyHash,
+        ts_checkpoint: &SerdeEnvelope<TimestampedCheckpoint>,
+    ) -> Result<(), ClientError> {
+        let key_id = ts_checkpoint.key_id();
+        if let Some(pinned) = &operator.pinned_key_id {
+            if pinned != key_id
+                && operator
+                    .state
+                    .key_has_permission(pinned, operator::Permission::Commit)
+            {
+                return Err(ClientError::OperatorKeyMismatch {
+                    pinned: pinned.clone(),
+                    found: key_id.clone(),
+                });
+            }
+        }
+
+        operator
+            .state
+            .public_key(key_id)
+            .filter(|_| {
+                operator
+                    .state
+                    .key_has_permission(key_id, operator::Permission::Commit)
+            })
+            .and_then(|key| ts_checkpoint.verify(key).ok())
+            .ok_or_else(|| ClientError::CheckpointSignatureInvalid {
+                checkpoint_id: checkpoint_id.clone(),
+                key_id: key_id.clone(),
+            })?;
+
+        operator.pinned_key_id = Some(key_id.clone());
+        Ok(())
+    }
+
+    /// Verifies that the checkpoint was cosigned by the required number of
+    /// trusted witnesses.
+    async fn verify_cosignatures(
+        &self,
+        checkpoint_id: &AnyHash,
+        checkpoint: &Checkpoint,
+    ) -> Result<(), ClientError> {
+        if self.witness_threshold == 0 {
+            return Ok(());
+        }
+
+        let found = self.count_cosignatures(checkpoint).await?;
+        if found < self.witness_threshold {
+            return Err(ClientError::InsufficientCosignatures {
+                checkpoint_id: checkpoint_id.clone(),
+                required: self.witness_threshold,
+                found,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Counts the trusted witnesses that cosigned the checkpoint.
+    async fn count_cosignatures(&self, checkpoint: &Checkpoint) -> Result<usize, ClientError> {
+        let response = self.api.fetch_cosignatures(checkpoint.log_length).await?;
+        Ok(response
+            .cosignatures
+            .iter()
+            .filter(|c| c.as_ref().checkpoint == *checkpoint)
+            .filter_map(|c| {
+                let key = self.witness_keys.get(c.key_id())?;
+                c.verify(key).ok().map(|_| c.key_id())
+            })
+            .collect::<HashSet<_>>()
+            .len())
+    }
+
     async fn fetch_package(&self, id: &PackageId) -> Result<PackageInfo, ClientError> {
         match self.registry.load_package(id).await? {
             Some(info) => {
@@ -515,7 +852,7 @@ impl<R: RegistryStorage, C: ContentStorage> Client<R, C> {
             }
             None => {
                 let mut info = PackageInfo::new(id.clone());
-                self.update_checkpoint(&self.api.latest_checkpoint().await?, [&mut info])
+                self.update_checkpoint(&self.latest_checkpoint().await?, [&mut info])
                     .await?;
 
                 Ok(info)
@@ -557,10 +894,20 @@ impl<R: RegistryStorage, C: ContentStorage> Client<R, C> {
                 Ok(path)
             }
             None => {
+                let offset = self.content.partial_content_len(digest);
+                if offset > 0 {
+                    tracing::info!("resuming download of content `{digest}` at offset {offset}");
+                }
+
                 self.content
-                    .store_content(
-                        Box::pin(self.api.download_content(log_id, record_id, digest).await?),
-                        Some(digest),
+                    .resume_content(
+                        Box::pin(
+                            self.api
+                                .download_content(log_id, record_id, digest, offset)
+                                .await?,
+                        ),
+                        digest,
+                        offset,
                     )
                     .await?;
 
@@ -614,11 +961,10 @@ impl FileSystemClient {
             (_, None) => return Ok(StorageLockResult::NotAcquired(content_dir)),
         };
 
-        Ok(StorageLockResult::Acquired(Self::new(
-            url.into_url(),
-            packages,
-            content,
-        )?))
+        Ok(StorageLockResult::Acquired(
+            Self::new(url.into_url(), packages, content)?
+                .with_witnesses(config.witness_keys.clone(), config.witness_threshold()),
+        ))
     }
 
     /// Creates a client for the given registry URL.
@@ -633,11 +979,12 @@ impl FileSystemClient {
             registries_dir,
             content_dir,
         } = config.storage_paths_for_url(url)?;
-        Self::new(
+        Ok(Self::new(
             registry_url.into_url(),
             FileSystemRegistryStorage::lock(registries_dir)?,
             FileSystemContentStorage::lock(content_dir)?,
-        )
+        )?
+        .with_witnesses(config.witness_keys.clone(), config.witness_threshold()))
     }
 }
 
@@ -666,6 +1013,35 @@ pub enum ClientError {
         inner: operator::ValidationError,
     },
 
+    /// The checkpoint was signed by a key other than the pinned key.
+    #[error("checkpoint was signed by key `{found}` but key `{pinned}` is pinned")]
+    OperatorKeyMismatch {
+        /// The ID of the pinned operator key.
+        pinned: signing::KeyID,
+        /// The ID of the key that signed the checkpoint.
+        found: signing::KeyID,
+    },
+
+    /// The checkpoint was not signed by a current operator key with commit permission.
+    #[error("checkpoint `{checkpoint_id}` was not signed by an operator key with commit permission (signed by key `{key_id}`)")]
+    CheckpointSignatureInvalid {
+        /// The identifier of the checkpoint.
+        checkpoint_id: AnyHash,
+        /// The ID of the key that signed the checkpoint.
+        key_id: signing::KeyID,
+    },
+
+    /// The checkpoint was not cosigned by enough trusted witnesses.
+    #[error("checkpoint `{checkpoint_id}` was cosigned by {found} trusted witness(es) but {required} are required")]
+    InsufficientCosignatures {
+        /// The identifier of the checkpoint.
+        checkpoint_id: AnyHash,
+        /// The number of cosignatures required.
+        required: usize,
+        /// The number of valid cosignatures found.
+        found: usize,
+    },
+
     /// The package already exists and cannot be initialized.
     #[error("package `{id}` already exists and cannot be initialized")]
     CannotInitializePackage {
@@ -673,6 +1049,13 @@ pub enum ClientError {
         id: PackageId,
     },
 
+    /// The initialization of a package cannot be co-signed.
+    #[error("the initialization of package `{id}` cannot be co-signed")]
+    CannotCosignInitialization {
+        /// The identifier of the package being initialized.
+        id: PackageId,
+    },
+
     /// The package must be initialized before publishing.
     #[error("package `{id}` must be initialized before publishing")]
     MustInitializePackage {
@@ -775,3 +1158,137 @@ impl ClientError {
 
 /// Represents the result of a client operation.
 pub type ClientResult<T> = Result<T, ClientError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::SystemTime;
+    use warg_crypto::{
+        hash::HashAlgorithm,
+        signing::{generate_p256_pair, PrivateKey},
+    };
+    use warg_protocol::ProtoEnvelope;
+
+    type TestClient = Client<FileSystemRegistryStorage, FileSystemContentStorage>;
+
+    fn operator_record(
+        signing_key: &PrivateKey,
+        operator: &mut OperatorInfo,
+        entries: Vec<operator::OperatorEntry>,
+    ) {
+        let record = ProtoEnvelope::signed_contents(
+            signing_key,
+            operator::OperatorRecord {
+                prev: operator.state.head().as_ref().map(|h| h.digest.clone()),
+                version: 0,
+                timestamp: SystemTime::now(),
+                entries,
+            },
+        )
+        .unwrap();
+        operator.state.validate(&record).unwrap();
+    }
+
+    fn checkpoint(signing_key: &PrivateKey) -> SerdeEnvelope<TimestampedCheckpoint> {
+        SerdeEnvelope::signed_contents(
+            signing_key,
+            TimestampedCheckpoint::now(Checkpoint {
+                log_length: 1,
+                log_root: HashAlgorithm::Sha256.digest(&[0]),
+                map_root: HashAlgorithm::Sha256.digest(&[1]),
+            })
+            .unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn verify(
+        operator: &mut OperatorInfo,
+        ts_checkpoint: &SerdeEnvelope<TimestampedCheckpoint>,
+    ) -> Result<(), ClientError> {
+        let checkpoint_id = Hash::<Sha256>::of(&ts_checkpoint.as_ref().checkpoint).into();
+        TestClient::verify_checkpoint_signature(operator, &checkpoint_id, ts_checkpoint)
+    }
+
+    #[test]
+    fn it_rejects_forged_checkpoints() {
+        let (alice_pub, alice_priv) = generate_p256_pair();
+        let (_, mallory_priv) = generate_p256_pair();
+        let mut operator = OperatorInfo::default();
+        operator_record(
+            &alice_priv,
+            &mut operator,
+            vec![operator::OperatorEntry::Init {
+                hash_algorithm: HashAlgorithm::Sha256,
+                key: alice_pub.clone(),
+            }],
+        );
+
+        // Signed by a key unknown to the operator log
+        let err = verify(&mut operator, &checkpoint(&mallory_priv)).unwrap_err();
+        assert!(matches!(
+            err,
+            ClientError::CheckpointSignatureInvalid { .. }
+        ));
+
+        // Claims the operator key but is signed by another key
+        let forged = checkpoint(&mallory_priv);
+        let forged = SerdeEnvelope::from_parts_unchecked(
+            forged.as_ref().clone(),
+            alice_pub.fingerprint(),
+            forged.signature().clone(),
+        );
+        let err = verify(&mut operator, &forged).unwrap_err();
+        assert!(matches!(
+            err,
+            ClientError::CheckpointSignatureInvalid { .. }
+        ));
+        assert!(operator.pinned_key_id.is_none());
+
+        verify(&mut operator, &checkpoint(&alice_priv)).unwrap();
+        assert_eq!(operator.pinned_key_id, Some(alice_pub.fingerprint()));
+    }
+
+    #[test]
+    fn it_checks_the_pinned_key() {
+        let (alice_pub, alice_priv) = generate_p256_pair();
+        let (bob_pub, bob_priv) = generate_p256_pair();
+        let mut operator = OperatorInfo::default();
+        operator_record(
+            &alice_priv,
+            &mut operator,
+            vec![
+                operator::OperatorEntry::Init {
+                    hash_algorithm: HashAlgorithm::Sha256,
+                    key: alice_pub.clone(),
+                },
+                operator::OperatorEntry::GrantFlat {
+                    key: bob_pub.clone(),
+                    permissions: vec![operator::Permission::Commit],
+                },
+            ],
+        );
+        verify(&mut operator, &checkpoint(&alice_priv)).unwrap();
+
+        // Another key with commit permission does not replace the pinned key
+        let err = verify(&mut operator, &checkpoint(&bob_priv)).unwrap_err();
+        assert!(matches!(
+            err,
+            ClientError::OperatorKeyMismatch { pinned, found }
+                if pinned == alice_pub.fingerprint() && found == bob_pub.fingerprint()
+        ));
+
+        // Once the pinned key's commit permission is revoked, the new key is pinned
+        operator_record(
+            &alice_priv,
+            &mut operator,
+            vec![operator::OperatorEntry::RevokeFlat {
+                key_id: alice_pub.fingerprint(),
+                permissions: vec![operator::Permission::Commit],
+            }],
+        );
+        verify(&mut operator, &checkpoint(&bob_priv)).unwrap();
+        assert_eq!(operator.pinned_key_id, Some(bob_pub.fingerprint()));
+        assert!(verify(&mut operator, &checkpoint(&alice_priv)).is_err());
+    }
+}
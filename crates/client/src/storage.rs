@@ -5,16 +5,16 @@ use async_trait::async_trait;
 use bytes::Bytes;
 use futures_util::Stream;
 use serde::{Deserialize, Serialize};
-use std::{path::PathBuf, pin::Pin, time::SystemTime};
+use std::{collections::HashMap, path::PathBuf, pin::Pin, time::SystemTime};
 use warg_crypto::{
     hash::{AnyHash, HashAlgorithm},
     signing::{self, KeyID, PublicKey},
 };
 use warg_protocol::{
     operator,
-    package::{self, PackageRecord, Permission, PACKAGE_RECORD_VERSION},
+    package::{self, PackageMetadata, PackageRecord, Permission, PACKAGE_RECORD_VERSION},
     registry::{Checkpoint, PackageId, RecordId, RegistryIndex, TimestampedCheckpoint},
-    ProtoEnvelope, SerdeEnvelope, Version,
+    Cosignature, ProtoEnvelope, SerdeEnvelope, Version, VersionReq,
 };
 
 mod fs;
@@ -88,18 +88,40 @@ pub trait ContentStorage: Send + Sync {
         digest: &AnyHash,
     ) -> Result<Option<Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>>>;
 
-    /// Stores the given stream as content.
+    /// Stores the given stream as content hashed with the given algorithm.
     ///
     /// If `expected_digest` is `Some`, the storage will verify that the written
-    /// content matches the given digest. If the digests do not match, an
-    /// error is returned.
+    /// content matches the given digest. If the digests do not match, an error
+    /// is returned.
     ///
     /// Returns the hash of the written content.
     async fn store_content(
         &self,
         stream: Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>,
+        algorithm: HashAlgorithm,
         expected_digest: Option<&AnyHash>,
     ) -> Result<AnyHash>;
+
+    /// Gets the number of bytes of partially stored content with the given digest.
+    ///
+    /// Returns `0` if no content with the given digest was partially stored.
+    fn partial_content_len(&self, digest: &AnyHash) -> u64;
+
+    /// Stores the given stream as content with the given digest, resuming
+    /// after the first `offset` bytes of partially stored content.
+    ///
+    /// If the stream fails, the bytes already received are kept so that the
+    /// content can be resumed later.
+    ///
+    /// Once the stream completes, the stored content is verified against the
+    /// digest. If the digests do not match, the partially stored content is
+    /// discarded and an error is returned.
+    async fn resume_content(
+        &self,
+        stream: Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>,
+        digest: &AnyHash,
+        offset: u64,
+    ) -> Result<()>;
 }
 
 /// Represents information about a registry operator.
@@ -112,6 +134,17 @@ pub struct OperatorInfo {
     /// The registry log index of the most recent record
     #[serde(default)]
     pub head_registry_index: Option<RegistryIndex>,
+    /// The ID of the operator key that signs checkpoints.
+    ///
+    /// The key is pinned on first use; checkpoints signed by a different
+    /// key are only trusted once the pinned key's commit permission has
+    /// been revoked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pinned_key_id: Option<KeyID>,
+    /// The registry log index of the operator record that defined each
+    /// namespace.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub namespace_indices: HashMap<String, RegistryIndex>,
 }
 
 /// Represents information about a registry package.
@@ -141,6 +174,13 @@ impl PackageInfo {
             head_registry_index: None,
         }
     }
+
+    /// Gets the current metadata of the package.
+    ///
+    /// Returns `None` if the package log has not set any metadata.
+    pub fn metadata(&self) -> Option<&PackageMetadata> {
+        self.state.metadata()
+    }
 }
 
 /// Represents a record entry being published.
@@ -148,7 +188,11 @@ impl PackageInfo {
 #[serde(tag = "type", rename_all = "camelCase")]
 pub enum PublishEntry {
     /// The package is being initialized.
-    Init,
+    Init {
+        /// The hash algorithm the package log will use.
+        #[serde(default)]
+        hash_algorithm: HashAlgorithm,
+    },
     /// A new release entry is being published.
     Release {
         /// The version of the release.
@@ -161,12 +205,20 @@ pub enum PublishEntry {
         /// The version of the release being yanked.
         version: Version,
     },
+    /// A yanked release is being unyanked.
+    Unyank {
+        /// The version of the release being unyanked.
+        version: Version,
+    },
     /// A key is being granted permission(s).
     Grant {
         /// The public key being granted to.
         key: PublicKey,
         /// The permission(s) being granted.
         permissions: Vec<Permission>,
+        /// The version requirement limiting the grant, if any.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        scope: Option<VersionReq>,
     },
     /// A key's permission(s) are being revoked.
     Revoke {
@@ -175,6 +227,25 @@ pub enum PublishEntry {
         /// The permission(s) being revoked.
         permissions: Vec<Permission>,
     },
+    /// A key's permissions are being transferred to a new key.
+    RotateKey {
+        /// The key ID being rotated.
+        key_id: KeyID,
+        /// The public key receiving the permissions.
+        key: PublicKey,
+    },
+    /// The package metadata is being set.
+    Metadata {
+        /// The new metadata of the package.
+        metadata: PackageMetadata,
+    },
+    /// The signature threshold of permission(s) is being set.
+    Threshold {
+        /// The permission(s) the threshold applies to.
+        permissions: Vec<Permission>,
+        /// The number of distinct keys that must sign records using the permission(s).
+        threshold: u32,
+    },
 }
 
 /// Represents information about a package publish.
@@ -190,24 +261,66 @@ pub struct PublishInfo {
     pub head: Option<RecordId>,
     /// The new record entries to publish.
     pub entries: Vec<PublishEntry>,
+    /// The timestamp of the record to publish.
+    ///
+    /// If `None`, the current time is used when the record is finalized.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<SystemTime>,
+    /// The co-signatures collected for the record to publish.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cosignatures: Vec<Cosignature>,
 }
 
 impl PublishInfo {
+    /// Creates publish information for the given entries.
+    ///
+    /// The record is timestamped when finalized and has no co-signatures.
+    pub fn new(id: PackageId, head: Option<RecordId>, entries: Vec<PublishEntry>) -> Self {
+        Self {
+            id,
+            head,
+            entries,
+            timestamp: None,
+            cosignatures: Vec::new(),
+        }
+    }
+
     /// Determines if the publish information is initializing the package.
     pub fn initializing(&self) -> bool {
-        self.entries.iter().any(|e| matches!(e, PublishEntry::Init))
+        self.entries
+            .iter()
+            .any(|e| matches!(e, PublishEntry::Init { .. }))
+    }
+
+    /// Co-signs the record to publish with the given key.
+    ///
+    /// This fixes the timestamp of the record so that every co-signature
+    /// covers the same record; the head must already be known.
+    pub(crate) fn cosign(&mut self, signing_key: &signing::PrivateKey) -> Result<()> {
+        self.timestamp.get_or_insert_with(SystemTime::now);
+
+        let cosignature = Cosignature::new(signing_key, &self.record(signing_key))?;
+        self.cosignatures.retain(|c| c.key_id != cosignature.key_id);
+        self.cosignatures.push(cosignature);
+        Ok(())
     }
 
     pub(crate) fn finalize(
         self,
         signing_key: &signing::PrivateKey,
     ) -> Result<ProtoEnvelope<PackageRecord>> {
+        let record = self.record(signing_key);
+        Ok(ProtoEnvelope::signed_contents(signing_key, record)?
+            .with_cosignatures(self.cosignatures))
+    }
+
+    fn record(&self, signing_key: &signing::PrivateKey) -> PackageRecord {
         let mut entries = Vec::with_capacity(self.entries.len());
-        for entry in self.entries {
+        for entry in self.entries.iter().cloned() {
             match entry {
-                PublishEntry::Init => {
+                PublishEntry::Init { hash_algorithm } => {
                     entries.push(package::PackageEntry::Init {
-                        hash_algorithm: HashAlgorithm::Sha256,
+                        hash_algorithm,
                         key: signing_key.public_key(),
                     });
                 }
@@ -217,9 +330,23 @@ impl PublishInfo {
                 PublishEntry::Yank { version } => {
                     entries.push(package::PackageEntry::Yank { version })
                 }
-                PublishEntry::Grant { key, permissions } => {
-                    entries.push(package::PackageEntry::GrantFlat { key, permissions })
+                PublishEntry::Unyank { version } => {
+                    entries.push(package::PackageEntry::Unyank { version })
                 }
+                PublishEntry::Grant {
+                    key,
+                    permissions,
+                    scope: None,
+                } => entries.push(package::PackageEntry::GrantFlat { key, permissions }),
+                PublishEntry::Grant {
+                    key,
+                    permissions,
+                    scope: Some(scope),
+                } => entries.push(package::PackageEntry::GrantScoped {
+                    key,
+                    permissions,
+                    scope,
+                }),
                 PublishEntry::Revoke {
                     key_id,
                     permissions,
@@ -227,19 +354,30 @@ impl PublishInfo {
                     key_id,
                     permissions,
                 }),
+                PublishEntry::RotateKey { key_id, key } => {
+                    entries.push(package::PackageEntry::RotateKey { key_id, key })
+                }
+                PublishEntry::Metadata { metadata } => {
+                    entries.push(package::PackageEntry::Metadata { metadata })
+                }
+                PublishEntry::Threshold {
+                    permissions,
+                    threshold,
+                } => entries.push(package::PackageEntry::SetThreshold {
+                    permissions,
+                    threshold,
+                }),
             }
         }
 
-        let record = package::PackageRecord {
-            prev: self.head,
+        package::PackageRecord {
+            prev: self.head.clone(),
             version: PACKAGE_RECORD_VERSION,
             // TODO: this seems wrong to record the current time client-side
             // How can we guarantee that the timestamps are monotonic?
             // Should incrementing timestamps even be a requirement?
-            timestamp: SystemTime::now(),
+            timestamp: self.timestamp.unwrap_or_else(SystemTime::now),
             entries,
-        };
-
-        Ok(ProtoEnvelope::signed_contents(signing_key, record)?)
+        }
     }
 }
@@ -14,16 +14,17 @@ use std::{
     pin::Pin,
 };
 use tempfile::NamedTempFile;
-use tokio::io::{AsyncWriteExt, BufReader, BufWriter};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt, BufReader, BufWriter};
 use tokio_util::io::ReaderStream;
 use walkdir::WalkDir;
-use warg_crypto::hash::{AnyHash, Digest, Hash, Sha256};
+use warg_crypto::hash::{AnyHash, HashAlgorithm, Sha256};
 use warg_protocol::{
     registry::{LogId, PackageId, TimestampedCheckpoint},
     SerdeEnvelope,
 };
 
 const TEMP_DIRECTORY: &str = "temp";
+const PARTIAL_DIRECTORY: &str = "partial";
 const PENDING_PUBLISH_FILE: &str = "pending-publish.json";
 const LOCK_FILE_NAME: &str = ".lock";
 const PACKAGE_LOGS_DIR: &str = "package-logs";
@@ -227,6 +228,37 @@ impl FileSystemContentStorage {
     fn content_path(&self, digest: &AnyHash) -> PathBuf {
         self.base_dir.join(digest.to_string().replace(':', "/"))
     }
+
+    fn partial_content_path(&self, digest: &AnyHash) -> PathBuf {
+        self.temp_dir
+            .join(PARTIAL_DIRECTORY)
+            .join(digest.to_string().replace(':', "-"))
+    }
+
+    fn persist_content(&self, from: &Path, digest: &AnyHash) -> Result<()> {
+        let content_path = self.content_path(digest);
+        if content_path.is_file() {
+            return fs::remove_file(from)
+                .with_context(|| format!("failed to remove `{path}`", path = from.display()));
+        }
+
+        if let Some(parent) = content_path.parent() {
+            fs::create_dir_all(parent).with_context(|| {
+                format!(
+                    "failed to create directory `{path}`",
+                    path = parent.display()
+                )
+            })?;
+        }
+
+        fs::rename(from, &content_path).with_context(|| {
+            format!(
+                "failed to move `{from}` to `{to}`",
+                from = from.display(),
+                to = content_path.display()
+            )
+        })
+    }
 }
 
 #[async_trait]
@@ -262,11 +294,12 @@ impl ContentStorage for FileSystemContentStorage {
     async fn store_content(
         &self,
         mut stream: Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>,
+        algorithm: HashAlgorithm,
         expected_digest: Option<&AnyHash>,
     ) -> Result<AnyHash> {
         let (file, path) = self.temp_file()?.into_parts();
         let mut writer = BufWriter::new(tokio::fs::File::from_std(file));
-        let mut hasher = Sha256::new();
+        let mut hasher = algorithm.hasher();
 
         while let Some(bytes) = stream.next().await.transpose()? {
             hasher.update(&bytes);
@@ -276,7 +309,7 @@ impl ContentStorage for FileSystemContentStorage {
                 .with_context(|| format!("failed to write to `{path}`", path = path.display()))?;
         }
 
-        let hash = AnyHash::from(Hash::<Sha256>::from(hasher.finalize()));
+        let hash = hasher.finalize();
 
         if let Some(expected) = expected_digest {
             if hash != *expected {
@@ -314,6 +347,108 @@ impl ContentStorage for FileSystemContentStorage {
 
         Ok(hash)
     }
+
+    fn partial_content_len(&self, digest: &AnyHash) -> u64 {
+        fs::metadata(self.partial_content_path(digest))
+            .map(|m| m.len())
+            .unwrap_or(0)
+    }
+
+    async fn resume_content(
+        &self,
+        mut stream: Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>,
+        digest: &AnyHash,
+        offset: u64,
+    ) -> Result<()> {
+        let path = self.partial_content_path(digest);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).with_context(|| {
+                format!(
+                    "failed to create directory `{path}`",
+                    path = parent.display()
+                )
+            })?;
+        }
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .read(true)
+            .write(true)
+            .truncate(false)
+            .open(&path)
+            .await
+            .with_context(|| format!("failed to open `{path}`", path = path.display()))?;
+
+        let len = file
+            .metadata()
+            .await
+            .with_context(|| format!("failed to read metadata of `{path}`", path = path.display()))?
+            .len();
+        if len < offset {
+            bail!(
+                "cannot resume content at offset {offset} as `{path}` has a length of {len}",
+                path = path.display()
+            );
+        }
+
+        // Discard anything past the offset and append from there
+        file.set_len(offset)
+            .await
+            .with_context(|| format!("failed to truncate `{path}`", path = path.display()))?;
+        file.seek(std::io::SeekFrom::Start(offset))
+            .await
+            .with_context(|| format!("failed to seek `{path}`", path = path.display()))?;
+
+        let mut writer = BufWriter::new(file);
+        let result = async {
+            while let Some(bytes) = stream.next().await.transpose()? {
+                writer.write_all(&bytes).await.with_context(|| {
+                    format!("failed to write to `{path}`", path = path.display())
+                })?;
+            }
+
+            anyhow::Ok(())
+        }
+        .await;
+
+        // Keep what was received, even if the stream failed
+        writer
+            .flush()
+            .await
+            .with_context(|| format!("failed to write `{path}`", path = path.display()))?;
+        result?;
+
+        let mut file = writer.into_inner();
+        file.rewind()
+            .await
+            .with_context(|| format!("failed to seek `{path}`", path = path.display()))?;
+
+        let mut hasher = digest.algorithm().hasher();
+        let mut reader = BufReader::new(file);
+        let mut buffer = vec![0; 8192];
+        loop {
+            let n = reader
+                .read(&mut buffer)
+                .await
+                .with_context(|| format!("failed to read `{path}`", path = path.display()))?;
+            if n == 0 {
+                break;
+            }
+
+            hasher.update(&buffer[..n]);
+        }
+
+        drop(reader);
+
+        let hash = hasher.finalize();
+        if hash != *digest {
+            fs::remove_file(&path)
+                .with_context(|| format!("failed to remove `{path}`", path = path.display()))?;
+            bail!("stored content has digest `{hash}` but a digest of `{digest}` was expected");
+        }
+
+        self.persist_content(&path, digest)
+    }
 }
 
 async fn load<T: for<'a> Deserialize<'a>>(path: &Path) -> Result<Option<T>> {
@@ -1,4 +1,5 @@
-use super::{Digest, HashAlgorithm, Sha256};
+use super::{Blake3, Digest, Hash, HashAlgorithm, Sha256, Sha512};
+use crate::VisitBytes;
 use anyhow::Error;
 use serde::{Deserialize, Serialize};
 use std::{fmt, ops::Deref, str::FromStr};
@@ -6,18 +7,24 @@ use thiserror::Error;
 
 pub enum Hasher {
     Sha256(Sha256),
+    Sha512(Sha512),
+    Blake3(Box<Blake3>),
 }
 
 impl Hasher {
     pub fn update(&mut self, bytes: &[u8]) {
         match self {
-            Self::Sha256(d) => d.update(bytes),
+            Self::Sha256(d) => Digest::update(d, bytes),
+            Self::Sha512(d) => Digest::update(d, bytes),
+            Self::Blake3(d) => Digest::update(d.as_mut(), bytes),
         }
     }
 
     pub fn finalize(self) -> AnyHash {
         let (algo, bytes) = match self {
             Self::Sha256(d) => (HashAlgorithm::Sha256, d.finalize().deref().into()),
+            Self::Sha512(d) => (HashAlgorithm::Sha512, d.finalize().deref().into()),
+            Self::Blake3(d) => (HashAlgorithm::Blake3, Digest::finalize(*d).deref().into()),
         };
 
         AnyHash { algo, bytes }
@@ -28,21 +35,23 @@ impl HashAlgorithm {
     pub fn hasher(&self) -> Hasher {
         match self {
             HashAlgorithm::Sha256 => Hasher::Sha256(Sha256::new()),
+            HashAlgorithm::Sha512 => Hasher::Sha512(Sha512::new()),
+            HashAlgorithm::Blake3 => Hasher::Blake3(Box::default()),
         }
     }
 
     pub fn digest(&self, content_bytes: &[u8]) -> AnyHash {
-        let hash_bytes: Vec<u8> = match self {
-            HashAlgorithm::Sha256 => {
-                let mut d = Sha256::new();
-                d.update(content_bytes);
-                d.finalize().deref().into()
-            }
-        };
+        let mut hasher = self.hasher();
+        hasher.update(content_bytes);
+        hasher.finalize()
+    }
 
-        AnyHash {
-            algo: *self,
-            bytes: hash_bytes,
+    /// Hashes the given content with this algorithm in the same manner as `Hash::of`.
+    pub fn hash_of(&self, content: impl VisitBytes) -> AnyHash {
+        match self {
+            HashAlgorithm::Sha256 => Hash::<Sha256>::of(content).into(),
+            HashAlgorithm::Sha512 => Hash::<Sha512>::of(content).into(),
+            HashAlgorithm::Blake3 => Hash::<Blake3>::of(content).into(),
         }
     }
 }
@@ -139,6 +148,43 @@ mod tests {
         assert_eq!(output, expected)
     }
 
+    #[test]
+    fn test_sha512_labeled_digest() {
+        let input = b"The quick brown fox jumped over the lazy dog";
+        let output = HashAlgorithm::Sha512.digest(input);
+        let output = format!("{}", output);
+
+        let expected = "sha512:db25330cfa5d14eaadf11a6263371cfa0e70fcd7a63a433b91f2300ca25d45b66a7b50d2f6747995c8fa0ff365b28974792e7acd5624e1ddd0d66731f346f0e7";
+
+        assert_eq!(output, expected)
+    }
+
+    #[test]
+    fn test_blake3_labeled_digest() {
+        let output = HashAlgorithm::Blake3.digest(b"");
+        let output = format!("{}", output);
+
+        let expected = "blake3:af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262";
+
+        assert_eq!(output, expected)
+    }
+
+    #[test]
+    fn test_hasher_matches_digest() {
+        let input = b"The quick brown fox jumped over the lazy dog";
+        for algorithm in [
+            HashAlgorithm::Sha256,
+            HashAlgorithm::Sha512,
+            HashAlgorithm::Blake3,
+        ] {
+            let mut hasher = algorithm.hasher();
+            for chunk in input.chunks(7) {
+                hasher.update(chunk);
+            }
+            assert_eq!(hasher.finalize(), algorithm.digest(input));
+        }
+    }
+
     #[test]
     fn test_labeled_digest_parse_rejects_uppercase() {
         let digest_str = "sha256:7d38b5cd25a2baf85ad3bb5b9311383e671a8a142eb302b324d4a5fba8748c69";
@@ -154,5 +200,12 @@ mod tests {
         let input = "sha256:7d38b5cd25a2baf85ad3bb5b9311383e671a8a142eb302b324d4a5fba8748c69";
         let output = format!("{}", input.parse::<AnyHash>().unwrap());
         assert_eq!(input, &output);
+
+        for algorithm in [HashAlgorithm::Sha512, HashAlgorithm::Blake3] {
+            let input = algorithm.digest(b"foo").to_string();
+            let hash = input.parse::<AnyHash>().unwrap();
+            assert_eq!(hash.algorithm(), algorithm);
+            assert_eq!(input, hash.to_string());
+        }
     }
 }
@@ -4,29 +4,38 @@ use serde::{Deserialize, Serialize};
 use std::{fmt, str::FromStr};
 use thiserror::Error;
 
+mod blake3;
 mod dynamic;
 mod r#static;
 
+pub use self::blake3::Blake3;
 pub use digest::{Digest, Output};
 pub use dynamic::{AnyHash, AnyHashError};
 pub use r#static::Hash;
-pub use sha2::Sha256;
+pub use sha2::{Sha256, Sha512};
 
 use crate::VisitBytes;
 
 use self::r#static::IncorrectLengthError;
 
-#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[derive(
+    Clone, Copy, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize,
+)]
 #[serde(rename_all = "camelCase")]
 #[non_exhaustive]
 pub enum HashAlgorithm {
+    #[default]
     Sha256,
+    Sha512,
+    Blake3,
 }
 
 impl fmt::Display for HashAlgorithm {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             HashAlgorithm::Sha256 => write!(f, "sha256"),
+            HashAlgorithm::Sha512 => write!(f, "sha512"),
+            HashAlgorithm::Blake3 => write!(f, "blake3"),
         }
     }
 }
@@ -37,13 +46,19 @@ impl FromStr for HashAlgorithm {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "sha256" => Ok(HashAlgorithm::Sha256),
+            "sha512" => Ok(HashAlgorithm::Sha512),
+            "blake3" => Ok(HashAlgorithm::Blake3),
             _ => Err(Error::msg(format!("Illegal hash algorithm '{}'", s))),
         }
     }
 }
 
-static EMPTY_TREE_HASH: Lazy<Vec<Hash<Sha256>>> = Lazy::new(|| {
-    let mut v: Vec<Hash<Sha256>> = Vec::with_capacity(257);
+static SHA256_EMPTY_TREE_HASH: Lazy<Vec<Hash<Sha256>>> = Lazy::new(empty_tree_hashes);
+static SHA512_EMPTY_TREE_HASH: Lazy<Vec<Hash<Sha512>>> = Lazy::new(empty_tree_hashes);
+static BLAKE3_EMPTY_TREE_HASH: Lazy<Vec<Hash<Blake3>>> = Lazy::new(empty_tree_hashes);
+
+fn empty_tree_hashes<D: SupportedDigest>() -> Vec<Hash<D>> {
+    let mut v: Vec<Hash<D>> = Vec::with_capacity(257);
     fn empty_tree_hash<D: SupportedDigest>(v: &mut Vec<Hash<D>>, height: u32) -> Hash<D> {
         let hash: Hash<D> = if height == 0 {
             hash_empty()
@@ -56,7 +71,7 @@ static EMPTY_TREE_HASH: Lazy<Vec<Hash<Sha256>>> = Lazy::new(|| {
     }
     empty_tree_hash(&mut v, 256);
     v
-});
+}
 
 // If updating this function, also update `hash_empty` in transparency map
 pub(crate) fn hash_empty<D: SupportedDigest>() -> Hash<D> {
@@ -88,15 +103,31 @@ pub trait SupportedDigest: Digest + private::Sealed + Default + Sized + 'static
 impl SupportedDigest for Sha256 {
     const ALGORITHM: HashAlgorithm = HashAlgorithm::Sha256;
     fn empty_tree_hash(height: usize) -> &'static Hash<Sha256> {
-        &EMPTY_TREE_HASH[height]
+        &SHA256_EMPTY_TREE_HASH[height]
+    }
+}
+
+impl SupportedDigest for Sha512 {
+    const ALGORITHM: HashAlgorithm = HashAlgorithm::Sha512;
+    fn empty_tree_hash(height: usize) -> &'static Hash<Sha512> {
+        &SHA512_EMPTY_TREE_HASH[height]
+    }
+}
+
+impl SupportedDigest for Blake3 {
+    const ALGORITHM: HashAlgorithm = HashAlgorithm::Blake3;
+    fn empty_tree_hash(height: usize) -> &'static Hash<Blake3> {
+        &BLAKE3_EMPTY_TREE_HASH[height]
     }
 }
 
 mod private {
-    use sha2::Sha256;
+    use sha2::{Sha256, Sha512};
 
     pub trait Sealed {}
     impl Sealed for Sha256 {}
+    impl Sealed for Sha512 {}
+    impl Sealed for super::Blake3 {}
 }
 
 impl<D: SupportedDigest> From<Hash<D>> for AnyHash {
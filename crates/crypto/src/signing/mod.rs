@@ -17,6 +17,7 @@ pub use self::signature::{Signature, SignatureParseError};
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub enum SignatureAlgorithm {
     EcdsaP256,
+    Ed25519,
 }
 
 impl SignatureAlgorithm {
@@ -24,7 +25,7 @@ impl SignatureAlgorithm {
     /// signing algorithm to generate digests.
     pub fn digest_algorithm(&self) -> HashAlgorithm {
         match self {
-            SignatureAlgorithm::EcdsaP256 => HashAlgorithm::Sha256,
+            SignatureAlgorithm::EcdsaP256 | SignatureAlgorithm::Ed25519 => HashAlgorithm::Sha256,
         }
     }
 }
@@ -33,6 +34,7 @@ impl fmt::Display for SignatureAlgorithm {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             SignatureAlgorithm::EcdsaP256 => write!(f, "ecdsa-p256"),
+            SignatureAlgorithm::Ed25519 => write!(f, "ed25519"),
         }
     }
 }
@@ -43,6 +45,7 @@ impl FromStr for SignatureAlgorithm {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "ecdsa-p256" => Ok(SignatureAlgorithm::EcdsaP256),
+            "ed25519" => Ok(SignatureAlgorithm::Ed25519),
             _ => Err(SignatureAlgorithmParseError {
                 value: s.to_owned(),
             }),
@@ -56,12 +59,28 @@ pub struct SignatureAlgorithmParseError {
     value: String,
 }
 
+/// Generate an ECDSA P-256 key pair.
 pub fn generate_p256_pair() -> (PublicKey, PrivateKey) {
     let private_key = p256::ecdsa::SigningKey::random(&mut OsRng);
     let public_key = p256::ecdsa::VerifyingKey::from(&private_key);
     (PublicKey::from(public_key), PrivateKey::from(private_key))
 }
 
+/// Generate an Ed25519 key pair.
+pub fn generate_ed25519_pair() -> (PublicKey, PrivateKey) {
+    let private_key = ed25519_dalek::SigningKey::generate(&mut OsRng);
+    let public_key = private_key.verifying_key();
+    (PublicKey::from(public_key), PrivateKey::from(private_key))
+}
+
+/// Generate a key pair for the given signature algorithm.
+pub fn generate_pair(algorithm: &SignatureAlgorithm) -> (PublicKey, PrivateKey) {
+    match algorithm {
+        SignatureAlgorithm::EcdsaP256 => generate_p256_pair(),
+        SignatureAlgorithm::Ed25519 => generate_ed25519_pair(),
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
@@ -74,6 +93,27 @@ pub mod tests {
         public.verify(&msg, &signature).unwrap();
     }
 
+    #[test]
+    pub fn test_correct_ed25519_key_passes_verify() {
+        let (public, private) = generate_ed25519_pair();
+        let msg = (0..255u8).collect::<Vec<u8>>();
+        let signature = private.sign(&msg).unwrap();
+        public.verify(&msg, &signature).unwrap();
+    }
+
+    #[test]
+    pub fn test_mismatched_algorithm_fails_verify() {
+        let (p256_public, p256_private) = generate_p256_pair();
+        let (ed25519_public, ed25519_private) = generate_ed25519_pair();
+
+        let msg = (0..255u8).collect::<Vec<u8>>();
+        let p256_signature = p256_private.sign(&msg).unwrap();
+        let ed25519_signature = ed25519_private.sign(&msg).unwrap();
+
+        assert!(ed25519_public.verify(&msg, &p256_signature).is_err());
+        assert!(p256_public.verify(&msg, &ed25519_signature).is_err());
+    }
+
     #[test]
     pub fn test_wrong_key_fails_verify() {
         let (alice_public, alice_private) = generate_p256_pair();
@@ -1,15 +1,16 @@
 use super::{Signature, SignatureAlgorithm, SignatureAlgorithmParseError};
 use base64::{engine::general_purpose::STANDARD, Engine};
-use core::fmt;
+use core::{cmp::Ordering, fmt};
 use p256;
 use serde::{Deserialize, Serialize};
 use signature::{Error as SignatureError, Verifier};
 use std::str::FromStr;
 use thiserror::Error;
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PublicKey {
     EcdsaP256(p256::ecdsa::VerifyingKey),
+    Ed25519(ed25519_dalek::VerifyingKey),
 }
 
 impl PublicKey {
@@ -17,6 +18,7 @@ impl PublicKey {
     pub fn signature_algorithm(&self) -> SignatureAlgorithm {
         match self {
             PublicKey::EcdsaP256(_) => SignatureAlgorithm::EcdsaP256,
+            PublicKey::Ed25519(_) => SignatureAlgorithm::Ed25519,
         }
     }
 
@@ -24,6 +26,7 @@ impl PublicKey {
     pub fn bytes(&self) -> Vec<u8> {
         match self {
             PublicKey::EcdsaP256(key) => key.to_encoded_point(true).as_bytes().to_vec(),
+            PublicKey::Ed25519(key) => key.to_bytes().to_vec(),
         }
     }
 
@@ -31,6 +34,9 @@ impl PublicKey {
     pub fn verify(&self, msg: &[u8], signature: &Signature) -> Result<(), SignatureError> {
         match (self, signature) {
             (PublicKey::EcdsaP256(key), Signature::P256(signature)) => key.verify(msg, signature),
+            (PublicKey::Ed25519(key), Signature::Ed25519(signature)) => key.verify(msg, signature),
+            // A signature made with a different algorithm cannot have been made by this key
+            _ => Err(SignatureError::new()),
         }
     }
 
@@ -45,6 +51,23 @@ impl PublicKey {
     }
 }
 
+impl PartialOrd for PublicKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PublicKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (PublicKey::EcdsaP256(a), PublicKey::EcdsaP256(b)) => a.cmp(b),
+            (PublicKey::Ed25519(a), PublicKey::Ed25519(b)) => a.as_bytes().cmp(b.as_bytes()),
+            (PublicKey::EcdsaP256(_), PublicKey::Ed25519(_)) => Ordering::Less,
+            (PublicKey::Ed25519(_), PublicKey::EcdsaP256(_)) => Ordering::Greater,
+        }
+    }
+}
+
 impl fmt::Display for PublicKey {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -71,6 +94,9 @@ impl FromStr for PublicKey {
             SignatureAlgorithm::EcdsaP256 => {
                 PublicKey::EcdsaP256(p256::ecdsa::VerifyingKey::from_sec1_bytes(&bytes)?)
             }
+            SignatureAlgorithm::Ed25519 => {
+                PublicKey::Ed25519(ed25519_dalek::VerifyingKey::try_from(bytes.as_slice())?)
+            }
         };
 
         Ok(key)
@@ -116,6 +142,12 @@ impl From<p256::ecdsa::VerifyingKey> for PublicKey {
     }
 }
 
+impl From<ed25519_dalek::VerifyingKey> for PublicKey {
+    fn from(key: ed25519_dalek::VerifyingKey) -> Self {
+        PublicKey::Ed25519(key)
+    }
+}
+
 #[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct KeyID(String);
@@ -156,4 +188,12 @@ mod tests {
         let pub_key: PublicKey = key_str.parse().unwrap();
         assert_eq!(key_str, &format!("{pub_key}"));
     }
+
+    #[test]
+    fn test_roundtrip_ed25519() {
+        let key_str = "ed25519:11qYAYKxCrfVS/7TyWQHOg7hcvPapiMlrwIaaPcHURo=";
+        let pub_key: PublicKey = key_str.parse().unwrap();
+        assert_eq!(pub_key.signature_algorithm(), SignatureAlgorithm::Ed25519);
+        assert_eq!(key_str, &format!("{pub_key}"));
+    }
 }
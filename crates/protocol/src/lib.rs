@@ -9,7 +9,8 @@ pub mod registry;
 mod serde_envelope;
 
 pub use proto_envelope::{
-    ProtoEnvelope, ProtoEnvelopeBody, PublishedProtoEnvelope, PublishedProtoEnvelopeBody,
+    Cosignature, ProtoEnvelope, ProtoEnvelopeBody, PublishedProtoEnvelope,
+    PublishedProtoEnvelopeBody,
 };
 pub use semver::{Version, VersionReq};
 pub use serde_envelope::SerdeEnvelope;
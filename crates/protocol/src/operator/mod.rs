@@ -9,8 +9,8 @@ use crate::{pbjson_to_prost_timestamp, prost_to_pbjson_timestamp, registry::Reco
 mod model;
 mod state;
 
-pub use model::{OperatorEntry, OperatorRecord};
-pub use state::{LogState, ValidationError};
+pub use model::{OperatorEntry, OperatorRecord, Permission};
+pub use state::{LogState, NamespaceState, ValidationError};
 
 /// The currently supported operator protocol version.
 pub const OPERATOR_RECORD_VERSION: u32 = 0;
@@ -83,6 +83,18 @@ impl TryFrom<protobuf::OperatorEntry> for model::OperatorEntry {
                     .map(TryInto::try_into)
                     .collect::<Result<_, _>>()?,
             },
+            Contents::RotateKey(rotate_key) => model::OperatorEntry::RotateKey {
+                key_id: rotate_key.key_id.into(),
+                key: rotate_key.key.parse()?,
+            },
+            Contents::DefineNamespace(define) => model::OperatorEntry::DefineNamespace {
+                namespace: define.namespace,
+                owners: define.owner_key_ids.into_iter().map(Into::into).collect(),
+            },
+            Contents::ImportNamespace(import) => model::OperatorEntry::ImportNamespace {
+                namespace: import.namespace,
+                registry: import.registry,
+            },
         };
         Ok(output)
     }
@@ -161,6 +173,25 @@ impl<'a> From<&'a model::OperatorEntry> for protobuf::OperatorEntry {
                 key_id: key_id.to_string(),
                 permissions: permissions.iter().map(Into::into).collect(),
             }),
+            model::OperatorEntry::RotateKey { key_id, key } => {
+                Contents::RotateKey(protobuf::OperatorRotateKey {
+                    key_id: key_id.to_string(),
+                    key: key.to_string(),
+                })
+            }
+            model::OperatorEntry::DefineNamespace { namespace, owners } => {
+                Contents::DefineNamespace(protobuf::OperatorDefineNamespace {
+                    namespace: namespace.clone(),
+                    owner_key_ids: owners.iter().map(ToString::to_string).collect(),
+                })
+            }
+            model::OperatorEntry::ImportNamespace {
+                namespace,
+                registry,
+            } => Contents::ImportNamespace(protobuf::OperatorImportNamespace {
+                namespace: namespace.clone(),
+                registry: registry.clone(),
+            }),
         };
         let contents = Some(contents);
         protobuf::OperatorEntry { contents }
@@ -190,6 +221,7 @@ mod tests {
     fn test_envelope_roundtrip() {
         let (alice_pub, alice_priv) = generate_p256_pair();
         let (bob_pub, _bob_priv) = generate_p256_pair();
+        let (carol_pub, _carol_priv) = generate_p256_pair();
 
         let record = model::OperatorRecord {
             prev: None,
@@ -208,6 +240,18 @@ mod tests {
                     key_id: bob_pub.fingerprint(),
                     permissions: vec![model::Permission::Commit],
                 },
+                model::OperatorEntry::RotateKey {
+                    key_id: bob_pub.fingerprint(),
+                    key: carol_pub,
+                },
+                model::OperatorEntry::DefineNamespace {
+                    namespace: "wasi".to_string(),
+                    owners: vec![bob_pub.fingerprint()],
+                },
+                model::OperatorEntry::ImportNamespace {
+                    namespace: "acme".to_string(),
+                    registry: "registry.acme.example".to_string(),
+                },
             ],
         };
 
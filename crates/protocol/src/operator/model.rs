@@ -49,12 +49,12 @@ impl fmt::Display for Permission {
 }
 
 impl FromStr for Permission {
-    type Err = ();
+    type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "commit" => Ok(Permission::Commit),
-            _ => Err(()),
+            _ => Err(format!("invalid permission {s:?}")),
         }
     }
 }
@@ -82,14 +82,35 @@ pub enum OperatorEntry {
         key_id: signing::KeyID,
         permissions: Vec<Permission>,
     },
+    /// Transfer all permissions of a key to a new key.
+    /// The author of this entry must be the key being rotated
+    /// or have every permission being transferred.
+    RotateKey {
+        key_id: signing::KeyID,
+        key: signing::PublicKey,
+    },
+    /// Define a namespace hosted by this registry.
+    /// Only the owner keys may initialize packages in the namespace.
+    /// The author of this entry must have the commit permission.
+    DefineNamespace {
+        namespace: String,
+        owners: Vec<signing::KeyID>,
+    },
+    /// Mark a namespace as imported from another registry.
+    /// Packages in the namespace may not be initialized in this registry.
+    /// The author of this entry must have the commit permission.
+    ImportNamespace { namespace: String, registry: String },
 }
 
 impl OperatorEntry {
     /// Check permission is required to submit this entry
     pub fn required_permission(&self) -> Option<Permission> {
         match self {
-            Self::Init { .. } => None,
-            Self::GrantFlat { .. } | Self::RevokeFlat { .. } => Some(Permission::Commit),
+            Self::Init { .. } | Self::RotateKey { .. } => None,
+            Self::GrantFlat { .. }
+            | Self::RevokeFlat { .. }
+            | Self::DefineNamespace { .. }
+            | Self::ImportNamespace { .. } => Some(Permission::Commit),
         }
     }
 }
@@ -7,6 +7,7 @@ use std::time::SystemTime;
 use thiserror::Error;
 use warg_crypto::hash::{HashAlgorithm, Sha256};
 use warg_crypto::{signing, Signable};
+use wasmparser::names::KebabStr;
 
 #[derive(Error, Debug)]
 pub enum ValidationError {
@@ -34,6 +35,18 @@ pub enum ValidationError {
         key_id: signing::KeyID,
     },
 
+    #[error("attempted to rotate key {key_id} which has no permissions")]
+    RotateKeyWithoutPermissions { key_id: signing::KeyID },
+
+    #[error("attempted to rotate key {key_id} to itself")]
+    RotateKeyToSelf { key_id: signing::KeyID },
+
+    #[error("namespace `{namespace}` is not a legal kebab-case identifier")]
+    InvalidNamespace { namespace: String },
+
+    #[error("namespace `{namespace}` is already defined")]
+    NamespaceAlreadyDefined { namespace: String },
+
     #[error("unable to verify signature: {0}")]
     SignatureError(#[from] signing::SignatureError),
 
@@ -72,6 +85,24 @@ pub struct Head {
     pub timestamp: SystemTime,
 }
 
+/// The state of a namespace defined in the operator log.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "state", rename_all = "camelCase")]
+pub enum NamespaceState {
+    /// The namespace is hosted by this registry.
+    #[serde(rename_all = "camelCase")]
+    Defined {
+        /// The keys permitted to initialize packages in the namespace.
+        owners: IndexSet<signing::KeyID>,
+    },
+    /// The namespace is imported from another registry.
+    #[serde(rename_all = "camelCase")]
+    Imported {
+        /// The registry the namespace is imported from.
+        registry: String,
+    },
+}
+
 /// Calculated state for an operator log.
 #[derive(Default, Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(default, rename_all = "camelCase")]
@@ -89,6 +120,9 @@ pub struct LogState {
     /// The keys known to the validator.
     #[serde(skip_serializing_if = "IndexMap::is_empty")]
     keys: IndexMap<signing::KeyID, signing::PublicKey>,
+    /// The namespaces defined in the log.
+    #[serde(skip_serializing_if = "IndexMap::is_empty")]
+    namespaces: IndexMap<String, NamespaceState>,
 }
 
 impl LogState {
@@ -132,6 +166,25 @@ impl LogState {
         self.keys.get(key_id)
     }
 
+    /// Determines if the given key currently has the given permission.
+    pub fn key_has_permission(
+        &self,
+        key_id: &signing::KeyID,
+        permission: model::Permission,
+    ) -> bool {
+        self.permissions
+            .get(key_id)
+            .map(|p| p.contains(&permission))
+            .unwrap_or(false)
+    }
+
+    /// Gets the state of the given namespace.
+    ///
+    /// Returns `None` if the namespace is not defined in the log.
+    pub fn namespace_state(&self, namespace: &str) -> Option<&NamespaceState> {
+        self.namespaces.get(namespace)
+    }
+
     fn initialized(&self) -> bool {
         // The package log is initialized if the hash algorithm is set
         self.algorithm.is_some()
@@ -261,6 +314,25 @@ impl LogState {
                     key_id,
                     permissions,
                 } => self.validate_revoke_entry(signer_key_id, key_id, permissions)?,
+                model::OperatorEntry::RotateKey { key_id, key } => {
+                    self.validate_rotate_key_entry(signer_key_id, key_id, key)?
+                }
+                model::OperatorEntry::DefineNamespace { namespace, owners } => self
+                    .validate_namespace_entry(
+                        namespace,
+                        NamespaceState::Defined {
+                            owners: owners.iter().cloned().collect(),
+                        },
+                    )?,
+                model::OperatorEntry::ImportNamespace {
+                    namespace,
+                    registry,
+                } => self.validate_namespace_entry(
+                    namespace,
+                    NamespaceState::Imported {
+                        registry: registry.clone(),
+                    },
+                )?,
             }
         }
 
@@ -334,18 +406,77 @@ impl LogState {
         Ok(())
     }
 
+    fn validate_rotate_key_entry(
+        &mut self,
+        signer_key_id: &signing::KeyID,
+        key_id: &signing::KeyID,
+        key: &signing::PublicKey,
+    ) -> Result<(), ValidationError> {
+        let new_key_id = key.fingerprint();
+        if &new_key_id == key_id {
+            return Err(ValidationError::RotateKeyToSelf {
+                key_id: key_id.clone(),
+            });
+        }
+
+        let permissions: Vec<_> = self
+            .permissions
+            .get(key_id)
+            .map(|set| set.iter().copied().collect())
+            .unwrap_or_default();
+
+        if permissions.is_empty() {
+            return Err(ValidationError::RotateKeyWithoutPermissions {
+                key_id: key_id.clone(),
+            });
+        }
+
+        // A key may always rotate itself; otherwise the author must have
+        // every permission being transferred
+        if signer_key_id != key_id {
+            self.check_key_permissions(signer_key_id, &permissions)?;
+        }
+
+        self.keys.insert(new_key_id.clone(), key.clone());
+        if let Some(set) = self.permissions.get_mut(key_id) {
+            set.clear();
+        }
+        self.permissions
+            .entry(new_key_id)
+            .or_default()
+            .extend(permissions);
+
+        Ok(())
+    }
+
+    fn validate_namespace_entry(
+        &mut self,
+        namespace: &str,
+        state: NamespaceState,
+    ) -> Result<(), ValidationError> {
+        if KebabStr::new(namespace).is_none() {
+            return Err(ValidationError::InvalidNamespace {
+                namespace: namespace.to_string(),
+            });
+        }
+
+        if self.namespaces.contains_key(namespace) {
+            return Err(ValidationError::NamespaceAlreadyDefined {
+                namespace: namespace.to_string(),
+            });
+        }
+
+        self.namespaces.insert(namespace.to_string(), state);
+        Ok(())
+    }
+
     fn check_key_permissions(
         &self,
         key_id: &signing::KeyID,
         permissions: &[model::Permission],
     ) -> Result<(), ValidationError> {
         for permission in permissions {
-            if !self
-                .permissions
-                .get(key_id)
-                .map(|p| p.contains(permission))
-                .unwrap_or(false)
-            {
+            if !self.key_has_permission(key_id, *permission) {
                 return Err(ValidationError::UnauthorizedAction {
                     key_id: key_id.clone(),
                     needed_permission: *permission,
@@ -361,13 +492,15 @@ impl LogState {
             head,
             permissions,
             keys,
+            namespaces,
         } = self;
 
         Snapshot {
             algorithm: *algorithm,
             head: head.clone(),
-            permissions: permissions.len(),
+            permissions: permissions.clone(),
             keys: keys.len(),
+            namespaces: namespaces.len(),
         }
     }
 
@@ -377,12 +510,14 @@ impl LogState {
             head,
             permissions,
             keys,
+            namespaces,
         } = snapshot;
 
         self.algorithm = algorithm;
         self.head = head;
-        self.permissions.truncate(permissions);
+        self.permissions = permissions;
         self.keys.truncate(keys);
+        self.namespaces.truncate(namespaces);
     }
 }
 
@@ -398,8 +533,9 @@ impl crate::Validator for LogState {
 struct Snapshot {
     algorithm: Option<HashAlgorithm>,
     head: Option<Head>,
-    permissions: usize,
+    permissions: IndexMap<signing::KeyID, IndexSet<model::Permission>>,
     keys: usize,
+    namespaces: usize,
 }
 
 #[cfg(test)]
@@ -432,6 +568,7 @@ mod tests {
             ProtoEnvelope::signed_contents(&alice_priv, record).expect("failed to sign envelope");
         let mut validator = LogState::default();
         validator.validate(&envelope).unwrap();
+        assert!(validator.key_has_permission(&alice_id, model::Permission::Commit));
 
         assert_eq!(
             validator,
@@ -446,6 +583,7 @@ mod tests {
                     IndexSet::from([model::Permission::Commit]),
                 )]),
                 keys: IndexMap::from([(alice_id, alice_pub)]),
+                namespaces: IndexMap::new(),
             }
         );
     }
@@ -483,6 +621,7 @@ mod tests {
                 IndexSet::from([model::Permission::Commit]),
             )]),
             keys: IndexMap::from([(alice_id, alice_pub)]),
+            namespaces: IndexMap::new(),
         };
 
         assert_eq!(validator, expected);
@@ -517,4 +656,229 @@ mod tests {
         // The validator should not have changed
         assert_eq!(validator, expected);
     }
+
+    #[test]
+    fn test_rotate_key() {
+        let (alice_pub, alice_priv) = generate_p256_pair();
+        let alice_id = alice_pub.fingerprint();
+        let (bob_pub, bob_priv) = generate_p256_pair();
+        let bob_id = bob_pub.fingerprint();
+
+        let timestamp = SystemTime::now();
+        let record = model::OperatorRecord {
+            prev: None,
+            version: OPERATOR_RECORD_VERSION,
+            timestamp,
+            entries: vec![
+                model::OperatorEntry::Init {
+                    hash_algorithm: HashAlgorithm::Sha256,
+                    key: alice_pub.clone(),
+                },
+                // Alice rotates her own key to bob's key
+                model::OperatorEntry::RotateKey {
+                    key_id: alice_id.clone(),
+                    key: bob_pub.clone(),
+                },
+            ],
+        };
+
+        let envelope =
+            ProtoEnvelope::signed_contents(&alice_priv, record).expect("failed to sign envelope");
+        let mut validator = LogState::default();
+        validator.validate(&envelope).unwrap();
+
+        assert_eq!(
+            validator,
+            LogState {
+                head: Some(Head {
+                    digest: RecordId::operator_record::<Sha256>(&envelope),
+                    timestamp,
+                }),
+                algorithm: Some(HashAlgorithm::Sha256),
+                permissions: IndexMap::from([
+                    (alice_id.clone(), IndexSet::default()),
+                    (bob_id.clone(), IndexSet::from([model::Permission::Commit])),
+                ]),
+                keys: IndexMap::from([(alice_id.clone(), alice_pub), (bob_id.clone(), bob_pub)]),
+                namespaces: IndexMap::new(),
+            }
+        );
+
+        // Alice's key can no longer grant permissions
+        let (carol_pub, _) = generate_p256_pair();
+        let record = model::OperatorRecord {
+            prev: Some(RecordId::operator_record::<Sha256>(&envelope)),
+            version: OPERATOR_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![model::OperatorEntry::GrantFlat {
+                key: carol_pub,
+                permissions: vec![model::Permission::Commit],
+            }],
+        };
+
+        let envelope =
+            ProtoEnvelope::signed_contents(&alice_priv, record).expect("failed to sign envelope");
+        match validator.validate(&envelope).unwrap_err() {
+            ValidationError::UnauthorizedAction { key_id, .. } if key_id == alice_id => {}
+            e => panic!("expected a different error: {e}"),
+        }
+
+        // Bob's key cannot be rotated to itself
+        let record = model::OperatorRecord {
+            prev: validator.head().as_ref().map(|h| h.digest.clone()),
+            version: OPERATOR_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![model::OperatorEntry::RotateKey {
+                key_id: bob_id.clone(),
+                key: bob_priv.public_key(),
+            }],
+        };
+
+        let envelope =
+            ProtoEnvelope::signed_contents(&bob_priv, record).expect("failed to sign envelope");
+        match validator.validate(&envelope).unwrap_err() {
+            ValidationError::RotateKeyToSelf { .. } => {}
+            e => panic!("expected a different error: {e}"),
+        }
+
+        // A rotation is rolled back if a later entry of the record fails
+        let expected = validator.clone();
+        let (dave_pub, _) = generate_p256_pair();
+        let record = model::OperatorRecord {
+            prev: validator.head().as_ref().map(|h| h.digest.clone()),
+            version: OPERATOR_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![
+                // This entry is valid
+                model::OperatorEntry::RotateKey {
+                    key_id: bob_id.clone(),
+                    key: dave_pub.clone(),
+                },
+                // This entry is not valid as bob's key was rotated
+                model::OperatorEntry::GrantFlat {
+                    key: dave_pub,
+                    permissions: vec![model::Permission::Commit],
+                },
+            ],
+        };
+
+        let envelope =
+            ProtoEnvelope::signed_contents(&bob_priv, record).expect("failed to sign envelope");
+        match validator.validate(&envelope).unwrap_err() {
+            ValidationError::UnauthorizedAction { key_id, .. } if key_id == bob_id => {}
+            e => panic!("expected a different error: {e}"),
+        }
+
+        // Bob's key keeps its permissions
+        assert_eq!(validator, expected);
+        assert!(validator.key_has_permission(&bob_id, model::Permission::Commit));
+    }
+
+    #[test]
+    fn test_namespaces() {
+        let (alice_pub, alice_priv) = generate_p256_pair();
+        let (bob_pub, bob_priv) = generate_p256_pair();
+        let bob_id = bob_pub.fingerprint();
+
+        let record = model::OperatorRecord {
+            prev: None,
+            version: OPERATOR_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![
+                model::OperatorEntry::Init {
+                    hash_algorithm: HashAlgorithm::Sha256,
+                    key: alice_pub,
+                },
+                model::OperatorEntry::DefineNamespace {
+                    namespace: "wasi".to_string(),
+                    owners: vec![bob_id.clone()],
+                },
+                model::OperatorEntry::ImportNamespace {
+                    namespace: "acme".to_string(),
+                    registry: "registry.acme.example".to_string(),
+                },
+            ],
+        };
+
+        let envelope =
+            ProtoEnvelope::signed_contents(&alice_priv, record).expect("failed to sign envelope");
+        let mut validator = LogState::default();
+        validator.validate(&envelope).unwrap();
+
+        assert_eq!(
+            validator.namespace_state("wasi"),
+            Some(&NamespaceState::Defined {
+                owners: IndexSet::from([bob_id]),
+            })
+        );
+        assert_eq!(
+            validator.namespace_state("acme"),
+            Some(&NamespaceState::Imported {
+                registry: "registry.acme.example".to_string(),
+            })
+        );
+        assert_eq!(validator.namespace_state("other"), None);
+
+        // A namespace cannot be defined twice
+        let expected = validator.clone();
+        let record = model::OperatorRecord {
+            prev: validator.head().as_ref().map(|h| h.digest.clone()),
+            version: OPERATOR_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![
+                model::OperatorEntry::DefineNamespace {
+                    namespace: "other".to_string(),
+                    owners: Vec::new(),
+                },
+                model::OperatorEntry::DefineNamespace {
+                    namespace: "acme".to_string(),
+                    owners: Vec::new(),
+                },
+            ],
+        };
+
+        let envelope =
+            ProtoEnvelope::signed_contents(&alice_priv, record).expect("failed to sign envelope");
+        match validator.validate(&envelope).unwrap_err() {
+            ValidationError::NamespaceAlreadyDefined { namespace } if namespace == "acme" => {}
+            e => panic!("expected a different error: {e}"),
+        }
+        assert_eq!(validator, expected);
+
+        // A namespace must be a valid kebab-case identifier
+        let record = model::OperatorRecord {
+            prev: validator.head().as_ref().map(|h| h.digest.clone()),
+            version: OPERATOR_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![model::OperatorEntry::DefineNamespace {
+                namespace: "not_valid".to_string(),
+                owners: Vec::new(),
+            }],
+        };
+
+        let envelope =
+            ProtoEnvelope::signed_contents(&alice_priv, record).expect("failed to sign envelope");
+        match validator.validate(&envelope).unwrap_err() {
+            ValidationError::InvalidNamespace { .. } => {}
+            e => panic!("expected a different error: {e}"),
+        }
+
+        // Defining a namespace requires the commit permission
+        let record = model::OperatorRecord {
+            prev: validator.head().as_ref().map(|h| h.digest.clone()),
+            version: OPERATOR_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![model::OperatorEntry::DefineNamespace {
+                namespace: "bob".to_string(),
+                owners: Vec::new(),
+            }],
+        };
+
+        let envelope =
+            ProtoEnvelope::signed_contents(&bob_priv, record).expect("failed to sign envelope");
+        match validator.validate(&envelope).unwrap_err() {
+            ValidationError::UnauthorizedAction { .. } => {}
+            e => panic!("expected a different error: {e}"),
+        }
+    }
 }
@@ -9,8 +9,8 @@ use crate::{pbjson_to_prost_timestamp, prost_to_pbjson_timestamp, registry::Reco
 mod model;
 mod state;
 
-pub use model::{PackageEntry, PackageRecord, Permission};
-pub use state::{LogState, Release, ReleaseState, ValidationError};
+pub use model::{PackageEntry, PackageMetadata, PackageRecord, Permission};
+pub use state::{LogState, Release, ReleaseState, ScopedPermission, ValidationError};
 
 /// The currently supported package protocol version.
 pub const PACKAGE_RECORD_VERSION: u32 = 0;
@@ -75,6 +75,15 @@ impl TryFrom<protobuf::PackageEntry> for model::PackageEntry {
                     .map(TryInto::try_into)
                     .collect::<Result<_, _>>()?,
             },
+            Contents::GrantScoped(grant_scoped) => model::PackageEntry::GrantScoped {
+                key: grant_scoped.key.parse()?,
+                permissions: grant_scoped
+                    .permissions
+                    .into_iter()
+                    .map(TryInto::try_into)
+                    .collect::<Result<_, _>>()?,
+                scope: grant_scoped.version_req.parse()?,
+            },
             Contents::RevokeFlat(revoke_flat) => model::PackageEntry::RevokeFlat {
                 key_id: revoke_flat.key_id.into(),
                 permissions: revoke_flat
@@ -83,6 +92,18 @@ impl TryFrom<protobuf::PackageEntry> for model::PackageEntry {
                     .map(TryInto::try_into)
                     .collect::<Result<_, _>>()?,
             },
+            Contents::RotateKey(rotate_key) => model::PackageEntry::RotateKey {
+                key_id: rotate_key.key_id.into(),
+                key: rotate_key.key.parse()?,
+            },
+            Contents::SetThreshold(set_threshold) => model::PackageEntry::SetThreshold {
+                permissions: set_threshold
+                    .permissions
+                    .into_iter()
+                    .map(TryInto::try_into)
+                    .collect::<Result<_, _>>()?,
+                threshold: set_threshold.threshold,
+            },
             Contents::Release(release) => model::PackageEntry::Release {
                 version: release
                     .version
@@ -93,6 +114,17 @@ impl TryFrom<protobuf::PackageEntry> for model::PackageEntry {
             Contents::Yank(yank) => model::PackageEntry::Yank {
                 version: yank.version.parse()?,
             },
+            Contents::Unyank(unyank) => model::PackageEntry::Unyank {
+                version: unyank.version.parse()?,
+            },
+            Contents::Metadata(metadata) => model::PackageEntry::Metadata {
+                metadata: model::PackageMetadata {
+                    description: metadata.description,
+                    license: metadata.license,
+                    repository: metadata.repository,
+                    authors: metadata.authors,
+                },
+            },
         };
         Ok(output)
     }
@@ -114,6 +146,7 @@ impl TryFrom<i32> for model::Permission {
             }
             protobuf::PackagePermission::Release => Ok(model::Permission::Release),
             protobuf::PackagePermission::Yank => Ok(model::Permission::Yank),
+            protobuf::PackagePermission::Unyank => Ok(model::Permission::Unyank),
         }
     }
 }
@@ -165,6 +198,15 @@ impl<'a> From<&'a model::PackageEntry> for protobuf::PackageEntry {
                     permissions: permissions.iter().map(Into::into).collect(),
                 })
             }
+            model::PackageEntry::GrantScoped {
+                key,
+                permissions,
+                scope,
+            } => Contents::GrantScoped(protobuf::PackageGrantScoped {
+                key: key.to_string(),
+                permissions: permissions.iter().map(Into::into).collect(),
+                version_req: scope.to_string(),
+            }),
             model::PackageEntry::RevokeFlat {
                 key_id,
                 permissions,
@@ -172,6 +214,19 @@ impl<'a> From<&'a model::PackageEntry> for protobuf::PackageEntry {
                 key_id: key_id.to_string(),
                 permissions: permissions.iter().map(Into::into).collect(),
             }),
+            model::PackageEntry::RotateKey { key_id, key } => {
+                Contents::RotateKey(protobuf::PackageRotateKey {
+                    key_id: key_id.to_string(),
+                    key: key.to_string(),
+                })
+            }
+            model::PackageEntry::SetThreshold {
+                permissions,
+                threshold,
+            } => Contents::SetThreshold(protobuf::PackageSetThreshold {
+                permissions: permissions.iter().map(Into::into).collect(),
+                threshold: *threshold,
+            }),
             model::PackageEntry::Release { version, content } => {
                 Contents::Release(protobuf::PackageRelease {
                     version: version.to_string(),
@@ -181,6 +236,17 @@ impl<'a> From<&'a model::PackageEntry> for protobuf::PackageEntry {
             model::PackageEntry::Yank { version } => Contents::Yank(protobuf::PackageYank {
                 version: version.to_string(),
             }),
+            model::PackageEntry::Unyank { version } => Contents::Unyank(protobuf::PackageUnyank {
+                version: version.to_string(),
+            }),
+            model::PackageEntry::Metadata { metadata } => {
+                Contents::Metadata(protobuf::PackageMetadata {
+                    description: metadata.description.clone(),
+                    license: metadata.license.clone(),
+                    repository: metadata.repository.clone(),
+                    authors: metadata.authors.clone(),
+                })
+            }
         };
         let contents = Some(contents);
         protobuf::PackageEntry { contents }
@@ -192,6 +258,7 @@ impl<'a> From<&'a model::Permission> for i32 {
         let proto_perm = match permission {
             model::Permission::Release => protobuf::PackagePermission::Release,
             model::Permission::Yank => protobuf::PackagePermission::Yank,
+            model::Permission::Unyank => protobuf::PackagePermission::Unyank,
         };
         proto_perm.into()
     }
@@ -207,13 +274,14 @@ mod tests {
 
     use warg_crypto::hash::HashAlgorithm;
 
-    use crate::ProtoEnvelope;
+    use crate::{Cosignature, ProtoEnvelope};
     use warg_crypto::signing::generate_p256_pair;
 
     #[test]
     fn test_envelope_roundtrip() {
         let (alice_pub, alice_priv) = generate_p256_pair();
-        let (bob_pub, _bob_priv) = generate_p256_pair();
+        let (bob_pub, bob_priv) = generate_p256_pair();
+        let (carol_pub, _carol_priv) = generate_p256_pair();
 
         let record = model::PackageRecord {
             prev: None,
@@ -226,21 +294,49 @@ mod tests {
                 },
                 model::PackageEntry::GrantFlat {
                     key: bob_pub.clone(),
-                    permissions: vec![model::Permission::Release, model::Permission::Yank],
+                    permissions: model::Permission::all().into(),
+                },
+                model::PackageEntry::GrantScoped {
+                    key: bob_pub.clone(),
+                    permissions: vec![model::Permission::Release],
+                    scope: ">=2.0.0, <3.0.0".parse().unwrap(),
                 },
                 model::PackageEntry::RevokeFlat {
                     key_id: bob_pub.fingerprint(),
                     permissions: vec![model::Permission::Release],
                 },
+                model::PackageEntry::RotateKey {
+                    key_id: bob_pub.fingerprint(),
+                    key: carol_pub,
+                },
+                model::PackageEntry::SetThreshold {
+                    permissions: vec![model::Permission::Yank],
+                    threshold: 2,
+                },
                 model::PackageEntry::Release {
                     version: Version::new(1, 0, 0),
                     content: HashAlgorithm::Sha256.digest(&[0, 1, 2, 3]),
                 },
+                model::PackageEntry::Yank {
+                    version: Version::new(1, 0, 0),
+                },
+                model::PackageEntry::Unyank {
+                    version: Version::new(1, 0, 0),
+                },
+                model::PackageEntry::Metadata {
+                    metadata: model::PackageMetadata {
+                        description: Some("A test package".to_string()),
+                        license: Some("Apache-2.0".to_string()),
+                        repository: None,
+                        authors: vec!["Alice".to_string()],
+                    },
+                },
             ],
         };
 
+        let cosignature = Cosignature::new(&bob_priv, &record).unwrap();
         let first_envelope = match ProtoEnvelope::signed_contents(&alice_priv, record) {
-            Ok(value) => value,
+            Ok(value) => value.with_cosignatures([cosignature]),
             Err(error) => panic!("Failed to sign envelope 1: {:?}", error),
         };
 
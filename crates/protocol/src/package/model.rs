@@ -1,6 +1,6 @@
 use crate::registry::RecordId;
 use core::fmt;
-use semver::Version;
+use semver::{Version, VersionReq};
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::{str::FromStr, time::SystemTime};
@@ -20,6 +20,26 @@ pub struct PackageRecord {
     pub entries: Vec<PackageEntry>,
 }
 
+impl PackageRecord {
+    /// Gets the hash algorithm of the package log this record belongs to.
+    ///
+    /// This is the algorithm of the previous record's hash or, for the
+    /// first record of a log, the algorithm given by its init entry.
+    pub fn hash_algorithm(&self) -> HashAlgorithm {
+        match &self.prev {
+            Some(prev) => prev.algorithm(),
+            None => self
+                .entries
+                .iter()
+                .find_map(|e| match e {
+                    PackageEntry::Init { hash_algorithm, .. } => Some(*hash_algorithm),
+                    _ => None,
+                })
+                .unwrap_or(HashAlgorithm::Sha256),
+        }
+    }
+}
+
 impl crate::Record for PackageRecord {
     fn contents(&self) -> HashSet<&AnyHash> {
         self.entries
@@ -36,11 +56,19 @@ impl crate::Record for PackageRecord {
 pub enum Permission {
     Release,
     Yank,
+    Unyank,
 }
 
 impl Permission {
     /// Gets an array of all permissions.
-    pub const fn all() -> [Permission; 2] {
+    pub const fn all() -> [Permission; 3] {
+        [Permission::Release, Permission::Yank, Permission::Unyank]
+    }
+
+    /// Gets an array of the permissions granted to the key initializing a package.
+    ///
+    /// Other permissions must be granted explicitly.
+    pub const fn init() -> [Permission; 2] {
         [Permission::Release, Permission::Yank]
     }
 }
@@ -50,6 +78,7 @@ impl fmt::Display for Permission {
         match self {
             Permission::Release => write!(f, "release"),
             Permission::Yank => write!(f, "yank"),
+            Permission::Unyank => write!(f, "unyank"),
         }
     }
 }
@@ -61,11 +90,30 @@ impl FromStr for Permission {
         match s {
             "release" => Ok(Permission::Release),
             "yank" => Ok(Permission::Yank),
+            "unyank" => Ok(Permission::Unyank),
             _ => Err(format!("invalid permission {s:?}")),
         }
     }
 }
 
+/// Descriptive metadata about a package.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageMetadata {
+    /// A short description of the package.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The SPDX license expression of the package.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    /// The URL of the package's source repository.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+    /// The authors of the package.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authors: Vec<String>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[non_exhaustive]
 pub enum PackageEntry {
@@ -83,27 +131,75 @@ pub enum PackageEntry {
         key: signing::PublicKey,
         permissions: Vec<Permission>,
     },
+    /// Grant the specified key a permission limited to versions
+    /// matching the given requirement.
+    /// The author of this entry must have the permission unscoped.
+    GrantScoped {
+        key: signing::PublicKey,
+        permissions: Vec<Permission>,
+        scope: VersionReq,
+    },
     /// Remove a permission from a key.
+    /// Both unscoped and scoped grants of the permission are removed.
     /// The author of this entry must have the permission.
     RevokeFlat {
         key_id: signing::KeyID,
         permissions: Vec<Permission>,
     },
+    /// Transfer all permissions of a key to a new key.
+    /// The author of this entry must be the key being rotated
+    /// or have every permission being transferred.
+    RotateKey {
+        key_id: signing::KeyID,
+        key: signing::PublicKey,
+    },
+    /// Set the number of distinct keys that must sign records
+    /// using the specified permissions.
+    /// The author of this entry must have the permissions.
+    SetThreshold {
+        permissions: Vec<Permission>,
+        threshold: u32,
+    },
     /// Release a version of a package.
     /// The version must not have been released yet.
     Release { version: Version, content: AnyHash },
     /// Yank a version of a package.
     /// The version must have been released and not yanked.
     Yank { version: Version },
+    /// Unyank a version of a package.
+    /// The version must have been released and yanked.
+    Unyank { version: Version },
+    /// Set the metadata of the package.
+    /// Replaces any metadata previously set for the package.
+    /// The author of this entry must have the release permission.
+    Metadata { metadata: PackageMetadata },
 }
 
 impl PackageEntry {
     /// Check permission is required to submit this entry
     pub fn required_permission(&self) -> Option<Permission> {
         match self {
-            Self::Init { .. } | Self::GrantFlat { .. } | Self::RevokeFlat { .. } => None,
-            Self::Release { .. } => Some(Permission::Release),
+            Self::Init { .. }
+            | Self::GrantFlat { .. }
+            | Self::GrantScoped { .. }
+            | Self::RevokeFlat { .. }
+            | Self::RotateKey { .. }
+            | Self::SetThreshold { .. } => None,
+            Self::Release { .. } | Self::Metadata { .. } => Some(Permission::Release),
             Self::Yank { .. } => Some(Permission::Yank),
+            Self::Unyank { .. } => Some(Permission::Unyank),
+        }
+    }
+
+    /// Gets the package version the entry applies to.
+    ///
+    /// Returns `None` if the entry does not apply to a specific version.
+    pub fn version(&self) -> Option<&Version> {
+        match self {
+            Self::Release { version, .. } | Self::Yank { version } | Self::Unyank { version } => {
+                Some(version)
+            }
+            _ => None,
         }
     }
 
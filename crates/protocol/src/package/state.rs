@@ -1,12 +1,13 @@
 use super::{model, PACKAGE_RECORD_VERSION};
-use crate::registry::RecordId;
+use crate::operator::{self, NamespaceState};
+use crate::registry::{PackageId, RecordId};
 use crate::ProtoEnvelope;
 use indexmap::{map::Entry, IndexMap, IndexSet};
 use semver::{Version, VersionReq};
 use serde::{Deserialize, Serialize};
 use std::time::SystemTime;
 use thiserror::Error;
-use warg_crypto::hash::{AnyHash, HashAlgorithm, Sha256};
+use warg_crypto::hash::{AnyHash, HashAlgorithm};
 use warg_crypto::{signing, Signable};
 
 #[derive(Error, Debug)]
@@ -44,6 +45,53 @@ pub enum ValidationError {
     #[error("an entry attempted to yank version {version} which is already yanked")]
     YankOfYanked { version: Version },
 
+    #[error("an entry attempted to unyank version {version} which had not yet been released")]
+    UnyankOfUnreleased { version: Version },
+
+    #[error("an entry attempted to unyank version {version} which is not yanked")]
+    UnyankOfNotYanked { version: Version },
+
+    #[error("an entry attempted to unyank version {version} whose content digest is unknown")]
+    UnyankOfUnknownContent { version: Version },
+
+    #[error("package metadata field `{field}` cannot be empty")]
+    EmptyMetadataField { field: &'static str },
+
+    #[error("attempted to rotate key {key_id} which has no permissions")]
+    RotateKeyWithoutPermissions { key_id: signing::KeyID },
+
+    #[error("attempted to rotate key {key_id} to itself")]
+    RotateKeyToSelf { key_id: signing::KeyID },
+
+    #[error("the key with ID {key_id} signed the record more than once")]
+    DuplicateSignature { key_id: signing::KeyID },
+
+    #[error("the record requires signatures from {threshold} keys with permission {permission} but was signed by {signatures}")]
+    ThresholdNotMet {
+        permission: model::Permission,
+        threshold: u32,
+        signatures: usize,
+    },
+
+    #[error("a signature threshold must be at least 1")]
+    ZeroThreshold,
+
+    #[error("a threshold of {threshold} for permission {permission} cannot be met by the {keys} key(s) holding it")]
+    ThresholdUnreachable {
+        permission: model::Permission,
+        threshold: u32,
+        keys: usize,
+    },
+
+    #[error("the key with ID {key_id} is not an owner of namespace `{namespace}`")]
+    NamespaceNotOwned {
+        namespace: String,
+        key_id: signing::KeyID,
+    },
+
+    #[error("namespace `{namespace}` is imported from registry `{registry}`")]
+    NamespaceImported { namespace: String, registry: String },
+
     #[error("unable to verify signature")]
     SignatureError(#[from] signing::SignatureError),
 
@@ -85,6 +133,14 @@ pub enum ReleaseState {
         /// The timestamp of the yank.
         #[serde(with = "crate::timestamp")]
         timestamp: SystemTime,
+        /// The content digest of the release prior to being yanked.
+        ///
+        /// This is retained so the release can be restored by an unyank.
+        ///
+        /// Releases yanked before the digest was retained have no content
+        /// and cannot be unyanked.
+        #[serde(default)]
+        content: Option<AnyHash>,
     },
 }
 
@@ -122,6 +178,17 @@ impl Release {
     }
 }
 
+/// Represents a permission granted to a key that is limited to
+/// versions matching a version requirement.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScopedPermission {
+    /// The permission that was granted.
+    pub permission: model::Permission,
+    /// The requirement that versions must match for the permission to apply.
+    pub scope: VersionReq,
+}
+
 /// Information about the current head of the package log.
 ///
 /// A head is the last validated record digest and timestamp.
@@ -149,12 +216,23 @@ pub struct LogState {
     /// The permissions of each key.
     #[serde(skip_serializing_if = "IndexMap::is_empty")]
     permissions: IndexMap<signing::KeyID, IndexSet<model::Permission>>,
+    /// The version-scoped permissions of each key.
+    #[serde(skip_serializing_if = "IndexMap::is_empty")]
+    scoped_permissions: IndexMap<signing::KeyID, Vec<ScopedPermission>>,
     /// The releases in the package log.
     #[serde(skip_serializing_if = "IndexMap::is_empty")]
     releases: IndexMap<Version, Release>,
     /// The keys known to the validator.
     #[serde(skip_serializing_if = "IndexMap::is_empty")]
     keys: IndexMap<signing::KeyID, signing::PublicKey>,
+    /// The current metadata of the package.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metadata: Option<model::PackageMetadata>,
+    /// The number of distinct keys that must sign records using each permission.
+    ///
+    /// Permissions not present require a single signature.
+    #[serde(skip_serializing_if = "IndexMap::is_empty")]
+    thresholds: IndexMap<model::Permission, u32>,
 }
 
 impl LogState {
@@ -183,7 +261,34 @@ impl LogState {
     ) -> Result<(), ValidationError> {
         let snapshot = self.snapshot();
 
-        let result = self.validate_record(record);
+        let result = self.validate_record(record, None);
+        if result.is_err() {
+            self.rollback(snapshot);
+        }
+
+        result
+    }
+
+    /// Validates an individual package record of the given package against
+    /// the namespaces defined by the given operator log state.
+    ///
+    /// The initial record of a package in a namespace defined by the
+    /// operator log must be signed by an owner of the namespace; packages in
+    /// imported namespaces cannot be initialized.
+    ///
+    /// This operation is transactional in the same way as `validate`.
+    pub fn validate_with_operator(
+        &mut self,
+        id: &PackageId,
+        operator: &operator::LogState,
+        record: &ProtoEnvelope<model::PackageRecord>,
+    ) -> Result<(), ValidationError> {
+        let snapshot = self.snapshot();
+
+        let namespace = operator
+            .namespace_state(id.namespace())
+            .map(|state| (id.namespace(), state));
+        let result = self.validate_record(record, namespace);
         if result.is_err() {
             self.rollback(snapshot);
         }
@@ -217,6 +322,40 @@ impl LogState {
             .max_by(|a, b| a.version.cmp(&b.version))
     }
 
+    /// Gets the version-scoped permissions granted to keys.
+    ///
+    /// The permissions are returned in package log order.
+    pub fn scoped_permissions(&self) -> impl Iterator<Item = (&signing::KeyID, &ScopedPermission)> {
+        self.scoped_permissions
+            .iter()
+            .flat_map(|(key_id, permissions)| permissions.iter().map(move |p| (key_id, p)))
+    }
+
+    /// Determines if the given key has any permissions, scoped or otherwise.
+    pub fn key_has_permissions(&self, key_id: &signing::KeyID) -> bool {
+        self.permissions
+            .get(key_id)
+            .map(|set| !set.is_empty())
+            .unwrap_or(false)
+            || self
+                .scoped_permissions
+                .get(key_id)
+                .map(|scoped| !scoped.is_empty())
+                .unwrap_or(false)
+    }
+
+    /// Gets the current metadata of the package.
+    ///
+    /// Returns `None` if no metadata has been set.
+    pub fn metadata(&self) -> Option<&model::PackageMetadata> {
+        self.metadata.as_ref()
+    }
+
+    /// Gets the number of distinct keys that must sign records using the given permission.
+    pub fn threshold(&self, permission: model::Permission) -> u32 {
+        self.thresholds.get(&permission).copied().unwrap_or(1)
+    }
+
     /// Gets the public key of the given key id.
     ///
     /// Returns `None` if the key id is not recognized.
@@ -232,9 +371,13 @@ impl LogState {
     fn validate_record(
         &mut self,
         envelope: &ProtoEnvelope<model::PackageRecord>,
+        namespace: Option<(&str, &NamespaceState)>,
     ) -> Result<(), ValidationError> {
         let record = envelope.as_ref();
-        let record_id = RecordId::package_record::<Sha256>(envelope);
+        let record_id = RecordId::package_record_with(
+            self.algorithm.unwrap_or_else(|| record.hash_algorithm()),
+            envelope,
+        );
 
         // Validate previous hash
         self.validate_record_hash(record)?;
@@ -245,12 +388,17 @@ impl LogState {
         // Validate timestamp
         self.validate_record_timestamp(record)?;
 
+        // Validate the envelope co-signatures against the keys known prior to the record
+        let signers = self.validate_cosignatures(envelope)?;
+
         // Validate entries
         self.validate_record_entries(
             &record_id,
             envelope.key_id(),
+            &signers,
             record.timestamp,
             &record.entries,
+            namespace,
         )?;
 
         // At this point the digest algorithm must be set via an init entry
@@ -325,25 +473,53 @@ impl LogState {
         Ok(())
     }
 
+    fn validate_cosignatures(
+        &self,
+        envelope: &ProtoEnvelope<model::PackageRecord>,
+    ) -> Result<IndexSet<signing::KeyID>, ValidationError> {
+        let mut signers = IndexSet::from([envelope.key_id().clone()]);
+        for cosignature in envelope.cosignatures() {
+            let key = self.keys.get(&cosignature.key_id).ok_or_else(|| {
+                ValidationError::KeyIDNotRecognized {
+                    key_id: cosignature.key_id.clone(),
+                }
+            })?;
+
+            model::PackageRecord::verify(key, envelope.content_bytes(), &cosignature.signature)?;
+
+            if !signers.insert(cosignature.key_id.clone()) {
+                return Err(ValidationError::DuplicateSignature {
+                    key_id: cosignature.key_id.clone(),
+                });
+            }
+        }
+
+        Ok(signers)
+    }
+
     fn validate_record_entries(
         &mut self,
         record_id: &RecordId,
         signer_key_id: &signing::KeyID,
+        signers: &IndexSet<signing::KeyID>,
         timestamp: SystemTime,
         entries: &[model::PackageEntry],
+        namespace: Option<(&str, &NamespaceState)>,
     ) -> Result<(), ValidationError> {
         for entry in entries {
             if let Some(permission) = entry.required_permission() {
-                self.check_key_permissions(signer_key_id, &[permission])?;
+                self.check_key_permission_for_version(signer_key_id, permission, entry.version())?;
             }
 
+            self.check_threshold(signers, &self.threshold_permissions(entry), entry.version())?;
+
             // Process an init entry specially
             if let model::PackageEntry::Init {
                 hash_algorithm,
                 key,
             } = entry
             {
-                self.validate_init_entry(signer_key_id, *hash_algorithm, key)?;
+                self.validate_init_entry(signer_key_id, *hash_algorithm, key, namespace)?;
                 continue;
             }
 
@@ -357,10 +533,22 @@ impl LogState {
                 model::PackageEntry::GrantFlat { key, permissions } => {
                     self.validate_grant_entry(signer_key_id, key, permissions)?
                 }
+                model::PackageEntry::GrantScoped {
+                    key,
+                    permissions,
+                    scope,
+                } => self.validate_grant_scoped_entry(signer_key_id, key, permissions, scope)?,
                 model::PackageEntry::RevokeFlat {
                     key_id,
                     permissions,
                 } => self.validate_revoke_entry(signer_key_id, key_id, permissions)?,
+                model::PackageEntry::RotateKey { key_id, key } => {
+                    self.validate_rotate_key_entry(signer_key_id, key_id, key)?
+                }
+                model::PackageEntry::SetThreshold {
+                    permissions,
+                    threshold,
+                } => self.validate_set_threshold_entry(signer_key_id, permissions, *threshold)?,
                 model::PackageEntry::Release { version, content } => self.validate_release_entry(
                     record_id,
                     signer_key_id,
@@ -371,6 +559,10 @@ impl LogState {
                 model::PackageEntry::Yank { version } => {
                     self.validate_yank_entry(signer_key_id, timestamp, version)?
                 }
+                model::PackageEntry::Unyank { version } => self.validate_unyank_entry(version)?,
+                model::PackageEntry::Metadata { metadata } => {
+                    self.validate_metadata_entry(metadata)?
+                }
             }
         }
 
@@ -382,6 +574,7 @@ impl LogState {
         signer_key_id: &signing::KeyID,
         algorithm: HashAlgorithm,
         init_key: &signing::PublicKey,
+        namespace: Option<(&str, &NamespaceState)>,
     ) -> Result<(), ValidationError> {
         if self.initialized() {
             return Err(ValidationError::InitialEntryAfterBeginning);
@@ -393,14 +586,33 @@ impl LogState {
             });
         }
 
+        // Check the signer against the namespace as defined by the operator log
+        match namespace {
+            None => {}
+            Some((_, NamespaceState::Defined { owners })) if owners.contains(signer_key_id) => {}
+            Some((namespace, NamespaceState::Defined { .. })) => {
+                return Err(ValidationError::NamespaceNotOwned {
+                    namespace: namespace.to_string(),
+                    key_id: signer_key_id.clone(),
+                });
+            }
+            Some((namespace, NamespaceState::Imported { registry })) => {
+                return Err(ValidationError::NamespaceImported {
+                    namespace: namespace.to_string(),
+                    registry: registry.clone(),
+                });
+            }
+        }
+
         assert!(self.permissions.is_empty());
+        assert!(self.scoped_permissions.is_empty());
         assert!(self.releases.is_empty());
         assert!(self.keys.is_empty());
 
         self.algorithm = Some(algorithm);
         self.permissions.insert(
             signer_key_id.clone(),
-            IndexSet::from(model::Permission::all()),
+            IndexSet::from(model::Permission::init()),
         );
         self.keys.insert(init_key.fingerprint(), init_key.clone());
 
@@ -414,7 +626,7 @@ impl LogState {
         permissions: &[model::Permission],
     ) -> Result<(), ValidationError> {
         // Check that the current key has the permission they're trying to grant
-        self.check_key_permissions(signer_key_id, permissions)?;
+        self.check_key_can_grant(signer_key_id, permissions)?;
 
         let grant_key_id = key.fingerprint();
         self.keys.insert(grant_key_id.clone(), key.clone());
@@ -426,6 +638,34 @@ impl LogState {
         Ok(())
     }
 
+    fn validate_grant_scoped_entry(
+        &mut self,
+        signer_key_id: &signing::KeyID,
+        key: &signing::PublicKey,
+        permissions: &[model::Permission],
+        scope: &VersionReq,
+    ) -> Result<(), ValidationError> {
+        // Check that the current key has the unscoped permission they're trying to grant
+        self.check_key_can_grant(signer_key_id, permissions)?;
+
+        let grant_key_id = key.fingerprint();
+        self.keys.insert(grant_key_id.clone(), key.clone());
+
+        let scoped = self.scoped_permissions.entry(grant_key_id).or_default();
+        for permission in permissions {
+            let scoped_permission = ScopedPermission {
+                permission: *permission,
+                scope: scope.clone(),
+            };
+
+            if !scoped.contains(&scoped_permission) {
+                scoped.push(scoped_permission);
+            }
+        }
+
+        Ok(())
+    }
+
     fn validate_revoke_entry(
         &mut self,
         signer_key_id: &signing::KeyID,
@@ -433,22 +673,126 @@ impl LogState {
         permissions: &[model::Permission],
     ) -> Result<(), ValidationError> {
         // Check that the current key has the permission they're trying to revoke
-        self.check_key_permissions(signer_key_id, permissions)?;
+        self.check_key_can_grant(signer_key_id, permissions)?;
 
         for permission in permissions {
-            if !self
+            let revoked_flat = self
                 .permissions
                 .get_mut(key_id)
                 .map(|set| set.remove(permission))
-                .unwrap_or(false)
-            {
+                .unwrap_or(false);
+
+            let revoked_scoped = self
+                .scoped_permissions
+                .get_mut(key_id)
+                .map(|scoped| {
+                    let len = scoped.len();
+                    scoped.retain(|p| p.permission != *permission);
+                    scoped.len() != len
+                })
+                .unwrap_or(false);
+
+            if !revoked_flat && !revoked_scoped {
                 return Err(ValidationError::PermissionNotFoundToRevoke {
                     permission: *permission,
                     key_id: key_id.clone(),
                 });
             }
         }
-        Ok(())
+
+        // Revoking must not leave a permission unable to meet its threshold
+        self.check_thresholds_reachable(permissions)
+    }
+
+    fn validate_rotate_key_entry(
+        &mut self,
+        signer_key_id: &signing::KeyID,
+        key_id: &signing::KeyID,
+        key: &signing::PublicKey,
+    ) -> Result<(), ValidationError> {
+        let new_key_id = key.fingerprint();
+        if &new_key_id == key_id {
+            return Err(ValidationError::RotateKeyToSelf {
+                key_id: key_id.clone(),
+            });
+        }
+
+        let permissions: Vec<_> = self
+            .permissions
+            .get(key_id)
+            .map(|set| set.iter().copied().collect())
+            .unwrap_or_default();
+        let scoped = self
+            .scoped_permissions
+            .get(key_id)
+            .cloned()
+            .unwrap_or_default();
+
+        if permissions.is_empty() && scoped.is_empty() {
+            return Err(ValidationError::RotateKeyWithoutPermissions {
+                key_id: key_id.clone(),
+            });
+        }
+
+        // A key may always rotate itself; otherwise the author must have
+        // every permission being transferred
+        if signer_key_id != key_id {
+            self.check_key_can_grant(signer_key_id, &permissions)?;
+            self.check_key_can_grant(
+                signer_key_id,
+                &scoped.iter().map(|p| p.permission).collect::<Vec<_>>(),
+            )?;
+        }
+
+        self.keys.insert(new_key_id.clone(), key.clone());
+
+        if let Some(set) = self.permissions.get_mut(key_id) {
+            set.clear();
+        }
+        self.permissions
+            .entry(new_key_id.clone())
+            .or_default()
+            .extend(permissions.iter().copied());
+
+        let mut rotated: IndexSet<_> = permissions.iter().copied().collect();
+        if !scoped.is_empty() {
+            rotated.extend(scoped.iter().map(|p| p.permission));
+            self.scoped_permissions.shift_remove(key_id);
+            let new_scoped = self.scoped_permissions.entry(new_key_id).or_default();
+            for scoped_permission in scoped {
+                if !new_scoped.contains(&scoped_permission) {
+                    new_scoped.push(scoped_permission);
+                }
+            }
+        }
+
+        // Rotating to a key that already holds a permission leaves fewer keys
+        // able to meet its threshold
+        self.check_thresholds_reachable(&rotated.into_iter().collect::<Vec<_>>())
+    }
+
+    fn validate_set_threshold_entry(
+        &mut self,
+        signer_key_id: &signing::KeyID,
+        permissions: &[model::Permission],
+        threshold: u32,
+    ) -> Result<(), ValidationError> {
+        // Check that the current key has the permissions they're setting the threshold of
+        self.check_key_permissions(signer_key_id, permissions)?;
+
+        if threshold == 0 {
+            return Err(ValidationError::ZeroThreshold);
+        }
+
+        for permission in permissions {
+            if threshold == 1 {
+                self.thresholds.shift_remove(permission);
+            } else {
+                self.thresholds.insert(*permission, threshold);
+            }
+        }
+
+        self.check_thresholds_reachable(permissions)
     }
 
     fn validate_release_entry(
@@ -489,14 +833,15 @@ impl LogState {
         version: &Version,
     ) -> Result<(), ValidationError> {
         match self.releases.get_mut(version) {
-            Some(e) => match e.state {
+            Some(e) => match &e.state {
                 ReleaseState::Yanked { .. } => Err(ValidationError::YankOfYanked {
                     version: version.clone(),
                 }),
-                ReleaseState::Released { .. } => {
+                ReleaseState::Released { content } => {
                     e.state = ReleaseState::Yanked {
                         by: signer_key_id.clone(),
                         timestamp,
+                        content: Some(content.clone()),
                     };
                     Ok(())
                 }
@@ -507,6 +852,210 @@ impl LogState {
         }
     }
 
+    fn validate_unyank_entry(&mut self, version: &Version) -> Result<(), ValidationError> {
+        match self.releases.get_mut(version) {
+            Some(e) => match &e.state {
+                ReleaseState::Released { .. } => Err(ValidationError::UnyankOfNotYanked {
+                    version: version.clone(),
+                }),
+                ReleaseState::Yanked {
+                    content: Some(content),
+                    ..
+                } => {
+                    e.state = ReleaseState::Released {
+                        content: content.clone(),
+                    };
+                    Ok(())
+                }
+                ReleaseState::Yanked { content: None, .. } => {
+                    Err(ValidationError::UnyankOfUnknownContent {
+                        version: version.clone(),
+                    })
+                }
+            },
+            None => Err(ValidationError::UnyankOfUnreleased {
+                version: version.clone(),
+            }),
+        }
+    }
+
+    fn validate_metadata_entry(
+        &mut self,
+        metadata: &model::PackageMetadata,
+    ) -> Result<(), ValidationError> {
+        let fields = [
+            ("description", metadata.description.as_deref()),
+            ("license", metadata.license.as_deref()),
+            ("repository", metadata.repository.as_deref()),
+        ];
+
+        for (field, value) in fields {
+            if value.map(|v| v.trim().is_empty()).unwrap_or(false) {
+                return Err(ValidationError::EmptyMetadataField { field });
+            }
+        }
+
+        if metadata.authors.iter().any(|a| a.trim().is_empty()) {
+            return Err(ValidationError::EmptyMetadataField { field: "authors" });
+        }
+
+        self.metadata = Some(metadata.clone());
+        Ok(())
+    }
+
+    /// Gets the permissions whose threshold applies to the given entry.
+    fn threshold_permissions(&self, entry: &model::PackageEntry) -> Vec<model::Permission> {
+        match entry {
+            model::PackageEntry::Init { .. } => Vec::new(),
+            model::PackageEntry::GrantFlat { permissions, .. }
+            | model::PackageEntry::GrantScoped { permissions, .. }
+            | model::PackageEntry::RevokeFlat { permissions, .. }
+            | model::PackageEntry::SetThreshold { permissions, .. } => permissions.clone(),
+            model::PackageEntry::RotateKey { key_id, .. } => {
+                let mut permissions: IndexSet<_> = self
+                    .permissions
+                    .get(key_id)
+                    .map(|set| set.iter().copied().collect())
+                    .unwrap_or_default();
+                if let Some(scoped) = self.scoped_permissions.get(key_id) {
+                    permissions.extend(scoped.iter().map(|p| p.permission));
+                }
+                permissions.into_iter().collect()
+            }
+            _ => entry.required_permission().into_iter().collect(),
+        }
+    }
+
+    fn check_threshold(
+        &self,
+        signers: &IndexSet<signing::KeyID>,
+        permissions: &[model::Permission],
+        version: Option<&Version>,
+    ) -> Result<(), ValidationError> {
+        for permission in permissions {
+            let threshold = self.threshold(*permission);
+            if threshold <= 1 {
+                continue;
+            }
+
+            let signatures = self.count_keys_with_permission(signers, *permission, version);
+            if signatures < threshold as usize {
+                return Err(ValidationError::ThresholdNotMet {
+                    permission: *permission,
+                    threshold,
+                    signatures,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_thresholds_reachable(
+        &self,
+        permissions: &[model::Permission],
+    ) -> Result<(), ValidationError> {
+        for permission in permissions {
+            let threshold = self.threshold(*permission);
+            if threshold <= 1 {
+                continue;
+            }
+
+            let keys = self.count_keys_with_permission(self.keys.keys(), *permission, None);
+            if keys < threshold as usize {
+                return Err(ValidationError::ThresholdUnreachable {
+                    permission: *permission,
+                    threshold,
+                    keys,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Counts the given keys that may sign towards the threshold of the
+    /// given permission.
+    ///
+    /// A scoped permission is counted if it matches the given version, or
+    /// for any version if no version is given.
+    fn count_keys_with_permission<'a>(
+        &self,
+        key_ids: impl IntoIterator<Item = &'a signing::KeyID>,
+        permission: model::Permission,
+        version: Option<&Version>,
+    ) -> usize {
+        key_ids
+            .into_iter()
+            .filter(|key_id| {
+                self.permissions
+                    .get(*key_id)
+                    .map(|set| set.contains(&permission))
+                    .unwrap_or(false)
+                    || self
+                        .scoped_permissions
+                        .get(*key_id)
+                        .map(|scoped| {
+                            scoped.iter().any(|p| {
+                                p.permission == permission
+                                    && version.map_or(true, |v| p.scope.matches(v))
+                            })
+                        })
+                        .unwrap_or(false)
+            })
+            .count()
+    }
+
+    fn check_key_permission_for_version(
+        &self,
+        key_id: &signing::KeyID,
+        permission: model::Permission,
+        version: Option<&Version>,
+    ) -> Result<(), ValidationError> {
+        // A scoped permission applies only to entries for a matching version
+        if let Some(version) = version {
+            if self
+                .scoped_permissions
+                .get(key_id)
+                .map(|scoped| {
+                    scoped
+                        .iter()
+                        .any(|p| p.permission == permission && p.scope.matches(version))
+                })
+                .unwrap_or(false)
+            {
+                return Ok(());
+            }
+        }
+
+        self.check_key_permissions(key_id, &[permission])
+    }
+
+    /// Checks that the given key may grant or revoke the given permissions.
+    ///
+    /// Unyank is not granted on init, so keys with the yank permission may
+    /// grant it.
+    fn check_key_can_grant(
+        &self,
+        key_id: &signing::KeyID,
+        permissions: &[model::Permission],
+    ) -> Result<(), ValidationError> {
+        for permission in permissions {
+            if *permission == model::Permission::Unyank
+                && self
+                    .permissions
+                    .get(key_id)
+                    .map(|p| p.contains(&model::Permission::Yank))
+                    .unwrap_or(false)
+            {
+                continue;
+            }
+
+            self.check_key_permissions(key_id, &[*permission])?;
+        }
+        Ok(())
+    }
+
     fn check_key_permissions(
         &self,
         key_id: &signing::KeyID,
@@ -534,15 +1083,21 @@ impl LogState {
             head,
             releases,
             permissions,
+            scoped_permissions,
             keys,
+            metadata,
+            thresholds,
         } = self;
 
         Snapshot {
             algorithm: *algorithm,
             head: head.clone(),
             releases: releases.len(),
-            permissions: permissions.len(),
+            permissions: permissions.clone(),
+            scoped_permissions: scoped_permissions.clone(),
             keys: keys.len(),
+            metadata: metadata.clone(),
+            thresholds: thresholds.clone(),
         }
     }
 
@@ -552,14 +1107,20 @@ impl LogState {
             head,
             releases,
             permissions,
+            scoped_permissions,
             keys,
+            metadata,
+            thresholds,
         } = snapshot;
 
         self.algorithm = algorithm;
         self.head = head;
         self.releases.truncate(releases);
-        self.permissions.truncate(permissions);
+        self.permissions = permissions;
+        self.scoped_permissions = scoped_permissions;
         self.keys.truncate(keys);
+        self.metadata = metadata;
+        self.thresholds = thresholds;
     }
 }
 
@@ -576,16 +1137,20 @@ struct Snapshot {
     algorithm: Option<HashAlgorithm>,
     head: Option<Head>,
     releases: usize,
-    permissions: usize,
+    permissions: IndexMap<signing::KeyID, IndexSet<model::Permission>>,
+    scoped_permissions: IndexMap<signing::KeyID, Vec<ScopedPermission>>,
     keys: usize,
+    metadata: Option<model::PackageMetadata>,
+    thresholds: IndexMap<model::Permission, u32>,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Cosignature;
     use pretty_assertions::assert_eq;
     use std::time::{Duration, SystemTime};
-    use warg_crypto::hash::HashAlgorithm;
+    use warg_crypto::hash::{HashAlgorithm, Sha256, Sha512};
     use warg_crypto::signing::generate_p256_pair;
 
     #[test]
@@ -618,10 +1183,13 @@ mod tests {
                 algorithm: Some(HashAlgorithm::Sha256),
                 permissions: IndexMap::from([(
                     alice_id.clone(),
-                    IndexSet::from([model::Permission::Release, model::Permission::Yank]),
+                    IndexSet::from(model::Permission::init()),
                 )]),
                 releases: IndexMap::default(),
+                scoped_permissions: IndexMap::default(),
                 keys: IndexMap::from([(alice_id, alice_pub)]),
+                metadata: None,
+                thresholds: IndexMap::default(),
             }
         );
     }
@@ -696,7 +1264,9 @@ mod tests {
                 version: Version::new(1, 1, 0),
                 by: bob_id.clone(),
                 timestamp: timestamp1,
-                state: ReleaseState::Released { content }
+                state: ReleaseState::Released {
+                    content: content.clone()
+                }
             }]
         );
 
@@ -732,7 +1302,8 @@ mod tests {
                 timestamp: timestamp1,
                 state: ReleaseState::Yanked {
                     by: alice_id.clone(),
-                    timestamp: timestamp2
+                    timestamp: timestamp2,
+                    content: Some(content.clone()),
                 }
             }]
         );
@@ -746,10 +1317,7 @@ mod tests {
                     timestamp: timestamp2,
                 }),
                 permissions: IndexMap::from([
-                    (
-                        alice_id.clone(),
-                        IndexSet::from([model::Permission::Release, model::Permission::Yank]),
-                    ),
+                    (alice_id.clone(), IndexSet::from(model::Permission::init()),),
                     (bob_id.clone(), IndexSet::default()),
                 ]),
                 releases: IndexMap::from([(
@@ -761,11 +1329,15 @@ mod tests {
                         timestamp: timestamp1,
                         state: ReleaseState::Yanked {
                             by: alice_id.clone(),
-                            timestamp: timestamp2
+                            timestamp: timestamp2,
+                            content: Some(content),
                         }
                     }
                 )]),
+                scoped_permissions: IndexMap::default(),
                 keys: IndexMap::from([(alice_id, alice_pub), (bob_id, bob_pub),]),
+                metadata: None,
+                thresholds: IndexMap::default(),
             }
         );
     }
@@ -801,9 +1373,12 @@ mod tests {
             releases: IndexMap::new(),
             permissions: IndexMap::from([(
                 alice_id.clone(),
-                IndexSet::from([model::Permission::Release, model::Permission::Yank]),
+                IndexSet::from(model::Permission::init()),
             )]),
+            scoped_permissions: IndexMap::default(),
             keys: IndexMap::from([(alice_id, alice_pub)]),
+            metadata: None,
+            thresholds: IndexMap::default(),
         };
 
         assert_eq!(validator, expected);
@@ -838,4 +1413,788 @@ mod tests {
         // The validator should not have changed
         assert_eq!(validator, expected);
     }
+
+    #[test]
+    fn test_validate_metadata() {
+        let (alice_pub, alice_priv) = generate_p256_pair();
+
+        let metadata = model::PackageMetadata {
+            description: Some("An example package".to_string()),
+            license: Some("Apache-2.0 WITH LLVM-exception".to_string()),
+            repository: Some("https://github.com/example/example".to_string()),
+            authors: vec!["Alice <alice@example.com>".to_string()],
+        };
+
+        let timestamp = SystemTime::now();
+        let record = model::PackageRecord {
+            prev: None,
+            version: PACKAGE_RECORD_VERSION,
+            timestamp,
+            entries: vec![
+                model::PackageEntry::Init {
+                    hash_algorithm: HashAlgorithm::Sha256,
+                    key: alice_pub,
+                },
+                model::PackageEntry::Metadata {
+                    metadata: metadata.clone(),
+                },
+            ],
+        };
+
+        let envelope = ProtoEnvelope::signed_contents(&alice_priv, record).unwrap();
+        let mut validator = LogState::default();
+        validator.validate(&envelope).unwrap();
+        assert_eq!(validator.metadata(), Some(&metadata));
+
+        // Empty metadata fields are rejected and the previous metadata is retained
+        let record = model::PackageRecord {
+            prev: Some(RecordId::package_record::<Sha256>(&envelope)),
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: timestamp + Duration::from_secs(1),
+            entries: vec![model::PackageEntry::Metadata {
+                metadata: model::PackageMetadata {
+                    license: Some(" ".to_string()),
+                    ..Default::default()
+                },
+            }],
+        };
+
+        let envelope = ProtoEnvelope::signed_contents(&alice_priv, record).unwrap();
+        match validator.validate(&envelope).unwrap_err() {
+            ValidationError::EmptyMetadataField { field: "license" } => {}
+            e => panic!("expected a different error: {e}"),
+        }
+        assert_eq!(validator.metadata(), Some(&metadata));
+    }
+
+    #[test]
+    fn test_validate_unyank() {
+        let (alice_pub, alice_priv) = generate_p256_pair();
+        let alice_id = alice_pub.fingerprint();
+
+        let hash_algo = HashAlgorithm::Sha256;
+        let content = hash_algo.digest(&[0, 1, 2, 3]);
+        let version = Version::new(1, 0, 0);
+        let mut validator = LogState::default();
+
+        // In envelope 0: alice inits, grants herself unyank, releases 1.0.0 and then yanks it
+        let timestamp0 = SystemTime::now();
+        let record0 = model::PackageRecord {
+            prev: None,
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: timestamp0,
+            entries: vec![
+                model::PackageEntry::Init {
+                    hash_algorithm: hash_algo,
+                    key: alice_pub.clone(),
+                },
+                model::PackageEntry::GrantFlat {
+                    key: alice_pub,
+                    permissions: vec![model::Permission::Unyank],
+                },
+                model::PackageEntry::Release {
+                    version: version.clone(),
+                    content: content.clone(),
+                },
+                model::PackageEntry::Yank {
+                    version: version.clone(),
+                },
+            ],
+        };
+        let envelope0 = ProtoEnvelope::signed_contents(&alice_priv, record0).unwrap();
+        let record_id0 = RecordId::package_record::<Sha256>(&envelope0);
+        validator.validate(&envelope0).unwrap();
+        assert!(validator.release(&version).unwrap().yanked());
+
+        // In envelope 1: alice unyanks 1.0.0
+        let timestamp1 = timestamp0 + Duration::from_secs(1);
+        let record1 = model::PackageRecord {
+            prev: Some(record_id0.clone()),
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: timestamp1,
+            entries: vec![model::PackageEntry::Unyank {
+                version: version.clone(),
+            }],
+        };
+        let envelope1 = ProtoEnvelope::signed_contents(&alice_priv, record1).unwrap();
+        validator.validate(&envelope1).unwrap();
+
+        // The release should be restored with its original content
+        assert_eq!(
+            validator.release(&version),
+            Some(&Release {
+                record_id: record_id0,
+                version: version.clone(),
+                by: alice_id,
+                timestamp: timestamp0,
+                state: ReleaseState::Released { content }
+            })
+        );
+
+        // Unyanking a release that is not yanked should fail
+        let record2 = model::PackageRecord {
+            prev: Some(RecordId::package_record::<Sha256>(&envelope1)),
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: timestamp1 + Duration::from_secs(1),
+            entries: vec![model::PackageEntry::Unyank {
+                version: version.clone(),
+            }],
+        };
+        let envelope2 = ProtoEnvelope::signed_contents(&alice_priv, record2).unwrap();
+        match validator.validate(&envelope2).unwrap_err() {
+            ValidationError::UnyankOfNotYanked { version: v } if v == version => {}
+            e => panic!("expected a different error: {e}"),
+        }
+
+        // Unyanking a release that does not exist should fail
+        let record3 = model::PackageRecord {
+            prev: Some(RecordId::package_record::<Sha256>(&envelope1)),
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: timestamp1 + Duration::from_secs(1),
+            entries: vec![model::PackageEntry::Unyank {
+                version: Version::new(2, 0, 0),
+            }],
+        };
+        let envelope3 = ProtoEnvelope::signed_contents(&alice_priv, record3).unwrap();
+        match validator.validate(&envelope3).unwrap_err() {
+            ValidationError::UnyankOfUnreleased { .. } => {}
+            e => panic!("expected a different error: {e}"),
+        }
+
+        // The unyank permission is not granted on init
+        let (carol_pub, carol_priv) = generate_p256_pair();
+        let carol_id = carol_pub.fingerprint();
+        let record = model::PackageRecord {
+            prev: None,
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![
+                model::PackageEntry::Init {
+                    hash_algorithm: hash_algo,
+                    key: carol_pub,
+                },
+                model::PackageEntry::Release {
+                    version: version.clone(),
+                    content: hash_algo.digest(&[0, 1, 2, 3]),
+                },
+                model::PackageEntry::Yank {
+                    version: version.clone(),
+                },
+                model::PackageEntry::Unyank { version },
+            ],
+        };
+        let envelope = ProtoEnvelope::signed_contents(&carol_priv, record).unwrap();
+        match LogState::default().validate(&envelope).unwrap_err() {
+            ValidationError::UnauthorizedAction {
+                key_id,
+                needed_permission: model::Permission::Unyank,
+            } if key_id == carol_id => {}
+            e => panic!("expected a different error: {e}"),
+        }
+    }
+
+    #[test]
+    fn test_unyank_of_legacy_yanked_release() {
+        let (alice_pub, alice_priv) = generate_p256_pair();
+
+        let hash_algo = HashAlgorithm::Sha256;
+        let version = Version::new(1, 0, 0);
+        let mut validator = LogState::default();
+
+        // In envelope 0: alice inits, grants herself unyank, releases 1.0.0 and then yanks it
+        let record0 = model::PackageRecord {
+            prev: None,
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![
+                model::PackageEntry::Init {
+                    hash_algorithm: hash_algo,
+                    key: alice_pub.clone(),
+                },
+                model::PackageEntry::GrantFlat {
+                    key: alice_pub,
+                    permissions: vec![model::Permission::Unyank],
+                },
+                model::PackageEntry::Release {
+                    version: version.clone(),
+                    content: hash_algo.digest(&[0, 1, 2, 3]),
+                },
+                model::PackageEntry::Yank {
+                    version: version.clone(),
+                },
+            ],
+        };
+        let envelope0 = ProtoEnvelope::signed_contents(&alice_priv, record0).unwrap();
+        validator.validate(&envelope0).unwrap();
+
+        // State persisted before yanked releases retained their content has no digest
+        let mut json = serde_json::to_value(&validator).unwrap();
+        let yanked = &mut json["releases"]["1.0.0"]["state"];
+        assert_eq!(yanked["status"], "yanked");
+        yanked.as_object_mut().unwrap().remove("content").unwrap();
+
+        let mut validator: LogState = serde_json::from_value(json).unwrap();
+        match &validator.release(&version).unwrap().state {
+            ReleaseState::Yanked { content: None, .. } => {}
+            state => panic!("expected a yanked release without content: {state:?}"),
+        }
+
+        // Unyanking the release should fail as its content is unknown
+        let record1 = model::PackageRecord {
+            prev: Some(RecordId::package_record::<Sha256>(&envelope0)),
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![model::PackageEntry::Unyank {
+                version: version.clone(),
+            }],
+        };
+        let envelope1 = ProtoEnvelope::signed_contents(&alice_priv, record1).unwrap();
+        match validator.validate(&envelope1).unwrap_err() {
+            ValidationError::UnyankOfUnknownContent { version: v } if v == version => {}
+            e => panic!("expected a different error: {e}"),
+        }
+        assert!(validator.release(&version).unwrap().yanked());
+    }
+
+    #[test]
+    fn test_validate_scoped_grant() {
+        let (alice_pub, alice_priv) = generate_p256_pair();
+        let (bob_pub, bob_priv) = generate_p256_pair();
+        let bob_id = bob_pub.fingerprint();
+
+        let hash_algo = HashAlgorithm::Sha256;
+        let mut validator = LogState::default();
+
+        // In envelope 0: alice inits and grants bob release of 2.x versions
+        let timestamp0 = SystemTime::now();
+        let scope: VersionReq = ">=2.0.0, <3.0.0".parse().unwrap();
+        let record0 = model::PackageRecord {
+            prev: None,
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: timestamp0,
+            entries: vec![
+                model::PackageEntry::Init {
+                    hash_algorithm: hash_algo,
+                    key: alice_pub,
+                },
+                model::PackageEntry::GrantScoped {
+                    key: bob_pub,
+                    permissions: vec![model::Permission::Release],
+                    scope: scope.clone(),
+                },
+            ],
+        };
+        let envelope0 = ProtoEnvelope::signed_contents(&alice_priv, record0).unwrap();
+        validator.validate(&envelope0).unwrap();
+        assert_eq!(
+            validator.scoped_permissions().collect::<Vec<_>>(),
+            vec![(
+                &bob_id,
+                &ScopedPermission {
+                    permission: model::Permission::Release,
+                    scope,
+                }
+            )]
+        );
+
+        // In envelope 1: bob releases 2.1.0, which is in scope
+        let timestamp1 = timestamp0 + Duration::from_secs(1);
+        let record1 = model::PackageRecord {
+            prev: Some(RecordId::package_record::<Sha256>(&envelope0)),
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: timestamp1,
+            entries: vec![model::PackageEntry::Release {
+                version: Version::new(2, 1, 0),
+                content: hash_algo.digest(&[0, 1, 2, 3]),
+            }],
+        };
+        let envelope1 = ProtoEnvelope::signed_contents(&bob_priv, record1).unwrap();
+        validator.validate(&envelope1).unwrap();
+        assert!(validator.release(&Version::new(2, 1, 0)).is_some());
+
+        // Bob may not release 3.0.0, yank 2.1.0, or grant permissions to others
+        let out_of_scope = [
+            model::PackageEntry::Release {
+                version: Version::new(3, 0, 0),
+                content: hash_algo.digest(&[4, 5, 6, 7]),
+            },
+            model::PackageEntry::Yank {
+                version: Version::new(2, 1, 0),
+            },
+            model::PackageEntry::GrantFlat {
+                key: generate_p256_pair().0,
+                permissions: vec![model::Permission::Release],
+            },
+        ];
+
+        for entry in out_of_scope {
+            let record = model::PackageRecord {
+                prev: Some(RecordId::package_record::<Sha256>(&envelope1)),
+                version: PACKAGE_RECORD_VERSION,
+                timestamp: timestamp1 + Duration::from_secs(1),
+                entries: vec![entry],
+            };
+            let envelope = ProtoEnvelope::signed_contents(&bob_priv, record).unwrap();
+            match validator.validate(&envelope).unwrap_err() {
+                ValidationError::UnauthorizedAction { key_id, .. } if key_id == bob_id => {}
+                e => panic!("expected a different error: {e}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_rotate_key() {
+        let (alice_pub, alice_priv) = generate_p256_pair();
+        let (bob_pub, bob_priv) = generate_p256_pair();
+        let bob_id = bob_pub.fingerprint();
+        let (carol_pub, carol_priv) = generate_p256_pair();
+        let carol_id = carol_pub.fingerprint();
+
+        let hash_algo = HashAlgorithm::Sha256;
+        let scope: VersionReq = "^2".parse().unwrap();
+        let mut validator = LogState::default();
+
+        // In envelope 0: alice inits and grants bob yank and release of 2.x versions
+        let timestamp0 = SystemTime::now();
+        let record0 = model::PackageRecord {
+            prev: None,
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: timestamp0,
+            entries: vec![
+                model::PackageEntry::Init {
+                    hash_algorithm: hash_algo,
+                    key: alice_pub,
+                },
+                model::PackageEntry::GrantFlat {
+                    key: bob_pub.clone(),
+                    permissions: vec![model::Permission::Yank],
+                },
+                model::PackageEntry::GrantScoped {
+                    key: bob_pub,
+                    permissions: vec![model::Permission::Release],
+                    scope: scope.clone(),
+                },
+            ],
+        };
+        let envelope0 = ProtoEnvelope::signed_contents(&alice_priv, record0).unwrap();
+        validator.validate(&envelope0).unwrap();
+        assert!(validator.key_has_permissions(&bob_id));
+
+        // In envelope 1: bob rotates his key to carol's key
+        let timestamp1 = timestamp0 + Duration::from_secs(1);
+        let record1 = model::PackageRecord {
+            prev: Some(RecordId::package_record::<Sha256>(&envelope0)),
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: timestamp1,
+            entries: vec![model::PackageEntry::RotateKey {
+                key_id: bob_id.clone(),
+                key: carol_pub,
+            }],
+        };
+        let envelope1 = ProtoEnvelope::signed_contents(&bob_priv, record1).unwrap();
+        validator.validate(&envelope1).unwrap();
+
+        assert!(!validator.key_has_permissions(&bob_id));
+        assert!(validator.key_has_permissions(&carol_id));
+        assert_eq!(
+            validator.scoped_permissions().collect::<Vec<_>>(),
+            vec![(
+                &carol_id,
+                &ScopedPermission {
+                    permission: model::Permission::Release,
+                    scope,
+                }
+            )]
+        );
+
+        // Bob can no longer release, but carol can
+        let release = model::PackageEntry::Release {
+            version: Version::new(2, 0, 0),
+            content: hash_algo.digest(&[0, 1, 2, 3]),
+        };
+        let record = model::PackageRecord {
+            prev: Some(RecordId::package_record::<Sha256>(&envelope1)),
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: timestamp1 + Duration::from_secs(1),
+            entries: vec![release.clone()],
+        };
+        let envelope = ProtoEnvelope::signed_contents(&bob_priv, record.clone()).unwrap();
+        match validator.validate(&envelope).unwrap_err() {
+            ValidationError::UnauthorizedAction { key_id, .. } if key_id == bob_id => {}
+            e => panic!("expected a different error: {e}"),
+        }
+
+        let envelope = ProtoEnvelope::signed_contents(&carol_priv, record).unwrap();
+        validator.validate(&envelope).unwrap();
+
+        // Bob's key has no permissions left to rotate
+        let (dave_pub, _) = generate_p256_pair();
+        let record = model::PackageRecord {
+            prev: Some(RecordId::package_record::<Sha256>(&envelope)),
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: timestamp1 + Duration::from_secs(2),
+            entries: vec![model::PackageEntry::RotateKey {
+                key_id: bob_id.clone(),
+                key: dave_pub,
+            }],
+        };
+        let envelope = ProtoEnvelope::signed_contents(&carol_priv, record).unwrap();
+        match validator.validate(&envelope).unwrap_err() {
+            ValidationError::RotateKeyWithoutPermissions { key_id } if key_id == bob_id => {}
+            e => panic!("expected a different error: {e}"),
+        }
+    }
+
+    #[test]
+    fn test_threshold() {
+        let (alice_pub, alice_priv) = generate_p256_pair();
+        let alice_id = alice_pub.fingerprint();
+        let (bob_pub, bob_priv) = generate_p256_pair();
+        let bob_id = bob_pub.fingerprint();
+        let (_, carol_priv) = generate_p256_pair();
+
+        let hash_algo = HashAlgorithm::Sha256;
+        let version = Version::new(1, 0, 0);
+        let mut validator = LogState::default();
+
+        // In envelope 0: alice inits, grants bob yank, releases 1.0.0, and requires two yank signatures
+        let timestamp0 = SystemTime::now();
+        let record0 = model::PackageRecord {
+            prev: None,
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: timestamp0,
+            entries: vec![
+                model::PackageEntry::Init {
+                    hash_algorithm: hash_algo,
+                    key: alice_pub,
+                },
+                model::PackageEntry::GrantFlat {
+                    key: bob_pub,
+                    permissions: vec![model::Permission::Yank],
+                },
+                model::PackageEntry::Release {
+                    version: version.clone(),
+                    content: hash_algo.digest(&[0, 1, 2, 3]),
+                },
+                model::PackageEntry::SetThreshold {
+                    permissions: vec![model::Permission::Yank],
+                    threshold: 2,
+                },
+            ],
+        };
+        let envelope0 = ProtoEnvelope::signed_contents(&alice_priv, record0).unwrap();
+        validator.validate(&envelope0).unwrap();
+        assert_eq!(validator.threshold(model::Permission::Yank), 2);
+        assert_eq!(validator.threshold(model::Permission::Release), 1);
+
+        let yank = model::PackageRecord {
+            prev: Some(RecordId::package_record::<Sha256>(&envelope0)),
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: timestamp0 + Duration::from_secs(1),
+            entries: vec![model::PackageEntry::Yank {
+                version: version.clone(),
+            }],
+        };
+
+        // A yank signed only by alice does not meet the threshold
+        let envelope = ProtoEnvelope::signed_contents(&alice_priv, yank.clone()).unwrap();
+        match validator.validate(&envelope).unwrap_err() {
+            ValidationError::ThresholdNotMet {
+                permission: model::Permission::Yank,
+                threshold: 2,
+                signatures: 1,
+            } => {}
+            e => panic!("expected a different error: {e}"),
+        }
+
+        // Co-signatures from unknown keys are rejected
+        let envelope = ProtoEnvelope::signed_contents(&alice_priv, yank.clone())
+            .unwrap()
+            .with_cosignatures([Cosignature::new(&carol_priv, &yank).unwrap()]);
+        match validator.validate(&envelope).unwrap_err() {
+            ValidationError::KeyIDNotRecognized { .. } => {}
+            e => panic!("expected a different error: {e}"),
+        }
+
+        // The author cannot co-sign their own record to meet the threshold
+        let envelope = ProtoEnvelope::signed_contents(&alice_priv, yank.clone())
+            .unwrap()
+            .with_cosignatures([Cosignature::new(&alice_priv, &yank).unwrap()]);
+        match validator.validate(&envelope).unwrap_err() {
+            ValidationError::DuplicateSignature { key_id } if key_id == alice_id => {}
+            e => panic!("expected a different error: {e}"),
+        }
+
+        // A yank co-signed by bob meets the threshold
+        let envelope1 = ProtoEnvelope::signed_contents(&alice_priv, yank.clone())
+            .unwrap()
+            .with_cosignatures([Cosignature::new(&bob_priv, &yank).unwrap()]);
+        validator.validate(&envelope1).unwrap();
+        assert!(validator.release(&version).unwrap().yanked());
+
+        // Revoking bob's yank permission would make the threshold unreachable
+        let record = model::PackageRecord {
+            prev: Some(RecordId::package_record::<Sha256>(&envelope1)),
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: timestamp0 + Duration::from_secs(2),
+            entries: vec![model::PackageEntry::RevokeFlat {
+                key_id: bob_id,
+                permissions: vec![model::Permission::Yank],
+            }],
+        };
+        let envelope = ProtoEnvelope::signed_contents(&alice_priv, record.clone())
+            .unwrap()
+            .with_cosignatures([Cosignature::new(&bob_priv, &record).unwrap()]);
+        match validator.validate(&envelope).unwrap_err() {
+            ValidationError::ThresholdUnreachable {
+                permission: model::Permission::Yank,
+                threshold: 2,
+                keys: 1,
+            } => {}
+            e => panic!("expected a different error: {e}"),
+        }
+
+        // Lowering the threshold also requires meeting the current threshold
+        let record = model::PackageRecord {
+            prev: Some(RecordId::package_record::<Sha256>(&envelope1)),
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: timestamp0 + Duration::from_secs(2),
+            entries: vec![model::PackageEntry::SetThreshold {
+                permissions: vec![model::Permission::Yank],
+                threshold: 1,
+            }],
+        };
+        let envelope = ProtoEnvelope::signed_contents(&alice_priv, record.clone()).unwrap();
+        match validator.validate(&envelope).unwrap_err() {
+            ValidationError::ThresholdNotMet { .. } => {}
+            e => panic!("expected a different error: {e}"),
+        }
+
+        let envelope = envelope.with_cosignatures([Cosignature::new(&bob_priv, &record).unwrap()]);
+        validator.validate(&envelope).unwrap();
+        assert_eq!(validator.threshold(model::Permission::Yank), 1);
+    }
+
+    #[test]
+    fn test_threshold_reachable() {
+        let (alice_pub, alice_priv) = generate_p256_pair();
+        let (bob_pub, bob_priv) = generate_p256_pair();
+        let bob_id = bob_pub.fingerprint();
+        let (carol_pub, _) = generate_p256_pair();
+
+        let hash_algo = HashAlgorithm::Sha256;
+        let mut validator = LogState::default();
+
+        // In envelope 0: alice inits, grants bob yank, and requires two yank signatures
+        let timestamp0 = SystemTime::now();
+        let record0 = model::PackageRecord {
+            prev: None,
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: timestamp0,
+            entries: vec![
+                model::PackageEntry::Init {
+                    hash_algorithm: hash_algo,
+                    key: alice_pub.clone(),
+                },
+                model::PackageEntry::GrantFlat {
+                    key: bob_pub.clone(),
+                    permissions: vec![model::Permission::Yank],
+                },
+                model::PackageEntry::SetThreshold {
+                    permissions: vec![model::Permission::Yank],
+                    threshold: 2,
+                },
+            ],
+        };
+        let envelope0 = ProtoEnvelope::signed_contents(&alice_priv, record0).unwrap();
+        validator.validate(&envelope0).unwrap();
+
+        // Rotating bob's key into alice's key would leave one key able to yank
+        let record = model::PackageRecord {
+            prev: Some(RecordId::package_record::<Sha256>(&envelope0)),
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: timestamp0 + Duration::from_secs(1),
+            entries: vec![model::PackageEntry::RotateKey {
+                key_id: bob_id.clone(),
+                key: alice_pub,
+            }],
+        };
+        let envelope = ProtoEnvelope::signed_contents(&bob_priv, record.clone())
+            .unwrap()
+            .with_cosignatures([Cosignature::new(&alice_priv, &record).unwrap()]);
+        match validator.validate(&envelope).unwrap_err() {
+            ValidationError::ThresholdUnreachable {
+                permission: model::Permission::Yank,
+                threshold: 2,
+                keys: 1,
+            } => {}
+            e => panic!("expected a different error: {e}"),
+        }
+
+        // A scoped yank grant to carol keeps the threshold reachable when bob's grant is revoked
+        let record = model::PackageRecord {
+            prev: Some(RecordId::package_record::<Sha256>(&envelope0)),
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: timestamp0 + Duration::from_secs(1),
+            entries: vec![
+                model::PackageEntry::GrantScoped {
+                    key: carol_pub,
+                    permissions: vec![model::Permission::Yank],
+                    scope: "^1".parse().unwrap(),
+                },
+                model::PackageEntry::RevokeFlat {
+                    key_id: bob_id,
+                    permissions: vec![model::Permission::Yank],
+                },
+            ],
+        };
+        let envelope = ProtoEnvelope::signed_contents(&alice_priv, record.clone())
+            .unwrap()
+            .with_cosignatures([Cosignature::new(&bob_priv, &record).unwrap()]);
+        validator.validate(&envelope).unwrap();
+    }
+
+    #[test]
+    fn test_log_hash_algorithm() {
+        let (alice_pub, alice_priv) = generate_p256_pair();
+
+        let timestamp = SystemTime::now();
+        let record0 = model::PackageRecord {
+            prev: None,
+            version: PACKAGE_RECORD_VERSION,
+            timestamp,
+            entries: vec![model::PackageEntry::Init {
+                hash_algorithm: HashAlgorithm::Sha512,
+                key: alice_pub.clone(),
+            }],
+        };
+        let envelope0 = ProtoEnvelope::signed_contents(&alice_priv, record0).unwrap();
+
+        let mut validator = LogState::default();
+        validator.validate(&envelope0).unwrap();
+
+        let record_id0 = RecordId::package_record::<Sha512>(&envelope0);
+        assert_eq!(validator.algorithm, Some(HashAlgorithm::Sha512));
+        assert_eq!(validator.head().as_ref().unwrap().digest, record_id0);
+
+        // A record linking with a different algorithm is rejected
+        let record1 = model::PackageRecord {
+            prev: Some(RecordId::package_record::<Sha256>(&envelope0)),
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: timestamp + Duration::from_secs(1),
+            entries: vec![model::PackageEntry::Yank {
+                version: "1.0.0".parse().unwrap(),
+            }],
+        };
+        let envelope1 = ProtoEnvelope::signed_contents(&alice_priv, record1).unwrap();
+        assert!(matches!(
+            validator.validate(&envelope1).unwrap_err(),
+            ValidationError::IncorrectHashAlgorithm {
+                found: HashAlgorithm::Sha256,
+                expected: HashAlgorithm::Sha512,
+            }
+        ));
+
+        // A record linking with the log's algorithm is accepted
+        let content: AnyHash = HashAlgorithm::Blake3.digest(b"content");
+        let record1 = model::PackageRecord {
+            prev: Some(record_id0),
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: timestamp + Duration::from_secs(1),
+            entries: vec![model::PackageEntry::Release {
+                version: "1.0.0".parse().unwrap(),
+                content: content.clone(),
+            }],
+        };
+        let envelope1 = ProtoEnvelope::signed_contents(&alice_priv, record1).unwrap();
+        validator.validate(&envelope1).unwrap();
+
+        assert_eq!(
+            validator.head().as_ref().unwrap().digest,
+            RecordId::package_record::<Sha512>(&envelope1)
+        );
+        assert_eq!(
+            validator
+                .release(&"1.0.0".parse().unwrap())
+                .unwrap()
+                .content(),
+            Some(&content)
+        );
+    }
+
+    #[test]
+    fn test_validate_namespace_owner() {
+        let (operator_pub, operator_priv) = generate_p256_pair();
+        let (alice_pub, alice_priv) = generate_p256_pair();
+        let (bob_pub, bob_priv) = generate_p256_pair();
+
+        let record = operator::OperatorRecord {
+            prev: None,
+            version: operator::OPERATOR_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![
+                operator::OperatorEntry::Init {
+                    hash_algorithm: HashAlgorithm::Sha256,
+                    key: operator_pub,
+                },
+                operator::OperatorEntry::DefineNamespace {
+                    namespace: "wasi".to_string(),
+                    owners: vec![alice_pub.fingerprint()],
+                },
+                operator::OperatorEntry::ImportNamespace {
+                    namespace: "acme".to_string(),
+                    registry: "registry.acme.example".to_string(),
+                },
+            ],
+        };
+        let envelope = ProtoEnvelope::signed_contents(&operator_priv, record)
+            .expect("failed to sign envelope");
+        let mut operator = operator::LogState::new();
+        operator.validate(&envelope).unwrap();
+
+        let init = |key: &signing::PublicKey, private: &signing::PrivateKey| {
+            let record = model::PackageRecord {
+                prev: None,
+                version: PACKAGE_RECORD_VERSION,
+                timestamp: SystemTime::now(),
+                entries: vec![model::PackageEntry::Init {
+                    hash_algorithm: HashAlgorithm::Sha256,
+                    key: key.clone(),
+                }],
+            };
+            ProtoEnvelope::signed_contents(private, record).expect("failed to sign envelope")
+        };
+
+        // Only an owner may initialize a package in a defined namespace
+        let id = PackageId::new("wasi:http").unwrap();
+        let mut validator = LogState::new();
+        match validator
+            .validate_with_operator(&id, &operator, &init(&bob_pub, &bob_priv))
+            .unwrap_err()
+        {
+            ValidationError::NamespaceNotOwned { namespace, .. } if namespace == "wasi" => {}
+            e => panic!("expected a different error: {e}"),
+        }
+        assert_eq!(validator, LogState::new());
+        validator
+            .validate_with_operator(&id, &operator, &init(&alice_pub, &alice_priv))
+            .unwrap();
+
+        // Packages in an imported namespace cannot be initialized
+        let id = PackageId::new("acme:foo").unwrap();
+        match LogState::new()
+            .validate_with_operator(&id, &operator, &init(&alice_pub, &alice_priv))
+            .unwrap_err()
+        {
+            ValidationError::NamespaceImported { registry, .. }
+                if registry == "registry.acme.example" => {}
+            e => panic!("expected a different error: {e}"),
+        }
+
+        // Namespaces not defined by the operator log are not restricted
+        let id = PackageId::new("other:foo").unwrap();
+        LogState::new()
+            .validate_with_operator(&id, &operator, &init(&bob_pub, &bob_priv))
+            .unwrap();
+    }
 }
@@ -31,6 +31,8 @@ pub struct ProtoEnvelope<Contents> {
     key_id: signing::KeyID,
     /// The signature for the content_bytes
     signature: signing::Signature,
+    /// The co-signatures for the content_bytes by other keys
+    cosignatures: Vec<Cosignature>,
 }
 
 impl<Contents> ProtoEnvelope<Contents> {
@@ -51,9 +53,22 @@ impl<Contents> ProtoEnvelope<Contents> {
             content_bytes,
             key_id,
             signature,
+            cosignatures: Vec::new(),
         })
     }
 
+    /// Adds co-signatures to the envelope.
+    ///
+    /// The co-signatures are not verified; verification happens
+    /// when the envelope is validated.
+    pub fn with_cosignatures(
+        mut self,
+        cosignatures: impl IntoIterator<Item = Cosignature>,
+    ) -> Self {
+        self.cosignatures.extend(cosignatures);
+        self
+    }
+
     /// Get the byte representation of the envelope contents.
     pub fn content_bytes(&self) -> &[u8] {
         &self.content_bytes
@@ -67,6 +82,11 @@ impl<Contents> ProtoEnvelope<Contents> {
         &self.signature
     }
 
+    /// Gets the co-signatures of the envelope.
+    pub fn cosignatures(&self) -> &[Cosignature] {
+        &self.cosignatures
+    }
+
     /// Get the representation of the entire envelope as a byte vector.
     /// This is the logical inverse of `Envelope::from_bytes`.
     pub fn to_protobuf(&self) -> Vec<u8> {
@@ -74,6 +94,14 @@ impl<Contents> ProtoEnvelope<Contents> {
             contents: self.content_bytes.clone(),
             key_id: self.key_id.to_string(),
             signature: self.signature.to_string(),
+            cosignatures: self
+                .cosignatures
+                .iter()
+                .map(|c| protobuf::EnvelopeCosignature {
+                    key_id: c.key_id.to_string(),
+                    signature: c.signature.to_string(),
+                })
+                .collect(),
         };
         proto_envelope.encode_to_vec()
     }
@@ -91,12 +119,23 @@ impl<Contents> ProtoEnvelope<Contents> {
         // Read key ID and signature
         let key_id = envelope.key_id.into();
         let signature = envelope.signature.parse()?;
+        let cosignatures = envelope
+            .cosignatures
+            .into_iter()
+            .map(|c| {
+                Ok(Cosignature {
+                    key_id: c.key_id.into(),
+                    signature: c.signature.parse()?,
+                })
+            })
+            .collect::<Result<_, ParseEnvelopeError>>()?;
 
         Ok(ProtoEnvelope {
             contents,
             content_bytes: envelope.contents,
             key_id,
             signature,
+            cosignatures,
         })
     }
 }
@@ -107,6 +146,32 @@ impl<Content> AsRef<Content> for ProtoEnvelope<Content> {
     }
 }
 
+/// Represents an additional signature over the contents of an envelope.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Cosignature {
+    /// The hash of the key that produced the co-signature
+    pub key_id: signing::KeyID,
+    /// The signature for the content bytes
+    pub signature: signing::Signature,
+}
+
+impl Cosignature {
+    /// Creates a co-signature for the given contents.
+    pub fn new<Contents>(
+        private_key: &signing::PrivateKey,
+        contents: &Contents,
+    ) -> Result<Self, signing::SignatureError>
+    where
+        Contents: Signable,
+    {
+        Ok(Self {
+            key_id: private_key.public_key().fingerprint(),
+            signature: contents.sign(private_key)?,
+        })
+    }
+}
+
 /// Errors that occur in the process of parsing an envelope from bytes
 #[derive(Error, Debug)]
 pub enum ParseEnvelopeError {
@@ -134,6 +199,9 @@ pub struct ProtoEnvelopeBody {
     key_id: signing::KeyID,
     /// The signature for the content_bytes
     signature: signing::Signature,
+    /// The co-signatures for the content_bytes by other keys
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    cosignatures: Vec<Cosignature>,
 }
 
 impl<Content> TryFrom<ProtoEnvelopeBody> for ProtoEnvelope<Content>
@@ -149,6 +217,7 @@ where
             content_bytes: value.content_bytes,
             key_id: value.key_id,
             signature: value.signature,
+            cosignatures: value.cosignatures,
         };
         Ok(envelope)
     }
@@ -160,6 +229,7 @@ impl<Content> From<ProtoEnvelope<Content>> for ProtoEnvelopeBody {
             content_bytes: value.content_bytes,
             key_id: value.key_id,
             signature: value.signature,
+            cosignatures: value.cosignatures,
         }
     }
 }
@@ -170,6 +240,7 @@ impl fmt::Debug for ProtoEnvelopeBody {
             .field("content_bytes", &STANDARD.encode(&self.content_bytes))
             .field("key_id", &self.key_id)
             .field("signature", &self.signature)
+            .field("cosignatures", &self.cosignatures)
             .finish()
     }
 }
@@ -217,6 +288,7 @@ impl fmt::Debug for PublishedProtoEnvelopeBody {
             )
             .field("key_id", &self.envelope.key_id)
             .field("signature", &self.envelope.signature)
+            .field("cosignatures", &self.envelope.cosignatures)
             .field("registry_index", &self.registry_index)
             .finish()
     }
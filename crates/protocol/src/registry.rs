@@ -81,6 +81,52 @@ impl VisitBytes for TimestampedCheckpoint {
     }
 }
 
+/// Represents a checkpoint countersigned by a witness of the registry.
+///
+/// A witness cosigns a checkpoint only if it is consistent with every
+/// checkpoint of the registry the witness has previously seen.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WitnessedCheckpoint {
+    #[serde(flatten)]
+    pub checkpoint: Checkpoint,
+    pub timestamp: u64,
+}
+
+impl WitnessedCheckpoint {
+    pub fn new(checkpoint: Checkpoint, time: SystemTime) -> anyhow::Result<Self> {
+        Ok(Self {
+            checkpoint,
+            timestamp: time.duration_since(std::time::UNIX_EPOCH)?.as_secs(),
+        })
+    }
+
+    pub fn now(checkpoint: Checkpoint) -> anyhow::Result<Self> {
+        Self::new(checkpoint, SystemTime::now())
+    }
+}
+
+impl Signable for WitnessedCheckpoint {
+    const PREFIX: &'static [u8] = b"WARG-CHECKPOINT-COSIGNATURE-V0";
+}
+
+impl prefix::VisitPrefixEncode for WitnessedCheckpoint {
+    fn visit_pe<BV: ?Sized + ByteVisitor>(&self, visitor: &mut prefix::PrefixEncodeVisitor<BV>) {
+        visitor.visit_str_raw("WARG-WITNESSED-CHECKPOINT-V0");
+        visitor.visit_unsigned(self.checkpoint.log_length as u64);
+        visitor.visit_str(&self.checkpoint.log_root.to_string());
+        visitor.visit_str(&self.checkpoint.map_root.to_string());
+        visitor.visit_unsigned(self.timestamp);
+    }
+}
+
+// Manual impls of VisitBytes for VisitPrefixEncode to avoid conflict with blanket impls
+impl VisitBytes for WitnessedCheckpoint {
+    fn visit<BV: ?Sized + ByteVisitor>(&self, visitor: &mut BV) {
+        self.visit_bv(visitor);
+    }
+}
+
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct MapLeaf {
     pub record_id: RecordId,
@@ -277,6 +323,15 @@ impl RecordId {
         let hash: Hash<D> = Hash::of((prefix, record.content_bytes()));
         Self(hash.into())
     }
+
+    /// Computes the id of a package record using the given hash algorithm.
+    pub fn package_record_with(
+        algorithm: HashAlgorithm,
+        record: &ProtoEnvelope<PackageRecord>,
+    ) -> Self {
+        let prefix: &[u8] = b"WARG-PACKAGE-LOG-RECORD-V0:".as_slice();
+        Self(algorithm.hash_of((prefix, record.content_bytes())))
+    }
 }
 
 impl fmt::Display for RecordId {
@@ -56,6 +56,14 @@ impl<Contents> SerdeEnvelope<Contents> {
     pub fn signature(&self) -> &signing::Signature {
         &self.signature
     }
+
+    /// Verifies the signature of the envelope with the given public key.
+    pub fn verify(&self, public_key: &signing::PublicKey) -> Result<(), signing::SignatureError>
+    where
+        Contents: Signable,
+    {
+        Contents::verify(public_key, &self.contents.encode(), &self.signature)
+    }
 }
 
 impl<Content> AsRef<Content> for SerdeEnvelope<Content> {
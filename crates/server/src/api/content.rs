@@ -0,0 +1,193 @@
+use crate::contentstore::{parse_content_file_name, ContentStore};
+use axum::{
+    body::{Body, StreamBody},
+    debug_handler,
+    extract::{Path, State},
+    headers::{
+        AcceptRanges, CacheControl, ContentLength, ContentRange, ContentType, ETag, HeaderMapExt,
+        IfNoneMatch, IfRange, Range,
+    },
+    http::{Method, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router, TypedHeader,
+};
+use std::{ops::Bound, str::FromStr, sync::Arc, time::Duration};
+
+/// The maximum age of cached content.
+///
+/// Content is addressed by digest, so it never changes.
+const CONTENT_MAX_AGE: Duration = Duration::from_secs(365 * 24 * 60 * 60);
+
+#[derive(Clone)]
+pub struct Config {
+    content_store: Arc<dyn ContentStore>,
+}
+
+impl Config {
+    pub fn new(content_store: Arc<dyn ContentStore>) -> Self {
+        Self { content_store }
+    }
+
+    pub fn into_router(self) -> Router {
+        Router::new()
+            .route("/:file_name", get(get_content))
+            .with_state(self)
+    }
+}
+
+/// Resolves the requested byte range of content of the given length.
+///
+/// Returns `Ok(None)` if the entire content should be sent and `Err(())`
+/// if the requested range cannot be satisfied.
+fn resolve_range(range: &Range, len: u64) -> Result<Option<(u64, u64)>, ()> {
+    let mut ranges = range.iter();
+    let (start, end) = match (ranges.next(), ranges.next()) {
+        (Some(range), None) => range,
+        // Multiple ranges are not supported; send the entire content instead
+        _ => return Ok(None),
+    };
+
+    let (start, end) = match (start, end) {
+        (Bound::Included(start), Bound::Included(end)) => (start, end.min(len.saturating_sub(1))),
+        (Bound::Included(start), Bound::Unbounded) => (start, len.saturating_sub(1)),
+        (Bound::Unbounded, Bound::Included(suffix)) if suffix > 0 => {
+            (len.saturating_sub(suffix), len.saturating_sub(1))
+        }
+        _ => return Err(()),
+    };
+
+    if start >= len || start > end {
+        return Err(());
+    }
+
+    Ok(Some((start, end)))
+}
+
+#[debug_handler]
+async fn get_content(
+    State(config): State<Config>,
+    method: Method,
+    Path(file_name): Path<String>,
+    range: Option<TypedHeader<Range>>,
+    if_range: Option<TypedHeader<IfRange>>,
+    if_none_match: Option<TypedHeader<IfNoneMatch>>,
+) -> Response {
+    let Some(digest) = parse_content_file_name(&file_name) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let len = match config.content_store.content_size(&digest).await {
+        Ok(Some(len)) => len,
+        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+        Err(e) => {
+            tracing::error!("failed to get the size of content `{digest}`: {e}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let etag = ETag::from_str(&format!("\"{digest}\"")).expect("digest should be a valid tag");
+
+    let mut response = Response::new(Body::empty());
+    let headers = response.headers_mut();
+    headers.typed_insert(etag.clone());
+    headers.typed_insert(AcceptRanges::bytes());
+    headers.typed_insert(
+        CacheControl::new()
+            .with_public()
+            .with_max_age(CONTENT_MAX_AGE),
+    );
+
+    if let Some(TypedHeader(if_none_match)) = if_none_match {
+        if !if_none_match.precondition_passes(&etag) {
+            *response.status_mut() = StatusCode::NOT_MODIFIED;
+            return response.into_response();
+        }
+    }
+
+    // A range is only honored if the content still matches the `If-Range` tag
+    let range = match (range, if_range) {
+        (Some(TypedHeader(range)), None) => Some(range),
+        (Some(TypedHeader(range)), Some(TypedHeader(if_range)))
+            if !if_range.is_modified(Some(&etag), None) =>
+        {
+            Some(range)
+        }
+        _ => None,
+    };
+
+    let (start, end) = match range.map(|r| resolve_range(&r, len)).transpose() {
+        Ok(Some(Some((start, end)))) => {
+            *response.status_mut() = StatusCode::PARTIAL_CONTENT;
+            response.headers_mut().typed_insert(
+                ContentRange::bytes(start..=end, len).expect("range should be valid"),
+            );
+            (start, end)
+        }
+        Ok(_) if len == 0 => (0, 0),
+        Ok(_) => (0, len - 1),
+        Err(()) => {
+            *response.status_mut() = StatusCode::RANGE_NOT_SATISFIABLE;
+            response
+                .headers_mut()
+                .typed_insert(ContentRange::unsatisfied_bytes(len));
+            return response.into_response();
+        }
+    };
+
+    let content_len = if len == 0 { 0 } else { end - start + 1 };
+    response
+        .headers_mut()
+        .typed_insert(ContentLength(content_len));
+    response
+        .headers_mut()
+        .typed_insert(ContentType::octet_stream());
+
+    if method == Method::HEAD || content_len == 0 {
+        return response.into_response();
+    }
+
+    let stream = match config
+        .content_store
+        .load_content_range(&digest, start, content_len)
+        .await
+    {
+        Ok(stream) => stream,
+        Err(e) => {
+            tracing::error!("failed to load content `{digest}`: {e}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let (parts, _) = response.into_parts();
+    Response::from_parts(parts, StreamBody::new(stream)).into_response()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn range(value: &'static str) -> Range {
+        let mut headers = axum::http::HeaderMap::new();
+        headers.insert(
+            axum::http::header::RANGE,
+            axum::http::HeaderValue::from_static(value),
+        );
+        headers.typed_get().unwrap()
+    }
+
+    #[test]
+    fn it_resolves_ranges() {
+        assert_eq!(resolve_range(&range("bytes=0-9"), 100), Ok(Some((0, 9))));
+        assert_eq!(resolve_range(&range("bytes=10-"), 100), Ok(Some((10, 99))));
+        assert_eq!(resolve_range(&range("bytes=-10"), 100), Ok(Some((90, 99))));
+        assert_eq!(
+            resolve_range(&range("bytes=90-200"), 100),
+            Ok(Some((90, 99)))
+        );
+        assert_eq!(resolve_range(&range("bytes=-200"), 100), Ok(Some((0, 99))));
+        assert_eq!(resolve_range(&range("bytes=0-1,5-6"), 100), Ok(None));
+        assert_eq!(resolve_range(&range("bytes=100-"), 100), Err(()));
+        assert_eq!(resolve_range(&range("bytes=0-"), 0), Err(()));
+    }
+}
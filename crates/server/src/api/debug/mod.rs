@@ -15,9 +15,9 @@ use warg_crypto::{
     signing::KeyID,
 };
 use warg_protocol::{
-    package::{LogState, Permission, Release},
+    package::{LogState, PackageMetadata, Permission, Release},
     registry::{LogId, PackageId, RecordId},
-    Version,
+    Version, VersionReq,
 };
 
 use crate::{api::v1::Json, services::CoreService};
@@ -55,6 +55,8 @@ struct PackageInfo {
     log_id: LogId,
     records: Vec<RecordInfo>,
     releases: Vec<Release>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metadata: Option<PackageMetadata>,
 }
 
 #[derive(Serialize)]
@@ -62,6 +64,8 @@ struct PackageInfo {
 struct RecordInfo {
     record_id: RecordId,
     timestamp: u64,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    cosigners: Vec<KeyID>,
     entries: Vec<EntryInfo>,
 }
 
@@ -76,9 +80,15 @@ struct EntryInfo {
     #[serde(skip_serializing_if = "Vec::is_empty")]
     permissions: Vec<Permission>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    scope: Option<VersionReq>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     version: Option<Version>,
     #[serde(skip_serializing_if = "Option::is_none")]
     content: Option<AnyHash>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metadata: Option<PackageMetadata>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    threshold: Option<u32>,
 }
 
 #[debug_handler]
@@ -108,7 +118,10 @@ async fn get_package_info(
             package_state
                 .validate(&record.envelope)
                 .context("validate")?;
-            let record_id = RecordId::package_record::<Sha256>(&record.envelope);
+            let record_id = RecordId::package_record_with(
+                record.envelope.as_ref().hash_algorithm(),
+                &record.envelope,
+            );
             let timestamp = record
                 .envelope
                 .as_ref()
@@ -135,6 +148,17 @@ async fn get_package_info(
                             permissions: permissions.clone(),
                             ..Default::default()
                         },
+                        GrantScoped {
+                            key,
+                            permissions,
+                            scope,
+                        } => EntryInfo {
+                            kind: "grant-scoped",
+                            key: Some(key.to_string()),
+                            permissions: permissions.clone(),
+                            scope: Some(scope.clone()),
+                            ..Default::default()
+                        },
                         RevokeFlat {
                             key_id,
                             permissions,
@@ -144,6 +168,12 @@ async fn get_package_info(
                             permissions: permissions.clone(),
                             ..Default::default()
                         },
+                        RotateKey { key_id, key } => EntryInfo {
+                            kind: "rotate-key",
+                            key: Some(key.to_string()),
+                            key_id: Some(key_id.clone()),
+                            ..Default::default()
+                        },
                         Release { version, content } => EntryInfo {
                             kind: "release",
                             version: Some(version.clone()),
@@ -155,6 +185,25 @@ async fn get_package_info(
                             version: Some(version.clone()),
                             ..Default::default()
                         },
+                        Unyank { version } => EntryInfo {
+                            kind: "unyank",
+                            version: Some(version.clone()),
+                            ..Default::default()
+                        },
+                        Metadata { metadata } => EntryInfo {
+                            kind: "metadata",
+                            metadata: Some(metadata.clone()),
+                            ..Default::default()
+                        },
+                        SetThreshold {
+                            permissions,
+                            threshold,
+                        } => EntryInfo {
+                            kind: "set-threshold",
+                            permissions: permissions.clone(),
+                            threshold: Some(*threshold),
+                            ..Default::default()
+                        },
                         _ => EntryInfo {
                             kind: "UNKNOWN",
                             ..Default::default()
@@ -162,21 +211,30 @@ async fn get_package_info(
                     }
                 })
                 .collect();
+            let cosigners = record
+                .envelope
+                .cosignatures()
+                .iter()
+                .map(|c| c.key_id.clone())
+                .collect();
             Ok(RecordInfo {
                 record_id,
                 timestamp,
+                cosigners,
                 entries,
             })
         })
         .collect::<Result<_, DebugError>>()?;
 
     let releases = package_state.releases().cloned().collect();
+    let metadata = package_state.metadata().cloned();
 
     Ok(Json(PackageInfo {
         package_id,
         log_id,
         records,
         releases,
+        metadata,
     }))
 }
 
@@ -0,0 +1,90 @@
+use crate::services::{CommitQueueMetrics, CoreService};
+use axum::{
+    debug_handler, extract::State, http::header, response::IntoResponse, routing::get, Router,
+};
+use std::fmt::Write;
+
+#[derive(Clone)]
+pub struct Config {
+    core_service: CoreService,
+}
+
+impl Config {
+    pub fn new(core_service: CoreService) -> Self {
+        Self { core_service }
+    }
+
+    pub fn into_router(self) -> Router {
+        Router::new().route("/", get(get_metrics)).with_state(self)
+    }
+}
+
+/// Formats the metrics in the Prometheus text exposition format.
+fn format_metrics(queue: &CommitQueueMetrics) -> String {
+    let metrics = [
+        (
+            "warg_commit_queue_depth",
+            "gauge",
+            "The number of submitted package records that have yet to be committed or rejected.",
+            queue.depth,
+        ),
+        (
+            "warg_commit_queue_deferred",
+            "gauge",
+            "The number of submitted package records deferred until a failed commit is retried.",
+            queue.deferred,
+        ),
+        (
+            "warg_commit_retries_total",
+            "counter",
+            "The total number of retried package record commits.",
+            queue.retries_total,
+        ),
+        (
+            "warg_commit_failures_total",
+            "counter",
+            "The total number of package records that failed to commit after all attempts.",
+            queue.failures_total,
+        ),
+    ];
+
+    let mut output = String::new();
+    for (name, kind, help, value) in metrics {
+        writeln!(output, "# HELP {name} {help}").unwrap();
+        writeln!(output, "# TYPE {name} {kind}").unwrap();
+        writeln!(output, "{name} {value}").unwrap();
+    }
+
+    output
+}
+
+#[debug_handler]
+async fn get_metrics(State(config): State<Config>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        format_metrics(&config.core_service.commit_queue_metrics()),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_formats_metrics() {
+        let output = format_metrics(&CommitQueueMetrics {
+            depth: 2,
+            deferred: 1,
+            retries_total: 3,
+            failures_total: 0,
+        });
+
+        assert!(
+            output.contains("# TYPE warg_commit_queue_depth gauge\nwarg_commit_queue_depth 2\n")
+        );
+        assert!(output.contains("warg_commit_queue_deferred 1\n"));
+        assert!(output
+            .contains("# TYPE warg_commit_retries_total counter\nwarg_commit_retries_total 3\n"));
+        assert!(output.contains("warg_commit_failures_total 0\n"));
+    }
+}
@@ -1,4 +1,5 @@
 use crate::{
+    contentstore::ContentStore,
     policy::{content::ContentPolicy, record::RecordPolicy},
     services::CoreService,
 };
@@ -7,43 +8,51 @@ use std::{path::PathBuf, sync::Arc};
 use tower::ServiceBuilder;
 use tower_http::{
     cors::{Any, CorsLayer},
-    services::ServeDir,
     trace::{DefaultMakeSpan, DefaultOnResponse, TraceLayer},
     LatencyUnit,
 };
 use tracing::{Level, Span};
-use url::Url;
+use warg_crypto::signing::PublicKey;
 
+pub mod content;
+pub mod metrics;
 pub mod v1;
 
 #[cfg(feature = "debug")]
 pub mod debug;
 
 /// Creates the router for the API.
+#[allow(clippy::too_many_arguments)]
 pub fn create_router(
-    content_base_url: Url,
+    content_store: Arc<dyn ContentStore>,
     core: CoreService,
     temp_dir: PathBuf,
-    files_dir: PathBuf,
     content_policy: Option<Arc<dyn ContentPolicy>>,
+    namespace_quota: Option<u64>,
     record_policy: Option<Arc<dyn RecordPolicy>>,
+    witness_keys: Vec<PublicKey>,
 ) -> Router {
     let router = Router::new();
     #[cfg(feature = "debug")]
     let router = router.nest("/debug", debug::Config::new(core.clone()).into_router());
     router
+        .nest("/metrics", metrics::Config::new(core.clone()).into_router())
         .nest(
             "/v1",
             v1::create_router(
-                content_base_url,
+                content_store.clone(),
                 core,
                 temp_dir,
-                files_dir.clone(),
                 content_policy,
+                namespace_quota,
                 record_policy,
+                witness_keys,
             ),
         )
-        .nest_service("/content", ServeDir::new(files_dir))
+        .nest(
+            "/content",
+            content::Config::new(content_store).into_router(),
+        )
         .layer(
             ServiceBuilder::new()
                 .layer(
@@ -61,10 +70,22 @@ pub fn create_router(
                 .layer(
                     CorsLayer::new()
                         .allow_origin(Any)
-                        .allow_methods([axum::http::Method::GET, axum::http::Method::POST])
+                        .allow_methods([
+                            axum::http::Method::GET,
+                            axum::http::Method::HEAD,
+                            axum::http::Method::POST,
+                        ])
                         .allow_headers([
                             axum::http::header::CONTENT_TYPE,
                             axum::http::header::ACCEPT,
+                            axum::http::header::RANGE,
+                            axum::http::header::IF_RANGE,
+                            axum::http::header::IF_NONE_MATCH,
+                        ])
+                        .expose_headers([
+                            axum::http::header::ACCEPT_RANGES,
+                            axum::http::header::CONTENT_RANGE,
+                            axum::http::header::ETAG,
                         ]),
                 ),
         )
@@ -1,37 +1,58 @@
-use super::Json;
+use super::{package, Json, Path, Query};
 use crate::datastore::DataStoreError;
-use crate::services::CoreService;
+use crate::services::{CoreService, CoreUpdate};
 use axum::http::StatusCode;
 use axum::{
     debug_handler,
     extract::State,
-    response::IntoResponse,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse,
+    },
     routing::{get, post},
     Router,
 };
-use std::collections::HashMap;
-use warg_api::v1::fetch::{FetchError, FetchLogsRequest, FetchLogsResponse};
+use futures::{stream, Stream};
+use std::collections::{HashMap, VecDeque};
+use std::convert::Infallible;
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+use warg_api::v1::{
+    fetch::{
+        FetchError, FetchLogsRequest, FetchLogsResponse, FetchPackagesRequest,
+        FetchPackagesResponse, SubscribeEvent, SubscribeRequest,
+    },
+    package::PackageRecordState,
+};
 use warg_crypto::hash::Sha256;
-use warg_protocol::registry::{LogId, TimestampedCheckpoint};
+use warg_protocol::registry::{LogId, RecordId, RegistryLen, TimestampedCheckpoint};
 use warg_protocol::{PublishedProtoEnvelopeBody, SerdeEnvelope};
 
 const DEFAULT_RECORDS_LIMIT: u16 = 100;
 const MAX_RECORDS_LIMIT: u16 = 1000;
+const DEFAULT_PACKAGES_LIMIT: u16 = 100;
+const MAX_PACKAGES_LIMIT: u16 = 1000;
 
 #[derive(Clone)]
 pub struct Config {
     core_service: CoreService,
+    package_config: package::Config,
 }
 
 impl Config {
-    pub fn new(core_service: CoreService) -> Self {
-        Self { core_service }
+    pub fn new(core_service: CoreService, package_config: package::Config) -> Self {
+        Self {
+            core_service,
+            package_config,
+        }
     }
 
     pub fn into_router(self) -> Router {
         Router::new()
             .route("/logs", post(fetch_logs))
             .route("/checkpoint", get(fetch_checkpoint))
+            .route("/checkpoint/:log_length", get(fetch_checkpoint_at))
+            .route("/packages", post(fetch_packages))
+            .route("/subscribe", get(subscribe))
             .with_state(self)
     }
 }
@@ -131,3 +152,212 @@ async fn fetch_checkpoint(
         config.core_service.store().get_latest_checkpoint().await?,
     ))
 }
+
+#[debug_handler]
+async fn fetch_checkpoint_at(
+    State(config): State<Config>,
+    Path(log_length): Path<RegistryLen>,
+) -> Result<Json<SerdeEnvelope<TimestampedCheckpoint>>, FetchApiError> {
+    Ok(Json(
+        config
+            .core_service
+            .store()
+            .get_checkpoint(log_length)
+            .await?,
+    ))
+}
+
+#[debug_handler]
+async fn fetch_packages(
+    State(config): State<Config>,
+    Json(body): Json<FetchPackagesRequest<'static>>,
+) -> Result<Json<FetchPackagesResponse>, FetchApiError> {
+    let limit = body.limit.unwrap_or(DEFAULT_PACKAGES_LIMIT);
+    if limit == 0 || limit > MAX_PACKAGES_LIMIT {
+        return Err(FetchApiError::bad_request(format!(
+            "invalid packages limit value `{limit}`: must be between 1 and {MAX_PACKAGES_LIMIT}"
+        )));
+    }
+
+    if let Some(namespace) = &body.namespace {
+        if !namespace
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-')
+        {
+            return Err(FetchApiError::bad_request(format!(
+                "invalid namespace prefix `{namespace}`"
+            )));
+        }
+    }
+
+    // Fetch one more package than the limit to determine if there are more
+    let mut packages = config
+        .core_service
+        .store()
+        .get_package_ids(body.namespace.as_deref(), body.since.as_deref(), limit + 1)
+        .await?;
+
+    let more = packages.len() > limit as usize;
+    packages.truncate(limit as usize);
+
+    Ok(Json(FetchPackagesResponse { more, packages }))
+}
+
+#[debug_handler]
+async fn subscribe(
+    State(config): State<Config>,
+    Query(request): Query<SubscribeRequest>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, FetchApiError> {
+    let record = match (request.log_id, request.record_id) {
+        (Some(log_id), Some(record_id)) => Some((log_id, record_id)),
+        (None, None) => None,
+        _ => {
+            return Err(FetchApiError::bad_request(
+                "both `logId` and `recordId` must be specified to subscribe to a record",
+            ))
+        }
+    };
+
+    // Subscribe before reading the current state so that no update is missed
+    let mut subscription = Subscription {
+        updates: config.core_service.subscribe(),
+        record: record.map(|(log_id, record_id)| TrackedRecord {
+            log_id,
+            record_id,
+            last: None,
+        }),
+        pending: VecDeque::new(),
+        config,
+    };
+
+    subscription.send_current_state().await?;
+
+    Ok(Sse::new(subscription.into_stream()).keep_alive(KeepAlive::default()))
+}
+
+/// Represents a package record whose status changes are sent to a subscriber.
+struct TrackedRecord {
+    log_id: LogId,
+    record_id: RecordId,
+    /// The serialized state of the record that was last sent.
+    last: Option<String>,
+}
+
+/// Represents the state of a subscriber of the subscribe API.
+struct Subscription {
+    config: Config,
+    updates: Receiver<CoreUpdate>,
+    record: Option<TrackedRecord>,
+    pending: VecDeque<Event>,
+}
+
+impl Subscription {
+    /// Queues the latest checkpoint and the current state of the tracked record.
+    async fn send_current_state(&mut self) -> Result<(), DataStoreError> {
+        match self
+            .config
+            .core_service
+            .store()
+            .get_latest_checkpoint()
+            .await
+        {
+            Ok(checkpoint) => self.send(SubscribeEvent::Checkpoint(checkpoint)),
+            // The registry may not have signed a checkpoint yet
+            Err(DataStoreError::CheckpointNotFound(_)) => {}
+            Err(e) => return Err(e),
+        }
+
+        self.update_record().await
+    }
+
+    /// Queues the state of the tracked record if it has changed.
+    ///
+    /// The record is no longer tracked once it has been published or rejected.
+    async fn update_record(&mut self) -> Result<(), DataStoreError> {
+        let Some(tracked) = &mut self.record else {
+            return Ok(());
+        };
+
+        let record = self
+            .config
+            .package_config
+            .package_record(&tracked.log_id, &tracked.record_id)
+            .await?;
+
+        let finished = matches!(
+            record.state,
+            PackageRecordState::Published { .. } | PackageRecordState::Rejected { .. }
+        );
+
+        let serialized = serde_json::to_string(&record).expect("record should serialize");
+        if tracked.last.as_ref() != Some(&serialized) {
+            tracked.last = Some(serialized);
+            self.send(SubscribeEvent::Record(record));
+        }
+
+        if finished {
+            self.record = None;
+        }
+
+        Ok(())
+    }
+
+    fn send(&mut self, event: SubscribeEvent) {
+        let data = match &event {
+            SubscribeEvent::Checkpoint(checkpoint) => serde_json::to_string(checkpoint),
+            SubscribeEvent::Record(record) => serde_json::to_string(record),
+        }
+        .expect("event should serialize");
+
+        self.pending
+            .push_back(Event::default().event(event.name()).data(data));
+    }
+
+    /// Handles an update from the core service.
+    ///
+    /// Returns `false` if the subscription has ended.
+    async fn handle_update(&mut self) -> bool {
+        let result = match self.updates.recv().await {
+            Ok(CoreUpdate::Checkpoint(checkpoint)) => {
+                self.send(SubscribeEvent::Checkpoint(checkpoint));
+                self.update_record().await
+            }
+            Ok(CoreUpdate::RecordRejected(leaf)) => match &self.record {
+                Some(tracked)
+                    if tracked.log_id == leaf.log_id && tracked.record_id == leaf.record_id =>
+                {
+                    self.update_record().await
+                }
+                _ => Ok(()),
+            },
+            Err(RecvError::Lagged(count)) => {
+                // Updates were missed, so resend the current state
+                tracing::debug!("subscriber lagged behind by {count} update(s)");
+                self.send_current_state().await
+            }
+            Err(RecvError::Closed) => return false,
+        };
+
+        match result {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::error!("failed to update subscriber: {e}");
+                false
+            }
+        }
+    }
+
+    fn into_stream(self) -> impl Stream<Item = Result<Event, Infallible>> {
+        stream::unfold(self, |mut subscription| async move {
+            loop {
+                if let Some(event) = subscription.pending.pop_front() {
+                    return Some((Ok(event), subscription));
+                }
+
+                if !subscription.handle_update().await {
+                    return None;
+                }
+            }
+        })
+    }
+}
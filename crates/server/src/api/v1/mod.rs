@@ -1,11 +1,12 @@
 use crate::{
+    contentstore::ContentStore,
     policy::{content::ContentPolicy, record::RecordPolicy},
     services::CoreService,
 };
 use anyhow::Result;
 use axum::{
     extract::{
-        rejection::{JsonRejection, PathRejection},
+        rejection::{JsonRejection, PathRejection, QueryRejection},
         FromRequest, FromRequestParts,
     },
     http::StatusCode,
@@ -14,11 +15,13 @@ use axum::{
 };
 use serde::{Serialize, Serializer};
 use std::{path::PathBuf, sync::Arc};
-use url::Url;
+use warg_crypto::signing::PublicKey;
 
 pub mod fetch;
+pub mod operator;
 pub mod package;
 pub mod proof;
+pub mod witness;
 
 /// An extractor that wraps the JSON extractor of Axum.
 ///
@@ -82,6 +85,22 @@ impl From<PathRejection> for Error {
     }
 }
 
+/// An extractor that wraps the query extractor of Axum.
+///
+/// This extractor returns an API error on rejection.
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Query), rejection(Error))]
+pub struct Query<T>(T);
+
+impl From<QueryRejection> for Error {
+    fn from(rejection: QueryRejection) -> Self {
+        Self {
+            status: rejection.status(),
+            message: rejection.body_text(),
+        }
+    }
+}
+
 pub async fn not_found() -> impl IntoResponse {
     Error {
         status: StatusCode::NOT_FOUND,
@@ -90,27 +109,32 @@ pub async fn not_found() -> impl IntoResponse {
 }
 
 pub fn create_router(
-    content_base_url: Url,
+    content_store: Arc<dyn ContentStore>,
     core: CoreService,
     temp_dir: PathBuf,
-    files_dir: PathBuf,
     content_policy: Option<Arc<dyn ContentPolicy>>,
+    namespace_quota: Option<u64>,
     record_policy: Option<Arc<dyn RecordPolicy>>,
+    witness_keys: Vec<PublicKey>,
 ) -> Router {
     let proof_config = proof::Config::new(core.clone());
     let package_config = package::Config::new(
         core.clone(),
-        content_base_url,
-        files_dir,
+        content_store,
         temp_dir,
         content_policy,
+        namespace_quota,
         record_policy,
     );
-    let fetch_config = fetch::Config::new(core);
+    let operator_config = operator::Config::new(core.clone());
+    let witness_config = witness::Config::new(core.clone(), witness_keys);
+    let fetch_config = fetch::Config::new(core, package_config.clone());
 
     Router::new()
+        .nest("/operator", operator_config.into_router())
         .nest("/package", package_config.into_router())
         .nest("/fetch", fetch_config.into_router())
         .nest("/proof", proof_config.into_router())
+        .nest("/witness", witness_config.into_router())
         .fallback(not_found)
 }
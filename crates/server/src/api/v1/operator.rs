@@ -0,0 +1,178 @@
+use super::{Json, Path};
+use crate::{
+    datastore::{DataStoreError, RecordStatus},
+    services::CoreService,
+};
+use axum::{
+    debug_handler,
+    extract::State,
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{get, post},
+    Router,
+};
+use warg_api::v1::operator::{
+    OperatorError, OperatorRecord, OperatorRecordState, PublishOperatorRecordRequest,
+};
+use warg_crypto::{hash::Sha256, Signable};
+use warg_protocol::{
+    operator,
+    registry::{LogId, RecordId},
+    ProtoEnvelope,
+};
+
+#[derive(Clone)]
+pub struct Config {
+    core_service: CoreService,
+}
+
+impl Config {
+    pub fn new(core_service: CoreService) -> Self {
+        Self { core_service }
+    }
+
+    pub fn into_router(self) -> Router {
+        Router::new()
+            .route("/record", post(publish_record))
+            .route("/record/:record_id", get(get_record))
+            .with_state(self)
+    }
+
+    /// Gets the current state of the given operator record.
+    async fn operator_record(
+        &self,
+        record_id: &RecordId,
+    ) -> Result<OperatorRecord, DataStoreError> {
+        let record = self
+            .core_service
+            .store()
+            .get_operator_record(&LogId::operator_log::<Sha256>(), record_id)
+            .await?;
+
+        let state = match record.status {
+            // Validated is considered still processing until included in a checkpoint
+            RecordStatus::MissingContent(_) | RecordStatus::Pending | RecordStatus::Validated => {
+                OperatorRecordState::Processing
+            }
+            RecordStatus::Rejected(reason) => OperatorRecordState::Rejected { reason },
+            RecordStatus::Published => OperatorRecordState::Published {
+                registry_index: record.registry_index.unwrap(),
+            },
+        };
+
+        Ok(OperatorRecord {
+            id: record_id.clone(),
+            state,
+        })
+    }
+}
+
+struct OperatorApiError(OperatorError);
+
+impl OperatorApiError {
+    fn bad_request(message: impl ToString) -> Self {
+        Self(OperatorError::Message {
+            status: StatusCode::BAD_REQUEST.as_u16(),
+            message: message.to_string(),
+        })
+    }
+
+    fn unsupported(message: impl ToString) -> Self {
+        Self(OperatorError::Message {
+            status: StatusCode::NOT_IMPLEMENTED.as_u16(),
+            message: message.to_string(),
+        })
+    }
+}
+
+impl From<DataStoreError> for OperatorApiError {
+    fn from(e: DataStoreError) -> Self {
+        Self(match e {
+            DataStoreError::OperatorValidationFailed(e) => {
+                return Self::bad_request(e);
+            }
+            DataStoreError::RecordNotFound(id) => OperatorError::RecordNotFound(id),
+            DataStoreError::UnknownKey(_) | DataStoreError::SignatureVerificationFailed => {
+                OperatorError::Unauthorized(e.to_string())
+            }
+            // Other errors are internal server errors
+            e => {
+                tracing::error!("unexpected data store error: {e}");
+                OperatorError::Message {
+                    status: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                    message: "an error occurred while processing the request".into(),
+                }
+            }
+        })
+    }
+}
+
+impl IntoResponse for OperatorApiError {
+    fn into_response(self) -> axum::response::Response {
+        (StatusCode::from_u16(self.0.status()).unwrap(), Json(self.0)).into_response()
+    }
+}
+
+#[debug_handler]
+async fn publish_record(
+    State(config): State<Config>,
+    Json(body): Json<PublishOperatorRecordRequest<'static>>,
+) -> Result<impl IntoResponse, OperatorApiError> {
+    if config.core_service.is_mirror() {
+        return Err(OperatorApiError::unsupported(
+            "the registry is a read-only mirror",
+        ));
+    }
+
+    let record: ProtoEnvelope<operator::OperatorRecord> = body
+        .record
+        .into_owned()
+        .try_into()
+        .map_err(OperatorApiError::bad_request)?;
+
+    let log_id = LogId::operator_log::<Sha256>();
+    let store = config.core_service.store();
+
+    // Verify the signature on the record before storing it; the record is
+    // validated against the operator log when it is committed
+    let state = store.get_operator_log_state(&log_id).await?;
+    let key = state
+        .public_key(record.key_id())
+        .ok_or_else(|| DataStoreError::UnknownKey(record.key_id().clone()))?;
+    operator::OperatorRecord::verify(key, record.content_bytes(), record.signature())
+        .map_err(|_| DataStoreError::SignatureVerificationFailed)?;
+
+    let record_id = RecordId::operator_record::<Sha256>(&record);
+
+    // Publishing the same record again returns its current state
+    match config.operator_record(&record_id).await {
+        Ok(record) => return Ok((StatusCode::ACCEPTED, Json(record))),
+        Err(DataStoreError::LogNotFound(_) | DataStoreError::RecordNotFound(_)) => {}
+        Err(e) => return Err(e.into()),
+    }
+
+    store
+        .store_operator_record(&log_id, &record_id, &record)
+        .await?;
+
+    config
+        .core_service
+        .submit_operator_record(record_id.clone())
+        .await;
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(OperatorRecord {
+            id: record_id,
+            state: OperatorRecordState::Processing,
+        }),
+    ))
+}
+
+#[debug_handler]
+async fn get_record(
+    State(config): State<Config>,
+    Path(record_id): Path<RecordId>,
+) -> Result<Json<OperatorRecord>, OperatorApiError> {
+    Ok(Json(config.operator_record(&record_id).await?))
+}
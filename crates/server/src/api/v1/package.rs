@@ -1,5 +1,6 @@
 use super::{Json, Path};
 use crate::{
+    contentstore::{ContentStore, ContentStoreError},
     datastore::{DataStoreError, RecordStatus},
     policy::{
         content::{ContentPolicy, ContentPolicyError},
@@ -8,6 +9,7 @@ use crate::{
     services::CoreService,
 };
 use axum::{
+    body::Bytes,
     debug_handler,
     extract::{BodyStream, State},
     http::StatusCode,
@@ -15,12 +17,11 @@ use axum::{
     routing::{get, post},
     Router,
 };
-use futures::StreamExt;
+use futures::{Stream, StreamExt};
 use std::sync::Arc;
 use std::{collections::HashMap, path::PathBuf};
 use tempfile::NamedTempFile;
 use tokio::io::AsyncWriteExt;
-use url::Url;
 use warg_api::v1::package::{
     ContentSource, MissingContent, PackageError, PackageRecord, PackageRecordState,
     PublishRecordRequest, UploadEndpoint,
@@ -35,28 +36,28 @@ use warg_protocol::{
 #[derive(Clone)]
 pub struct Config {
     core_service: CoreService,
-    content_base_url: Url,
-    files_dir: PathBuf,
+    content_store: Arc<dyn ContentStore>,
     temp_dir: PathBuf,
     content_policy: Option<Arc<dyn ContentPolicy>>,
+    namespace_quota: Option<u64>,
     record_policy: Option<Arc<dyn RecordPolicy>>,
 }
 
 impl Config {
     pub fn new(
         core_service: CoreService,
-        content_base_url: Url,
-        files_dir: PathBuf,
+        content_store: Arc<dyn ContentStore>,
         temp_dir: PathBuf,
         content_policy: Option<Arc<dyn ContentPolicy>>,
+        namespace_quota: Option<u64>,
         record_policy: Option<Arc<dyn RecordPolicy>>,
     ) -> Self {
         Self {
             core_service,
-            content_base_url,
-            files_dir,
+            content_store,
             temp_dir,
             content_policy,
+            namespace_quota,
             record_policy,
         }
     }
@@ -69,28 +70,227 @@ impl Config {
                 "/:log_id/record/:record_id/content/:digest",
                 post(upload_content),
             )
+            .route(
+                "/:log_id/record/:record_id/content/:digest/finalize",
+                post(finalize_content),
+            )
             .with_state(self)
     }
 
-    fn content_present(&self, digest: &AnyHash) -> bool {
-        self.content_path(digest).is_file()
+    /// Rejects changes to package logs if the registry is a read-only mirror.
+    fn check_writable(&self) -> Result<(), PackageApiError> {
+        if self.core_service.is_mirror() {
+            return Err(PackageApiError::unsupported(
+                "the registry is a read-only mirror",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Gets the current state of the given package record.
+    pub(super) async fn package_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<PackageRecord, DataStoreError> {
+        let record = self
+            .core_service
+            .store()
+            .get_package_record(log_id, record_id)
+            .await?;
+
+        let state = match record.status {
+            RecordStatus::MissingContent(missing) => PackageRecordState::Sourcing {
+                missing_content: self.build_missing_content(log_id, record_id, &missing),
+            },
+            // Validated is considered still processing until included in a checkpoint
+            RecordStatus::Pending | RecordStatus::Validated => PackageRecordState::Processing,
+            RecordStatus::Rejected(reason) => PackageRecordState::Rejected { reason },
+            RecordStatus::Published => {
+                let content_sources = record
+                    .envelope
+                    .as_ref()
+                    .contents()
+                    .into_iter()
+                    .map(|digest| {
+                        (
+                            digest.clone(),
+                            vec![ContentSource::Http {
+                                url: self.content_store.download_url(digest),
+                            }],
+                        )
+                    })
+                    .collect();
+
+                PackageRecordState::Published {
+                    record: record.envelope.into(),
+                    registry_index: record.registry_index.unwrap(),
+                    content_sources,
+                }
+            }
+        };
+
+        Ok(PackageRecord {
+            id: record_id.clone(),
+            state,
+        })
+    }
+
+    /// Checks a package record against the record policy once all of its
+    /// content is present.
+    ///
+    /// If the policy rejects the record, the record is transitioned to rejected
+    /// and the reason is returned.
+    async fn check_record_content(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<Option<String>, PackageApiError> {
+        let Some(policy) = &self.record_policy else {
+            return Ok(None);
+        };
+
+        let store = self.core_service.store();
+        let record = store.get_package_record(log_id, record_id).await?;
+        let package_id = store.get_package_id(log_id).await?;
+        let state = store.get_package_log_state(log_id).await?;
+
+        // The policy loads content synchronously, so check it on a blocking thread
+        let policy = policy.clone();
+        let content_store = self.content_store.clone();
+        let handle = tokio::runtime::Handle::current();
+        let result = tokio::task::spawn_blocking(move || {
+            policy.check_content(&package_id, &state, &record.envelope, &|digest| {
+                handle
+                    .block_on(content_store.read_content(digest))
+                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))
+            })
+        })
+        .await
+        .map_err(PackageApiError::internal_error)?;
+
+        let reason = match result {
+            Ok(()) => return Ok(None),
+            Err(e) => e.to_string(),
+        };
+
+        store
+            .reject_package_record(log_id, record_id, &reason)
+            .await?;
+
+        Ok(Some(reason))
+    }
+
+    /// Checks that the given content is missing for a package record.
+    async fn check_content_missing(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        digest: &AnyHash,
+    ) -> Result<(), PackageApiError> {
+        match self
+            .core_service
+            .store()
+            .is_content_missing(log_id, record_id, digest)
+            .await
+        {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(PackageApiError::bad_request(format!(
+                "content digest `{digest}` is not required for package record `{record_id}`"
+            ))),
+            Err(DataStoreError::RecordNotPending(_)) => {
+                Err(PackageApiError(PackageError::RecordNotSourcing))
+            }
+            Err(e) => Err(e.into()),
+        }
     }
 
-    fn content_file_name(&self, digest: &AnyHash) -> String {
-        digest.to_string().replace(':', "-")
+    /// Determines how much of the namespace's storage quota remains for the
+    /// content of a package log.
+    async fn namespace_quota(
+        &self,
+        log_id: &LogId,
+    ) -> Result<Option<NamespaceQuota>, PackageApiError> {
+        let Some(quota) = self.namespace_quota else {
+            return Ok(None);
+        };
+
+        let store = self.core_service.store();
+        let package_id = store.get_package_id(log_id).await?;
+        let namespace = package_id.namespace();
+        let used = store.get_namespace_content_size(namespace).await?;
+        Ok(Some(NamespaceQuota {
+            namespace: namespace.to_string(),
+            quota,
+            remaining: quota.saturating_sub(used),
+        }))
     }
 
-    fn content_path(&self, digest: &AnyHash) -> PathBuf {
-        self.files_dir.join(self.content_file_name(digest))
+    /// Transitions a package record to rejected if processing its content
+    /// failed with a rejection.
+    async fn reject_content(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        digest: &AnyHash,
+        error: &PackageApiError,
+    ) -> Result<(), PackageApiError> {
+        if let PackageApiError(PackageError::Rejection(reason)) = error {
+            self.core_service
+                .store()
+                .reject_package_record(
+                    log_id,
+                    record_id,
+                    &format!("content with digest `{digest}` was rejected by policy: {reason}"),
+                )
+                .await?;
+        }
+
+        Ok(())
     }
 
-    fn content_url(&self, digest: &AnyHash) -> String {
-        self.content_base_url
-            .join("content/")
-            .unwrap()
-            .join(&self.content_file_name(digest))
-            .unwrap()
-            .to_string()
+    /// Marks stored content as present for a package record.
+    ///
+    /// If this is the last content needed, the record is submitted for processing.
+    async fn content_present(
+        &self,
+        log_id: LogId,
+        record_id: RecordId,
+        digest: &AnyHash,
+        size: u64,
+    ) -> Result<impl IntoResponse, PackageApiError> {
+        let ready = match self
+            .core_service
+            .store()
+            .set_content_present(&log_id, &record_id, digest, size, self.namespace_quota)
+            .await
+        {
+            Ok(ready) => ready,
+            Err(e) => {
+                let e = PackageApiError::from(e);
+                self.reject_content(&log_id, &record_id, digest, &e).await?;
+                return Err(e);
+            }
+        };
+
+        if ready {
+            if let Some(reason) = self.check_record_content(&log_id, &record_id).await? {
+                return Err(PackageApiError(PackageError::Rejection(reason)));
+            }
+
+            self.core_service
+                .submit_package_record(log_id, record_id)
+                .await;
+        }
+
+        Ok((
+            StatusCode::CREATED,
+            [(
+                axum::http::header::LOCATION,
+                self.content_store.download_url(digest),
+            )],
+        ))
     }
 
     fn build_missing_content<'a>(
@@ -103,12 +303,19 @@ impl Config {
             .into_iter()
             .map(|digest| {
                 let url = format!("v1/package/{log_id}/record/{record_id}/content/{digest}");
-                (
-                    digest.clone(),
-                    MissingContent {
-                        upload: vec![UploadEndpoint::HttpPost { url }],
-                    },
-                )
+                let mut upload = Vec::new();
+
+                // Prefer uploading directly to the content store, if supported
+                if let Some(put_url) = self.content_store.upload_url(&upload_id(record_id, digest))
+                {
+                    upload.push(UploadEndpoint::HttpPut {
+                        url: put_url,
+                        finalize_url: format!("{url}/finalize"),
+                    });
+                }
+
+                upload.push(UploadEndpoint::HttpPost { url });
+                (digest.clone(), MissingContent { upload })
             })
             .collect()
     }
@@ -151,6 +358,7 @@ impl From<DataStoreError> for PackageApiError {
             DataStoreError::UnknownKey(_) | DataStoreError::SignatureVerificationFailed => {
                 PackageError::Unauthorized(e.to_string())
             }
+            DataStoreError::QuotaExceeded { .. } => PackageError::Rejection(e.to_string()),
             // Other errors are internal server errors
             e => {
                 tracing::error!("unexpected data store error: {e}");
@@ -163,6 +371,12 @@ impl From<DataStoreError> for PackageApiError {
     }
 }
 
+impl From<ContentStoreError> for PackageApiError {
+    fn from(e: ContentStoreError) -> Self {
+        Self::internal_error(e)
+    }
+}
+
 impl From<ContentPolicyError> for PackageApiError {
     fn from(e: ContentPolicyError) -> Self {
         match e {
@@ -192,6 +406,8 @@ async fn publish_record(
     Path(log_id): Path<LogId>,
     Json(body): Json<PublishRecordRequest<'static>>,
 ) -> Result<impl IntoResponse, PackageApiError> {
+    config.check_writable()?;
+
     let expected_log_id = LogId::package_log::<Sha256>(&body.id);
     if expected_log_id != log_id {
         return Err(PackageApiError::bad_request(format!(
@@ -226,9 +442,13 @@ async fn publish_record(
         .verify_package_record_signature(&log_id, &record)
         .await?;
 
-    let record_id = RecordId::package_record::<Sha256>(&record);
+    let record_id = RecordId::package_record_with(record.as_ref().hash_algorithm(), &record);
     let mut missing = record.as_ref().contents();
-    missing.retain(|d| !config.content_present(d));
+    for digest in missing.clone() {
+        if config.content_store.content_present(digest).await? {
+            missing.remove(digest);
+        }
+    }
 
     config
         .core_service
@@ -238,6 +458,16 @@ async fn publish_record(
 
     // If there's no missing content, submit the record for processing now
     if missing.is_empty() {
+        if let Some(reason) = config.check_record_content(&log_id, &record_id).await? {
+            return Ok((
+                StatusCode::ACCEPTED,
+                Json(PackageRecord {
+                    id: record_id,
+                    state: PackageRecordState::Rejected { reason },
+                }),
+            ));
+        }
+
         config
             .core_service
             .submit_package_record(log_id, record_id.clone())
@@ -267,57 +497,7 @@ async fn get_record(
     State(config): State<Config>,
     Path((log_id, record_id)): Path<(LogId, RecordId)>,
 ) -> Result<Json<PackageRecord>, PackageApiError> {
-    let record = config
-        .core_service
-        .store()
-        .get_package_record(&log_id, &record_id)
-        .await?;
-
-    match record.status {
-        RecordStatus::MissingContent(missing) => {
-            let missing_content = config.build_missing_content(&log_id, &record_id, &missing);
-            Ok(Json(PackageRecord {
-                id: record_id,
-                state: PackageRecordState::Sourcing { missing_content },
-            }))
-        }
-        // Validated is considered still processing until included in a checkpoint
-        RecordStatus::Pending | RecordStatus::Validated => Ok(Json(PackageRecord {
-            id: record_id,
-            state: PackageRecordState::Processing,
-        })),
-        RecordStatus::Rejected(reason) => Ok(Json(PackageRecord {
-            id: record_id,
-            state: PackageRecordState::Rejected { reason },
-        })),
-        RecordStatus::Published => {
-            let content_sources = record
-                .envelope
-                .as_ref()
-                .contents()
-                .into_iter()
-                .map(|digest| {
-                    (
-                        digest.clone(),
-                        vec![ContentSource::Http {
-                            url: config.content_url(digest),
-                        }],
-                    )
-                })
-                .collect();
-
-            let registry_index = record.registry_index.unwrap();
-
-            Ok(Json(PackageRecord {
-                id: record_id,
-                state: PackageRecordState::Published {
-                    record: record.envelope.into(),
-                    registry_index,
-                    content_sources,
-                },
-            }))
-        }
-    }
+    Ok(Json(config.package_record(&log_id, &record_id).await?))
 }
 
 #[debug_handler]
@@ -326,23 +506,10 @@ async fn upload_content(
     Path((log_id, record_id, digest)): Path<(LogId, RecordId, AnyHash)>,
     stream: BodyStream,
 ) -> Result<impl IntoResponse, PackageApiError> {
-    match config
-        .core_service
-        .store()
-        .is_content_missing(&log_id, &record_id, &digest)
-        .await
-    {
-        Ok(true) => {}
-        Ok(false) => {
-            return Err(PackageApiError::bad_request(
-                "content digest `{digest}` is not required for package record `{record_id}`",
-            ));
-        }
-        Err(DataStoreError::RecordNotPending(_)) => {
-            return Err(PackageApiError(PackageError::RecordNotSourcing))
-        }
-        Err(e) => return Err(e.into()),
-    }
+    config.check_writable()?;
+    config
+        .check_content_missing(&log_id, &record_id, &digest)
+        .await?;
 
     let tmp_path = NamedTempFile::new_in(&config.temp_dir)
         .map_err(PackageApiError::internal_error)?
@@ -353,59 +520,135 @@ async fn upload_content(
         path = tmp_path.display()
     );
 
-    let res = process_content(&tmp_path, &digest, stream, config.content_policy.as_deref()).await;
+    let quota = config.namespace_quota(&log_id).await?;
+    let res = process_content(
+        Some(&tmp_path),
+        &digest,
+        stream,
+        config.content_policy.as_deref(),
+        quota.as_ref(),
+    )
+    .await;
+
+    // Only store the content if it was successfully processed
+    let size = match res {
+        Ok(size) => size,
+        Err(e) => {
+            config
+                .reject_content(&log_id, &record_id, &digest, &e)
+                .await?;
+            return Err(e);
+        }
+    };
 
-    // If the error was a rejection, transition the record itself to rejected
-    if let Err(PackageApiError(PackageError::Rejection(reason))) = &res {
-        config
-            .core_service
-            .store()
-            .reject_package_record(
-                &log_id,
-                &record_id,
-                &format!("content with digest `{digest}` was rejected by policy: {reason}"),
-            )
-            .await?;
-    }
+    config
+        .content_store
+        .store_content(&digest, &tmp_path)
+        .await?;
 
-    // Only persist the file if the content was successfully processed
-    res?;
+    config
+        .content_present(log_id, record_id, &digest, size)
+        .await
+}
 
-    tmp_path
-        .persist(config.content_path(&digest))
-        .map_err(PackageApiError::internal_error)?;
+#[debug_handler]
+async fn finalize_content(
+    State(config): State<Config>,
+    Path((log_id, record_id, digest)): Path<(LogId, RecordId, AnyHash)>,
+) -> Result<impl IntoResponse, PackageApiError> {
+    config.check_writable()?;
+    config
+        .check_content_missing(&log_id, &record_id, &digest)
+        .await?;
 
-    // If this is the last content needed, submit the record for processing now
-    if config
-        .core_service
-        .store()
-        .set_content_present(&log_id, &record_id, &digest)
-        .await?
-    {
-        config
-            .core_service
-            .submit_package_record(log_id, record_id)
-            .await;
-    }
+    tracing::debug!(
+        "finalizing content `{digest}` uploaded for record `{record_id}` from `{log_id}`"
+    );
 
-    Ok((
-        StatusCode::CREATED,
-        [(axum::http::header::LOCATION, config.content_url(&digest))],
-    ))
+    let upload_id = upload_id(&record_id, &digest);
+    let stream = match config.content_store.load_upload(&upload_id).await {
+        Ok(stream) => stream,
+        Err(ContentStoreError::UploadNotFound(_)) => {
+            return Err(PackageApiError::bad_request(format!(
+                "content with digest `{digest}` has not been uploaded"
+            )));
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let tmp_path = NamedTempFile::new_in(&config.temp_dir)
+        .map_err(PackageApiError::internal_error)?
+        .into_temp_path();
+
+    // Content uploaded directly to the store is subject to the same checks
+    // as content uploaded through the registry; the verified copy is stored
+    // so that the staged upload cannot change after it was checked
+    let quota = config.namespace_quota(&log_id).await?;
+    let res = process_content(
+        Some(&tmp_path),
+        &digest,
+        stream,
+        config.content_policy.as_deref(),
+        quota.as_ref(),
+    )
+    .await;
+
+    let size = match res {
+        Ok(size) => size,
+        Err(e) => {
+            config.content_store.delete_upload(&upload_id).await?;
+            config
+                .reject_content(&log_id, &record_id, &digest, &e)
+                .await?;
+            return Err(e);
+        }
+    };
+
+    config
+        .content_store
+        .store_content(&digest, &tmp_path)
+        .await?;
+    config.content_store.delete_upload(&upload_id).await?;
+
+    config
+        .content_present(log_id, record_id, &digest, size)
+        .await
+}
+
+/// Gets the identifier of the direct upload of content for a package record.
+fn upload_id(record_id: &RecordId, digest: &AnyHash) -> String {
+    format!("{record_id}-{digest}").replace(':', "-")
+}
+
+/// Represents the remaining storage quota of a namespace.
+struct NamespaceQuota {
+    namespace: String,
+    quota: u64,
+    remaining: u64,
 }
 
-async fn process_content(
-    path: &std::path::Path,
+/// Processes the content stream, returning the size of the content in bytes.
+///
+/// If a path is given, the content is written to it.
+async fn process_content<E: std::fmt::Display>(
+    path: Option<&std::path::Path>,
     digest: &AnyHash,
-    mut stream: BodyStream,
+    mut stream: impl Stream<Item = Result<Bytes, E>> + Unpin,
     policy: Option<&dyn ContentPolicy>,
-) -> Result<(), PackageApiError> {
-    let mut tmp_file = tokio::fs::File::create(&path)
-        .await
-        .map_err(PackageApiError::internal_error)?;
+    quota: Option<&NamespaceQuota>,
+) -> Result<u64, PackageApiError> {
+    let mut tmp_file = match path {
+        Some(path) => Some(
+            tokio::fs::File::create(path)
+                .await
+                .map_err(PackageApiError::internal_error)?,
+        ),
+        None => None,
+    };
 
     let mut hasher = digest.algorithm().hasher();
     let mut policy = policy.map(|p| p.new_stream_policy(digest)).transpose()?;
+    let mut size: u64 = 0;
 
     while let Some(chunk) = stream
         .next()
@@ -413,15 +656,28 @@ async fn process_content(
         .transpose()
         .map_err(PackageApiError::internal_error)?
     {
+        size = size.saturating_add(chunk.len() as u64);
+        if let Some(quota) = quota {
+            if size > quota.remaining {
+                return Err(PackageApiError(PackageError::Rejection(format!(
+                    "content exceeds the storage quota of {max} bytes for namespace `{namespace}`",
+                    max = quota.quota,
+                    namespace = quota.namespace
+                ))));
+            }
+        }
+
         if let Some(policy) = policy.as_mut() {
             policy.check(&chunk)?;
         }
 
         hasher.update(&chunk);
-        tmp_file
-            .write_all(&chunk)
-            .await
-            .map_err(PackageApiError::internal_error)?;
+        if let Some(tmp_file) = tmp_file.as_mut() {
+            tmp_file
+                .write_all(&chunk)
+                .await
+                .map_err(PackageApiError::internal_error)?;
+        }
     }
 
     let result = hasher.finalize();
@@ -435,5 +691,12 @@ async fn process_content(
         policy.finalize()?;
     }
 
-    Ok(())
+    if let Some(mut tmp_file) = tmp_file {
+        tmp_file
+            .flush()
+            .await
+            .map_err(PackageApiError::internal_error)?;
+    }
+
+    Ok(size)
 }
@@ -0,0 +1,380 @@
+use super::{Json, Path};
+use crate::{datastore::DataStoreError, services::CoreService};
+use axum::{
+    debug_handler,
+    extract::State,
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{get, post},
+    Router,
+};
+use std::{collections::HashMap, sync::Arc};
+use warg_api::v1::witness::{CosignaturesResponse, PublishCosignatureRequest, WitnessError};
+use warg_crypto::signing::{KeyID, PublicKey};
+use warg_protocol::registry::RegistryLen;
+
+#[derive(Clone)]
+pub struct Config {
+    core_service: CoreService,
+    witnesses: Arc<HashMap<KeyID, PublicKey>>,
+}
+
+impl Config {
+    pub fn new(core_service: CoreService, witness_keys: Vec<PublicKey>) -> Self {
+        Self {
+            core_service,
+            witnesses: Arc::new(
+                witness_keys
+                    .into_iter()
+                    .map(|key| (key.fingerprint(), key))
+                    .collect(),
+            ),
+        }
+    }
+
+    pub fn into_router(self) -> Router {
+        Router::new()
+            .route("/cosignature", post(publish_cosignature))
+            .route("/cosignatures", get(fetch_latest_cosignatures))
+            .route("/cosignatures/:log_length", get(fetch_cosignatures))
+            .with_state(self)
+    }
+}
+
+struct WitnessApiError(WitnessError);
+
+impl WitnessApiError {
+    fn unprocessable(message: impl ToString) -> Self {
+        Self(WitnessError::Message {
+            status: StatusCode::UNPROCESSABLE_ENTITY.as_u16(),
+            message: message.to_string(),
+        })
+    }
+}
+
+impl From<DataStoreError> for WitnessApiError {
+    fn from(e: DataStoreError) -> Self {
+        Self(match e {
+            DataStoreError::CheckpointNotFound(log_length) => {
+                WitnessError::CheckpointNotFound(log_length)
+            }
+            DataStoreError::UnknownKey(_) | DataStoreError::SignatureVerificationFailed => {
+                WitnessError::Unauthorized(e.to_string())
+            }
+            // Other errors are internal server errors
+            e => {
+                tracing::error!("unexpected data store error: {e}");
+                WitnessError::Message {
+                    status: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                    message: "an error occurred while processing the request".into(),
+                }
+            }
+        })
+    }
+}
+
+impl IntoResponse for WitnessApiError {
+    fn into_response(self) -> axum::response::Response {
+        (StatusCode::from_u16(self.0.status()).unwrap(), Json(self.0)).into_response()
+    }
+}
+
+#[debug_handler]
+async fn publish_cosignature(
+    State(config): State<Config>,
+    Json(body): Json<PublishCosignatureRequest<'static>>,
+) -> Result<Json<CosignaturesResponse>, WitnessApiError> {
+    let cosignature = body.cosignature.into_owned();
+
+    // Only cosignatures of the configured witnesses are accepted
+    let key = config
+        .witnesses
+        .get(cosignature.key_id())
+        .ok_or_else(|| DataStoreError::UnknownKey(cosignature.key_id().clone()))?;
+    cosignature
+        .verify(key)
+        .map_err(|_| DataStoreError::SignatureVerificationFailed)?;
+
+    // The witness must have cosigned the checkpoint signed by the registry
+    let log_length = cosignature.as_ref().checkpoint.log_length;
+    let store = config.core_service.store();
+    let checkpoint = store.get_checkpoint(log_length).await?;
+    if checkpoint.as_ref().checkpoint != cosignature.as_ref().checkpoint {
+        return Err(WitnessApiError::unprocessable(format!(
+            "the cosigned checkpoint does not match the registry checkpoint for log length {log_length}"
+        )));
+    }
+
+    store.store_cosignature(cosignature).await?;
+
+    Ok(Json(CosignaturesResponse {
+        cosignatures: store.get_cosignatures(log_length).await?,
+    }))
+}
+
+#[debug_handler]
+async fn fetch_cosignatures(
+    State(config): State<Config>,
+    Path(log_length): Path<RegistryLen>,
+) -> Result<Json<CosignaturesResponse>, WitnessApiError> {
+    let store = config.core_service.store();
+
+    // Ensure the checkpoint exists so an unknown log length is not mistaken
+    // for a checkpoint that has not been witnessed
+    store.get_checkpoint(log_length).await?;
+
+    Ok(Json(CosignaturesResponse {
+        cosignatures: store.get_cosignatures(log_length).await?,
+    }))
+}
+
+#[debug_handler]
+async fn fetch_latest_cosignatures(
+    State(config): State<Config>,
+) -> Result<Json<CosignaturesResponse>, WitnessApiError> {
+    Ok(Json(CosignaturesResponse {
+        cosignatures: config
+            .core_service
+            .store()
+            .get_latest_cosignatures()
+            .await?,
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{contentstore::FileSystemContentStore, datastore::MemoryDataStore};
+    use std::{net::TcpListener, time::Duration};
+    use warg_client::{
+        api::{Client, ClientError},
+        storage::{FileSystemContentStorage, FileSystemRegistryStorage},
+    };
+    use warg_crypto::{
+        hash::{Hash, HashAlgorithm, Sha256},
+        signing::{generate_p256_pair, PrivateKey},
+    };
+    use warg_protocol::{
+        registry::{Checkpoint, TimestampedCheckpoint, WitnessedCheckpoint},
+        SerdeEnvelope,
+    };
+
+    fn cosign(key: &PrivateKey, checkpoint: Checkpoint) -> SerdeEnvelope<WitnessedCheckpoint> {
+        SerdeEnvelope::signed_contents(key, WitnessedCheckpoint::now(checkpoint).unwrap()).unwrap()
+    }
+
+    /// Serves the registry API accepting cosignatures of the given
+    /// witnesses, returning its URL.
+    fn serve(core: CoreService, witness_keys: Vec<PublicKey>, dir: &std::path::Path) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{addr}", addr = listener.local_addr().unwrap());
+        let router = crate::api::create_router(
+            Arc::new(FileSystemContentStore::new(dir, url.parse().unwrap())),
+            core,
+            dir.to_path_buf(),
+            None,
+            None,
+            None,
+            witness_keys,
+        );
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(router.into_make_service()),
+        );
+
+        url
+    }
+
+    #[tokio::test]
+    async fn it_stores_witness_cosignatures() {
+        let (_, operator_key) = generate_p256_pair();
+        let (core, _) = CoreService::start(
+            operator_key,
+            None,
+            Box::<MemoryDataStore>::default(),
+            Duration::from_millis(10),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let (witness_public, witness_key) = generate_p256_pair();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = Client::new(format!(
+            "http://{addr}",
+            addr = listener.local_addr().unwrap()
+        ))
+        .unwrap();
+        let router = Router::new().nest(
+            "/v1/witness",
+            Config::new(core.clone(), vec![witness_public]).into_router(),
+        );
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(router.into_make_service()),
+        );
+
+        let checkpoint = core
+            .store()
+            .get_latest_checkpoint()
+            .await
+            .unwrap()
+            .as_ref()
+            .checkpoint
+            .clone();
+        let log_length = checkpoint.log_length;
+
+        // Cosignatures of unknown witnesses are rejected
+        let (_, unknown_key) = generate_p256_pair();
+        assert!(matches!(
+            client
+                .publish_cosignature(&cosign(&unknown_key, checkpoint.clone()))
+                .await,
+            Err(ClientError::Witness(WitnessError::Unauthorized(_)))
+        ));
+
+        // Cosignatures of checkpoints the registry did not sign are rejected
+        let forged = Checkpoint {
+            log_root: HashAlgorithm::Sha256.digest(b"forged"),
+            ..checkpoint.clone()
+        };
+        assert!(matches!(
+            client
+                .publish_cosignature(&cosign(&witness_key, forged))
+                .await,
+            Err(ClientError::Witness(WitnessError::Message {
+                status: 422,
+                ..
+            }))
+        ));
+
+        let unknown = Checkpoint {
+            log_length: log_length + 1,
+            ..checkpoint.clone()
+        };
+        assert!(matches!(
+            client.publish_cosignature(&cosign(&witness_key, unknown)).await,
+            Err(ClientError::Witness(WitnessError::CheckpointNotFound(len))) if len == log_length + 1
+        ));
+
+        // A witness's later cosignature replaces its earlier one
+        for _ in 0..2 {
+            let response = client
+                .publish_cosignature(&cosign(&witness_key, checkpoint.clone()))
+                .await
+                .unwrap();
+            assert_eq!(response.cosignatures.len(), 1);
+        }
+
+        let response = client.fetch_cosignatures(log_length).await.unwrap();
+        assert_eq!(response.cosignatures.len(), 1);
+        assert_eq!(response.cosignatures[0].as_ref().checkpoint, checkpoint);
+        assert!(matches!(
+            client.fetch_cosignatures(log_length + 1).await,
+            Err(ClientError::Witness(WitnessError::CheckpointNotFound(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_gets_the_latest_cosigned_checkpoint() {
+        let (_, operator_key) = generate_p256_pair();
+        let (core, _) = CoreService::start(
+            PrivateKey::decode(operator_key.encode().to_string()).unwrap(),
+            None,
+            Box::<MemoryDataStore>::default(),
+            Duration::from_secs(60),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        // Sign checkpoints that have yet to be cosigned by every witness
+        let checkpoints = [2, 3].map(|log_length| {
+            SerdeEnvelope::signed_contents(
+                &operator_key,
+                TimestampedCheckpoint::now(Checkpoint {
+                    log_length,
+                    log_root: HashAlgorithm::Sha256.digest(&[log_length as u8]),
+                    map_root: HashAlgorithm::Sha256.digest(&[log_length as u8]),
+                })
+                .unwrap(),
+            )
+            .unwrap()
+        });
+        for checkpoint in &checkpoints {
+            let checkpoint_id = Hash::<Sha256>::of(&checkpoint.as_ref().checkpoint).into();
+            core.store()
+                .store_checkpoint(&checkpoint_id, checkpoint.clone())
+                .await
+                .unwrap();
+        }
+
+        let (first_public, first_key) = generate_p256_pair();
+        let (second_public, second_key) = generate_p256_pair();
+        let (untrusted_public, untrusted_key) = generate_p256_pair();
+        let dir = tempfile::tempdir().unwrap();
+        let url = serve(
+            core,
+            vec![
+                first_public.clone(),
+                second_public.clone(),
+                untrusted_public,
+            ],
+            dir.path(),
+        );
+
+        let api = Client::new(url.as_str()).unwrap();
+        let [cosigned, latest] = checkpoints.map(|c| c.as_ref().checkpoint.clone());
+        for (key, checkpoint) in [
+            (&first_key, &cosigned),
+            (&second_key, &cosigned),
+            (&first_key, &latest),
+            (&untrusted_key, &latest),
+        ] {
+            api.publish_cosignature(&cosign(key, checkpoint.clone()))
+                .await
+                .unwrap();
+        }
+
+        let latest_checkpoint = |threshold| {
+            let url = url.clone();
+            let keys = vec![first_public.clone(), second_public.clone()];
+            let dir = dir.path().to_path_buf();
+            async move {
+                warg_client::Client::new(
+                    url,
+                    FileSystemRegistryStorage::lock(dir.join(format!("registry-{threshold}")))
+                        .unwrap(),
+                    FileSystemContentStorage::lock(dir.join(format!("content-{threshold}")))
+                        .unwrap(),
+                )
+                .unwrap()
+                .with_witnesses(keys, threshold)
+                .latest_checkpoint()
+                .await
+                .map(|c| c.into_contents().checkpoint)
+            }
+        };
+
+        // The latest checkpoint is used once enough trusted witnesses cosigned it
+        assert_eq!(latest_checkpoint(1).await.unwrap(), latest);
+
+        // Cosignatures of untrusted witnesses are not counted, so the latest
+        // checkpoint cosigned by enough trusted witnesses is used instead
+        assert_eq!(latest_checkpoint(2).await.unwrap(), cosigned);
+
+        // No checkpoint was cosigned by enough trusted witnesses
+        assert!(matches!(
+            latest_checkpoint(3).await,
+            Err(warg_client::ClientError::InsufficientCosignatures {
+                required: 3,
+                found: 1,
+                ..
+            })
+        ));
+    }
+}
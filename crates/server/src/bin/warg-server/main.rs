@@ -0,0 +1,439 @@
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand, ValueEnum};
+use secrecy::SecretString;
+use std::{net::SocketAddr, path::PathBuf, time::Duration};
+use tokio::signal;
+use tracing_subscriber::filter::LevelFilter;
+use url::Url;
+use warg_crypto::signing::{PrivateKey, PublicKey};
+use warg_server::{
+    args::get_opt_secret,
+    policy::{
+        content::{ContentPolicyCollection, SizeContentPolicy},
+        record::{AuthorizedKeyPolicy, RecordPolicyCollection, WitCompatibilityPolicy},
+    },
+    Config, Server,
+};
+
+mod operator;
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum DataStoreKind {
+    #[cfg(feature = "filesystem")]
+    Filesystem,
+    #[cfg(feature = "postgres")]
+    Postgres,
+    #[default]
+    Memory,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum ContentStoreKind {
+    #[default]
+    Filesystem,
+    #[cfg(feature = "s3")]
+    S3,
+}
+
+#[derive(Parser, Debug)]
+#[command(subcommand_negates_reqs = true)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Use verbose output
+    #[arg(short, long, env = "WARG_VERBOSE", action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Address to listen to
+    #[arg(short, long, env = "WARG_LISTEN", default_value = "127.0.0.1:8090")]
+    listen: SocketAddr,
+
+    /// The content storage directory to use.
+    #[arg(long, env = "WARG_CONTENT_DIR", required = true)]
+    content_dir: Option<PathBuf>,
+
+    /// The base content URL to use; defaults to the server address.
+    #[arg(long, env = "WARG_CONTENT_BASE_URL")]
+    content_base_url: Option<Url>,
+
+    /// The content store to use for the server.
+    #[arg(long, env = "WARG_CONTENT_STORE", default_value = "filesystem")]
+    content_store: ContentStoreKind,
+
+    /// The endpoint URL of the object store if content-store is set to s3.
+    #[cfg(feature = "s3")]
+    #[arg(long, env = "WARG_S3_ENDPOINT")]
+    s3_endpoint: Option<Url>,
+
+    /// The bucket to store content in if content-store is set to s3.
+    #[cfg(feature = "s3")]
+    #[arg(long, env = "WARG_S3_BUCKET")]
+    s3_bucket: Option<String>,
+
+    /// The region of the object store if content-store is set to s3.
+    #[cfg(feature = "s3")]
+    #[arg(long, env = "WARG_S3_REGION", default_value = "us-east-1")]
+    s3_region: String,
+
+    /// The prefix of the keys of content objects if content-store is set to s3.
+    #[cfg(feature = "s3")]
+    #[arg(long, env = "WARG_S3_PREFIX")]
+    s3_prefix: Option<String>,
+
+    /// The access key id to use if content-store is set to s3.
+    #[cfg(feature = "s3")]
+    #[arg(long, env = "WARG_S3_ACCESS_KEY_ID")]
+    s3_access_key_id: Option<String>,
+
+    /// The secret access key to use if content-store is set to s3.
+    ///
+    /// Prefer using `s3-secret-access-key-file`, or environment variable variation,
+    /// to avoid exposing sensitive information.
+    #[cfg(feature = "s3")]
+    #[arg(long, env = "WARG_S3_SECRET_ACCESS_KEY")]
+    s3_secret_access_key: Option<SecretString>,
+
+    /// The path to the secret access key file.
+    #[cfg(feature = "s3")]
+    #[arg(
+        long,
+        env = "WARG_S3_SECRET_ACCESS_KEY_FILE",
+        conflicts_with = "s3_secret_access_key"
+    )]
+    s3_secret_access_key_file: Option<PathBuf>,
+
+    /// The lifetime, in seconds, of presigned content URLs if content-store is set to s3.
+    #[cfg(feature = "s3")]
+    #[arg(long, env = "WARG_S3_PRESIGN_EXPIRY", default_value = "3600")]
+    s3_presign_expiry: u64,
+
+    /// The data store to use for the server.
+    #[arg(long, env = "WARG_DATA_STORE", default_value = "memory")]
+    data_store: DataStoreKind,
+
+    /// The data directory to use if data-store is set to filesystem.
+    #[cfg(feature = "filesystem")]
+    #[arg(long, env = "WARG_DATA_DIR")]
+    data_dir: Option<PathBuf>,
+
+    /// The database connection URL if data-store is set to postgres.
+    ///
+    /// Prefer using `database-url-file`, or environment variable variation,
+    /// to avoid exposing sensitive information.
+    #[cfg(feature = "postgres")]
+    #[arg(long, env = "WARG_DATABASE_URL")]
+    database_url: Option<SecretString>,
+
+    /// The path to the database connection URL file.
+    #[cfg(feature = "postgres")]
+    #[arg(long, env = "WARG_DATABASE_URL_FILE", conflicts_with = "database_url")]
+    database_url_file: Option<PathBuf>,
+
+    /// Run database migrations
+    #[cfg(feature = "postgres")]
+    #[arg(long)]
+    database_run_migrations: bool,
+
+    /// The operator key.
+    ///
+    /// Prefer using `operator-key-file`, or environment variable variation.
+    #[arg(long, env = "WARG_OPERATOR_KEY", global = true)]
+    operator_key: Option<SecretString>,
+
+    /// The path to the operator key.
+    #[arg(
+        long,
+        env = "WARG_OPERATOR_KEY_FILE",
+        conflicts_with = "operator_key",
+        global = true
+    )]
+    operator_key_file: Option<PathBuf>,
+
+    /// The key that signs checkpoints once the operator key is revoked or
+    /// rotated in the operator log.
+    ///
+    /// Prefer using `replacement-operator-key-file`, or environment variable
+    /// variation.
+    #[arg(long, env = "WARG_REPLACEMENT_OPERATOR_KEY")]
+    replacement_operator_key: Option<SecretString>,
+
+    /// The path to the replacement operator key.
+    #[arg(
+        long,
+        env = "WARG_REPLACEMENT_OPERATOR_KEY_FILE",
+        conflicts_with = "replacement_operator_key"
+    )]
+    replacement_operator_key_file: Option<PathBuf>,
+
+    /// The URL of an upstream registry to run as a read-only mirror of.
+    ///
+    /// A mirror does not require an operator key.
+    #[arg(long, env = "WARG_MIRROR", value_name = "URL")]
+    mirror: Option<Url>,
+
+    /// The interval, in seconds, at which a mirror replicates the upstream
+    /// registry.
+    #[arg(long, env = "WARG_MIRROR_INTERVAL", default_value = "5")]
+    mirror_interval: u64,
+
+    /// The public key of a witness permitted to publish cosignatures of
+    /// the registry's checkpoints.
+    #[arg(
+        long = "witness-key",
+        env = "WARG_WITNESS_KEYS",
+        value_delimiter = ',',
+        value_name = "PUBLIC_KEY"
+    )]
+    witness_keys: Vec<PublicKey>,
+
+    /// The path to the authorized keys record policy file.
+    #[arg(long, env = "WARG_AUTHORIZED_KEYS_FILE")]
+    authorized_keys_file: Option<PathBuf>,
+
+    /// Reject component releases that break compatibility with the
+    /// exports of the previous release in the same semver line.
+    #[arg(long, env = "WARG_WIT_COMPATIBILITY")]
+    wit_compatibility: bool,
+
+    /// The time, in seconds, after which package records that are still
+    /// missing content are rejected.
+    #[arg(long, env = "WARG_PENDING_RECORD_TTL", default_value = "86400")]
+    pending_record_ttl: u64,
+
+    /// The number of new registry log entries after which a snapshot of the
+    /// transparency state is stored; 0 disables snapshots.
+    #[arg(long, env = "WARG_SNAPSHOT_THRESHOLD", default_value = "10000")]
+    snapshot_threshold: usize,
+
+    /// The maximum size, in bytes, of uploaded content.
+    #[arg(long, env = "WARG_MAX_CONTENT_SIZE")]
+    max_content_size: Option<u64>,
+
+    /// The storage quota, in bytes, of each namespace.
+    #[arg(long, env = "WARG_NAMESPACE_QUOTA")]
+    namespace_quota: Option<u64>,
+
+    /// The interval, in seconds, at which content not referenced by any
+    /// package record is garbage collected; disabled if not set.
+    #[arg(long, env = "WARG_CONTENT_GC_INTERVAL")]
+    content_gc_interval: Option<u64>,
+
+    /// The grace period, in seconds, before content of pending records and
+    /// recently stored content may be garbage collected.
+    #[arg(long, env = "WARG_CONTENT_GC_GRACE_PERIOD", default_value = "86400")]
+    content_gc_grace_period: u64,
+
+    /// Only report content that would be garbage collected without deleting it.
+    #[arg(long, env = "WARG_CONTENT_GC_DRY_RUN")]
+    content_gc_dry_run: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Manage the operator log of a running registry.
+    #[command(subcommand)]
+    Operator(operator::OperatorCommand),
+}
+
+impl Args {
+    fn init_tracing(&self) {
+        let level_filter = match self.verbose {
+            0 => LevelFilter::INFO,
+            1 => LevelFilter::DEBUG,
+            _ => LevelFilter::TRACE,
+        };
+        tracing_subscriber::fmt()
+            .with_max_level(level_filter)
+            .init();
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    args.init_tracing();
+    tracing::debug!("args: {args:?}");
+
+    if let Some(Command::Operator(command)) = args.command {
+        return command
+            .exec(operator_key(args.operator_key_file, args.operator_key)?)
+            .await;
+    }
+
+    // The content directory is only optional when running a command
+    let content_dir = args
+        .content_dir
+        .context("the `content-dir` option is required")?;
+    let config = match args.mirror {
+        Some(upstream) => Config::mirror(upstream, content_dir)
+            .with_mirror_interval(Duration::from_secs(args.mirror_interval)),
+        None => Config::new(
+            operator_key(args.operator_key_file, args.operator_key)?,
+            content_dir,
+        ),
+    };
+    let mut config = config
+        .with_addr(args.listen)
+        .with_pending_record_ttl(Duration::from_secs(args.pending_record_ttl))
+        .with_snapshot_threshold(args.snapshot_threshold)
+        .with_witness_keys(args.witness_keys)
+        .with_shutdown(shutdown_signal());
+
+    if let Some(url) = args.content_base_url {
+        config = config.with_content_base_url(url);
+    }
+
+    let mut record_policy = RecordPolicyCollection::new();
+    if let Some(path) = args.authorized_keys_file {
+        let authorized_keys_data = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read authorized keys from {path:?}"))?;
+        let authorized_key_policy: AuthorizedKeyPolicy = toml::from_str(&authorized_keys_data)
+            .with_context(|| format!("failed to decode authorized keys from {path:?}"))?;
+        record_policy.push(authorized_key_policy);
+    }
+
+    if args.wit_compatibility {
+        record_policy.push(WitCompatibilityPolicy::new());
+    }
+
+    if !record_policy.is_empty() {
+        config = config.with_record_policy(record_policy);
+    }
+
+    if args.replacement_operator_key.is_some() || args.replacement_operator_key_file.is_some() {
+        config = config.with_replacement_operator_key(
+            operator_key(
+                args.replacement_operator_key_file,
+                args.replacement_operator_key,
+            )
+            .context("failed to parse replacement operator key")?,
+        );
+    }
+
+    let mut content_policy = ContentPolicyCollection::new();
+    if let Some(max_size) = args.max_content_size {
+        content_policy.push(SizeContentPolicy::new(max_size));
+    }
+
+    if !content_policy.is_empty() {
+        config = config.with_content_policy(content_policy);
+    }
+
+    if let Some(quota) = args.namespace_quota {
+        config = config.with_namespace_quota(quota);
+    }
+
+    if let Some(interval) = args.content_gc_interval {
+        config = config
+            .with_content_gc_interval(Duration::from_secs(interval))
+            .with_content_gc_grace_period(Duration::from_secs(args.content_gc_grace_period))
+            .with_content_gc_dry_run(args.content_gc_dry_run);
+    }
+
+    let config = match args.content_store {
+        ContentStoreKind::Filesystem => {
+            tracing::info!("using filesystem content store");
+            config
+        }
+        #[cfg(feature = "s3")]
+        ContentStoreKind::S3 => {
+            use secrecy::ExposeSecret;
+            use warg_server::contentstore::S3ContentStore;
+            tracing::info!("using s3 content store");
+            let endpoint = args
+                .s3_endpoint
+                .context("the `s3-endpoint` option is required for the s3 content store")?;
+            let bucket = args
+                .s3_bucket
+                .context("the `s3-bucket` option is required for the s3 content store")?;
+            let access_key_id = args
+                .s3_access_key_id
+                .context("the `s3-access-key-id` option is required for the s3 content store")?;
+            let secret_access_key = get_opt_secret(
+                "s3-secret-access-key",
+                args.s3_secret_access_key_file,
+                args.s3_secret_access_key,
+            )?;
+
+            let mut store = S3ContentStore::new(
+                endpoint,
+                bucket,
+                args.s3_region,
+                access_key_id,
+                // Ignore any trailing newline from reading the secret from a file
+                secret_access_key.expose_secret().trim().to_string().into(),
+            )
+            .with_presign_expiry(Duration::from_secs(args.s3_presign_expiry));
+            if let Some(prefix) = args.s3_prefix {
+                store = store.with_prefix(prefix);
+            }
+
+            config.with_content_store(store)
+        }
+    };
+
+    let config = match args.data_store {
+        #[cfg(feature = "filesystem")]
+        DataStoreKind::Filesystem => {
+            use warg_server::datastore::FileSystemDataStore;
+            tracing::info!("using filesystem data store");
+            let data_dir = args
+                .data_dir
+                .context("the `data-dir` option is required for the filesystem data store")?;
+            config.with_data_store(FileSystemDataStore::open(data_dir).await?)
+        }
+        #[cfg(feature = "postgres")]
+        DataStoreKind::Postgres => {
+            use warg_server::datastore::PostgresDataStore;
+            tracing::info!("using postgres data store");
+            let database_url =
+                get_opt_secret("database-url", args.database_url_file, args.database_url)?;
+            let pg_store = PostgresDataStore::new(database_url)?;
+            if args.database_run_migrations {
+                tracing::info!("running any pending database migration(s)");
+                pg_store.run_pending_migrations().await?;
+            }
+            config.with_data_store(pg_store)
+        }
+        DataStoreKind::Memory => {
+            tracing::info!("using memory data store");
+            config
+        }
+    };
+
+    Server::new(config).run().await
+}
+
+fn operator_key(file: Option<PathBuf>, key: Option<SecretString>) -> Result<PrivateKey> {
+    let operator_key_str = get_opt_secret("operator-key", file, key)?;
+    PrivateKey::decode(operator_key_str).context("failed to parse operator key")
+}
+
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+
+        tracing::info!("starting shutdown (SIGINT)");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        signal::unix::signal(signal::unix::SignalKind::terminate())
+            .expect("failed to install signal handler")
+            .recv()
+            .await;
+
+        tracing::info!("starting shutdown (SIGTERM)");
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
@@ -0,0 +1,264 @@
+//! The `operator` command of the server, which publishes records to the
+//! operator log of a running registry.
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::Subcommand;
+use std::{
+    borrow::Cow,
+    time::{Duration, SystemTime},
+};
+use url::Url;
+use warg_api::v1::{
+    fetch::FetchLogsRequest,
+    operator::{OperatorRecordState, PublishOperatorRecordRequest},
+};
+use warg_client::api::Client;
+use warg_crypto::{
+    hash::Sha256,
+    signing::{KeyID, PrivateKey, PublicKey},
+};
+use warg_protocol::{
+    operator::{self, OperatorEntry, Permission},
+    registry::RecordId,
+    ProtoEnvelope, PublishedProtoEnvelope,
+};
+
+/// The interval at which the state of a published record is polled.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The maximum number of times the state of a published record is polled.
+const MAX_POLL_ATTEMPTS: u32 = 60;
+
+/// Publishes records to the operator log of a running registry.
+///
+/// Records are signed with the operator key, which must have the commit
+/// permission.
+#[derive(Subcommand, Debug)]
+pub enum OperatorCommand {
+    /// Grant operator permissions to a key.
+    Grant {
+        /// The URL of the registry.
+        #[arg(
+            long,
+            env = "WARG_REGISTRY_URL",
+            default_value = "http://127.0.0.1:8090"
+        )]
+        registry: Url,
+        /// The public key to grant permissions to.
+        #[arg(value_name = "PUBLIC_KEY")]
+        key: PublicKey,
+        /// The permission(s) to grant.
+        #[arg(long = "permission", value_delimiter = ',', default_value = "commit")]
+        permissions: Vec<Permission>,
+    },
+    /// Revoke operator permissions from a key.
+    Revoke {
+        /// The URL of the registry.
+        #[arg(
+            long,
+            env = "WARG_REGISTRY_URL",
+            default_value = "http://127.0.0.1:8090"
+        )]
+        registry: Url,
+        /// The key ID to revoke permissions from.
+        #[arg(value_name = "KEY_ID")]
+        key_id: KeyID,
+        /// The permission(s) to revoke.
+        #[arg(long = "permission", value_delimiter = ',', default_value = "commit")]
+        permissions: Vec<Permission>,
+    },
+    /// Define a namespace hosted by the registry.
+    DefineNamespace {
+        /// The URL of the registry.
+        #[arg(
+            long,
+            env = "WARG_REGISTRY_URL",
+            default_value = "http://127.0.0.1:8090"
+        )]
+        registry: Url,
+        /// The namespace to define.
+        #[arg(value_name = "NAMESPACE")]
+        namespace: String,
+        /// The ID of a key permitted to initialize packages in the namespace.
+        #[arg(long = "owner", value_name = "KEY_ID")]
+        owners: Vec<KeyID>,
+    },
+    /// Mark a namespace as imported from another registry.
+    ImportNamespace {
+        /// The URL of the registry.
+        #[arg(
+            long,
+            env = "WARG_REGISTRY_URL",
+            default_value = "http://127.0.0.1:8090"
+        )]
+        registry: Url,
+        /// The namespace to import.
+        #[arg(value_name = "NAMESPACE")]
+        namespace: String,
+        /// The registry the namespace is imported from.
+        #[arg(long = "from", value_name = "REGISTRY")]
+        from: String,
+    },
+}
+
+impl OperatorCommand {
+    /// Executes the command.
+    pub async fn exec(self, operator_key: PrivateKey) -> Result<()> {
+        let (registry, entry, message) = match self {
+            Self::Grant {
+                registry,
+                key,
+                permissions,
+            } => {
+                let message = format!(
+                    "granted ({permissions}) to key ID `{key_id}`",
+                    permissions = join(&permissions),
+                    key_id = key.fingerprint()
+                );
+                (
+                    registry,
+                    OperatorEntry::GrantFlat { key, permissions },
+                    message,
+                )
+            }
+            Self::Revoke {
+                registry,
+                key_id,
+                permissions,
+            } => {
+                let message = format!(
+                    "revoked ({permissions}) from key ID `{key_id}`",
+                    permissions = join(&permissions)
+                );
+                (
+                    registry,
+                    OperatorEntry::RevokeFlat {
+                        key_id,
+                        permissions,
+                    },
+                    message,
+                )
+            }
+            Self::DefineNamespace {
+                registry,
+                namespace,
+                owners,
+            } => {
+                let message = format!("defined namespace `{namespace}`");
+                (
+                    registry,
+                    OperatorEntry::DefineNamespace { namespace, owners },
+                    message,
+                )
+            }
+            Self::ImportNamespace {
+                registry,
+                namespace,
+                from,
+            } => {
+                let message = format!("imported namespace `{namespace}` from `{from}`");
+                (
+                    registry,
+                    OperatorEntry::ImportNamespace {
+                        namespace,
+                        registry: from,
+                    },
+                    message,
+                )
+            }
+        };
+
+        let client = Client::new(registry)?;
+        let record_id = publish(&client, &operator_key, entry).await?;
+        wait_for_publish(&client, &record_id).await?;
+        println!("{message}");
+        Ok(())
+    }
+}
+
+fn join(permissions: &[Permission]) -> String {
+    permissions
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Publishes a record with the given entry after the current head of the
+/// operator log.
+async fn publish(
+    client: &Client,
+    operator_key: &PrivateKey,
+    entry: OperatorEntry,
+) -> Result<RecordId> {
+    let record = operator::OperatorRecord {
+        prev: Some(operator_head(client).await?),
+        version: 0,
+        timestamp: SystemTime::now(),
+        entries: vec![entry],
+    };
+    let record = ProtoEnvelope::signed_contents(operator_key, record)
+        .context("failed to sign operator record")?;
+
+    let record = client
+        .publish_operator_record(PublishOperatorRecordRequest {
+            record: Cow::Owned(record.into()),
+        })
+        .await?;
+    match record.state {
+        OperatorRecordState::Rejected { reason } => {
+            bail!(
+                "operator record `{id}` was rejected: {reason}",
+                id = record.id
+            )
+        }
+        _ => Ok(record.id),
+    }
+}
+
+/// Waits for the given record to be published.
+async fn wait_for_publish(client: &Client, record_id: &RecordId) -> Result<()> {
+    for _ in 0..MAX_POLL_ATTEMPTS {
+        match client.get_operator_record(record_id).await?.state {
+            OperatorRecordState::Processing => tokio::time::sleep(POLL_INTERVAL).await,
+            OperatorRecordState::Rejected { reason } => {
+                bail!("operator record `{record_id}` was rejected: {reason}")
+            }
+            OperatorRecordState::Published { .. } => return Ok(()),
+        }
+    }
+
+    bail!("timed out waiting for operator record `{record_id}` to be published")
+}
+
+/// Gets the identifier of the latest operator record in the latest
+/// checkpoint.
+async fn operator_head(client: &Client) -> Result<RecordId> {
+    let checkpoint = client.latest_checkpoint().await?;
+    let log_length = checkpoint.as_ref().checkpoint.log_length;
+
+    let mut head = None;
+    loop {
+        let response = client
+            .fetch_logs(FetchLogsRequest {
+                log_length,
+                limit: None,
+                operator: head.as_ref().map(Cow::Borrowed),
+                packages: Default::default(),
+            })
+            .await?;
+
+        if let Some(record) = response.operator.into_iter().last() {
+            let record: PublishedProtoEnvelope<operator::OperatorRecord> = record
+                .try_into()
+                .map_err(|e| anyhow!("failed to decode operator record: {e}"))?;
+            head = Some(RecordId::operator_record::<Sha256>(&record.envelope));
+        }
+
+        if !response.more {
+            break;
+        }
+    }
+
+    head.ok_or_else(|| anyhow!("the operator log of the registry is empty"))
+}
@@ -0,0 +1,355 @@
+//! A witness of the checkpoints of a registry.
+//!
+//! The witness periodically fetches the latest checkpoint of a registry,
+//! verifies it is consistent with the last checkpoint it witnessed, and
+//! publishes a cosignature of the checkpoint to the registry.
+//!
+//! The witness only verifies the append-only property of the registry log;
+//! it does not validate the registry's records.
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use secrecy::{ExposeSecret, SecretString};
+use std::{borrow::Cow, path::PathBuf, time::Duration};
+use tokio::signal;
+use tracing_subscriber::filter::LevelFilter;
+use url::Url;
+use warg_api::v1::proof::ConsistencyRequest;
+use warg_client::api::Client;
+use warg_crypto::signing::PrivateKey;
+use warg_protocol::{
+    registry::{Checkpoint, WitnessedCheckpoint},
+    SerdeEnvelope,
+};
+use warg_server::args::get_opt_secret;
+
+#[derive(Parser, Debug)]
+struct Args {
+    /// Use verbose output
+    #[arg(short, long, env = "WARG_VERBOSE", action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// The URL of the registry to witness.
+    #[arg(
+        long,
+        env = "WARG_REGISTRY_URL",
+        default_value = "http://127.0.0.1:8090"
+    )]
+    registry: Url,
+
+    /// The witness key.
+    ///
+    /// Prefer using `witness-key-file`, or environment variable variation.
+    #[arg(long, env = "WARG_WITNESS_KEY")]
+    witness_key: Option<SecretString>,
+
+    /// The path to the witness key.
+    #[arg(long, env = "WARG_WITNESS_KEY_FILE", conflicts_with = "witness_key")]
+    witness_key_file: Option<PathBuf>,
+
+    /// The path to the file storing the last witnessed checkpoint.
+    #[arg(long, env = "WARG_WITNESS_STATE_FILE")]
+    state_file: PathBuf,
+
+    /// The interval, in seconds, at which the registry is polled for new
+    /// checkpoints.
+    #[arg(long, env = "WARG_WITNESS_INTERVAL", default_value = "5")]
+    interval: u64,
+}
+
+impl Args {
+    fn init_tracing(&self) {
+        let level_filter = match self.verbose {
+            0 => LevelFilter::INFO,
+            1 => LevelFilter::DEBUG,
+            _ => LevelFilter::TRACE,
+        };
+        tracing_subscriber::fmt()
+            .with_max_level(level_filter)
+            .init();
+    }
+}
+
+struct Witness {
+    client: Client,
+    key: PrivateKey,
+    state_file: PathBuf,
+}
+
+impl Witness {
+    /// Witnesses the latest checkpoint of the registry.
+    async fn witness(&self) -> Result<()> {
+        let latest = self
+            .client
+            .latest_checkpoint()
+            .await
+            .context("failed to fetch the latest checkpoint")?;
+        let checkpoint = &latest.as_ref().checkpoint;
+
+        let last = self.load()?;
+        match &last {
+            Some(last) if last == checkpoint => return Ok(()),
+            Some(last) if checkpoint.log_length <= last.log_length => bail!(
+                "the registry checkpoint for log length {len} is inconsistent with the \
+                 witnessed checkpoint for log length {last_len}; refusing to cosign",
+                len = checkpoint.log_length,
+                last_len = last.log_length,
+            ),
+            Some(last) => self
+                .client
+                .prove_log_consistency(
+                    ConsistencyRequest {
+                        from: last.log_length,
+                        to: checkpoint.log_length,
+                    },
+                    Cow::Borrowed(&last.log_root),
+                    Cow::Borrowed(&checkpoint.log_root),
+                )
+                .await
+                .with_context(|| {
+                    format!(
+                        "the registry failed to prove consistency from log length {from} to {to}; \
+                         refusing to cosign",
+                        from = last.log_length,
+                        to = checkpoint.log_length
+                    )
+                })?,
+            // Trust the first checkpoint witnessed
+            None => {}
+        }
+
+        let cosignature = SerdeEnvelope::signed_contents(
+            &self.key,
+            WitnessedCheckpoint::now(checkpoint.clone())?,
+        )?;
+        let response = self
+            .client
+            .publish_cosignature(&cosignature)
+            .await
+            .context("failed to publish cosignature")?;
+
+        // Only persist the checkpoint once the cosignature was published so
+        // that it is published again after a failure
+        self.store(checkpoint)?;
+
+        tracing::info!(
+            "cosigned checkpoint for log length {len} ({count} cosignature(s))",
+            len = checkpoint.log_length,
+            count = response.cosignatures.len()
+        );
+
+        Ok(())
+    }
+
+    /// Loads the last witnessed checkpoint.
+    fn load(&self) -> Result<Option<Checkpoint>> {
+        let contents = match std::fs::read(&self.state_file) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(e).with_context(|| {
+                    format!(
+                        "failed to read state file `{path}`",
+                        path = self.state_file.display()
+                    )
+                })
+            }
+        };
+
+        serde_json::from_slice(&contents)
+            .map(Some)
+            .with_context(|| {
+                format!(
+                    "failed to deserialize state file `{path}`",
+                    path = self.state_file.display()
+                )
+            })
+    }
+
+    /// Stores the last witnessed checkpoint.
+    fn store(&self, checkpoint: &Checkpoint) -> Result<()> {
+        // Write to a temporary file first so a partially written state is never loaded
+        let temp_path = self.state_file.with_extension("tmp");
+        std::fs::write(&temp_path, serde_json::to_vec_pretty(checkpoint)?).with_context(|| {
+            format!(
+                "failed to write state file `{path}`",
+                path = temp_path.display()
+            )
+        })?;
+        std::fs::rename(&temp_path, &self.state_file).with_context(|| {
+            format!(
+                "failed to write state file `{path}`",
+                path = self.state_file.display()
+            )
+        })
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    args.init_tracing();
+    tracing::debug!("args: {args:?}");
+
+    let key = get_opt_secret("witness-key", args.witness_key_file, args.witness_key)?;
+    // Ignore any trailing newline from reading the key from a file
+    let key = PrivateKey::decode(key.expose_secret().trim().to_string())
+        .context("failed to parse witness key")?;
+    tracing::info!(
+        "witnessing registry `{registry}` with key `{key_id}`",
+        registry = args.registry,
+        key_id = key.public_key().fingerprint()
+    );
+
+    let witness = Witness {
+        client: Client::new(args.registry)?,
+        key,
+        state_file: args.state_file,
+    };
+
+    let mut interval = tokio::time::interval(Duration::from_secs(args.interval));
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                if let Err(e) = witness.witness().await {
+                    tracing::error!("{e:?}");
+                }
+            }
+            _ = signal::ctrl_c() => {
+                tracing::info!("shutting down");
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use warg_api::v1::package::PublishRecordRequest;
+    use warg_crypto::{
+        hash::{HashAlgorithm, Sha256},
+        signing::generate_p256_pair,
+    };
+    use warg_protocol::{
+        package,
+        registry::{LogId, PackageId},
+        ProtoEnvelope,
+    };
+    use warg_server::{Config, Server};
+
+    /// Publishes a package to the registry and waits for a checkpoint that
+    /// includes it.
+    async fn publish(client: &Client, name: &str) -> Checkpoint {
+        let log_length = client
+            .latest_checkpoint()
+            .await
+            .unwrap()
+            .as_ref()
+            .checkpoint
+            .log_length;
+
+        let (public_key, private_key) = generate_p256_pair();
+        let id = PackageId::new(name).unwrap();
+        let record = ProtoEnvelope::signed_contents(
+            &private_key,
+            package::PackageRecord {
+                prev: None,
+                version: 0,
+                timestamp: std::time::SystemTime::now(),
+                entries: vec![package::PackageEntry::Init {
+                    hash_algorithm: HashAlgorithm::Sha256,
+                    key: public_key,
+                }],
+            },
+        )
+        .unwrap();
+        client
+            .publish_package_record(
+                &LogId::package_log::<Sha256>(&id),
+                PublishRecordRequest {
+                    id: Cow::Owned(id),
+                    record: Cow::Owned(record.into()),
+                    content_sources: Default::default(),
+                },
+            )
+            .await
+            .unwrap();
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let checkpoint = client.latest_checkpoint().await.unwrap().into_contents();
+                if checkpoint.checkpoint.log_length > log_length {
+                    return checkpoint.checkpoint;
+                }
+
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_cosigns_consistent_checkpoints() {
+        let dir = tempfile::tempdir().unwrap();
+        let (_, operator_key) = generate_p256_pair();
+        let (witness_public, witness_key) = generate_p256_pair();
+        let server = Server::new(
+            Config::new(operator_key, dir.path().join("content"))
+                .with_addr(([127, 0, 0, 1], 0))
+                .with_checkpoint_interval(Duration::from_millis(10))
+                .with_witness_keys([witness_public]),
+        )
+        .initialize()
+        .await
+        .unwrap();
+        let url = format!("http://{addr}", addr = server.local_addr().unwrap());
+        tokio::spawn(server.serve());
+
+        let client = Client::new(url.as_str()).unwrap();
+        let witness = Witness {
+            client: Client::new(url.as_str()).unwrap(),
+            key: witness_key,
+            state_file: dir.path().join("witness.json"),
+        };
+
+        // The first checkpoint witnessed is trusted
+        let first = publish(&client, "test:first").await;
+        witness.witness().await.unwrap();
+        assert_eq!(witness.load().unwrap().as_ref(), Some(&first));
+
+        // Witnessing the same checkpoint again does nothing
+        witness.witness().await.unwrap();
+        let response = client.fetch_cosignatures(first.log_length).await.unwrap();
+        assert_eq!(response.cosignatures.len(), 1);
+        assert_eq!(response.cosignatures[0].as_ref().checkpoint, first);
+
+        // A later checkpoint is cosigned once proven consistent
+        let second = publish(&client, "test:second").await;
+        witness.witness().await.unwrap();
+        assert_eq!(witness.load().unwrap().as_ref(), Some(&second));
+        let response = client.fetch_cosignatures(second.log_length).await.unwrap();
+        assert_eq!(response.cosignatures[0].as_ref().checkpoint, second);
+
+        // A checkpoint that rewrites the witnessed log is not cosigned
+        witness
+            .store(&Checkpoint {
+                log_root: HashAlgorithm::Sha256.digest(b"forked"),
+                ..first
+            })
+            .unwrap();
+        let third = publish(&client, "test:third").await;
+        let err = witness.witness().await.unwrap_err();
+        assert!(
+            format!("{err:#}").contains("failed to prove consistency"),
+            "unexpected error: {err:#}"
+        );
+        assert!(client
+            .fetch_cosignatures(third.log_length)
+            .await
+            .unwrap()
+            .cosignatures
+            .is_empty());
+    }
+}
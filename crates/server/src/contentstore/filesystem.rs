@@ -0,0 +1,129 @@
+use super::{
+    content_file_name, parse_content_file_name, ContentInfo, ContentStore, ContentStoreError,
+    ContentStream,
+};
+use futures::TryStreamExt;
+use std::{
+    io::SeekFrom,
+    path::{Path, PathBuf},
+};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio_util::io::ReaderStream;
+use url::Url;
+use warg_crypto::hash::AnyHash;
+
+/// A content store that stores content as files in a local directory.
+///
+/// Content is downloaded through the server's `content` route.
+pub struct FileSystemContentStore {
+    files_dir: PathBuf,
+    base_url: Url,
+}
+
+impl FileSystemContentStore {
+    /// Creates a new file system content store.
+    ///
+    /// The `base_url` is the base URL of the server hosting the files.
+    pub fn new(files_dir: impl Into<PathBuf>, base_url: Url) -> Self {
+        Self {
+            files_dir: files_dir.into(),
+            base_url,
+        }
+    }
+
+    fn content_path(&self, digest: &AnyHash) -> PathBuf {
+        self.files_dir.join(content_file_name(digest))
+    }
+
+    async fn open_content(&self, digest: &AnyHash) -> Result<tokio::fs::File, ContentStoreError> {
+        match tokio::fs::File::open(self.content_path(digest)).await {
+            Ok(file) => Ok(file),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Err(ContentStoreError::ContentNotFound(digest.clone()))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[axum::async_trait]
+impl ContentStore for FileSystemContentStore {
+    async fn content_present(&self, digest: &AnyHash) -> Result<bool, ContentStoreError> {
+        Ok(tokio::fs::metadata(self.content_path(digest))
+            .await
+            .map(|m| m.is_file())
+            .unwrap_or(false))
+    }
+
+    async fn store_content(&self, digest: &AnyHash, path: &Path) -> Result<(), ContentStoreError> {
+        tokio::fs::rename(path, self.content_path(digest)).await?;
+        Ok(())
+    }
+
+    async fn content_size(&self, digest: &AnyHash) -> Result<Option<u64>, ContentStoreError> {
+        match tokio::fs::metadata(self.content_path(digest)).await {
+            Ok(metadata) if metadata.is_file() => Ok(Some(metadata.len())),
+            Ok(_) => Ok(None),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn load_content(&self, digest: &AnyHash) -> Result<ContentStream, ContentStoreError> {
+        let file = self.open_content(digest).await?;
+        Ok(Box::pin(ReaderStream::new(file).map_err(Into::into)))
+    }
+
+    async fn load_content_range(
+        &self,
+        digest: &AnyHash,
+        start: u64,
+        len: u64,
+    ) -> Result<ContentStream, ContentStoreError> {
+        let mut file = self.open_content(digest).await?;
+        file.seek(SeekFrom::Start(start)).await?;
+        Ok(Box::pin(
+            ReaderStream::new(file.take(len)).map_err(Into::into),
+        ))
+    }
+
+    async fn delete_content(&self, digest: &AnyHash) -> Result<(), ContentStoreError> {
+        match tokio::fs::remove_file(self.content_path(digest)).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn list_content(&self) -> Result<Vec<ContentInfo>, ContentStoreError> {
+        let mut content = Vec::new();
+        let mut entries = tokio::fs::read_dir(&self.files_dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let Some(digest) = entry.file_name().to_str().and_then(parse_content_file_name) else {
+                continue;
+            };
+
+            let metadata = entry.metadata().await?;
+            if !metadata.is_file() {
+                continue;
+            }
+
+            content.push(ContentInfo {
+                digest,
+                size: metadata.len(),
+                last_modified: metadata.modified()?,
+            });
+        }
+
+        Ok(content)
+    }
+
+    fn download_url(&self, digest: &AnyHash) -> String {
+        self.base_url
+            .join("content/")
+            .unwrap()
+            .join(&content_file_name(digest))
+            .unwrap()
+            .to_string()
+    }
+}
@@ -0,0 +1,169 @@
+use bytes::Bytes;
+use futures::{Stream, TryStreamExt};
+use std::{path::Path, pin::Pin, str::FromStr, time::SystemTime};
+use thiserror::Error;
+use warg_crypto::hash::AnyHash;
+
+mod filesystem;
+#[cfg(feature = "s3")]
+mod s3;
+
+pub use filesystem::*;
+#[cfg(feature = "s3")]
+pub use s3::*;
+
+#[derive(Debug, Error)]
+pub enum ContentStoreError {
+    #[error("content with digest `{0}` was not found")]
+    ContentNotFound(AnyHash),
+
+    #[error("upload `{0}` was not found")]
+    UploadNotFound(String),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[cfg(feature = "s3")]
+    #[error("failed to send request to the object store: {0}")]
+    Request(#[from] reqwest::Error),
+
+    #[cfg(feature = "s3")]
+    #[error("the object store responded with status {status}: {message}")]
+    UnexpectedResponse {
+        status: reqwest::StatusCode,
+        message: String,
+    },
+}
+
+/// Represents a stream of content bytes.
+pub type ContentStream = Pin<Box<dyn Stream<Item = Result<Bytes, ContentStoreError>> + Send>>;
+
+/// Represents information about content in a content store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentInfo {
+    /// The digest of the content.
+    pub digest: AnyHash,
+    /// The size of the content, in bytes.
+    pub size: u64,
+    /// The time the content was last modified in the store.
+    pub last_modified: SystemTime,
+}
+
+/// Implemented by content stores.
+///
+/// A content store holds the content of package records, addressed by digest.
+#[axum::async_trait]
+pub trait ContentStore: Send + Sync {
+    /// Determines if content with the given digest is present in the store.
+    async fn content_present(&self, digest: &AnyHash) -> Result<bool, ContentStoreError>;
+
+    /// Stores the content of the given file with the given digest.
+    ///
+    /// The content must have already been verified against the digest.
+    ///
+    /// The store may move the file.
+    async fn store_content(&self, digest: &AnyHash, path: &Path) -> Result<(), ContentStoreError>;
+
+    /// Gets the size, in bytes, of the content with the given digest.
+    ///
+    /// Returns `None` if the content is not present in the store.
+    async fn content_size(&self, digest: &AnyHash) -> Result<Option<u64>, ContentStoreError>;
+
+    /// Loads the content with the given digest as a stream.
+    async fn load_content(&self, digest: &AnyHash) -> Result<ContentStream, ContentStoreError>;
+
+    /// Loads `len` bytes of the content with the given digest, starting at
+    /// byte offset `start`, as a stream.
+    async fn load_content_range(
+        &self,
+        digest: &AnyHash,
+        start: u64,
+        len: u64,
+    ) -> Result<ContentStream, ContentStoreError>;
+
+    /// Deletes the content with the given digest.
+    ///
+    /// It is not an error if the content is not present.
+    async fn delete_content(&self, digest: &AnyHash) -> Result<(), ContentStoreError>;
+
+    /// Lists all content in the store.
+    ///
+    /// This is an expensive operation and should only be performed when
+    /// collecting unreferenced content.
+    async fn list_content(&self) -> Result<Vec<ContentInfo>, ContentStoreError>;
+
+    /// Gets the URL that content with the given digest can be downloaded from.
+    fn download_url(&self, digest: &AnyHash) -> String;
+
+    /// Gets a URL that the content of the upload with the given identifier
+    /// can be uploaded to directly with an HTTP PUT request.
+    ///
+    /// Uploaded content is staged apart from the stored content, as it has
+    /// not been verified against its digest; see [`ContentStore::load_upload`].
+    ///
+    /// Returns `None` if the store does not support direct uploads, in which
+    /// case content is uploaded through the registry.
+    fn upload_url(&self, _upload_id: &str) -> Option<String> {
+        None
+    }
+
+    /// Loads the staged content of the upload with the given identifier as
+    /// a stream.
+    async fn load_upload(&self, upload_id: &str) -> Result<ContentStream, ContentStoreError> {
+        Err(ContentStoreError::UploadNotFound(upload_id.to_string()))
+    }
+
+    /// Deletes the staged content of the upload with the given identifier.
+    ///
+    /// It is not an error if the upload is not present.
+    async fn delete_upload(&self, _upload_id: &str) -> Result<(), ContentStoreError> {
+        Ok(())
+    }
+
+    /// Reads the entire content with the given digest.
+    async fn read_content(&self, digest: &AnyHash) -> Result<Vec<u8>, ContentStoreError> {
+        let mut stream = self.load_content(digest).await?;
+        let mut content = Vec::new();
+        while let Some(bytes) = stream.try_next().await? {
+            content.extend_from_slice(&bytes);
+        }
+
+        Ok(content)
+    }
+}
+
+/// Gets the name of the object or file that stores content with the given digest.
+pub fn content_file_name(digest: &AnyHash) -> String {
+    digest.to_string().replace(':', "-")
+}
+
+/// Parses the digest from the name of the object or file that stores content.
+///
+/// Returns `None` if the name is not a content file name.
+pub fn parse_content_file_name(name: &str) -> Option<AnyHash> {
+    let (algorithm, digest) = name.split_once('-')?;
+    AnyHash::from_str(&format!("{algorithm}:{digest}")).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_parses_content_file_names() {
+        let digest = parse_content_file_name(
+            "sha256-e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+        )
+        .unwrap();
+        assert_eq!(
+            digest.to_string(),
+            "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            content_file_name(&digest),
+            digest.to_string().replace(':', "-")
+        );
+        assert!(parse_content_file_name("sha256").is_none());
+        assert!(parse_content_file_name("foo-bar").is_none());
+    }
+}
@@ -0,0 +1,1224 @@
+use super::{DataStore, DataStoreError, MemoryDataStore, Record, TransparencySnapshot};
+use anyhow::{Context, Result};
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    io::SeekFrom,
+    path::{Path, PathBuf},
+    pin::Pin,
+    time::SystemTime,
+};
+use tokio::{
+    fs::{File, OpenOptions},
+    io::{AsyncBufReadExt, AsyncSeekExt, AsyncWriteExt, BufReader, BufWriter},
+    sync::Mutex,
+};
+use warg_crypto::hash::AnyHash;
+use warg_protocol::{
+    operator, package,
+    registry::{
+        LogId, LogLeaf, PackageId, RecordId, RegistryIndex, RegistryLen, TimestampedCheckpoint,
+        WitnessedCheckpoint,
+    },
+    ProtoEnvelope, ProtoEnvelopeBody, PublishedProtoEnvelope, SerdeEnvelope,
+};
+
+const JOURNAL_FILE_NAME: &str = "journal.jsonl";
+const SNAPSHOT_FILE_NAME: &str = "snapshot.json";
+
+/// The minimum number of operations in the journal before it is compacted.
+const COMPACTION_MIN_OPERATIONS: usize = 10_000;
+
+/// Represents an update to the data store recorded in the journal.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub(super) enum Operation {
+    #[serde(rename_all = "camelCase")]
+    StoreOperatorRecord {
+        log_id: LogId,
+        record_id: RecordId,
+        record: ProtoEnvelopeBody,
+    },
+    #[serde(rename_all = "camelCase")]
+    RejectOperatorRecord {
+        log_id: LogId,
+        record_id: RecordId,
+        reason: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    CommitOperatorRecord {
+        log_id: LogId,
+        record_id: RecordId,
+        registry_index: RegistryIndex,
+    },
+    #[serde(rename_all = "camelCase")]
+    StorePackageRecord {
+        log_id: LogId,
+        package_id: PackageId,
+        record_id: RecordId,
+        record: ProtoEnvelopeBody,
+        missing: Vec<AnyHash>,
+        /// Journals written before the receipt time was recorded do not
+        /// have it.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        received: Option<SystemTime>,
+    },
+    #[serde(rename_all = "camelCase")]
+    RejectPackageRecord {
+        log_id: LogId,
+        record_id: RecordId,
+        reason: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    CommitPackageRecord {
+        log_id: LogId,
+        record_id: RecordId,
+        registry_index: RegistryIndex,
+    },
+    #[serde(rename_all = "camelCase")]
+    CommitPackageRecords {
+        leafs: Vec<LogLeaf>,
+        registry_index: RegistryIndex,
+    },
+    #[serde(rename_all = "camelCase")]
+    SetContentPresent {
+        log_id: LogId,
+        record_id: RecordId,
+        digest: AnyHash,
+        size: u64,
+    },
+    /// Only written when the journal is compacted.
+    #[serde(rename_all = "camelCase")]
+    SetContentSize { digest: AnyHash, size: u64 },
+    #[serde(rename_all = "camelCase")]
+    StoreCheckpoint {
+        checkpoint_id: AnyHash,
+        checkpoint: SerdeEnvelope<TimestampedCheckpoint>,
+    },
+    #[serde(rename_all = "camelCase")]
+    StoreCosignature {
+        cosignature: SerdeEnvelope<WitnessedCheckpoint>,
+    },
+}
+
+impl Operation {
+    /// Applies the operation to the given in-memory store.
+    async fn apply(self, store: &MemoryDataStore) -> Result<()> {
+        match self {
+            Self::StoreOperatorRecord {
+                log_id,
+                record_id,
+                record,
+            } => {
+                store
+                    .store_operator_record(&log_id, &record_id, &record.try_into()?)
+                    .await?
+            }
+            Self::RejectOperatorRecord {
+                log_id,
+                record_id,
+                reason,
+            } => {
+                store
+                    .reject_operator_record(&log_id, &record_id, &reason)
+                    .await?
+            }
+            Self::CommitOperatorRecord {
+                log_id,
+                record_id,
+                registry_index,
+            } => {
+                store
+                    .commit_operator_record(&log_id, &record_id, registry_index)
+                    .await?
+            }
+            Self::StorePackageRecord {
+                log_id,
+                package_id,
+                record_id,
+                record,
+                missing,
+                received,
+            } => {
+                let record: ProtoEnvelope<package::PackageRecord> = record.try_into()?;
+                // The record's timestamp approximates when it was received
+                let received = received.unwrap_or(record.as_ref().timestamp);
+                store
+                    .store_package_record_received_at(
+                        &log_id,
+                        &package_id,
+                        &record_id,
+                        &record,
+                        &missing.iter().collect(),
+                        received,
+                    )
+                    .await?
+            }
+            Self::RejectPackageRecord {
+                log_id,
+                record_id,
+                reason,
+            } => {
+                store
+                    .reject_package_record(&log_id, &record_id, &reason)
+                    .await?
+            }
+            Self::CommitPackageRecord {
+                log_id,
+                record_id,
+                registry_index,
+            } => {
+                store
+                    .commit_package_record(&log_id, &record_id, registry_index)
+                    .await?
+            }
+            Self::CommitPackageRecords {
+                leafs,
+                registry_index,
+            } => {
+                // Records that failed to validate were rejected when the
+                // operation was first applied
+                store.commit_package_records(&leafs, registry_index).await?;
+            }
+            Self::SetContentPresent {
+                log_id,
+                record_id,
+                digest,
+                size,
+            } => {
+                store
+                    .set_content_present(&log_id, &record_id, &digest, size, None)
+                    .await?;
+            }
+            Self::SetContentSize { digest, size } => store.set_content_size(&digest, size).await,
+            Self::StoreCheckpoint {
+                checkpoint_id,
+                checkpoint,
+            } => store.store_checkpoint(&checkpoint_id, checkpoint).await?,
+            Self::StoreCosignature { cosignature } => store.store_cosignature(cosignature).await?,
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents a data store persisted to an append-only journal file.
+///
+/// Every update to the data store is appended to the journal before it is
+/// applied in memory; the journal is replayed when the data store is opened.
+///
+/// This is intended for single-node deployments that need durable storage
+/// without an external database; the journal must not be shared between
+/// multiple servers.
+///
+/// Once the journal is large, it is compacted whenever it has grown to twice
+/// the number of operations needed to recreate the data store.
+///
+/// The latest transparency snapshot is stored in a separate file.
+pub struct FileSystemDataStore {
+    memory: MemoryDataStore,
+    journal: Mutex<Journal>,
+    dir: PathBuf,
+}
+
+/// Represents the journal file of the data store.
+struct Journal {
+    file: File,
+    path: PathBuf,
+    /// The number of operations in the journal.
+    operations: usize,
+    /// The number of operations in the journal when it was last compacted.
+    compacted: usize,
+}
+
+impl Journal {
+    /// Appends the given operation to the journal.
+    ///
+    /// The journal is compacted first if needed; the caller must hold the
+    /// journal lock until the operation is applied to the given store.
+    async fn append(
+        &mut self,
+        store: &MemoryDataStore,
+        operation: &Operation,
+    ) -> Result<(), DataStoreError> {
+        if self.needs_compaction() {
+            self.compact(store).await?;
+        }
+
+        let mut line = serde_json::to_vec(operation).map_err(std::io::Error::from)?;
+        line.push(b'\n');
+
+        self.file.write_all(&line).await?;
+        self.file.sync_data().await?;
+        self.operations += 1;
+        Ok(())
+    }
+
+    fn needs_compaction(&self) -> bool {
+        self.operations >= COMPACTION_MIN_OPERATIONS && self.operations >= self.compacted * 2
+    }
+
+    /// Replaces the journal with the operations that recreate the current
+    /// state of the given store.
+    async fn compact(&mut self, store: &MemoryDataStore) -> std::io::Result<()> {
+        let operations = store.journal_operations().await;
+
+        // Write to a temporary file first so a partially written journal is never replayed
+        let temp_path = self.path.with_extension("jsonl.tmp");
+        let mut writer = BufWriter::new(File::create(&temp_path).await?);
+        for operation in &operations {
+            let mut line = serde_json::to_vec(operation)?;
+            line.push(b'\n');
+            writer.write_all(&line).await?;
+        }
+        writer.flush().await?;
+        writer.get_ref().sync_data().await?;
+        tokio::fs::rename(&temp_path, &self.path).await?;
+
+        tracing::debug!(
+            "compacted journal `{path}` from {before} to {after} operations",
+            path = self.path.display(),
+            before = self.operations,
+            after = operations.len()
+        );
+
+        self.file = OpenOptions::new().append(true).open(&self.path).await?;
+        self.operations = operations.len();
+        self.compacted = operations.len();
+        Ok(())
+    }
+}
+
+impl FileSystemDataStore {
+    /// Opens the data store in the given directory.
+    ///
+    /// The directory is created if it does not exist.
+    pub async fn open(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        tokio::fs::create_dir_all(&dir).await.with_context(|| {
+            format!(
+                "failed to create data store directory `{path}`",
+                path = dir.display()
+            )
+        })?;
+
+        let path = dir.join(JOURNAL_FILE_NAME);
+        let memory = MemoryDataStore::new();
+        let (len, operations) = replay(&path, &memory).await?;
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .await
+            .with_context(|| format!("failed to open journal `{path}`", path = path.display()))?;
+
+        // Discard any incomplete operation at the end of the journal
+        file.set_len(len).await.with_context(|| {
+            format!("failed to truncate journal `{path}`", path = path.display())
+        })?;
+
+        file.seek(SeekFrom::End(0))
+            .await
+            .with_context(|| format!("failed to seek journal `{path}`", path = path.display()))?;
+
+        let mut journal = Journal {
+            file,
+            path,
+            operations,
+            compacted: 0,
+        };
+
+        if journal.needs_compaction() {
+            journal.compact(&memory).await.with_context(|| {
+                format!(
+                    "failed to compact journal `{path}`",
+                    path = journal.path.display()
+                )
+            })?;
+        }
+
+        Ok(Self {
+            memory,
+            journal: Mutex::new(journal),
+            dir,
+        })
+    }
+}
+
+/// Replays the journal at the given path into the given store.
+///
+/// Returns the length of the journal up to the last complete operation and
+/// the number of operations replayed.
+async fn replay(path: &Path, store: &MemoryDataStore) -> Result<(u64, usize)> {
+    let file = match File::open(path).await {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((0, 0)),
+        Err(e) => {
+            return Err(e)
+                .with_context(|| format!("failed to open journal `{path}`", path = path.display()))
+        }
+    };
+
+    let mut reader = BufReader::new(file);
+    let mut line = Vec::new();
+    let mut len = 0;
+    let mut operations = 0;
+    loop {
+        line.clear();
+        let read = reader
+            .read_until(b'\n', &mut line)
+            .await
+            .with_context(|| format!("failed to read journal `{path}`", path = path.display()))?;
+        if read == 0 {
+            break;
+        }
+
+        // An operation without a terminating newline was not completely written
+        if !line.ends_with(b"\n") {
+            tracing::warn!(
+                "discarding incomplete operation at the end of journal `{path}`",
+                path = path.display()
+            );
+            break;
+        }
+
+        let operation: Operation = serde_json::from_slice(&line).with_context(|| {
+            format!(
+                "failed to parse operation at offset {len} of journal `{path}`",
+                path = path.display()
+            )
+        })?;
+
+        // Operations that failed when first applied fail the same way on replay
+        if let Err(e) = operation.apply(store).await {
+            tracing::debug!("replayed operation failed: {e}");
+        }
+
+        len += read as u64;
+        operations += 1;
+    }
+
+    Ok((len, operations))
+}
+
+#[axum::async_trait]
+impl DataStore for FileSystemDataStore {
+    async fn get_all_checkpoints(
+        &self,
+    ) -> Result<
+        Pin<Box<dyn Stream<Item = Result<TimestampedCheckpoint, DataStoreError>> + Send>>,
+        DataStoreError,
+    > {
+        self.memory.get_all_checkpoints().await
+    }
+
+    async fn get_validated_records(
+        &self,
+        start: RegistryIndex,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<LogLeaf, DataStoreError>> + Send>>, DataStoreError>
+    {
+        self.memory.get_validated_records(start).await
+    }
+
+    async fn get_log_leafs_with_registry_index(
+        &self,
+        entries: &[RegistryIndex],
+    ) -> Result<Vec<LogLeaf>, DataStoreError> {
+        self.memory.get_log_leafs_with_registry_index(entries).await
+    }
+
+    async fn store_operator_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        record: &ProtoEnvelope<operator::OperatorRecord>,
+    ) -> Result<(), DataStoreError> {
+        let mut journal = self.journal.lock().await;
+        journal
+            .append(
+                &self.memory,
+                &Operation::StoreOperatorRecord {
+                    log_id: log_id.clone(),
+                    record_id: record_id.clone(),
+                    record: record.clone().into(),
+                },
+            )
+            .await?;
+
+        self.memory
+            .store_operator_record(log_id, record_id, record)
+            .await
+    }
+
+    async fn reject_operator_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        reason: &str,
+    ) -> Result<(), DataStoreError> {
+        let mut journal = self.journal.lock().await;
+        journal
+            .append(
+                &self.memory,
+                &Operation::RejectOperatorRecord {
+                    log_id: log_id.clone(),
+                    record_id: record_id.clone(),
+                    reason: reason.to_string(),
+                },
+            )
+            .await?;
+
+        self.memory
+            .reject_operator_record(log_id, record_id, reason)
+            .await
+    }
+
+    async fn commit_operator_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        registry_index: RegistryIndex,
+    ) -> Result<(), DataStoreError> {
+        let mut journal = self.journal.lock().await;
+        journal
+            .append(
+                &self.memory,
+                &Operation::CommitOperatorRecord {
+                    log_id: log_id.clone(),
+                    record_id: record_id.clone(),
+                    registry_index,
+                },
+            )
+            .await?;
+
+        self.memory
+            .commit_operator_record(log_id, record_id, registry_index)
+            .await
+    }
+
+    async fn store_package_record(
+        &self,
+        log_id: &LogId,
+        package_id: &PackageId,
+        record_id: &RecordId,
+        record: &ProtoEnvelope<package::PackageRecord>,
+        missing: &HashSet<&AnyHash>,
+    ) -> Result<(), DataStoreError> {
+        let received = SystemTime::now();
+        let mut journal = self.journal.lock().await;
+        journal
+            .append(
+                &self.memory,
+                &Operation::StorePackageRecord {
+                    log_id: log_id.clone(),
+                    package_id: package_id.clone(),
+                    record_id: record_id.clone(),
+                    record: record.clone().into(),
+                    missing: missing.iter().map(|&d| d.clone()).collect(),
+                    received: Some(received),
+                },
+            )
+            .await?;
+
+        self.memory
+            .store_package_record_received_at(
+                log_id, package_id, record_id, record, missing, received,
+            )
+            .await
+    }
+
+    async fn reject_package_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        reason: &str,
+    ) -> Result<(), DataStoreError> {
+        let mut journal = self.journal.lock().await;
+        journal
+            .append(
+                &self.memory,
+                &Operation::RejectPackageRecord {
+                    log_id: log_id.clone(),
+                    record_id: record_id.clone(),
+                    reason: reason.to_string(),
+                },
+            )
+            .await?;
+
+        self.memory
+            .reject_package_record(log_id, record_id, reason)
+            .await
+    }
+
+    async fn get_pending_package_records(
+        &self,
+        submitted_before: SystemTime,
+    ) -> Result<Vec<LogLeaf>, DataStoreError> {
+        self.memory
+            .get_pending_package_records(submitted_before)
+            .await
+    }
+
+    async fn get_ready_package_records(&self) -> Result<Vec<LogLeaf>, DataStoreError> {
+        self.memory.get_ready_package_records().await
+    }
+
+    async fn commit_package_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        registry_index: RegistryIndex,
+    ) -> Result<(), DataStoreError> {
+        let mut journal = self.journal.lock().await;
+        journal
+            .append(
+                &self.memory,
+                &Operation::CommitPackageRecord {
+                    log_id: log_id.clone(),
+                    record_id: record_id.clone(),
+                    registry_index,
+                },
+            )
+            .await?;
+
+        self.memory
+            .commit_package_record(log_id, record_id, registry_index)
+            .await
+    }
+
+    async fn commit_package_records(
+        &self,
+        leafs: &[LogLeaf],
+        registry_index: RegistryIndex,
+    ) -> Result<Vec<Result<(), DataStoreError>>, DataStoreError> {
+        let mut journal = self.journal.lock().await;
+        journal
+            .append(
+                &self.memory,
+                &Operation::CommitPackageRecords {
+                    leafs: leafs.to_vec(),
+                    registry_index,
+                },
+            )
+            .await?;
+
+        self.memory
+            .commit_package_records(leafs, registry_index)
+            .await
+    }
+
+    async fn is_content_missing(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        digest: &AnyHash,
+    ) -> Result<bool, DataStoreError> {
+        self.memory
+            .is_content_missing(log_id, record_id, digest)
+            .await
+    }
+
+    async fn set_content_present(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        digest: &AnyHash,
+        size: u64,
+        quota: Option<u64>,
+    ) -> Result<bool, DataStoreError> {
+        // Every update holds the journal lock, so the quota cannot change
+        // between the check and the update
+        let mut journal = self.journal.lock().await;
+        if let Some(quota) = quota {
+            self.memory
+                .check_namespace_quota(log_id, digest, size, quota)
+                .await?;
+        }
+
+        journal
+            .append(
+                &self.memory,
+                &Operation::SetContentPresent {
+                    log_id: log_id.clone(),
+                    record_id: record_id.clone(),
+                    digest: digest.clone(),
+                    size,
+                },
+            )
+            .await?;
+
+        self.memory
+            .set_content_present(log_id, record_id, digest, size, None)
+            .await
+    }
+
+    async fn get_namespace_content_size(&self, namespace: &str) -> Result<u64, DataStoreError> {
+        self.memory.get_namespace_content_size(namespace).await
+    }
+
+    async fn get_referenced_content(
+        &self,
+        pending_since: SystemTime,
+    ) -> Result<HashSet<AnyHash>, DataStoreError> {
+        self.memory.get_referenced_content(pending_since).await
+    }
+
+    async fn get_operator_log_state(
+        &self,
+        log_id: &LogId,
+    ) -> Result<operator::LogState, DataStoreError> {
+        self.memory.get_operator_log_state(log_id).await
+    }
+
+    async fn get_package_log_state(
+        &self,
+        log_id: &LogId,
+    ) -> Result<package::LogState, DataStoreError> {
+        self.memory.get_package_log_state(log_id).await
+    }
+
+    async fn get_package_id(&self, log_id: &LogId) -> Result<PackageId, DataStoreError> {
+        self.memory.get_package_id(log_id).await
+    }
+
+    async fn get_package_ids(
+        &self,
+        namespace: Option<&str>,
+        since: Option<&PackageId>,
+        limit: u16,
+    ) -> Result<Vec<PackageId>, DataStoreError> {
+        self.memory.get_package_ids(namespace, since, limit).await
+    }
+
+    async fn store_checkpoint(
+        &self,
+        checkpoint_id: &AnyHash,
+        ts_checkpoint: SerdeEnvelope<TimestampedCheckpoint>,
+    ) -> Result<(), DataStoreError> {
+        let mut journal = self.journal.lock().await;
+        journal
+            .append(
+                &self.memory,
+                &Operation::StoreCheckpoint {
+                    checkpoint_id: checkpoint_id.clone(),
+                    checkpoint: ts_checkpoint.clone(),
+                },
+            )
+            .await?;
+
+        self.memory
+            .store_checkpoint(checkpoint_id, ts_checkpoint)
+            .await
+    }
+
+    async fn store_snapshot(&self, snapshot: &TransparencySnapshot) -> Result<(), DataStoreError> {
+        let contents = serde_json::to_vec(snapshot).map_err(std::io::Error::from)?;
+
+        // Write to a temporary file first so a partially written snapshot is never loaded
+        let path = self.dir.join(SNAPSHOT_FILE_NAME);
+        let temp_path = path.with_extension("json.tmp");
+        let mut file = File::create(&temp_path)
+            .await
+            .map_err(DataStoreError::Snapshot)?;
+        file.write_all(&contents)
+            .await
+            .map_err(DataStoreError::Snapshot)?;
+        file.sync_data().await.map_err(DataStoreError::Snapshot)?;
+        tokio::fs::rename(&temp_path, &path)
+            .await
+            .map_err(DataStoreError::Snapshot)
+    }
+
+    async fn get_latest_snapshot(&self) -> Result<Option<TransparencySnapshot>, DataStoreError> {
+        let contents = match tokio::fs::read(self.dir.join(SNAPSHOT_FILE_NAME)).await {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(DataStoreError::Snapshot(e)),
+        };
+
+        serde_json::from_slice(&contents)
+            .map(Some)
+            .map_err(|e| DataStoreError::Snapshot(e.into()))
+    }
+
+    async fn get_latest_checkpoint(
+        &self,
+    ) -> Result<SerdeEnvelope<TimestampedCheckpoint>, DataStoreError> {
+        self.memory.get_latest_checkpoint().await
+    }
+
+    async fn get_checkpoint(
+        &self,
+        log_length: RegistryLen,
+    ) -> Result<SerdeEnvelope<TimestampedCheckpoint>, DataStoreError> {
+        self.memory.get_checkpoint(log_length).await
+    }
+
+    async fn store_cosignature(
+        &self,
+        cosignature: SerdeEnvelope<WitnessedCheckpoint>,
+    ) -> Result<(), DataStoreError> {
+        let mut journal = self.journal.lock().await;
+        journal
+            .append(
+                &self.memory,
+                &Operation::StoreCosignature {
+                    cosignature: cosignature.clone(),
+                },
+            )
+            .await?;
+
+        self.memory.store_cosignature(cosignature).await
+    }
+
+    async fn get_cosignatures(
+        &self,
+        log_length: RegistryLen,
+    ) -> Result<Vec<SerdeEnvelope<WitnessedCheckpoint>>, DataStoreError> {
+        self.memory.get_cosignatures(log_length).await
+    }
+
+    async fn get_latest_cosignatures(
+        &self,
+    ) -> Result<Vec<SerdeEnvelope<WitnessedCheckpoint>>, DataStoreError> {
+        self.memory.get_latest_cosignatures().await
+    }
+
+    async fn get_operator_records(
+        &self,
+        log_id: &LogId,
+        registry_log_length: RegistryLen,
+        since: Option<&RecordId>,
+        limit: u16,
+    ) -> Result<Vec<PublishedProtoEnvelope<operator::OperatorRecord>>, DataStoreError> {
+        self.memory
+            .get_operator_records(log_id, registry_log_length, since, limit)
+            .await
+    }
+
+    async fn get_package_records(
+        &self,
+        log_id: &LogId,
+        registry_log_length: RegistryLen,
+        since: Option<&RecordId>,
+        limit: u16,
+    ) -> Result<Vec<PublishedProtoEnvelope<package::PackageRecord>>, DataStoreError> {
+        self.memory
+            .get_package_records(log_id, registry_log_length, since, limit)
+            .await
+    }
+
+    async fn get_operator_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<Record<operator::OperatorRecord>, DataStoreError> {
+        self.memory.get_operator_record(log_id, record_id).await
+    }
+
+    async fn get_package_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<Record<package::PackageRecord>, DataStoreError> {
+        self.memory.get_package_record(log_id, record_id).await
+    }
+
+    async fn verify_package_record_signature(
+        &self,
+        log_id: &LogId,
+        record: &ProtoEnvelope<package::PackageRecord>,
+    ) -> Result<(), DataStoreError> {
+        self.memory
+            .verify_package_record_signature(log_id, record)
+            .await
+    }
+
+    #[cfg(feature = "debug")]
+    async fn debug_list_package_ids(&self) -> anyhow::Result<Vec<PackageId>> {
+        self.memory.debug_list_package_ids().await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::datastore::RecordStatus;
+    use std::time::Duration;
+    use warg_crypto::{
+        hash::{HashAlgorithm, Sha256},
+        signing::generate_p256_pair,
+    };
+    use warg_protocol::registry::Checkpoint;
+
+    fn checkpoint(log_length: RegistryLen) -> (AnyHash, SerdeEnvelope<TimestampedCheckpoint>) {
+        let (_, key) = generate_p256_pair();
+        let checkpoint = SerdeEnvelope::signed_contents(
+            &key,
+            TimestampedCheckpoint::now(Checkpoint {
+                log_length,
+                log_root: HashAlgorithm::Sha256.digest(&[0]),
+                map_root: HashAlgorithm::Sha256.digest(&[1]),
+            })
+            .unwrap(),
+        )
+        .unwrap();
+
+        (
+            HashAlgorithm::Sha256.digest(&[log_length as u8]),
+            checkpoint,
+        )
+    }
+
+    fn package_record(
+        name: &str,
+        content: &AnyHash,
+        timestamp: SystemTime,
+    ) -> (
+        LogId,
+        PackageId,
+        RecordId,
+        ProtoEnvelope<package::PackageRecord>,
+    ) {
+        let (public_key, signing_key) = generate_p256_pair();
+        let package_id = PackageId::new(format!("test:{name}")).unwrap();
+        let record = ProtoEnvelope::signed_contents(
+            &signing_key,
+            package::PackageRecord {
+                prev: None,
+                version: 0,
+                timestamp,
+                entries: vec![
+                    package::PackageEntry::Init {
+                        hash_algorithm: HashAlgorithm::Sha256,
+                        key: public_key,
+                    },
+                    package::PackageEntry::Release {
+                        version: "1.0.0".parse().unwrap(),
+                        content: content.clone(),
+                    },
+                ],
+            },
+        )
+        .unwrap();
+
+        (
+            LogId::package_log::<Sha256>(&package_id),
+            package_id,
+            RecordId::package_record::<Sha256>(&record),
+            record,
+        )
+    }
+
+    #[tokio::test]
+    async fn it_replays_the_journal() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let store = FileSystemDataStore::open(dir.path()).await.unwrap();
+        for log_length in 1..=2 {
+            let (id, checkpoint) = checkpoint(log_length);
+            store.store_checkpoint(&id, checkpoint).await.unwrap();
+        }
+        drop(store);
+
+        // Simulate an operation that was interrupted while being written
+        let path = dir.path().join(JOURNAL_FILE_NAME);
+        let len = std::fs::metadata(&path).unwrap().len();
+        let mut contents = std::fs::read(&path).unwrap();
+        contents.extend_from_slice(br#"{"op":"storeCheckpoint","#);
+        std::fs::write(&path, contents).unwrap();
+
+        let store = FileSystemDataStore::open(dir.path()).await.unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), len);
+        assert_eq!(
+            store
+                .get_latest_checkpoint()
+                .await
+                .unwrap()
+                .as_ref()
+                .checkpoint
+                .log_length,
+            2
+        );
+
+        let (id, checkpoint) = checkpoint(3);
+        store.store_checkpoint(&id, checkpoint).await.unwrap();
+        drop(store);
+
+        let store = FileSystemDataStore::open(dir.path()).await.unwrap();
+        assert_eq!(
+            store
+                .get_latest_checkpoint()
+                .await
+                .unwrap()
+                .as_ref()
+                .checkpoint
+                .log_length,
+            3
+        );
+    }
+
+    #[tokio::test]
+    async fn it_persists_when_records_were_received() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileSystemDataStore::open(dir.path()).await.unwrap();
+
+        // The record's timestamp is not when it was received
+        let (public_key, signing_key) = generate_p256_pair();
+        let package_id = PackageId::new("test:pending").unwrap();
+        let digest = HashAlgorithm::Sha256.digest(b"missing");
+        let record = ProtoEnvelope::signed_contents(
+            &signing_key,
+            package::PackageRecord {
+                prev: None,
+                version: 0,
+                timestamp: SystemTime::now() - Duration::from_secs(60 * 60),
+                entries: vec![
+                    package::PackageEntry::Init {
+                        hash_algorithm: HashAlgorithm::Sha256,
+                        key: public_key,
+                    },
+                    package::PackageEntry::Release {
+                        version: "1.0.0".parse().unwrap(),
+                        content: digest.clone(),
+                    },
+                ],
+            },
+        )
+        .unwrap();
+        let log_id = LogId::package_log::<Sha256>(&package_id);
+        let record_id = RecordId::package_record::<Sha256>(&record);
+        store
+            .store_package_record(
+                &log_id,
+                &package_id,
+                &record_id,
+                &record,
+                &HashSet::from([&digest]),
+            )
+            .await
+            .unwrap();
+        let received = SystemTime::now();
+        drop(store);
+
+        let store = FileSystemDataStore::open(dir.path()).await.unwrap();
+        assert!(store
+            .get_pending_package_records(received - Duration::from_secs(60))
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            store
+                .get_pending_package_records(received + Duration::from_secs(1))
+                .await
+                .unwrap(),
+            vec![LogLeaf { log_id, record_id }]
+        );
+    }
+
+    #[tokio::test]
+    async fn it_does_not_journal_content_over_the_quota() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileSystemDataStore::open(dir.path()).await.unwrap();
+
+        let (public_key, signing_key) = generate_p256_pair();
+        let package_id = PackageId::new("test:quota").unwrap();
+        let digest = HashAlgorithm::Sha256.digest(b"content");
+        let record = ProtoEnvelope::signed_contents(
+            &signing_key,
+            package::PackageRecord {
+                prev: None,
+                version: 0,
+                timestamp: SystemTime::now(),
+                entries: vec![
+                    package::PackageEntry::Init {
+                        hash_algorithm: HashAlgorithm::Sha256,
+                        key: public_key,
+                    },
+                    package::PackageEntry::Release {
+                        version: "1.0.0".parse().unwrap(),
+                        content: digest.clone(),
+                    },
+                ],
+            },
+        )
+        .unwrap();
+        let log_id = LogId::package_log::<Sha256>(&package_id);
+        let record_id = RecordId::package_record::<Sha256>(&record);
+        store
+            .store_package_record(
+                &log_id,
+                &package_id,
+                &record_id,
+                &record,
+                &HashSet::from([&digest]),
+            )
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            store
+                .set_content_present(&log_id, &record_id, &digest, 11, Some(10))
+                .await
+                .unwrap_err(),
+            DataStoreError::QuotaExceeded { .. }
+        ));
+        drop(store);
+
+        let store = FileSystemDataStore::open(dir.path()).await.unwrap();
+        assert!(store
+            .is_content_missing(&log_id, &record_id, &digest)
+            .await
+            .unwrap());
+        assert!(store
+            .set_content_present(&log_id, &record_id, &digest, 10, Some(10))
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn it_compacts_the_journal() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileSystemDataStore::open(dir.path()).await.unwrap();
+
+        // A published record with its content present
+        let published_content = HashAlgorithm::Sha256.digest(b"published");
+        let (published_log_id, package_id, published_id, record) =
+            package_record("published", &published_content, SystemTime::now());
+        store
+            .store_package_record(
+                &published_log_id,
+                &package_id,
+                &published_id,
+                &record,
+                &HashSet::from([&published_content]),
+            )
+            .await
+            .unwrap();
+        assert!(store
+            .set_content_present(
+                &published_log_id,
+                &published_id,
+                &published_content,
+                5,
+                None
+            )
+            .await
+            .unwrap());
+        store
+            .commit_package_record(&published_log_id, &published_id, 1)
+            .await
+            .unwrap();
+
+        // A pending record that was received after its timestamp
+        let missing = HashAlgorithm::Sha256.digest(b"missing");
+        let (pending_log_id, package_id, pending_id, record) = package_record(
+            "pending",
+            &missing,
+            SystemTime::now() - Duration::from_secs(60 * 60),
+        );
+        store
+            .store_package_record(
+                &pending_log_id,
+                &package_id,
+                &pending_id,
+                &record,
+                &HashSet::from([&missing]),
+            )
+            .await
+            .unwrap();
+        let received = SystemTime::now();
+
+        // A rejected record
+        let (rejected_log_id, package_id, rejected_id, record) = package_record(
+            "rejected",
+            &HashAlgorithm::Sha256.digest(b"rejected"),
+            SystemTime::now(),
+        );
+        store
+            .store_package_record(
+                &rejected_log_id,
+                &package_id,
+                &rejected_id,
+                &record,
+                &HashSet::new(),
+            )
+            .await
+            .unwrap();
+        store
+            .reject_package_record(&rejected_log_id, &rejected_id, "rejected")
+            .await
+            .unwrap();
+
+        // Storing a checkpoint again replaces it
+        let (id, checkpoint) = checkpoint(2);
+        store
+            .store_checkpoint(&id, checkpoint.clone())
+            .await
+            .unwrap();
+        store.store_checkpoint(&id, checkpoint).await.unwrap();
+
+        {
+            let mut journal = store.journal.lock().await;
+            assert_eq!(journal.operations, 8);
+            journal.compact(&store.memory).await.unwrap();
+            assert_eq!(journal.operations, 7);
+        }
+        drop(store);
+
+        let path = dir.path().join(JOURNAL_FILE_NAME);
+        assert_eq!(
+            std::fs::read(path)
+                .unwrap()
+                .iter()
+                .filter(|b| **b == b'\n')
+                .count(),
+            7
+        );
+
+        let store = FileSystemDataStore::open(dir.path()).await.unwrap();
+        let record = store
+            .get_package_record(&published_log_id, &published_id)
+            .await
+            .unwrap();
+        assert!(matches!(record.status, RecordStatus::Published));
+        assert_eq!(record.registry_index, Some(1));
+        assert_eq!(store.get_namespace_content_size("test").await.unwrap(), 5);
+
+        assert!(store
+            .get_pending_package_records(received - Duration::from_secs(60))
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            store
+                .get_pending_package_records(received + Duration::from_secs(1))
+                .await
+                .unwrap(),
+            vec![LogLeaf {
+                log_id: pending_log_id,
+                record_id: pending_id,
+            }]
+        );
+
+        let record = store
+            .get_package_record(&rejected_log_id, &rejected_id)
+            .await
+            .unwrap();
+        assert!(matches!(record.status, RecordStatus::Rejected(reason) if reason == "rejected"));
+
+        assert_eq!(
+            store
+                .get_latest_checkpoint()
+                .await
+                .unwrap()
+                .as_ref()
+                .checkpoint
+                .log_length,
+            2
+        );
+    }
+}
@@ -1,18 +1,25 @@
-use super::{DataStore, DataStoreError};
+use super::{DataStore, DataStoreError, TransparencySnapshot};
 use futures::Stream;
 use indexmap::IndexMap;
 use std::{
     collections::{BTreeSet, HashMap, HashSet},
+    ops::Bound,
     pin::Pin,
     sync::Arc,
+    time::SystemTime,
 };
 use tokio::sync::RwLock;
-use warg_crypto::{hash::AnyHash, Signable};
+use warg_crypto::{
+    hash::{AnyHash, Sha256},
+    signing::KeyID,
+    Signable,
+};
 use warg_protocol::{
     operator,
     package::{self, PackageEntry},
     registry::{
         LogId, LogLeaf, PackageId, RecordId, RegistryIndex, RegistryLen, TimestampedCheckpoint,
+        WitnessedCheckpoint,
     },
     ProtoEnvelope, PublishedProtoEnvelope, SerdeEnvelope,
 };
@@ -53,6 +60,8 @@ enum PendingRecord {
     Package {
         record: Option<ProtoEnvelope<package::PackageRecord>>,
         missing: HashSet<AnyHash>,
+        /// When the record was received by the registry.
+        received: SystemTime,
     },
 }
 
@@ -78,9 +87,191 @@ struct State {
     operators: HashMap<LogId, Log<operator::LogState, operator::OperatorRecord>>,
     packages: HashMap<LogId, Log<package::LogState, package::PackageRecord>>,
     package_ids: BTreeSet<PackageId>,
+    log_package_ids: HashMap<LogId, PackageId>,
+    content_sizes: HashMap<AnyHash, u64>,
     checkpoints: IndexMap<RegistryLen, SerdeEnvelope<TimestampedCheckpoint>>,
+    cosignatures: HashMap<RegistryLen, IndexMap<KeyID, SerdeEnvelope<WitnessedCheckpoint>>>,
     records: HashMap<LogId, HashMap<RecordId, RecordStatus>>,
     log_leafs: HashMap<RegistryIndex, LogLeaf>,
+    snapshot: Option<TransparencySnapshot>,
+}
+
+impl State {
+    fn is_content_missing(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        digest: &AnyHash,
+    ) -> Result<bool, DataStoreError> {
+        let log = self
+            .records
+            .get(log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+
+        let status = log
+            .get(record_id)
+            .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?;
+
+        match status {
+            RecordStatus::Pending(PendingRecord::Operator { .. }) => {
+                // Operator records have no content
+                Ok(false)
+            }
+            RecordStatus::Pending(PendingRecord::Package { missing, .. }) => {
+                Ok(missing.contains(digest))
+            }
+            _ => Err(DataStoreError::RecordNotPending(record_id.clone())),
+        }
+    }
+
+    /// Gets the distinct content that is present and referenced by the
+    /// validated or pending records of packages in the given namespace.
+    fn namespace_content(&self, namespace: &str) -> HashSet<&AnyHash> {
+        use warg_protocol::Record as _;
+
+        let mut content = HashSet::new();
+        for (log_id, package_id) in &self.log_package_ids {
+            if package_id.namespace() != namespace {
+                continue;
+            }
+
+            for status in self
+                .records
+                .get(log_id)
+                .into_iter()
+                .flat_map(|r| r.values())
+            {
+                match status {
+                    RecordStatus::Pending(PendingRecord::Package {
+                        record: Some(record),
+                        missing,
+                        ..
+                    }) => content.extend(
+                        record
+                            .as_ref()
+                            .contents()
+                            .into_iter()
+                            .filter(|digest| !missing.contains(*digest)),
+                    ),
+                    RecordStatus::Validated(Record { index, .. }) => {
+                        if let Some(log) = self.packages.get(log_id) {
+                            content.extend(log.entries[*index].record_content.as_ref().contents());
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        content
+    }
+
+    fn namespace_content_size(&self, content: &HashSet<&AnyHash>) -> u64 {
+        content
+            .iter()
+            .filter_map(|digest| self.content_sizes.get(*digest))
+            .sum()
+    }
+
+    /// Checks that the given content would not exceed the storage quota of
+    /// the namespace of the given package log.
+    fn check_namespace_quota(
+        &self,
+        log_id: &LogId,
+        digest: &AnyHash,
+        size: u64,
+        quota: u64,
+    ) -> Result<(), DataStoreError> {
+        let Some(package_id) = self.log_package_ids.get(log_id) else {
+            return Ok(());
+        };
+
+        let namespace = package_id.namespace();
+        let content = self.namespace_content(namespace);
+        if content.contains(digest) {
+            // The content is already counted towards the quota
+            return Ok(());
+        }
+
+        if self.namespace_content_size(&content).saturating_add(size) > quota {
+            return Err(DataStoreError::QuotaExceeded {
+                namespace: namespace.to_string(),
+                quota,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn commit_package_record(
+        &mut self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        registry_index: RegistryIndex,
+    ) -> Result<(), DataStoreError> {
+        let Self {
+            operators,
+            packages,
+            log_package_ids,
+            records,
+            log_leafs,
+            ..
+        } = self;
+
+        let status = records
+            .get_mut(log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?
+            .get_mut(record_id)
+            .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?;
+
+        match status {
+            RecordStatus::Pending(PendingRecord::Package { record, .. }) => {
+                let record = record.take().unwrap();
+                let package_id = log_package_ids
+                    .get(log_id)
+                    .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+                let default_operator = operator::LogState::default();
+                let operator = operators
+                    .get(&LogId::operator_log::<Sha256>())
+                    .map(|log| &log.validator)
+                    .unwrap_or(&default_operator);
+                let log = packages.entry(log_id.clone()).or_default();
+                match log
+                    .validator
+                    .validate_with_operator(package_id, operator, &record)
+                    .map_err(DataStoreError::from)
+                {
+                    Ok(_) => {
+                        let index = log.entries.len();
+                        log.entries.push(Entry {
+                            registry_index,
+                            record_content: record,
+                        });
+                        *status = RecordStatus::Validated(Record {
+                            index,
+                            registry_index,
+                        });
+                        log_leafs.insert(
+                            registry_index,
+                            LogLeaf {
+                                log_id: log_id.clone(),
+                                record_id: record_id.clone(),
+                            },
+                        );
+                        Ok(())
+                    }
+                    Err(e) => {
+                        *status = RecordStatus::Rejected(RejectedRecord::Package {
+                            record,
+                            reason: e.to_string(),
+                        });
+                        Err(e)
+                    }
+                }
+            }
+            _ => Err(DataStoreError::RecordNotPending(record_id.clone())),
+        }
+    }
 }
 
 /// Represents an in-memory data store.
@@ -95,6 +286,200 @@ impl MemoryDataStore {
     pub fn new() -> Self {
         Self(Arc::new(RwLock::new(State::default())))
     }
+
+    /// Stores the given package record as received by the registry at the
+    /// given time.
+    ///
+    /// This is used to restore records with their original receipt time.
+    pub(crate) async fn store_package_record_received_at(
+        &self,
+        log_id: &LogId,
+        package_id: &PackageId,
+        record_id: &RecordId,
+        record: &ProtoEnvelope<package::PackageRecord>,
+        missing: &HashSet<&AnyHash>,
+        received: SystemTime,
+    ) -> Result<(), DataStoreError> {
+        // Ensure the set of missing hashes is a subset of the record contents.
+        debug_assert!({
+            use warg_protocol::Record;
+            let contents = record.as_ref().contents();
+            missing.is_subset(&contents)
+        });
+
+        let mut state = self.0.write().await;
+        let prev = state.records.entry(log_id.clone()).or_default().insert(
+            record_id.clone(),
+            RecordStatus::Pending(PendingRecord::Package {
+                record: Some(record.clone()),
+                missing: missing.iter().map(|&d| d.clone()).collect(),
+                received,
+            }),
+        );
+        state.package_ids.insert(package_id.clone());
+        state
+            .log_package_ids
+            .insert(log_id.clone(), package_id.clone());
+
+        assert!(prev.is_none());
+        Ok(())
+    }
+
+    /// Checks that the given content would not exceed the storage quota of
+    /// the namespace of the given package log.
+    #[cfg(feature = "filesystem")]
+    pub(crate) async fn check_namespace_quota(
+        &self,
+        log_id: &LogId,
+        digest: &AnyHash,
+        size: u64,
+        quota: u64,
+    ) -> Result<(), DataStoreError> {
+        self.0
+            .read()
+            .await
+            .check_namespace_quota(log_id, digest, size, quota)
+    }
+
+    /// Sets the size of the given content.
+    #[cfg(feature = "filesystem")]
+    pub(crate) async fn set_content_size(&self, digest: &AnyHash, size: u64) {
+        self.0
+            .write()
+            .await
+            .content_sizes
+            .insert(digest.clone(), size);
+    }
+
+    /// Gets the journal operations that recreate the current state of the store.
+    ///
+    /// Validated records are committed in registry order so that each is
+    /// validated against the same state as when it was first committed.
+    #[cfg(feature = "filesystem")]
+    pub(super) async fn journal_operations(&self) -> Vec<super::filesystem::Operation> {
+        use super::filesystem::Operation;
+        use warg_crypto::hash::Hash;
+
+        let state = self.0.read().await;
+        let mut operations = Vec::new();
+
+        let mut registry_indexes = state.log_leafs.keys().copied().collect::<Vec<_>>();
+        registry_indexes.sort_unstable();
+        for registry_index in registry_indexes {
+            let LogLeaf { log_id, record_id } = &state.log_leafs[&registry_index];
+            let Some(RecordStatus::Validated(Record { index, .. })) = state
+                .records
+                .get(log_id)
+                .and_then(|records| records.get(record_id))
+            else {
+                continue;
+            };
+
+            if let Some(log) = state.operators.get(log_id) {
+                operations.push(Operation::StoreOperatorRecord {
+                    log_id: log_id.clone(),
+                    record_id: record_id.clone(),
+                    record: log.entries[*index].record_content.clone().into(),
+                });
+                operations.push(Operation::CommitOperatorRecord {
+                    log_id: log_id.clone(),
+                    record_id: record_id.clone(),
+                    registry_index,
+                });
+            } else if let Some(log) = state.packages.get(log_id) {
+                operations.push(Operation::StorePackageRecord {
+                    log_id: log_id.clone(),
+                    package_id: state.log_package_ids[log_id].clone(),
+                    record_id: record_id.clone(),
+                    record: log.entries[*index].record_content.clone().into(),
+                    missing: Vec::new(),
+                    received: None,
+                });
+                operations.push(Operation::CommitPackageRecord {
+                    log_id: log_id.clone(),
+                    record_id: record_id.clone(),
+                    registry_index,
+                });
+            }
+        }
+
+        for (log_id, records) in &state.records {
+            for (record_id, status) in records {
+                match status {
+                    RecordStatus::Pending(PendingRecord::Operator {
+                        record: Some(record),
+                    }) => operations.push(Operation::StoreOperatorRecord {
+                        log_id: log_id.clone(),
+                        record_id: record_id.clone(),
+                        record: record.clone().into(),
+                    }),
+                    RecordStatus::Pending(PendingRecord::Package {
+                        record: Some(record),
+                        missing,
+                        received,
+                    }) => operations.push(Operation::StorePackageRecord {
+                        log_id: log_id.clone(),
+                        package_id: state.log_package_ids[log_id].clone(),
+                        record_id: record_id.clone(),
+                        record: record.clone().into(),
+                        missing: missing.iter().cloned().collect(),
+                        received: Some(*received),
+                    }),
+                    RecordStatus::Rejected(RejectedRecord::Operator { record, reason }) => {
+                        operations.push(Operation::StoreOperatorRecord {
+                            log_id: log_id.clone(),
+                            record_id: record_id.clone(),
+                            record: record.clone().into(),
+                        });
+                        operations.push(Operation::RejectOperatorRecord {
+                            log_id: log_id.clone(),
+                            record_id: record_id.clone(),
+                            reason: reason.clone(),
+                        });
+                    }
+                    RecordStatus::Rejected(RejectedRecord::Package { record, reason }) => {
+                        operations.push(Operation::StorePackageRecord {
+                            log_id: log_id.clone(),
+                            package_id: state.log_package_ids[log_id].clone(),
+                            record_id: record_id.clone(),
+                            record: record.clone().into(),
+                            missing: Vec::new(),
+                            received: None,
+                        });
+                        operations.push(Operation::RejectPackageRecord {
+                            log_id: log_id.clone(),
+                            record_id: record_id.clone(),
+                            reason: reason.clone(),
+                        });
+                    }
+                    // Records are only taken out of pending while the state is locked
+                    RecordStatus::Pending(_) | RecordStatus::Validated(_) => {}
+                }
+            }
+        }
+
+        operations.extend(state.content_sizes.iter().map(|(digest, size)| {
+            Operation::SetContentSize {
+                digest: digest.clone(),
+                size: *size,
+            }
+        }));
+
+        operations.extend(state.checkpoints.values().map(|checkpoint| {
+            Operation::StoreCheckpoint {
+                checkpoint_id: Hash::<Sha256>::of(&checkpoint.as_ref().checkpoint).into(),
+                checkpoint: checkpoint.clone(),
+            }
+        }));
+
+        operations.extend(state.cosignatures.values().flat_map(|c| c.values()).map(
+            |cosignature| Operation::StoreCosignature {
+                cosignature: cosignature.clone(),
+            },
+        ));
+
+        operations
+    }
 }
 
 impl Default for MemoryDataStore {
@@ -111,14 +496,35 @@ impl DataStore for MemoryDataStore {
         Pin<Box<dyn Stream<Item = Result<TimestampedCheckpoint, DataStoreError>> + Send>>,
         DataStoreError,
     > {
-        Ok(Box::pin(futures::stream::empty()))
+        let state = self.0.read().await;
+        let checkpoints = state
+            .checkpoints
+            .values()
+            .map(|checkpoint| Ok(checkpoint.as_ref().clone()))
+            .collect::<Vec<_>>();
+
+        Ok(Box::pin(futures::stream::iter(checkpoints)))
     }
 
-    async fn get_all_validated_records(
+    async fn get_validated_records(
         &self,
+        start: RegistryIndex,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<LogLeaf, DataStoreError>> + Send>>, DataStoreError>
     {
-        Ok(Box::pin(futures::stream::empty()))
+        let state = self.0.read().await;
+        let mut leafs = state
+            .log_leafs
+            .iter()
+            .filter(|(index, _)| **index >= start)
+            .collect::<Vec<_>>();
+        leafs.sort_by_key(|(index, _)| **index);
+
+        Ok(Box::pin(futures::stream::iter(
+            leafs
+                .into_iter()
+                .map(|(_, leaf)| Ok(leaf.clone()))
+                .collect::<Vec<_>>(),
+        )))
     }
 
     async fn get_log_leafs_with_registry_index(
@@ -254,25 +660,15 @@ impl DataStore for MemoryDataStore {
         record: &ProtoEnvelope<package::PackageRecord>,
         missing: &HashSet<&AnyHash>,
     ) -> Result<(), DataStoreError> {
-        // Ensure the set of missing hashes is a subset of the record contents.
-        debug_assert!({
-            use warg_protocol::Record;
-            let contents = record.as_ref().contents();
-            missing.is_subset(&contents)
-        });
-
-        let mut state = self.0.write().await;
-        let prev = state.records.entry(log_id.clone()).or_default().insert(
-            record_id.clone(),
-            RecordStatus::Pending(PendingRecord::Package {
-                record: Some(record.clone()),
-                missing: missing.iter().map(|&d| d.clone()).collect(),
-            }),
-        );
-        state.package_ids.insert(package_id.clone());
-
-        assert!(prev.is_none());
-        Ok(())
+        self.store_package_record_received_at(
+            log_id,
+            package_id,
+            record_id,
+            record,
+            missing,
+            SystemTime::now(),
+        )
+        .await
     }
 
     async fn reject_package_record(
@@ -303,66 +699,91 @@ impl DataStore for MemoryDataStore {
         Ok(())
     }
 
+    async fn get_pending_package_records(
+        &self,
+        submitted_before: SystemTime,
+    ) -> Result<Vec<LogLeaf>, DataStoreError> {
+        let state = self.0.read().await;
+        Ok(state
+            .records
+            .iter()
+            .flat_map(|(log_id, records)| {
+                records
+                    .iter()
+                    .filter_map(move |(record_id, status)| match status {
+                        RecordStatus::Pending(PendingRecord::Package {
+                            record: Some(_),
+                            missing,
+                            received,
+                        }) if !missing.is_empty() && *received < submitted_before => {
+                            Some(LogLeaf {
+                                log_id: log_id.clone(),
+                                record_id: record_id.clone(),
+                            })
+                        }
+                        _ => None,
+                    })
+            })
+            .collect())
+    }
+
+    async fn get_ready_package_records(&self) -> Result<Vec<LogLeaf>, DataStoreError> {
+        let state = self.0.read().await;
+        let mut records = state
+            .records
+            .iter()
+            .flat_map(|(log_id, records)| {
+                records
+                    .iter()
+                    .filter_map(move |(record_id, status)| match status {
+                        RecordStatus::Pending(PendingRecord::Package {
+                            record: Some(_),
+                            missing,
+                            received,
+                        }) if missing.is_empty() => Some((
+                            *received,
+                            LogLeaf {
+                                log_id: log_id.clone(),
+                                record_id: record_id.clone(),
+                            },
+                        )),
+                        _ => None,
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        records.sort_by_key(|(received, _)| *received);
+        Ok(records.into_iter().map(|(_, leaf)| leaf).collect())
+    }
+
     async fn commit_package_record(
         &self,
         log_id: &LogId,
         record_id: &RecordId,
         registry_index: RegistryIndex,
     ) -> Result<(), DataStoreError> {
-        let mut state = self.0.write().await;
-
-        let State {
-            packages,
-            records,
-            log_leafs,
-            ..
-        } = &mut *state;
-
-        let status = records
-            .get_mut(log_id)
-            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?
-            .get_mut(record_id)
-            .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?;
+        self.0
+            .write()
+            .await
+            .commit_package_record(log_id, record_id, registry_index)
+    }
 
-        match status {
-            RecordStatus::Pending(PendingRecord::Package { record, .. }) => {
-                let record = record.take().unwrap();
-                let log = packages.entry(log_id.clone()).or_default();
-                match log
-                    .validator
-                    .validate(&record)
-                    .map_err(DataStoreError::from)
-                {
-                    Ok(_) => {
-                        let index = log.entries.len();
-                        log.entries.push(Entry {
-                            registry_index,
-                            record_content: record,
-                        });
-                        *status = RecordStatus::Validated(Record {
-                            index,
-                            registry_index,
-                        });
-                        log_leafs.insert(
-                            registry_index,
-                            LogLeaf {
-                                log_id: log_id.clone(),
-                                record_id: record_id.clone(),
-                            },
-                        );
-                        Ok(())
-                    }
-                    Err(e) => {
-                        *status = RecordStatus::Rejected(RejectedRecord::Package {
-                            record,
-                            reason: e.to_string(),
-                        });
-                        Err(e)
-                    }
+    async fn commit_package_records(
+        &self,
+        leafs: &[LogLeaf],
+        mut registry_index: RegistryIndex,
+    ) -> Result<Vec<Result<(), DataStoreError>>, DataStoreError> {
+        let mut state = self.0.write().await;
+        Ok(leafs
+            .iter()
+            .map(|LogLeaf { log_id, record_id }| {
+                let res = state.commit_package_record(log_id, record_id, registry_index);
+                if res.is_ok() {
+                    registry_index += 1;
                 }
-            }
-            _ => Err(DataStoreError::RecordNotPending(record_id.clone())),
-        }
+                res
+            })
+            .collect())
     }
 
     async fn is_content_missing(
@@ -371,26 +792,10 @@ impl DataStore for MemoryDataStore {
         record_id: &RecordId,
         digest: &AnyHash,
     ) -> Result<bool, DataStoreError> {
-        let state = self.0.read().await;
-        let log = state
-            .records
-            .get(log_id)
-            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
-
-        let status = log
-            .get(record_id)
-            .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?;
-
-        match status {
-            RecordStatus::Pending(PendingRecord::Operator { .. }) => {
-                // Operator records have no content
-                Ok(false)
-            }
-            RecordStatus::Pending(PendingRecord::Package { missing, .. }) => {
-                Ok(missing.contains(digest))
-            }
-            _ => return Err(DataStoreError::RecordNotPending(record_id.clone())),
-        }
+        self.0
+            .read()
+            .await
+            .is_content_missing(log_id, record_id, digest)
     }
 
     async fn set_content_present(
@@ -398,35 +803,130 @@ impl DataStore for MemoryDataStore {
         log_id: &LogId,
         record_id: &RecordId,
         digest: &AnyHash,
+        size: u64,
+        quota: Option<u64>,
     ) -> Result<bool, DataStoreError> {
         let mut state = self.0.write().await;
-        let log = state
-            .records
-            .get_mut(log_id)
-            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
 
-        let status = log
-            .get_mut(record_id)
-            .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?;
+        if !state.is_content_missing(log_id, record_id, digest)? {
+            return Ok(false);
+        }
 
-        match status {
-            RecordStatus::Pending(PendingRecord::Operator { .. }) => {
-                // Operator records have no content, so conceptually already present
-                Ok(false)
-            }
-            RecordStatus::Pending(PendingRecord::Package { missing, .. }) => {
-                if missing.is_empty() {
-                    return Ok(false);
-                }
+        if let Some(quota) = quota {
+            state.check_namespace_quota(log_id, digest, size, quota)?;
+        }
 
-                // Return true if this was the last missing content
+        state.content_sizes.insert(digest.clone(), size);
+        match state
+            .records
+            .get_mut(log_id)
+            .and_then(|records| records.get_mut(record_id))
+        {
+            Some(RecordStatus::Pending(PendingRecord::Package { missing, .. })) => {
                 missing.remove(digest);
+
+                // Return true if this was the last missing content
                 Ok(missing.is_empty())
             }
-            _ => return Err(DataStoreError::RecordNotPending(record_id.clone())),
+            _ => unreachable!("the content should be missing"),
         }
     }
 
+    async fn get_namespace_content_size(&self, namespace: &str) -> Result<u64, DataStoreError> {
+        let state = self.0.read().await;
+        Ok(state.namespace_content_size(&state.namespace_content(namespace)))
+    }
+
+    async fn get_referenced_content(
+        &self,
+        pending_since: SystemTime,
+    ) -> Result<HashSet<AnyHash>, DataStoreError> {
+        use warg_protocol::Record;
+
+        let state = self.0.read().await;
+        let validated = state
+            .packages
+            .values()
+            .flat_map(|log| log.entries.iter())
+            .map(|entry| &entry.record_content);
+        let pending = state
+            .records
+            .values()
+            .flat_map(|records| records.values())
+            .filter_map(|status| match status {
+                RecordStatus::Pending(PendingRecord::Package {
+                    record: Some(record),
+                    received,
+                    ..
+                }) if *received >= pending_since => Some(record),
+                _ => None,
+            });
+
+        Ok(validated
+            .chain(pending)
+            .flat_map(|record| record.as_ref().contents())
+            .cloned()
+            .collect())
+    }
+
+    async fn get_operator_log_state(
+        &self,
+        log_id: &LogId,
+    ) -> Result<operator::LogState, DataStoreError> {
+        let state = self.0.read().await;
+        Ok(state
+            .operators
+            .get(log_id)
+            .map(|log| log.validator.clone())
+            .unwrap_or_default())
+    }
+
+    async fn get_package_log_state(
+        &self,
+        log_id: &LogId,
+    ) -> Result<package::LogState, DataStoreError> {
+        let state = self.0.read().await;
+        Ok(state
+            .packages
+            .get(log_id)
+            .map(|log| log.validator.clone())
+            .unwrap_or_default())
+    }
+
+    async fn get_package_id(&self, log_id: &LogId) -> Result<PackageId, DataStoreError> {
+        let state = self.0.read().await;
+        state
+            .log_package_ids
+            .get(log_id)
+            .cloned()
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))
+    }
+
+    async fn get_package_ids(
+        &self,
+        namespace: Option<&str>,
+        since: Option<&PackageId>,
+        limit: u16,
+    ) -> Result<Vec<PackageId>, DataStoreError> {
+        let state = self.0.read().await;
+        let start = since.map_or(Bound::Unbounded, |id| Bound::Excluded(id.clone()));
+
+        Ok(state
+            .package_ids
+            .range((start, Bound::Unbounded))
+            .filter(|id| namespace.map_or(true, |ns| id.namespace().starts_with(ns)))
+            .filter(|id| {
+                state
+                    .packages
+                    .get(&LogId::package_log::<Sha256>(id))
+                    .map(|log| !log.entries.is_empty())
+                    .unwrap_or(false)
+            })
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
     async fn store_checkpoint(
         &self,
         _checkpoint_id: &AnyHash,
@@ -441,12 +941,92 @@ impl DataStore for MemoryDataStore {
         Ok(())
     }
 
+    async fn store_snapshot(&self, snapshot: &TransparencySnapshot) -> Result<(), DataStoreError> {
+        self.0.write().await.snapshot = Some(snapshot.clone());
+        Ok(())
+    }
+
+    async fn get_latest_snapshot(&self) -> Result<Option<TransparencySnapshot>, DataStoreError> {
+        Ok(self.0.read().await.snapshot.clone())
+    }
+
     async fn get_latest_checkpoint(
         &self,
     ) -> Result<SerdeEnvelope<TimestampedCheckpoint>, DataStoreError> {
         let state = self.0.read().await;
-        let checkpoint = state.checkpoints.values().last().unwrap();
-        Ok(checkpoint.clone())
+        // A mirror has no checkpoint until it first replicates its upstream
+        state
+            .checkpoints
+            .values()
+            .last()
+            .cloned()
+            .ok_or(DataStoreError::CheckpointNotFound(0))
+    }
+
+    async fn get_checkpoint(
+        &self,
+        log_length: RegistryLen,
+    ) -> Result<SerdeEnvelope<TimestampedCheckpoint>, DataStoreError> {
+        let state = self.0.read().await;
+        state
+            .checkpoints
+            .get(&log_length)
+            .cloned()
+            .ok_or(DataStoreError::CheckpointNotFound(log_length))
+    }
+
+    async fn store_cosignature(
+        &self,
+        cosignature: SerdeEnvelope<WitnessedCheckpoint>,
+    ) -> Result<(), DataStoreError> {
+        let mut state = self.0.write().await;
+
+        state
+            .cosignatures
+            .entry(cosignature.as_ref().checkpoint.log_length)
+            .or_default()
+            .insert(cosignature.key_id().clone(), cosignature);
+
+        Ok(())
+    }
+
+    async fn get_cosignatures(
+        &self,
+        log_length: RegistryLen,
+    ) -> Result<Vec<SerdeEnvelope<WitnessedCheckpoint>>, DataStoreError> {
+        let state = self.0.read().await;
+        Ok(state
+            .cosignatures
+            .get(&log_length)
+            .map(|c| c.values().cloned().collect())
+            .unwrap_or_default())
+    }
+
+    async fn get_latest_cosignatures(
+        &self,
+    ) -> Result<Vec<SerdeEnvelope<WitnessedCheckpoint>>, DataStoreError> {
+        let state = self.0.read().await;
+        let mut latest: HashMap<&KeyID, &SerdeEnvelope<WitnessedCheckpoint>> = HashMap::new();
+        for cosignature in state.cosignatures.values().flat_map(|c| c.values()) {
+            let log_length = cosignature.as_ref().checkpoint.log_length;
+            latest
+                .entry(cosignature.key_id())
+                .and_modify(|c| {
+                    if c.as_ref().checkpoint.log_length < log_length {
+                        *c = cosignature;
+                    }
+                })
+                .or_insert(cosignature);
+        }
+
+        let mut latest = latest.into_values().cloned().collect::<Vec<_>>();
+        latest.sort_by(|a, b| {
+            b.as_ref()
+                .checkpoint
+                .log_length
+                .cmp(&a.as_ref().checkpoint.log_length)
+        });
+        Ok(latest)
     }
 
     async fn get_operator_records(
@@ -664,3 +1244,134 @@ impl DataStore for MemoryDataStore {
         Ok(state.package_ids.iter().cloned().collect())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use warg_crypto::{hash::HashAlgorithm, signing::generate_p256_pair};
+
+    /// Stores a pending record releasing the given content for a package.
+    async fn store_record(
+        store: &MemoryDataStore,
+        package_id: &PackageId,
+        content: &[&AnyHash],
+    ) -> (LogId, RecordId) {
+        let (public_key, signing_key) = generate_p256_pair();
+        let mut entries = vec![PackageEntry::Init {
+            hash_algorithm: HashAlgorithm::Sha256,
+            key: public_key,
+        }];
+        entries.extend(
+            content
+                .iter()
+                .enumerate()
+                .map(|(i, digest)| PackageEntry::Release {
+                    version: format!("1.0.{i}").parse().unwrap(),
+                    content: (*digest).clone(),
+                }),
+        );
+
+        let record = ProtoEnvelope::signed_contents(
+            &signing_key,
+            package::PackageRecord {
+                prev: None,
+                version: 0,
+                timestamp: SystemTime::now(),
+                entries,
+            },
+        )
+        .unwrap();
+        let log_id = LogId::package_log::<Sha256>(package_id);
+        let record_id = RecordId::package_record::<Sha256>(&record);
+        store
+            .store_package_record(
+                &log_id,
+                package_id,
+                &record_id,
+                &record,
+                &content.iter().copied().collect(),
+            )
+            .await
+            .unwrap();
+
+        (log_id, record_id)
+    }
+
+    #[tokio::test]
+    async fn it_counts_distinct_referenced_content() {
+        let store = MemoryDataStore::default();
+        let shared = HashAlgorithm::Sha256.digest(b"shared");
+        let missing = HashAlgorithm::Sha256.digest(b"missing");
+        let rejected = HashAlgorithm::Sha256.digest(b"rejected");
+
+        // Content referenced by two packages is only counted once
+        let (log_id, record_id) =
+            store_record(&store, &"test:foo".parse().unwrap(), &[&shared, &missing]).await;
+        store
+            .set_content_present(&log_id, &record_id, &shared, 10, None)
+            .await
+            .unwrap();
+        let (log_id, record_id) =
+            store_record(&store, &"test:bar".parse().unwrap(), &[&shared]).await;
+        store
+            .set_content_present(&log_id, &record_id, &shared, 10, None)
+            .await
+            .unwrap();
+        assert_eq!(store.get_namespace_content_size("test").await.unwrap(), 10);
+
+        // Content of rejected records is not counted
+        let (log_id, record_id) =
+            store_record(&store, &"test:baz".parse().unwrap(), &[&rejected]).await;
+        store
+            .set_content_present(&log_id, &record_id, &rejected, 100, None)
+            .await
+            .unwrap();
+        assert_eq!(store.get_namespace_content_size("test").await.unwrap(), 110);
+        store
+            .reject_package_record(&log_id, &record_id, "rejected")
+            .await
+            .unwrap();
+        assert_eq!(store.get_namespace_content_size("test").await.unwrap(), 10);
+        assert_eq!(store.get_namespace_content_size("other").await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn it_enforces_the_namespace_quota() {
+        let store = MemoryDataStore::default();
+        let first = HashAlgorithm::Sha256.digest(b"first");
+        let second = HashAlgorithm::Sha256.digest(b"second");
+        let (foo_log_id, foo_record_id) =
+            store_record(&store, &"test:foo".parse().unwrap(), &[&first]).await;
+        let (bar_log_id, bar_record_id) =
+            store_record(&store, &"test:bar".parse().unwrap(), &[&second]).await;
+
+        // Only one of the concurrent updates fits within the quota
+        let (foo, bar) = tokio::join!(
+            store.set_content_present(&foo_log_id, &foo_record_id, &first, 60, Some(100)),
+            store.set_content_present(&bar_log_id, &bar_record_id, &second, 60, Some(100)),
+        );
+        assert!(foo.unwrap());
+        assert!(matches!(
+            bar.unwrap_err(),
+            DataStoreError::QuotaExceeded { namespace, quota: 100 } if namespace == "test"
+        ));
+        assert!(store
+            .is_content_missing(&bar_log_id, &bar_record_id, &second)
+            .await
+            .unwrap());
+        assert_eq!(store.get_namespace_content_size("test").await.unwrap(), 60);
+
+        // Content already counted towards the quota is not counted again
+        let (log_id, record_id) =
+            store_record(&store, &"test:baz".parse().unwrap(), &[&first]).await;
+        assert!(store
+            .set_content_present(&log_id, &record_id, &first, 60, Some(100))
+            .await
+            .unwrap());
+        assert!(store
+            .set_content_present(&bar_log_id, &bar_record_id, &second, 40, Some(100))
+            .await
+            .unwrap());
+        assert_eq!(store.get_namespace_content_size("test").await.unwrap(), 100);
+    }
+}
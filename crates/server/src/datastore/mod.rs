@@ -1,19 +1,25 @@
 use futures::Stream;
-use std::{collections::HashSet, pin::Pin};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, pin::Pin, time::SystemTime};
 use thiserror::Error;
 use warg_crypto::{hash::AnyHash, signing::KeyID};
 use warg_protocol::{
     operator, package,
     registry::{
         LogId, LogLeaf, PackageId, RecordId, RegistryIndex, RegistryLen, TimestampedCheckpoint,
+        WitnessedCheckpoint,
     },
     ProtoEnvelope, PublishedProtoEnvelope, SerdeEnvelope,
 };
 
+#[cfg(feature = "filesystem")]
+mod filesystem;
 mod memory;
 #[cfg(feature = "postgres")]
 mod postgres;
 
+#[cfg(feature = "filesystem")]
+pub use filesystem::*;
 pub use memory::*;
 #[cfg(feature = "postgres")]
 pub use postgres::*;
@@ -59,6 +65,17 @@ pub enum DataStoreError {
     #[error("the record was rejected: {0}")]
     Rejection(String),
 
+    #[error("content exceeds the storage quota of {quota} bytes for namespace `{namespace}`")]
+    QuotaExceeded { namespace: String, quota: u64 },
+
+    #[cfg(feature = "filesystem")]
+    #[error("failed to write to the data store journal: {0}")]
+    Journal(#[from] std::io::Error),
+
+    #[cfg(feature = "filesystem")]
+    #[error("failed to access the transparency snapshot: {0}")]
+    Snapshot(std::io::Error),
+
     #[cfg(feature = "postgres")]
     #[error("a connection could not be established to the PostgreSQL server: {0}")]
     ConnectionPool(#[from] diesel_async::pooled_connection::deadpool::PoolError),
@@ -98,6 +115,21 @@ where
     pub registry_index: Option<RegistryIndex>,
 }
 
+/// Represents a snapshot of the registry's transparency state.
+///
+/// A snapshot is taken at a checkpoint so that the state can be restored
+/// without replaying every validated record.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransparencySnapshot {
+    /// The registry log length of the checkpoint the snapshot was taken at.
+    pub log_length: RegistryLen,
+    /// The node hashes of the registry log tree.
+    pub log_tree: Vec<AnyHash>,
+    /// The latest record of each log in the verifiable map.
+    pub map: Vec<LogLeaf>,
+}
+
 /// Implemented by data stores.
 #[axum::async_trait]
 pub trait DataStore: Send + Sync {
@@ -111,11 +143,13 @@ pub trait DataStore: Send + Sync {
         DataStoreError,
     >;
 
-    /// Gets a stream of all validated records.
+    /// Gets a stream of the validated records starting at the given registry
+    /// log index.
     ///
     /// This is an expensive operation and should only be performed on startup.
-    async fn get_all_validated_records(
+    async fn get_validated_records(
         &self,
+        start: RegistryIndex,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<LogLeaf, DataStoreError>> + Send>>, DataStoreError>;
 
     /// Looks up the log_id and record_id from the registry log index.  
@@ -177,6 +211,19 @@ pub trait DataStore: Send + Sync {
         reason: &str,
     ) -> Result<(), DataStoreError>;
 
+    /// Gets the package records that are pending with missing content and
+    /// were received by the registry before the given time.
+    async fn get_pending_package_records(
+        &self,
+        submitted_before: SystemTime,
+    ) -> Result<Vec<LogLeaf>, DataStoreError>;
+
+    /// Gets the package records that are pending with all of their content
+    /// present, in the order they were submitted.
+    ///
+    /// These records are ready to be committed.
+    async fn get_ready_package_records(&self) -> Result<Vec<LogLeaf>, DataStoreError>;
+
     /// Commits the given package record.
     ///
     /// The record must be in a pending state.
@@ -189,6 +236,22 @@ pub trait DataStore: Send + Sync {
         registry_index: RegistryIndex,
     ) -> Result<(), DataStoreError>;
 
+    /// Commits the given package records in a single transaction.
+    ///
+    /// The records must be in a pending state.
+    ///
+    /// Records that validate are assigned consecutive registry indexes,
+    /// starting at `registry_index`, in the given order; records that fail
+    /// validation are rejected.
+    ///
+    /// Returns the result of committing each record, in the given order, or
+    /// an error if the transaction failed and no record was committed.
+    async fn commit_package_records(
+        &self,
+        leafs: &[LogLeaf],
+        registry_index: RegistryIndex,
+    ) -> Result<Vec<Result<(), DataStoreError>>, DataStoreError>;
+
     /// Determines if the given content digest is missing for the record.
     ///
     /// The record must be in a pending state.
@@ -201,6 +264,14 @@ pub trait DataStore: Send + Sync {
 
     /// Sets the present flag for the given record and content digest.
     ///
+    /// The size of the content, in bytes, is counted towards the storage
+    /// used by the namespace of the record's package.
+    ///
+    /// If a quota is given, the content is only marked present if the
+    /// storage used by the namespace would not exceed it; otherwise
+    /// `DataStoreError::QuotaExceeded` is returned. The check is atomic with
+    /// the update.
+    ///
     /// The record must be in a pending state.
     ///
     /// Returns true if the record has all of its content present as a
@@ -212,8 +283,61 @@ pub trait DataStore: Send + Sync {
         log_id: &LogId,
         record_id: &RecordId,
         digest: &AnyHash,
+        size: u64,
+        quota: Option<u64>,
     ) -> Result<bool, DataStoreError>;
 
+    /// Gets the total size, in bytes, of the storage used by packages in
+    /// the given namespace.
+    ///
+    /// Content is counted once per namespace if it is present and referenced
+    /// by a validated or pending record; the content of rejected records is
+    /// not counted.
+    async fn get_namespace_content_size(&self, namespace: &str) -> Result<u64, DataStoreError>;
+
+    /// Gets the digests of the content referenced by package records.
+    ///
+    /// This includes the content of validated records and of pending records
+    /// received by the registry at or after `pending_since`; the content of
+    /// rejected records is not included.
+    ///
+    /// This is an expensive operation and should only be performed when
+    /// collecting unreferenced content.
+    async fn get_referenced_content(
+        &self,
+        pending_since: SystemTime,
+    ) -> Result<HashSet<AnyHash>, DataStoreError>;
+
+    /// Gets the current validation state of the given operator log.
+    ///
+    /// Returns the default state if no records of the log have been validated.
+    async fn get_operator_log_state(
+        &self,
+        log_id: &LogId,
+    ) -> Result<operator::LogState, DataStoreError>;
+
+    /// Gets the current validation state of the given package log.
+    ///
+    /// Returns the default state if no records of the log have been validated.
+    async fn get_package_log_state(
+        &self,
+        log_id: &LogId,
+    ) -> Result<package::LogState, DataStoreError>;
+
+    /// Gets the package id of the given package log.
+    async fn get_package_id(&self, log_id: &LogId) -> Result<PackageId, DataStoreError>;
+
+    /// Gets the identifiers of the packages with at least one published record.
+    ///
+    /// Identifiers are returned in sorted order, starting after `since`, and
+    /// are limited to namespaces starting with the given prefix.
+    async fn get_package_ids(
+        &self,
+        namespace: Option<&str>,
+        since: Option<&PackageId>,
+        limit: u16,
+    ) -> Result<Vec<PackageId>, DataStoreError>;
+
     /// Stores a new checkpoint.
     async fn store_checkpoint(
         &self,
@@ -221,11 +345,48 @@ pub trait DataStore: Send + Sync {
         ts_checkpoint: SerdeEnvelope<TimestampedCheckpoint>,
     ) -> Result<(), DataStoreError>;
 
+    /// Stores a snapshot of the transparency state.
+    ///
+    /// The snapshot replaces any previously stored snapshot.
+    async fn store_snapshot(&self, snapshot: &TransparencySnapshot) -> Result<(), DataStoreError>;
+
+    /// Gets the latest stored snapshot of the transparency state.
+    async fn get_latest_snapshot(&self) -> Result<Option<TransparencySnapshot>, DataStoreError>;
+
     /// Gets the latest checkpoint.
     async fn get_latest_checkpoint(
         &self,
     ) -> Result<SerdeEnvelope<TimestampedCheckpoint>, DataStoreError>;
 
+    /// Gets the checkpoint for the given registry log length.
+    async fn get_checkpoint(
+        &self,
+        log_length: RegistryLen,
+    ) -> Result<SerdeEnvelope<TimestampedCheckpoint>, DataStoreError>;
+
+    /// Stores a witness cosignature of a checkpoint.
+    ///
+    /// The cosignature replaces any previously stored cosignature of the
+    /// same checkpoint by the same key.
+    async fn store_cosignature(
+        &self,
+        cosignature: SerdeEnvelope<WitnessedCheckpoint>,
+    ) -> Result<(), DataStoreError>;
+
+    /// Gets the witness cosignatures of the checkpoint for the given
+    /// registry log length.
+    async fn get_cosignatures(
+        &self,
+        log_length: RegistryLen,
+    ) -> Result<Vec<SerdeEnvelope<WitnessedCheckpoint>>, DataStoreError>;
+
+    /// Gets the most recent cosignature of each witness.
+    ///
+    /// The cosignatures are ordered by descending log length.
+    async fn get_latest_cosignatures(
+        &self,
+    ) -> Result<Vec<SerdeEnvelope<WitnessedCheckpoint>>, DataStoreError>;
+
     /// Gets the operator records for the given registry log length.
     async fn get_operator_records(
         &self,
@@ -277,3 +438,314 @@ pub trait DataStore: Send + Sync {
         anyhow::bail!("not implemented")
     }
 }
+
+#[cfg(test)]
+pub(crate) mod failing {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// A data store that fails to commit package records a given number of
+    /// times before delegating to a [`MemoryDataStore`].
+    #[derive(Default)]
+    pub(crate) struct FailingDataStore {
+        pub(crate) store: MemoryDataStore,
+        pub(crate) commit_failures: AtomicU32,
+    }
+
+    #[axum::async_trait]
+    impl DataStore for FailingDataStore {
+        async fn get_all_checkpoints(
+            &self,
+        ) -> Result<
+            Pin<Box<dyn Stream<Item = Result<TimestampedCheckpoint, DataStoreError>> + Send>>,
+            DataStoreError,
+        > {
+            self.store.get_all_checkpoints().await
+        }
+
+        async fn get_validated_records(
+            &self,
+            start: RegistryIndex,
+        ) -> Result<
+            Pin<Box<dyn Stream<Item = Result<LogLeaf, DataStoreError>> + Send>>,
+            DataStoreError,
+        > {
+            self.store.get_validated_records(start).await
+        }
+
+        async fn get_log_leafs_with_registry_index(
+            &self,
+            entries: &[RegistryIndex],
+        ) -> Result<Vec<LogLeaf>, DataStoreError> {
+            self.store.get_log_leafs_with_registry_index(entries).await
+        }
+
+        async fn store_operator_record(
+            &self,
+            log_id: &LogId,
+            record_id: &RecordId,
+            record: &ProtoEnvelope<operator::OperatorRecord>,
+        ) -> Result<(), DataStoreError> {
+            self.store
+                .store_operator_record(log_id, record_id, record)
+                .await
+        }
+
+        async fn reject_operator_record(
+            &self,
+            log_id: &LogId,
+            record_id: &RecordId,
+            reason: &str,
+        ) -> Result<(), DataStoreError> {
+            self.store
+                .reject_operator_record(log_id, record_id, reason)
+                .await
+        }
+
+        async fn commit_operator_record(
+            &self,
+            log_id: &LogId,
+            record_id: &RecordId,
+            registry_index: RegistryIndex,
+        ) -> Result<(), DataStoreError> {
+            self.store
+                .commit_operator_record(log_id, record_id, registry_index)
+                .await
+        }
+
+        async fn store_package_record(
+            &self,
+            log_id: &LogId,
+            package_id: &PackageId,
+            record_id: &RecordId,
+            record: &ProtoEnvelope<package::PackageRecord>,
+            missing: &HashSet<&AnyHash>,
+        ) -> Result<(), DataStoreError> {
+            self.store
+                .store_package_record(log_id, package_id, record_id, record, missing)
+                .await
+        }
+
+        async fn reject_package_record(
+            &self,
+            log_id: &LogId,
+            record_id: &RecordId,
+            reason: &str,
+        ) -> Result<(), DataStoreError> {
+            self.store
+                .reject_package_record(log_id, record_id, reason)
+                .await
+        }
+
+        async fn get_pending_package_records(
+            &self,
+            submitted_before: SystemTime,
+        ) -> Result<Vec<LogLeaf>, DataStoreError> {
+            self.store
+                .get_pending_package_records(submitted_before)
+                .await
+        }
+
+        async fn get_ready_package_records(&self) -> Result<Vec<LogLeaf>, DataStoreError> {
+            self.store.get_ready_package_records().await
+        }
+
+        async fn commit_package_record(
+            &self,
+            log_id: &LogId,
+            record_id: &RecordId,
+            registry_index: RegistryIndex,
+        ) -> Result<(), DataStoreError> {
+            self.store
+                .commit_package_record(log_id, record_id, registry_index)
+                .await
+        }
+
+        async fn commit_package_records(
+            &self,
+            leafs: &[LogLeaf],
+            registry_index: RegistryIndex,
+        ) -> Result<Vec<Result<(), DataStoreError>>, DataStoreError> {
+            // Consume a failure, if any remain
+            if self
+                .commit_failures
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1))
+                .is_ok()
+            {
+                return Err(DataStoreError::Conflict);
+            }
+
+            self.store
+                .commit_package_records(leafs, registry_index)
+                .await
+        }
+
+        async fn is_content_missing(
+            &self,
+            log_id: &LogId,
+            record_id: &RecordId,
+            digest: &AnyHash,
+        ) -> Result<bool, DataStoreError> {
+            self.store
+                .is_content_missing(log_id, record_id, digest)
+                .await
+        }
+
+        async fn set_content_present(
+            &self,
+            log_id: &LogId,
+            record_id: &RecordId,
+            digest: &AnyHash,
+            size: u64,
+            quota: Option<u64>,
+        ) -> Result<bool, DataStoreError> {
+            self.store
+                .set_content_present(log_id, record_id, digest, size, quota)
+                .await
+        }
+
+        async fn get_namespace_content_size(&self, namespace: &str) -> Result<u64, DataStoreError> {
+            self.store.get_namespace_content_size(namespace).await
+        }
+
+        async fn get_referenced_content(
+            &self,
+            pending_since: SystemTime,
+        ) -> Result<HashSet<AnyHash>, DataStoreError> {
+            self.store.get_referenced_content(pending_since).await
+        }
+
+        async fn get_operator_log_state(
+            &self,
+            log_id: &LogId,
+        ) -> Result<operator::LogState, DataStoreError> {
+            self.store.get_operator_log_state(log_id).await
+        }
+
+        async fn get_package_log_state(
+            &self,
+            log_id: &LogId,
+        ) -> Result<package::LogState, DataStoreError> {
+            self.store.get_package_log_state(log_id).await
+        }
+
+        async fn get_package_id(&self, log_id: &LogId) -> Result<PackageId, DataStoreError> {
+            self.store.get_package_id(log_id).await
+        }
+
+        async fn get_package_ids(
+            &self,
+            namespace: Option<&str>,
+            since: Option<&PackageId>,
+            limit: u16,
+        ) -> Result<Vec<PackageId>, DataStoreError> {
+            self.store.get_package_ids(namespace, since, limit).await
+        }
+
+        async fn store_checkpoint(
+            &self,
+            checkpoint_id: &AnyHash,
+            ts_checkpoint: SerdeEnvelope<TimestampedCheckpoint>,
+        ) -> Result<(), DataStoreError> {
+            self.store
+                .store_checkpoint(checkpoint_id, ts_checkpoint)
+                .await
+        }
+
+        async fn store_snapshot(
+            &self,
+            snapshot: &TransparencySnapshot,
+        ) -> Result<(), DataStoreError> {
+            self.store.store_snapshot(snapshot).await
+        }
+
+        async fn get_latest_snapshot(
+            &self,
+        ) -> Result<Option<TransparencySnapshot>, DataStoreError> {
+            self.store.get_latest_snapshot().await
+        }
+
+        async fn get_latest_checkpoint(
+            &self,
+        ) -> Result<SerdeEnvelope<TimestampedCheckpoint>, DataStoreError> {
+            self.store.get_latest_checkpoint().await
+        }
+
+        async fn get_checkpoint(
+            &self,
+            log_length: RegistryLen,
+        ) -> Result<SerdeEnvelope<TimestampedCheckpoint>, DataStoreError> {
+            self.store.get_checkpoint(log_length).await
+        }
+
+        async fn store_cosignature(
+            &self,
+            cosignature: SerdeEnvelope<WitnessedCheckpoint>,
+        ) -> Result<(), DataStoreError> {
+            self.store.store_cosignature(cosignature).await
+        }
+
+        async fn get_cosignatures(
+            &self,
+            log_length: RegistryLen,
+        ) -> Result<Vec<SerdeEnvelope<WitnessedCheckpoint>>, DataStoreError> {
+            self.store.get_cosignatures(log_length).await
+        }
+
+        async fn get_latest_cosignatures(
+            &self,
+        ) -> Result<Vec<SerdeEnvelope<WitnessedCheckpoint>>, DataStoreError> {
+            self.store.get_latest_cosignatures().await
+        }
+
+        async fn get_operator_records(
+            &self,
+            log_id: &LogId,
+            registry_log_length: RegistryLen,
+            since: Option<&RecordId>,
+            limit: u16,
+        ) -> Result<Vec<PublishedProtoEnvelope<operator::OperatorRecord>>, DataStoreError> {
+            self.store
+                .get_operator_records(log_id, registry_log_length, since, limit)
+                .await
+        }
+
+        async fn get_package_records(
+            &self,
+            log_id: &LogId,
+            registry_log_length: RegistryLen,
+            since: Option<&RecordId>,
+            limit: u16,
+        ) -> Result<Vec<PublishedProtoEnvelope<package::PackageRecord>>, DataStoreError> {
+            self.store
+                .get_package_records(log_id, registry_log_length, since, limit)
+                .await
+        }
+
+        async fn get_operator_record(
+            &self,
+            log_id: &LogId,
+            record_id: &RecordId,
+        ) -> Result<Record<operator::OperatorRecord>, DataStoreError> {
+            self.store.get_operator_record(log_id, record_id).await
+        }
+
+        async fn get_package_record(
+            &self,
+            log_id: &LogId,
+            record_id: &RecordId,
+        ) -> Result<Record<package::PackageRecord>, DataStoreError> {
+            self.store.get_package_record(log_id, record_id).await
+        }
+
+        async fn verify_package_record_signature(
+            &self,
+            log_id: &LogId,
+            record: &ProtoEnvelope<package::PackageRecord>,
+        ) -> Result<(), DataStoreError> {
+            self.store
+                .verify_package_record_signature(log_id, record)
+                .await
+        }
+    }
+}
@@ -0,0 +1,6 @@
+CREATE TABLE snapshots (
+  id SERIAL PRIMARY KEY,
+  log_length BIGINT NOT NULL,
+  data JSONB NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,8 @@
+CREATE TABLE cosignatures (
+  id SERIAL PRIMARY KEY,
+  log_length BIGINT NOT NULL,
+  key_id TEXT NOT NULL,
+  data JSONB NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  UNIQUE (log_length, key_id)
+);
@@ -1,9 +1,10 @@
 use self::models::{
-    CheckpointData, NewCheckpoint, NewContent, NewLog, NewRecord, ParsedText, RecordContent,
-    RecordStatus, TextRef,
+    CheckpointData, NamespaceContent, NewCheckpoint, NewContent, NewCosignature, NewLog, NewRecord,
+    NewSnapshot, ParsedText, RecordContent, RecordStatus, TextRef,
 };
-use super::{DataStore, DataStoreError, Record};
+use super::{DataStore, DataStoreError, Record, TransparencySnapshot};
 use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
 use diesel::{prelude::*, result::DatabaseErrorKind};
 use diesel_async::{
     pooled_connection::{deadpool::Pool, AsyncDieselConnectionManager},
@@ -19,14 +20,18 @@ use secrecy::{ExposeSecret, SecretString};
 use std::{
     collections::{HashMap, HashSet},
     pin::Pin,
+    time::SystemTime,
+};
+use warg_crypto::{
+    hash::{AnyHash, Sha256},
+    Decode, Signable,
 };
-use warg_crypto::{hash::AnyHash, Decode, Signable};
 use warg_protocol::{
     operator,
     package::{self, PackageEntry},
     registry::{
         Checkpoint, LogId, LogLeaf, PackageId, RecordId, RegistryIndex, RegistryLen,
-        TimestampedCheckpoint,
+        TimestampedCheckpoint, WitnessedCheckpoint,
     },
     ProtoEnvelope, PublishedProtoEnvelope, Record as _, SerdeEnvelope, Validator,
 };
@@ -34,6 +39,21 @@ use warg_protocol::{
 mod models;
 mod schema;
 
+fn checkpoint_envelope(checkpoint: CheckpointData) -> SerdeEnvelope<TimestampedCheckpoint> {
+    SerdeEnvelope::from_parts_unchecked(
+        TimestampedCheckpoint {
+            checkpoint: Checkpoint {
+                log_root: checkpoint.log_root.0,
+                log_length: checkpoint.log_length.try_into().unwrap(),
+                map_root: checkpoint.map_root.0,
+            },
+            timestamp: checkpoint.timestamp.try_into().unwrap(),
+        },
+        checkpoint.key_id.0,
+        checkpoint.signature.0,
+    )
+}
+
 async fn get_records<R: Decode>(
     conn: &mut AsyncPgConnection,
     log_id: i32,
@@ -206,16 +226,18 @@ async fn reject_record(
     Ok(())
 }
 
-async fn commit_record<V>(
+async fn commit_record<V, F>(
     conn: &mut AsyncPgConnection,
     log_id: i32,
     record_id: &RecordId,
     registry_index: RegistryIndex,
+    validate: F,
 ) -> Result<(), DataStoreError>
 where
     V: Validator + 'static,
     <V as Validator>::Error: ToString + Send + Sync,
     DataStoreError: From<<V as Validator>::Error>,
+    F: FnOnce(&mut V, &ProtoEnvelope<V::Record>) -> Result<(), V::Error> + Send,
 {
     let registry_index: i64 = registry_index.try_into().unwrap();
     conn.transaction::<_, DataStoreError, _>(|conn| {
@@ -248,7 +270,7 @@ where
             })?;
 
             // Validate the record
-            validator.validate(&record).map_err(Into::into)?;
+            validate(&mut validator, &record)?;
 
             // Store the updated validation state
             diesel::update(schema::logs::table)
@@ -274,6 +296,60 @@ where
     .await
 }
 
+/// Gets the package identifier of the given log.
+async fn get_log_package_id(
+    conn: &mut AsyncPgConnection,
+    log_id: &LogId,
+) -> Result<PackageId, DataStoreError> {
+    schema::logs::table
+        .select(schema::logs::name)
+        .filter(schema::logs::log_id.eq(TextRef(log_id)))
+        .first::<Option<String>>(conn)
+        .await
+        .optional()?
+        .flatten()
+        .and_then(|name| name.parse().ok())
+        .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))
+}
+
+/// Gets the size of the distinct content that is present and referenced by
+/// the validated or pending records of packages in the given namespace.
+async fn get_namespace_content(
+    conn: &mut AsyncPgConnection,
+    namespace: &str,
+) -> Result<Vec<NamespaceContent>, DataStoreError> {
+    // The size of content is only recorded for the record it was uploaded
+    // for, so it is looked up by digest
+    Ok(diesel::sql_query(
+        "SELECT contents.digest, COALESCE(( \
+            SELECT MAX(sized.size) FROM contents AS sized WHERE sized.digest = contents.digest \
+         ), 0) AS size \
+         FROM contents \
+         INNER JOIN records ON records.id = contents.record_id \
+         INNER JOIN logs ON logs.id = records.log_id \
+         WHERE logs.name LIKE $1 AND NOT contents.missing AND records.status <> 'rejected' \
+         GROUP BY contents.digest",
+    )
+    .bind::<diesel::sql_types::Text, _>(format!("{namespace}:%"))
+    .load::<NamespaceContent>(conn)
+    .await?)
+}
+
+/// Gets the current state of the operator log, against which the namespaces
+/// of package records are validated.
+async fn get_operator_state(
+    conn: &mut AsyncPgConnection,
+) -> Result<operator::LogState, DataStoreError> {
+    Ok(schema::logs::table
+        .select(schema::logs::validator)
+        .filter(schema::logs::log_id.eq(TextRef(&LogId::operator_log::<Sha256>())))
+        .first::<Json<operator::LogState>>(conn)
+        .await
+        .optional()?
+        .map(|validator| validator.0)
+        .unwrap_or_default())
+}
+
 async fn get_record<V>(
     conn: &mut AsyncPgConnection,
     log_id: &LogId,
@@ -421,8 +497,9 @@ impl DataStore for PostgresDataStore {
             .boxed())
     }
 
-    async fn get_all_validated_records(
+    async fn get_validated_records(
         &self,
+        start: RegistryIndex,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<LogLeaf, DataStoreError>> + Send>>, DataStoreError>
     {
         // The returned future will keep the connection from the pool until dropped
@@ -436,7 +513,11 @@ impl DataStore for PostgresDataStore {
             schema::records::table
                 .inner_join(schema::logs::table)
                 .select((schema::logs::log_id, schema::records::record_id))
-                .filter(schema::records::status.eq(RecordStatus::Validated))
+                .filter(
+                    schema::records::status
+                        .eq(RecordStatus::Validated)
+                        .and(schema::records::registry_log_index.ge(start as i64)),
+                )
                 .order(schema::records::registry_log_index.asc())
                 .load_stream::<(ParsedText<AnyHash>, ParsedText<AnyHash>)>(&mut conn)
                 .await?
@@ -542,8 +623,14 @@ impl DataStore for PostgresDataStore {
             .optional()?
             .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
 
-        match commit_record::<operator::LogState>(conn.as_mut(), log_id, record_id, registry_index)
-            .await
+        match commit_record::<operator::LogState, _>(
+            conn.as_mut(),
+            log_id,
+            record_id,
+            registry_index,
+            operator::LogState::validate,
+        )
+        .await
         {
             Ok(()) => Ok(()),
             Err(e) => {
@@ -591,6 +678,67 @@ impl DataStore for PostgresDataStore {
         reject_record(conn.as_mut(), log_id, record_id, reason).await
     }
 
+    async fn get_pending_package_records(
+        &self,
+        submitted_before: SystemTime,
+    ) -> Result<Vec<LogLeaf>, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        Ok(schema::records::table
+            .inner_join(schema::logs::table)
+            .select((schema::logs::log_id, schema::records::record_id))
+            .filter(
+                schema::records::status
+                    .eq(RecordStatus::Pending)
+                    .and(schema::records::created_at.lt(DateTime::<Utc>::from(submitted_before)))
+                    // Only package logs have a name
+                    .and(schema::logs::name.is_not_null())
+                    .and(diesel::dsl::exists(
+                        schema::contents::table.filter(
+                            schema::contents::record_id
+                                .eq(schema::records::id)
+                                .and(schema::contents::missing.eq(true)),
+                        ),
+                    )),
+            )
+            .load::<(ParsedText<AnyHash>, ParsedText<AnyHash>)>(conn.as_mut())
+            .await?
+            .into_iter()
+            .map(|(log_id, record_id)| LogLeaf {
+                log_id: log_id.0.into(),
+                record_id: record_id.0.into(),
+            })
+            .collect())
+    }
+
+    async fn get_ready_package_records(&self) -> Result<Vec<LogLeaf>, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        Ok(schema::records::table
+            .inner_join(schema::logs::table)
+            .select((schema::logs::log_id, schema::records::record_id))
+            .filter(
+                schema::records::status
+                    .eq(RecordStatus::Pending)
+                    // Only package logs have a name
+                    .and(schema::logs::name.is_not_null())
+                    .and(diesel::dsl::not(diesel::dsl::exists(
+                        schema::contents::table.filter(
+                            schema::contents::record_id
+                                .eq(schema::records::id)
+                                .and(schema::contents::missing.eq(true)),
+                        ),
+                    ))),
+            )
+            .order(schema::records::id.asc())
+            .load::<(ParsedText<AnyHash>, ParsedText<AnyHash>)>(conn.as_mut())
+            .await?
+            .into_iter()
+            .map(|(log_id, record_id)| LogLeaf {
+                log_id: log_id.0.into(),
+                record_id: record_id.0.into(),
+            })
+            .collect())
+    }
+
     async fn commit_package_record(
         &self,
         log_id: &LogId,
@@ -598,6 +746,8 @@ impl DataStore for PostgresDataStore {
         registry_index: RegistryIndex,
     ) -> Result<(), DataStoreError> {
         let mut conn = self.pool.get().await?;
+        let package_id = get_log_package_id(conn.as_mut(), log_id).await?;
+        let operator = get_operator_state(conn.as_mut()).await?;
         let log_id = schema::logs::table
             .select(schema::logs::id)
             .filter(schema::logs::log_id.eq(TextRef(log_id)))
@@ -606,8 +756,14 @@ impl DataStore for PostgresDataStore {
             .optional()?
             .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
 
-        match commit_record::<package::LogState>(conn.as_mut(), log_id, record_id, registry_index)
-            .await
+        match commit_record::<package::LogState, _>(
+            conn.as_mut(),
+            log_id,
+            record_id,
+            registry_index,
+            |validator, record| validator.validate_with_operator(&package_id, &operator, record),
+        )
+        .await
         {
             Ok(()) => Ok(()),
             Err(e) => {
@@ -617,6 +773,69 @@ impl DataStore for PostgresDataStore {
         }
     }
 
+    async fn commit_package_records(
+        &self,
+        leafs: &[LogLeaf],
+        registry_index: RegistryIndex,
+    ) -> Result<Vec<Result<(), DataStoreError>>, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        conn.transaction::<_, DataStoreError, _>(|conn| {
+            async move {
+                let operator = get_operator_state(conn).await?;
+                let mut registry_index = registry_index;
+                let mut results = Vec::with_capacity(leafs.len());
+                for LogLeaf { log_id, record_id } in leafs {
+                    let Some(id) = schema::logs::table
+                        .select(schema::logs::id)
+                        .filter(schema::logs::log_id.eq(TextRef(log_id)))
+                        .first::<i32>(conn)
+                        .await
+                        .optional()?
+                    else {
+                        results.push(Err(DataStoreError::LogNotFound(log_id.clone())));
+                        continue;
+                    };
+
+                    let package_id = get_log_package_id(conn, log_id).await?;
+
+                    // Each record is committed in a nested transaction (i.e. a
+                    // savepoint) so that a rejected record does not abort the batch
+                    match commit_record::<package::LogState, _>(
+                        conn,
+                        id,
+                        record_id,
+                        registry_index,
+                        |validator, record| {
+                            validator.validate_with_operator(&package_id, &operator, record)
+                        },
+                    )
+                    .await
+                    {
+                        Ok(()) => {
+                            registry_index += 1;
+                            results.push(Ok(()));
+                        }
+                        Err(e @ DataStoreError::RecordNotPending(_)) => results.push(Err(e)),
+                        Err(
+                            e @ (DataStoreError::Rejection(_)
+                            | DataStoreError::PackageValidationFailed(_)
+                            | DataStoreError::InvalidRecordContents { .. }),
+                        ) => {
+                            reject_record(conn, id, record_id, &e.to_string()).await?;
+                            results.push(Err(e));
+                        }
+                        // Any other error fails the entire batch
+                        Err(e) => return Err(e),
+                    }
+                }
+
+                Ok(results)
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+
     async fn is_content_missing(
         &self,
         log_id: &LogId,
@@ -646,6 +865,8 @@ impl DataStore for PostgresDataStore {
         log_id: &LogId,
         record_id: &RecordId,
         digest: &AnyHash,
+        size: u64,
+        quota: Option<u64>,
     ) -> Result<bool, DataStoreError> {
         let mut conn = self.pool.get().await?;
         conn.transaction::<_, DataStoreError, _>(|conn| {
@@ -653,20 +874,43 @@ impl DataStore for PostgresDataStore {
             // See: https://github.com/diesel-rs/diesel/issues/1478
             // So we select the record id first and then update the content
             async move {
-                let record_id = schema::records::table
+                let (record_id, name) = schema::records::table
                     .inner_join(schema::logs::table)
-                    .select(schema::records::id)
+                    .select((schema::records::id, schema::logs::name))
                     .filter(
                         schema::records::status
                             .eq(RecordStatus::Pending)
                             .and(schema::logs::log_id.eq(TextRef(log_id)))
                             .and(schema::records::record_id.eq(TextRef(record_id))),
                     )
-                    .first::<i32>(conn.as_mut())
+                    .first::<(i32, Option<String>)>(conn.as_mut())
                     .await
                     .optional()?
                     .ok_or_else(|| DataStoreError::RecordNotPending(record_id.clone()))?;
 
+                let package_id = name.and_then(|name| name.parse::<PackageId>().ok());
+                if let (Some(quota), Some(package_id)) = (quota, package_id) {
+                    let namespace = package_id.namespace();
+
+                    // Serialize the quota checks of the namespace until the
+                    // transaction completes
+                    diesel::sql_query("SELECT pg_advisory_xact_lock(hashtext($1))")
+                        .bind::<diesel::sql_types::Text, _>(namespace)
+                        .execute(conn.as_mut())
+                        .await?;
+
+                    let content = get_namespace_content(conn.as_mut(), namespace).await?;
+                    if !content.iter().any(|c| &c.digest.0 == digest) {
+                        let used: u64 = content.iter().map(|c| c.size as u64).sum();
+                        if used.saturating_add(size) > quota {
+                            return Err(DataStoreError::QuotaExceeded {
+                                namespace: namespace.to_string(),
+                                quota,
+                            });
+                        }
+                    }
+                }
+
                 // If the row was already updated, return false since this update
                 // didn't change anything
                 if diesel::update(schema::contents::table)
@@ -675,7 +919,10 @@ impl DataStore for PostgresDataStore {
                             .eq(record_id)
                             .and(schema::contents::digest.eq(TextRef(digest))),
                     )
-                    .set(schema::contents::missing.eq(false))
+                    .set((
+                        schema::contents::missing.eq(false),
+                        schema::contents::size.eq(Some(size as i64)),
+                    ))
                     .execute(conn.as_mut())
                     .await?
                     == 0
@@ -703,6 +950,130 @@ impl DataStore for PostgresDataStore {
         .await
     }
 
+    async fn get_namespace_content_size(&self, namespace: &str) -> Result<u64, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        Ok(get_namespace_content(conn.as_mut(), namespace)
+            .await?
+            .iter()
+            .map(|c| c.size as u64)
+            .sum())
+    }
+
+    async fn get_referenced_content(
+        &self,
+        pending_since: SystemTime,
+    ) -> Result<HashSet<AnyHash>, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        let digests = schema::contents::table
+            .inner_join(schema::records::table)
+            .select(schema::contents::digest)
+            .filter(
+                schema::records::status
+                    .eq(RecordStatus::Validated)
+                    .or(schema::records::status
+                        .eq(RecordStatus::Pending)
+                        .and(schema::records::created_at.ge(DateTime::<Utc>::from(pending_since)))),
+            )
+            .distinct()
+            .load::<ParsedText<AnyHash>>(conn.as_mut())
+            .await?;
+
+        Ok(digests.into_iter().map(|d| d.0).collect())
+    }
+
+    async fn get_operator_log_state(
+        &self,
+        log_id: &LogId,
+    ) -> Result<operator::LogState, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        Ok(schema::logs::table
+            .select(schema::logs::validator)
+            .filter(schema::logs::log_id.eq(TextRef(log_id)))
+            .first::<Json<operator::LogState>>(conn.as_mut())
+            .await
+            .optional()?
+            .map(|validator| validator.0)
+            .unwrap_or_default())
+    }
+
+    async fn get_package_log_state(
+        &self,
+        log_id: &LogId,
+    ) -> Result<package::LogState, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        Ok(schema::logs::table
+            .select(schema::logs::validator)
+            .filter(schema::logs::log_id.eq(TextRef(log_id)))
+            .first::<Json<package::LogState>>(conn.as_mut())
+            .await
+            .optional()?
+            .map(|validator| validator.0)
+            .unwrap_or_default())
+    }
+
+    async fn get_package_id(&self, log_id: &LogId) -> Result<PackageId, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        schema::logs::table
+            .select(schema::logs::name)
+            .filter(schema::logs::log_id.eq(TextRef(log_id)))
+            .first::<Option<String>>(conn.as_mut())
+            .await
+            .optional()?
+            .flatten()
+            .and_then(|name| name.parse().ok())
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))
+    }
+
+    async fn get_package_ids(
+        &self,
+        namespace: Option<&str>,
+        since: Option<&PackageId>,
+        limit: u16,
+    ) -> Result<Vec<PackageId>, DataStoreError> {
+        use diesel::{dsl::sql, sql_types::Text};
+
+        let mut conn = self.pool.get().await?;
+
+        // Package identifiers are compared bytewise to match the ordering of `PackageId`
+        let mut query = schema::logs::table
+            .into_boxed()
+            .select(schema::logs::name)
+            .order_by(sql::<Text>(r#"logs.name COLLATE "C""#))
+            .limit(limit as i64)
+            .filter(
+                schema::logs::name.is_not_null().and(diesel::dsl::exists(
+                    schema::records::table.filter(
+                        schema::records::log_id
+                            .eq(schema::logs::id)
+                            .and(schema::records::status.eq(RecordStatus::Validated)),
+                    ),
+                )),
+            );
+
+        if let Some(namespace) = namespace {
+            let escaped = namespace
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            query = query.filter(schema::logs::name.like(format!("{escaped}%:%")));
+        }
+
+        if let Some(since) = since {
+            query = query.filter(
+                sql::<diesel::sql_types::Bool>(r#"logs.name COLLATE "C" > "#)
+                    .bind::<Text, _>(since.as_ref()),
+            );
+        }
+
+        Ok(query
+            .load::<Option<String>>(conn.as_mut())
+            .await?
+            .into_iter()
+            .flatten()
+            .filter_map(|name| name.parse().ok())
+            .collect())
+    }
+
     async fn store_checkpoint(
         &self,
         checkpoint_id: &AnyHash,
@@ -754,30 +1125,142 @@ impl DataStore for PostgresDataStore {
         Ok(())
     }
 
+    async fn store_snapshot(&self, snapshot: &TransparencySnapshot) -> Result<(), DataStoreError> {
+        let mut conn = self.pool.get().await?;
+
+        conn.transaction::<_, DataStoreError, _>(|conn| {
+            async move {
+                // Only the latest snapshot is kept
+                diesel::delete(schema::snapshots::table)
+                    .execute(conn)
+                    .await?;
+
+                diesel::insert_into(schema::snapshots::table)
+                    .values(NewSnapshot {
+                        log_length: snapshot.log_length as i64,
+                        data: &Json(snapshot.clone()),
+                    })
+                    .execute(conn)
+                    .await?;
+
+                Ok(())
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+
+    async fn get_latest_snapshot(&self) -> Result<Option<TransparencySnapshot>, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        Ok(schema::snapshots::table
+            .select(schema::snapshots::data)
+            .order(schema::snapshots::log_length.desc())
+            .first::<Json<TransparencySnapshot>>(conn.as_mut())
+            .await
+            .optional()?
+            .map(|data| data.0))
+    }
+
     async fn get_latest_checkpoint(
         &self,
     ) -> Result<SerdeEnvelope<TimestampedCheckpoint>, DataStoreError> {
         let mut conn = self.pool.get().await?;
 
+        // A mirror has no checkpoint until it first replicates its upstream
         let checkpoint = schema::checkpoints::table
             .order_by(schema::checkpoints::id.desc())
             .first::<CheckpointData>(&mut conn)
-            .await?;
+            .await
+            .optional()?
+            .ok_or(DataStoreError::CheckpointNotFound(0))?;
 
-        let log_length = checkpoint.log_length.try_into().unwrap();
+        Ok(checkpoint_envelope(checkpoint))
+    }
 
-        Ok(SerdeEnvelope::from_parts_unchecked(
-            TimestampedCheckpoint {
-                checkpoint: Checkpoint {
-                    log_root: checkpoint.log_root.0,
-                    log_length,
-                    map_root: checkpoint.map_root.0,
-                },
-                timestamp: checkpoint.timestamp.try_into().unwrap(),
-            },
-            checkpoint.key_id.0,
-            checkpoint.signature.0,
-        ))
+    async fn get_checkpoint(
+        &self,
+        log_length: RegistryLen,
+    ) -> Result<SerdeEnvelope<TimestampedCheckpoint>, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+
+        let checkpoint = schema::checkpoints::table
+            .filter(schema::checkpoints::log_length.eq(log_length as i64))
+            .order_by(schema::checkpoints::id.desc())
+            .first::<CheckpointData>(&mut conn)
+            .await
+            .optional()?
+            .ok_or(DataStoreError::CheckpointNotFound(log_length))?;
+
+        Ok(checkpoint_envelope(checkpoint))
+    }
+
+    async fn store_cosignature(
+        &self,
+        cosignature: SerdeEnvelope<WitnessedCheckpoint>,
+    ) -> Result<(), DataStoreError> {
+        let mut conn = self.pool.get().await?;
+
+        conn.transaction::<_, DataStoreError, _>(|conn| {
+            async move {
+                let log_length = cosignature.as_ref().checkpoint.log_length as i64;
+
+                // Replacing any existing cosignature of the checkpoint by the same key
+                diesel::delete(
+                    schema::cosignatures::table
+                        .filter(schema::cosignatures::log_length.eq(log_length))
+                        .filter(schema::cosignatures::key_id.eq(TextRef(cosignature.key_id()))),
+                )
+                .execute(conn)
+                .await?;
+
+                diesel::insert_into(schema::cosignatures::table)
+                    .values(NewCosignature {
+                        log_length,
+                        key_id: TextRef(cosignature.key_id()),
+                        data: &Json(cosignature.clone()),
+                    })
+                    .execute(conn)
+                    .await?;
+
+                Ok(())
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+
+    async fn get_cosignatures(
+        &self,
+        log_length: RegistryLen,
+    ) -> Result<Vec<SerdeEnvelope<WitnessedCheckpoint>>, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        Ok(schema::cosignatures::table
+            .select(schema::cosignatures::data)
+            .filter(schema::cosignatures::log_length.eq(log_length as i64))
+            .order(schema::cosignatures::id)
+            .load::<Json<SerdeEnvelope<WitnessedCheckpoint>>>(conn.as_mut())
+            .await?
+            .into_iter()
+            .map(|data| data.0)
+            .collect())
+    }
+
+    async fn get_latest_cosignatures(
+        &self,
+    ) -> Result<Vec<SerdeEnvelope<WitnessedCheckpoint>>, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        let mut cosignatures = schema::cosignatures::table
+            .select((schema::cosignatures::log_length, schema::cosignatures::data))
+            .distinct_on(schema::cosignatures::key_id)
+            .order((
+                schema::cosignatures::key_id,
+                schema::cosignatures::log_length.desc(),
+            ))
+            .load::<(i64, Json<SerdeEnvelope<WitnessedCheckpoint>>)>(conn.as_mut())
+            .await?;
+
+        cosignatures.sort_by(|(a, _), (b, _)| b.cmp(a));
+        Ok(cosignatures.into_iter().map(|(_, data)| data.0).collect())
     }
 
     async fn get_operator_records(
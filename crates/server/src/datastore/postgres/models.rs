@@ -1,4 +1,5 @@
-use super::schema::{checkpoints, contents, logs, records};
+use super::schema::{checkpoints, contents, cosignatures, logs, records, snapshots};
+use crate::datastore::TransparencySnapshot;
 use chrono::{DateTime, Utc};
 use diesel::{
     deserialize::{self, FromSql},
@@ -14,7 +15,10 @@ use warg_crypto::{
     hash::AnyHash,
     signing::{KeyID, Signature},
 };
-use warg_protocol::registry::{LogId, RecordId};
+use warg_protocol::{
+    registry::{LogId, RecordId, WitnessedCheckpoint},
+    SerdeEnvelope,
+};
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, diesel_derive_enum::DbEnum)]
 #[ExistingTypePath = "crate::datastore::postgres::schema::sql_types::RecordStatus"]
@@ -104,7 +108,30 @@ pub struct CheckpointData {
     pub timestamp: i64,
 }
 
+#[derive(Insertable)]
+#[diesel(table_name = snapshots)]
+pub struct NewSnapshot<'a> {
+    pub log_length: i64,
+    pub data: &'a Json<TransparencySnapshot>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = cosignatures)]
+pub struct NewCosignature<'a> {
+    pub log_length: i64,
+    pub key_id: TextRef<'a, KeyID>,
+    pub data: &'a Json<SerdeEnvelope<WitnessedCheckpoint>>,
+}
+
 /// Selects only the record content and status
+#[derive(QueryableByName)]
+pub struct NamespaceContent {
+    #[diesel(sql_type = sql_types::Text)]
+    pub digest: ParsedText<AnyHash>,
+    #[diesel(sql_type = sql_types::BigInt)]
+    pub size: i64,
+}
+
 #[derive(Queryable, Selectable)]
 #[diesel(table_name = records)]
 pub struct RecordContent {
@@ -21,6 +21,16 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    cosignatures (id) {
+        id -> Int4,
+        log_length -> Int8,
+        key_id -> Text,
+        data -> Jsonb,
+        created_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     contents (id) {
         id -> Int4,
@@ -29,6 +39,7 @@ diesel::table! {
         missing -> Bool,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
+        size -> Nullable<Int8>,
     }
 }
 
@@ -60,7 +71,23 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    snapshots (id) {
+        id -> Int4,
+        log_length -> Int8,
+        data -> Jsonb,
+        created_at -> Timestamptz,
+    }
+}
+
 diesel::joinable!(contents -> records (record_id));
 diesel::joinable!(records -> logs (log_id));
 
-diesel::allow_tables_to_appear_in_same_query!(checkpoints, contents, logs, records,);
+diesel::allow_tables_to_appear_in_same_query!(
+    checkpoints,
+    contents,
+    cosignatures,
+    logs,
+    records,
+    snapshots,
+);
@@ -1,10 +1,14 @@
-use crate::{api::create_router, datastore::MemoryDataStore};
+use crate::{
+    api::create_router,
+    contentstore::{ContentStore, FileSystemContentStore},
+    datastore::MemoryDataStore,
+};
 use anyhow::{Context, Result};
 use axum::Router;
 use datastore::DataStore;
 use futures::Future;
 use policy::{content::ContentPolicy, record::RecordPolicy};
-use services::CoreService;
+use services::{CoreService, GarbageCollector, Mirror};
 use std::{
     fs,
     net::{SocketAddr, TcpListener},
@@ -15,52 +19,88 @@ use std::{
 };
 use tokio::task::JoinHandle;
 use url::Url;
-use warg_crypto::signing::PrivateKey;
+use warg_crypto::signing::{PrivateKey, PublicKey};
 
 pub mod api;
 pub mod args;
+pub mod contentstore;
 pub mod datastore;
 pub mod policy;
 pub mod services;
 
 const DEFAULT_BIND_ADDRESS: &str = "127.0.0.1:8090";
 const DEFAULT_CHECKPOINT_INTERVAL: Duration = Duration::from_secs(5);
+const DEFAULT_PENDING_RECORD_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+const DEFAULT_SNAPSHOT_THRESHOLD: usize = 10_000;
+const DEFAULT_CONTENT_GC_GRACE_PERIOD: Duration = Duration::from_secs(24 * 60 * 60);
+const DEFAULT_MIRROR_INTERVAL: Duration = Duration::from_secs(5);
 
 type ShutdownFut = Pin<Box<dyn Future<Output = ()> + Send + Sync>>;
 
 /// The server configuration.
 pub struct Config {
-    operator_key: PrivateKey,
+    operator_key: Option<PrivateKey>,
+    replacement_operator_key: Option<PrivateKey>,
+    mirror: Option<Url>,
+    mirror_interval: Option<Duration>,
     addr: Option<SocketAddr>,
     data_store: Option<Box<dyn DataStore>>,
+    content_store: Option<Arc<dyn ContentStore>>,
     content_dir: PathBuf,
     content_base_url: Option<Url>,
     shutdown: Option<ShutdownFut>,
     checkpoint_interval: Option<Duration>,
+    pending_record_ttl: Option<Duration>,
+    snapshot_threshold: Option<usize>,
     content_policy: Option<Arc<dyn ContentPolicy>>,
+    namespace_quota: Option<u64>,
     record_policy: Option<Arc<dyn RecordPolicy>>,
+    witness_keys: Vec<PublicKey>,
+    content_gc_interval: Option<Duration>,
+    content_gc_grace_period: Option<Duration>,
+    content_gc_dry_run: bool,
 }
 
 impl std::fmt::Debug for Config {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Config")
-            .field("operator_key", &"<redacted>")
+            .field(
+                "operator_key",
+                &self.operator_key.as_ref().map(|_| "<redacted>"),
+            )
+            .field(
+                "replacement_operator_key",
+                &self.replacement_operator_key.as_ref().map(|_| "<redacted>"),
+            )
+            .field("mirror", &self.mirror)
+            .field("mirror_interval", &self.mirror_interval)
             .field("addr", &self.addr)
             .field(
                 "data_store",
                 &self.data_store.as_ref().map(|_| "dyn DataStore"),
             )
+            .field(
+                "content_store",
+                &self.content_store.as_ref().map(|_| "dyn ContentStore"),
+            )
             .field("content_dir", &self.content_dir)
             .field("shutdown", &self.shutdown.as_ref().map(|_| "dyn Future"))
             .field("checkpoint_interval", &self.checkpoint_interval)
+            .field("pending_record_ttl", &self.pending_record_ttl)
+            .field("snapshot_threshold", &self.snapshot_threshold)
             .field(
                 "content_policy",
                 &self.content_policy.as_ref().map(|_| "dyn ContentPolicy"),
             )
+            .field("namespace_quota", &self.namespace_quota)
             .field(
                 "record_policy",
                 &self.record_policy.as_ref().map(|_| "dyn RecordPolicy"),
             )
+            .field("witness_keys", &self.witness_keys)
+            .field("content_gc_interval", &self.content_gc_interval)
+            .field("content_gc_grace_period", &self.content_gc_grace_period)
+            .field("content_gc_dry_run", &self.content_gc_dry_run)
             .finish()
     }
 }
@@ -68,19 +108,58 @@ impl std::fmt::Debug for Config {
 impl Config {
     /// Creates a new server configuration.
     pub fn new(operator_key: PrivateKey, content_dir: PathBuf) -> Self {
+        Self::with_source(Some(operator_key), None, content_dir)
+    }
+
+    /// Creates a new configuration of a server that mirrors the registry at
+    /// the given URL.
+    ///
+    /// A mirror has no operator key; it serves the records, content, and
+    /// checkpoints replicated from the upstream registry and rejects
+    /// publishing.
+    pub fn mirror(upstream: Url, content_dir: PathBuf) -> Self {
+        Self::with_source(None, Some(upstream), content_dir)
+    }
+
+    fn with_source(
+        operator_key: Option<PrivateKey>,
+        mirror: Option<Url>,
+        content_dir: PathBuf,
+    ) -> Self {
         Self {
             operator_key,
+            replacement_operator_key: None,
+            mirror,
+            mirror_interval: None,
             addr: None,
             data_store: None,
+            content_store: None,
             content_dir,
             content_base_url: None,
             shutdown: None,
             checkpoint_interval: None,
+            pending_record_ttl: None,
+            snapshot_threshold: None,
             content_policy: None,
+            namespace_quota: None,
             record_policy: None,
+            witness_keys: Vec::new(),
+            content_gc_interval: None,
+            content_gc_grace_period: None,
+            content_gc_dry_run: false,
         }
     }
 
+    /// Sets the key that signs checkpoints once the operator key no longer
+    /// has the commit permission.
+    ///
+    /// Without a replacement key, operator records that revoke the commit
+    /// permission of the operator key or rotate it are rejected.
+    pub fn with_replacement_operator_key(mut self, key: PrivateKey) -> Self {
+        self.replacement_operator_key = Some(key);
+        self
+    }
+
     /// Specify the address for the server to listen on.
     pub fn with_addr(mut self, addr: impl Into<SocketAddr>) -> Self {
         self.addr = Some(addr.into());
@@ -111,6 +190,15 @@ impl Config {
         self
     }
 
+    /// Specify the content store to use.
+    ///
+    /// If this is not specified, the server will store content in the
+    /// content directory.
+    pub fn with_content_store(mut self, store: impl ContentStore + 'static) -> Self {
+        self.content_store = Some(Arc::new(store));
+        self
+    }
+
     /// Specifies the future to wait on to shutdown the server.
     ///
     /// If the future completes, the server will initiate a graceful shutdown.
@@ -128,17 +216,90 @@ impl Config {
         self
     }
 
+    /// Sets the time after which package records that are still pending
+    /// (i.e. missing content) are rejected.
+    ///
+    /// Defaults to 24 hours.
+    pub fn with_pending_record_ttl(mut self, ttl: Duration) -> Self {
+        self.pending_record_ttl = Some(ttl);
+        self
+    }
+
+    /// Sets the number of new registry log entries after which a snapshot of
+    /// the transparency state is stored; a threshold of zero disables
+    /// snapshots.
+    ///
+    /// The latest snapshot is used to restore the state on startup instead of
+    /// replaying every record. Defaults to 10,000 entries.
+    pub fn with_snapshot_threshold(mut self, threshold: usize) -> Self {
+        self.snapshot_threshold = Some(threshold);
+        self
+    }
+
+    /// Sets the interval at which a mirror replicates the upstream registry.
+    ///
+    /// Defaults to 5 seconds.
+    pub fn with_mirror_interval(mut self, interval: Duration) -> Self {
+        self.mirror_interval = Some(interval);
+        self
+    }
+
     /// Sets the content policy to use for the server.
     pub fn with_content_policy(mut self, policy: impl ContentPolicy + 'static) -> Self {
         self.content_policy = Some(Arc::new(policy));
         self
     }
 
+    /// Sets the storage quota, in bytes, of each namespace.
+    ///
+    /// Uploads of content that would exceed the quota of the package's
+    /// namespace are rejected.
+    pub fn with_namespace_quota(mut self, quota: u64) -> Self {
+        self.namespace_quota = Some(quota);
+        self
+    }
+
     /// Sets the record policy to use for the server.
     pub fn with_record_policy(mut self, policy: impl RecordPolicy + 'static) -> Self {
         self.record_policy = Some(Arc::new(policy));
         self
     }
+
+    /// Sets the public keys of the witnesses permitted to publish
+    /// cosignatures of the server's checkpoints.
+    ///
+    /// If no keys are specified, cosignatures are rejected.
+    pub fn with_witness_keys(mut self, keys: impl IntoIterator<Item = PublicKey>) -> Self {
+        self.witness_keys = keys.into_iter().collect();
+        self
+    }
+
+    /// Sets the interval at which content not referenced by any package
+    /// record is garbage collected.
+    ///
+    /// If this is not specified, content is never garbage collected.
+    pub fn with_content_gc_interval(mut self, interval: Duration) -> Self {
+        self.content_gc_interval = Some(interval);
+        self
+    }
+
+    /// Sets the grace period of content garbage collection.
+    ///
+    /// Content of pending records submitted within the grace period, and
+    /// content stored within the grace period, is not collected.
+    ///
+    /// Defaults to 24 hours.
+    pub fn with_content_gc_grace_period(mut self, grace_period: Duration) -> Self {
+        self.content_gc_grace_period = Some(grace_period);
+        self
+    }
+
+    /// Sets whether content garbage collection only reports unreferenced
+    /// content rather than deleting it.
+    pub fn with_content_gc_dry_run(mut self, dry_run: bool) -> Self {
+        self.content_gc_dry_run = dry_run;
+        self
+    }
 }
 
 /// Represents the warg registry server.
@@ -185,14 +346,32 @@ impl Server {
             .config
             .data_store
             .unwrap_or_else(|| Box::<MemoryDataStore>::default());
-        let (core, core_handle) = CoreService::start(
-            self.config.operator_key,
-            store,
+        let snapshot_threshold = Some(
             self.config
-                .checkpoint_interval
-                .unwrap_or(DEFAULT_CHECKPOINT_INTERVAL),
+                .snapshot_threshold
+                .unwrap_or(DEFAULT_SNAPSHOT_THRESHOLD),
         )
-        .await?;
+        .filter(|threshold| *threshold > 0);
+        let (core, core_handle) = match self.config.operator_key {
+            Some(operator_key) => {
+                CoreService::start(
+                    operator_key,
+                    self.config.replacement_operator_key,
+                    store,
+                    self.config
+                        .checkpoint_interval
+                        .unwrap_or(DEFAULT_CHECKPOINT_INTERVAL),
+                    Some(
+                        self.config
+                            .pending_record_ttl
+                            .unwrap_or(DEFAULT_PENDING_RECORD_TTL),
+                    ),
+                    snapshot_threshold,
+                )
+                .await?
+            }
+            None => CoreService::start_mirror(store, snapshot_threshold).await?,
+        };
 
         let temp_dir = self.config.content_dir.join("tmp");
         fs::create_dir_all(&temp_dir).with_context(|| {
@@ -210,24 +389,66 @@ impl Server {
             )
         })?;
 
-        let content_base_url = self
-            .config
-            .content_base_url
-            .unwrap_or_else(|| Url::parse(&format!("http://{addr}")).unwrap());
+        let content_store = match self.config.content_store {
+            Some(store) => store,
+            None => {
+                let content_base_url = self
+                    .config
+                    .content_base_url
+                    .unwrap_or_else(|| Url::parse(&format!("http://{addr}")).unwrap());
+                Arc::new(FileSystemContentStore::new(files_dir, content_base_url))
+            }
+        };
+
+        let gc_handle = self.config.content_gc_interval.map(|interval| {
+            GarbageCollector::new(
+                core.clone(),
+                content_store.clone(),
+                self.config
+                    .content_gc_grace_period
+                    .unwrap_or(DEFAULT_CONTENT_GC_GRACE_PERIOD),
+            )
+            .with_dry_run(self.config.content_gc_dry_run)
+            .start(interval)
+        });
+
+        let mirror_handle = match self.config.mirror {
+            Some(upstream) => {
+                tracing::info!("mirroring upstream registry `{upstream}`");
+                let client = warg_client::api::Client::new(upstream)?;
+                Some(
+                    Mirror::new(
+                        core.clone(),
+                        content_store.clone(),
+                        temp_dir.clone(),
+                        client,
+                    )
+                    .start(
+                        self.config
+                            .mirror_interval
+                            .unwrap_or(DEFAULT_MIRROR_INTERVAL),
+                    ),
+                )
+            }
+            None => None,
+        };
 
         let router = create_router(
-            content_base_url,
+            content_store,
             core,
             temp_dir,
-            files_dir,
             self.config.content_policy,
+            self.config.namespace_quota,
             self.config.record_policy,
+            self.config.witness_keys,
         );
 
         Ok(InitializedServer {
             listener,
             router,
             core_handle,
+            gc_handle,
+            mirror_handle,
             shutdown: self.config.shutdown,
         })
     }
@@ -238,6 +459,8 @@ pub struct InitializedServer {
     listener: TcpListener,
     router: Router,
     core_handle: JoinHandle<()>,
+    gc_handle: Option<JoinHandle<()>>,
+    mirror_handle: Option<JoinHandle<()>>,
     shutdown: Option<ShutdownFut>,
 }
 
@@ -265,6 +488,12 @@ impl InitializedServer {
             server.await?;
         }
 
+        // The garbage collector and mirror hold handles to the core service
+        for handle in [self.gc_handle, self.mirror_handle].into_iter().flatten() {
+            handle.abort();
+            let _ = handle.await;
+        }
+
         tracing::info!("waiting for core service to stop");
         self.core_handle.await?;
 
@@ -2,8 +2,10 @@
 use thiserror::Error;
 use warg_crypto::hash::AnyHash;
 
+mod size;
 mod wasm;
 
+pub use size::*;
 pub use wasm::*;
 
 /// Represents a content policy error.
@@ -66,6 +68,11 @@ impl ContentPolicyCollection {
     pub fn push(&mut self, policy: impl ContentPolicy + 'static) {
         self.policies.push(Box::new(policy));
     }
+
+    /// Determines if the collection has no policies.
+    pub fn is_empty(&self) -> bool {
+        self.policies.is_empty()
+    }
 }
 
 impl ContentPolicy for ContentPolicyCollection {
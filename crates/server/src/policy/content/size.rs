@@ -0,0 +1,75 @@
+use super::{ContentPolicy, ContentPolicyError, ContentPolicyResult, ContentStreamPolicy};
+use warg_crypto::hash::AnyHash;
+
+/// A policy that limits the size of uploaded content.
+pub struct SizeContentPolicy {
+    max_size: u64,
+}
+
+impl SizeContentPolicy {
+    /// Creates a new size content policy with the given maximum
+    /// content size, in bytes.
+    pub fn new(max_size: u64) -> Self {
+        Self { max_size }
+    }
+}
+
+impl ContentPolicy for SizeContentPolicy {
+    fn new_stream_policy(
+        &self,
+        _digest: &AnyHash,
+    ) -> ContentPolicyResult<Box<dyn ContentStreamPolicy>> {
+        Ok(Box::new(SizeContentStreamPolicy {
+            size: 0,
+            max_size: self.max_size,
+        }))
+    }
+}
+
+struct SizeContentStreamPolicy {
+    size: u64,
+    max_size: u64,
+}
+
+impl ContentStreamPolicy for SizeContentStreamPolicy {
+    fn check(&mut self, bytes: &[u8]) -> ContentPolicyResult<()> {
+        self.size = self.size.saturating_add(bytes.len() as u64);
+        if self.size > self.max_size {
+            return Err(ContentPolicyError::Rejection(format!(
+                "content exceeds the maximum size of {max} bytes",
+                max = self.max_size
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> ContentPolicyResult<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use warg_crypto::hash::HashAlgorithm;
+
+    #[test]
+    fn it_rejects_content_over_the_limit() {
+        let policy = SizeContentPolicy::new(10);
+        let digest = HashAlgorithm::Sha256.digest(&[]);
+
+        let mut stream = policy.new_stream_policy(&digest).unwrap();
+        stream.check(&[0; 4]).unwrap();
+        stream.check(&[0; 6]).unwrap();
+        stream.finalize().unwrap();
+
+        let mut stream = policy.new_stream_policy(&digest).unwrap();
+        stream.check(&[0; 4]).unwrap();
+        match stream.check(&[0; 7]).unwrap_err() {
+            ContentPolicyError::Rejection(message) => {
+                assert_eq!(message, "content exceeds the maximum size of 10 bytes")
+            }
+        }
+    }
+}
@@ -1,9 +1,17 @@
 //! Module for server record policy implementations.
 use thiserror::Error;
-use warg_protocol::{package::PackageRecord, registry::PackageId, ProtoEnvelope};
+use warg_crypto::hash::AnyHash;
+use warg_protocol::{
+    package::{LogState, PackageRecord},
+    registry::PackageId,
+    ProtoEnvelope,
+};
 
 mod authorization;
+mod wit;
+
 pub use authorization::*;
+pub use wit::*;
 
 /// Represents a record policy error.
 #[derive(Debug, Error)]
@@ -30,6 +38,22 @@ pub trait RecordPolicy: Send + Sync {
         id: &PackageId,
         record: &ProtoEnvelope<PackageRecord>,
     ) -> RecordPolicyResult<()>;
+
+    /// Checks the record against the policy once all of its content is present.
+    ///
+    /// The given state is the state of the package log prior to the record
+    /// and `load_content` loads the stored content with the given digest.
+    ///
+    /// The default implementation accepts the record.
+    fn check_content(
+        &self,
+        _id: &PackageId,
+        _state: &LogState,
+        _record: &ProtoEnvelope<PackageRecord>,
+        _load_content: &dyn Fn(&AnyHash) -> std::io::Result<Vec<u8>>,
+    ) -> RecordPolicyResult<()> {
+        Ok(())
+    }
 }
 
 /// Represents a collection of record policies.
@@ -51,6 +75,11 @@ impl RecordPolicyCollection {
     pub fn push(&mut self, policy: impl RecordPolicy + 'static) {
         self.policies.push(Box::new(policy));
     }
+
+    /// Determines if the collection has no policies.
+    pub fn is_empty(&self) -> bool {
+        self.policies.is_empty()
+    }
 }
 
 impl RecordPolicy for RecordPolicyCollection {
@@ -65,4 +94,18 @@ impl RecordPolicy for RecordPolicyCollection {
 
         Ok(())
     }
+
+    fn check_content(
+        &self,
+        id: &PackageId,
+        state: &LogState,
+        record: &ProtoEnvelope<PackageRecord>,
+        load_content: &dyn Fn(&AnyHash) -> std::io::Result<Vec<u8>>,
+    ) -> RecordPolicyResult<()> {
+        for policy in &self.policies {
+            policy.check_content(id, state, record, load_content)?;
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,412 @@
+use super::{RecordPolicy, RecordPolicyError, RecordPolicyResult};
+use anyhow::{anyhow, Result};
+use warg_crypto::hash::AnyHash;
+use warg_protocol::{
+    package::{LogState, PackageEntry, PackageRecord, Release},
+    registry::PackageId,
+    ProtoEnvelope, Version,
+};
+use wasmparser::{
+    types::{ComponentEntityType, Types},
+    Parser, Payload, ValidPayload, Validator, WasmFeatures,
+};
+
+/// A policy that ensures a new release of a component remains compatible
+/// with the interface of the previous release in the same semver line.
+///
+/// Every export of the previous non-yanked release must be present in the
+/// new release with a compatible type.
+///
+/// Releases of content that is not a component are not checked.
+pub struct WitCompatibilityPolicy {
+    features: WasmFeatures,
+}
+
+impl WitCompatibilityPolicy {
+    /// Creates a new WIT compatibility policy.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the WebAssembly features to use when decoding components.
+    pub fn with_features(mut self, mut features: WasmFeatures) -> Self {
+        // Always allow the component model feature
+        features.component_model = true;
+        self.features = features;
+        self
+    }
+
+    fn check_release(
+        &self,
+        state: &LogState,
+        version: &Version,
+        content: &AnyHash,
+        load_content: &dyn Fn(&AnyHash) -> std::io::Result<Vec<u8>>,
+    ) -> RecordPolicyResult<()> {
+        let Some(previous) = previous_release(state, version) else {
+            return Ok(());
+        };
+
+        let Some(previous_content) = previous.content() else {
+            return Ok(());
+        };
+
+        let load = |digest: &AnyHash| {
+            load_content(digest).map_err(|e| {
+                RecordPolicyError::Rejection(format!(
+                    "failed to load content with digest `{digest}`: {e}"
+                ))
+            })
+        };
+
+        let previous_bytes = load(previous_content)?;
+        if !Parser::is_component(&previous_bytes) {
+            return Ok(());
+        }
+
+        let previous_exports =
+            ComponentExports::new(&previous_bytes, self.features).map_err(|e| {
+                RecordPolicyError::Rejection(format!(
+                    "release {previous} is not a valid component: {e}",
+                    previous = previous.version
+                ))
+            })?;
+
+        let bytes = load(content)?;
+        if !Parser::is_component(&bytes) {
+            return Err(RecordPolicyError::Rejection(format!(
+                "release {version} is not a component but release {previous} is",
+                previous = previous.version
+            )));
+        }
+
+        let exports = ComponentExports::new(&bytes, self.features).map_err(|e| {
+            RecordPolicyError::Rejection(format!("release {version} is not a valid component: {e}"))
+        })?;
+
+        for name in &previous_exports.names {
+            let Some(previous_ty) = previous_exports.types.component_entity_type_of_export(name)
+            else {
+                return Err(RecordPolicyError::Rejection(format!(
+                    "export `{name}` of release {previous} has no type",
+                    previous = previous.version
+                )));
+            };
+
+            match exports.types.component_entity_type_of_export(name) {
+                Some(ty) => {
+                    if !ComponentEntityType::is_subtype_of(
+                        &ty,
+                        exports.types.as_ref(),
+                        &previous_ty,
+                        previous_exports.types.as_ref(),
+                    ) {
+                        return Err(RecordPolicyError::Rejection(format!(
+                            "export `{name}` of release {version} is incompatible with release {previous}",
+                            previous = previous.version
+                        )));
+                    }
+                }
+                None => {
+                    return Err(RecordPolicyError::Rejection(format!(
+                        "release {version} is missing export `{name}` of release {previous}",
+                        previous = previous.version
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for WitCompatibilityPolicy {
+    fn default() -> Self {
+        Self {
+            features: WasmFeatures {
+                component_model: true,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl RecordPolicy for WitCompatibilityPolicy {
+    fn check(
+        &self,
+        _id: &PackageId,
+        _record: &ProtoEnvelope<PackageRecord>,
+    ) -> RecordPolicyResult<()> {
+        Ok(())
+    }
+
+    fn check_content(
+        &self,
+        _id: &PackageId,
+        state: &LogState,
+        record: &ProtoEnvelope<PackageRecord>,
+        load_content: &dyn Fn(&AnyHash) -> std::io::Result<Vec<u8>>,
+    ) -> RecordPolicyResult<()> {
+        for entry in &record.as_ref().entries {
+            if let PackageEntry::Release { version, content } = entry {
+                self.check_release(state, version, content, load_content)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Finds the latest non-yanked release that precedes the given version
+/// in the same semver line.
+///
+/// Pre-release versions are not considered.
+fn previous_release<'a>(state: &'a LogState, version: &Version) -> Option<&'a Release> {
+    if !version.pre.is_empty() {
+        return None;
+    }
+
+    state
+        .releases()
+        .filter(|release| {
+            !release.yanked()
+                && release.version.pre.is_empty()
+                && release.version < *version
+                && same_line(&release.version, version)
+        })
+        .max_by(|a, b| a.version.cmp(&b.version))
+}
+
+/// Determines if two versions are in the same semver line.
+///
+/// For versions prior to 1.0.0, the minor version determines the line;
+/// versions prior to 0.1.0 are never in the same line.
+fn same_line(a: &Version, b: &Version) -> bool {
+    match (a.major, b.major) {
+        (0, 0) => a.minor != 0 && a.minor == b.minor,
+        (a, b) => a == b,
+    }
+}
+
+/// Represents the exports of a validated component.
+struct ComponentExports {
+    types: Types,
+    names: Vec<String>,
+}
+
+impl ComponentExports {
+    fn new(bytes: &[u8], features: WasmFeatures) -> Result<Self> {
+        let mut validator = Validator::new_with_features(features);
+        let mut types = None;
+        let mut names = Vec::new();
+        let mut depth = 0usize;
+
+        // Function bodies are not validated as only the type information is needed
+        for payload in Parser::new(0).parse_all(bytes) {
+            let payload = payload?;
+            if let ValidPayload::End(t) = validator.payload(&payload)? {
+                // Only the last (top-level) type information is kept
+                types = Some(t);
+            }
+
+            match payload {
+                Payload::ModuleSection { .. } | Payload::ComponentSection { .. } => depth += 1,
+                Payload::End(_) => depth = depth.saturating_sub(1),
+                Payload::ComponentExportSection(reader) if depth == 0 => {
+                    for export in reader {
+                        names.push(export?.name.as_str().to_string());
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            types: types.ok_or_else(|| anyhow!("the component has no type information"))?,
+            names,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{collections::HashMap, time::SystemTime};
+    use warg_crypto::{hash::HashAlgorithm, signing::generate_p256_pair};
+    use warg_protocol::package::PACKAGE_RECORD_VERSION;
+    use wasm_encoder::{
+        Component, ComponentExportKind, ComponentExportSection, ComponentImportSection,
+        ComponentTypeRef, ComponentTypeSection, PrimitiveValType,
+    };
+
+    /// Creates a component that re-exports imported functions with the given parameter types.
+    fn component(funcs: &[(&str, PrimitiveValType)]) -> Vec<u8> {
+        let mut types = ComponentTypeSection::new();
+        let mut imports = ComponentImportSection::new();
+        let mut exports = ComponentExportSection::new();
+        for (i, (name, ty)) in funcs.iter().enumerate() {
+            types.function().params([("x", *ty)]).result(*ty);
+            imports.import(
+                format!("import-{name}").as_str(),
+                ComponentTypeRef::Func(i as u32),
+            );
+            exports.export(name, ComponentExportKind::Func, i as u32, None);
+        }
+
+        let mut component = Component::new();
+        component.section(&types);
+        component.section(&imports);
+        component.section(&exports);
+        component.finish()
+    }
+
+    struct Log {
+        state: LogState,
+        content: HashMap<AnyHash, Vec<u8>>,
+        key: warg_crypto::signing::PrivateKey,
+        prev: Option<warg_protocol::registry::RecordId>,
+        timestamp: SystemTime,
+    }
+
+    impl Log {
+        fn new() -> Self {
+            let (public_key, key) = generate_p256_pair();
+            let mut log = Self {
+                state: LogState::new(),
+                content: HashMap::new(),
+                key,
+                prev: None,
+                timestamp: SystemTime::now(),
+            };
+
+            let record = log.record(vec![PackageEntry::Init {
+                hash_algorithm: HashAlgorithm::Sha256,
+                key: public_key,
+            }]);
+            log.append(&record);
+            log
+        }
+
+        fn record(&mut self, entries: Vec<PackageEntry>) -> ProtoEnvelope<PackageRecord> {
+            self.timestamp += std::time::Duration::from_secs(1);
+            ProtoEnvelope::signed_contents(
+                &self.key,
+                PackageRecord {
+                    prev: self.prev.clone(),
+                    version: PACKAGE_RECORD_VERSION,
+                    timestamp: self.timestamp,
+                    entries,
+                },
+            )
+            .unwrap()
+        }
+
+        fn append(&mut self, record: &ProtoEnvelope<PackageRecord>) {
+            self.state.validate(record).unwrap();
+            self.prev = Some(self.state.head().as_ref().unwrap().digest.clone());
+        }
+
+        fn release(&mut self, version: &str, bytes: Vec<u8>) -> ProtoEnvelope<PackageRecord> {
+            let content = HashAlgorithm::Sha256.digest(&bytes);
+            self.content.insert(content.clone(), bytes);
+            self.record(vec![PackageEntry::Release {
+                version: version.parse().unwrap(),
+                content,
+            }])
+        }
+
+        fn check(&self, record: &ProtoEnvelope<PackageRecord>) -> RecordPolicyResult<()> {
+            WitCompatibilityPolicy::new().check_content(
+                &"test:package".parse().unwrap(),
+                &self.state,
+                record,
+                &|digest| Ok(self.content[digest].clone()),
+            )
+        }
+    }
+
+    #[test]
+    fn it_accepts_compatible_releases() {
+        let mut log = Log::new();
+        let record = log.release("1.0.0", component(&[("a", PrimitiveValType::U32)]));
+        log.check(&record).unwrap();
+        log.append(&record);
+
+        // Adding an export is compatible
+        let record = log.release(
+            "1.1.0",
+            component(&[("a", PrimitiveValType::U32), ("b", PrimitiveValType::U8)]),
+        );
+        log.check(&record).unwrap();
+        log.append(&record);
+
+        // A new major version may break compatibility
+        let record = log.release("2.0.0", component(&[("b", PrimitiveValType::U8)]));
+        log.check(&record).unwrap();
+    }
+
+    #[test]
+    fn it_rejects_incompatible_releases() {
+        let mut log = Log::new();
+        let record = log.release(
+            "1.0.0",
+            component(&[("a", PrimitiveValType::U32), ("b", PrimitiveValType::U8)]),
+        );
+        log.append(&record);
+
+        let record = log.release("1.1.0", component(&[("b", PrimitiveValType::U8)]));
+        match log.check(&record).unwrap_err() {
+            RecordPolicyError::Rejection(message) => assert_eq!(
+                message,
+                "release 1.1.0 is missing export `a` of release 1.0.0"
+            ),
+            _ => panic!("expected a rejection"),
+        }
+
+        let record = log.release(
+            "1.1.0",
+            component(&[("a", PrimitiveValType::String), ("b", PrimitiveValType::U8)]),
+        );
+        match log.check(&record).unwrap_err() {
+            RecordPolicyError::Rejection(message) => assert_eq!(
+                message,
+                "export `a` of release 1.1.0 is incompatible with release 1.0.0"
+            ),
+            _ => panic!("expected a rejection"),
+        }
+    }
+
+    #[test]
+    fn it_rejects_invalid_components() {
+        let mut log = Log::new();
+        let record = log.release("1.0.0", component(&[("a", PrimitiveValType::U32)]));
+        log.append(&record);
+
+        let mut bytes = component(&[("a", PrimitiveValType::U32)]);
+        bytes.truncate(bytes.len() - 1);
+        let record = log.release("1.1.0", bytes);
+        match log.check(&record).unwrap_err() {
+            RecordPolicyError::Rejection(message) => {
+                assert!(message.starts_with("release 1.1.0 is not a valid component"))
+            }
+            _ => panic!("expected a rejection"),
+        }
+    }
+
+    #[test]
+    fn it_ignores_yanked_releases() {
+        let mut log = Log::new();
+        let record = log.release("1.0.0", component(&[("a", PrimitiveValType::U32)]));
+        log.append(&record);
+        let record = log.release("1.1.0", component(&[("b", PrimitiveValType::U32)]));
+        log.append(&record);
+        let record = log.record(vec![PackageEntry::Yank {
+            version: "1.1.0".parse().unwrap(),
+        }]);
+        log.append(&record);
+
+        // Compatibility is checked against 1.0.0 as 1.1.0 was yanked
+        let record = log.release("1.2.0", component(&[("a", PrimitiveValType::U32)]));
+        log.check(&record).unwrap();
+    }
+}
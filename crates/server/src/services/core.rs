@@ -1,19 +1,22 @@
 use std::{
     collections::HashMap,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
     time::{Duration, SystemTime},
 };
 
 use futures::{pin_mut, StreamExt};
 use thiserror::Error;
 use tokio::{
-    sync::{mpsc, RwLock},
+    sync::{broadcast, mpsc, RwLock},
     task::JoinHandle,
-    time::MissedTickBehavior,
+    time::{Instant, MissedTickBehavior},
 };
 use warg_crypto::{
     hash::{AnyHash, Hash, Sha256, SupportedDigest},
-    signing::PrivateKey,
+    signing::{KeyID, PrivateKey},
 };
 use warg_protocol::{
     operator,
@@ -28,13 +31,92 @@ use warg_transparency::{
     map::{Map, MapProofBundle},
 };
 
-use crate::datastore::{DataStore, DataStoreError};
+use crate::datastore::{DataStore, DataStoreError, TransparencySnapshot};
+
+/// The number of updates buffered for each subscriber of the service.
+const UPDATES_CAPACITY: usize = 16;
+
+/// The maximum interval at which pending records are checked for expiry.
+const MAX_PENDING_RECORD_EXPIRY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The reason given for rejecting an expired pending record.
+const PENDING_RECORD_EXPIRED_REASON: &str = "content upload timed out";
+
+/// The maximum number of attempts made to commit a submitted record.
+///
+/// A record that fails to commit after all attempts is rejected.
+const MAX_COMMIT_ATTEMPTS: u32 = 5;
+
+/// The reason given for rejecting a record that failed to commit.
+const COMMIT_FAILED_REASON: &str = "the registry failed to commit the record";
+
+/// The delay before the first retry of a failed commit.
+///
+/// The delay doubles with each subsequent retry.
+const INITIAL_COMMIT_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// The maximum delay before retrying a failed commit.
+const MAX_COMMIT_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// The maximum number of package records committed in a single batch.
+const MAX_COMMIT_BATCH_SIZE: usize = 256;
+
+/// Represents an update produced by the core service.
+#[derive(Debug, Clone)]
+pub enum CoreUpdate {
+    /// A new checkpoint was signed and stored.
+    Checkpoint(SerdeEnvelope<TimestampedCheckpoint>),
+    /// A submitted package record was rejected.
+    RecordRejected(LogLeaf),
+}
+
+/// Represents metrics of the queue of package records submitted to the
+/// core service.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CommitQueueMetrics {
+    /// The number of submitted records that have yet to be committed or
+    /// rejected.
+    pub depth: u64,
+    /// The number of submitted records deferred until a failed commit is
+    /// retried.
+    pub deferred: u64,
+    /// The total number of retried commits.
+    pub retries_total: u64,
+    /// The total number of records that failed to commit after all attempts.
+    pub failures_total: u64,
+}
+
+#[derive(Default)]
+struct CommitQueueCounters {
+    depth: AtomicU64,
+    deferred: AtomicU64,
+    retries_total: AtomicU64,
+    failures_total: AtomicU64,
+}
+
+/// Represents a submitted package entry in the commit queue.
+struct QueuedEntry {
+    leaf: LogLeaf,
+    attempts: u32,
+    ready_at: Instant,
+}
+
+impl QueuedEntry {
+    fn new(leaf: LogLeaf) -> Self {
+        Self {
+            leaf,
+            attempts: 0,
+            ready_at: Instant::now(),
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct CoreService<Digest: SupportedDigest = Sha256> {
     inner: Arc<Inner<Digest>>,
 
-    // Channel sender used by `submit_package_record` to serialize submissions.
+    // Channel sender used by `submit_package_record` and `submit_operator_record`
+    // to serialize submissions.
     submit_entry_tx: mpsc::Sender<LogLeaf>,
 }
 
@@ -42,27 +124,64 @@ impl<Digest: SupportedDigest> CoreService<Digest> {
     /// Starts the `CoreService`, returning a `clone`able handle to the
     /// service and a [`JoinHandle`] which should be awaited after dropping all
     /// copies of the service handle to allow for graceful shutdown.
+    ///
+    /// Checkpoints are signed with `operator_key` while it has the commit
+    /// permission in the operator log, and otherwise with `replacement_key`;
+    /// operator records that would leave neither key with the commit
+    /// permission are rejected.
+    ///
+    /// If `pending_record_ttl` is set, package records that are still pending
+    /// after the TTL are rejected.
+    ///
+    /// Package records left pending with all of their content present (e.g.
+    /// when the server was previously stopped) are submitted again.
+    ///
+    /// If `snapshot_threshold` is set, a snapshot of the transparency state is
+    /// stored at the first checkpoint after the registry log grows by the
+    /// given number of entries; the latest snapshot is used to restore the
+    /// state on startup.
     pub async fn start(
         operator_key: PrivateKey,
+        replacement_key: Option<PrivateKey>,
         store: Box<dyn DataStore>,
         checkpoint_interval: Duration,
+        pending_record_ttl: Option<Duration>,
+        snapshot_threshold: Option<RegistryLen>,
     ) -> Result<(Self, JoinHandle<()>), CoreServiceError> {
         // Build service
         let mut inner = Inner {
-            operator_key,
+            operator_key: Some(operator_key),
+            replacement_key,
             store,
             state: Default::default(),
+            updates: broadcast::channel(UPDATES_CAPACITY).0,
+            queue: Default::default(),
+            snapshot_threshold,
         };
         inner.initialize().await?;
+        inner.signing_key().await?;
+
+        let recovered = inner.store.get_ready_package_records().await?;
+        if !recovered.is_empty() {
+            tracing::info!(
+                "resubmitting {len} pending package record(s)",
+                len = recovered.len()
+            );
+        }
+        inner
+            .queue
+            .depth
+            .store(recovered.len() as u64, Ordering::Relaxed);
 
         // Spawn state update task
         let inner = Arc::new(inner);
         let (submit_entry_tx, submit_entry_rx) = tokio::sync::mpsc::channel(4);
-        let handle = tokio::spawn(
-            inner
-                .clone()
-                .process_state_updates(submit_entry_rx, checkpoint_interval),
-        );
+        let handle = tokio::spawn(inner.clone().process_state_updates(
+            submit_entry_rx,
+            recovered,
+            checkpoint_interval,
+            pending_record_ttl,
+        ));
 
         let svc = Self {
             inner,
@@ -71,6 +190,75 @@ impl<Digest: SupportedDigest> CoreService<Digest> {
         Ok((svc, handle))
     }
 
+    /// Starts the `CoreService` for a read-only mirror of another registry.
+    ///
+    /// The service has no operator key, so it neither accepts submitted
+    /// records nor signs checkpoints; the registry log is instead extended
+    /// with records and checkpoints replicated from the upstream registry
+    /// (see [`Mirror`](super::Mirror)).
+    pub async fn start_mirror(
+        store: Box<dyn DataStore>,
+        snapshot_threshold: Option<RegistryLen>,
+    ) -> Result<(Self, JoinHandle<()>), CoreServiceError> {
+        let mut inner = Inner {
+            operator_key: None,
+            replacement_key: None,
+            store,
+            state: Default::default(),
+            updates: broadcast::channel(UPDATES_CAPACITY).0,
+            queue: Default::default(),
+            snapshot_threshold,
+        };
+        inner.initialize().await?;
+
+        // Records are never submitted to a mirror, but the channel must be
+        // drained until every handle to the service is dropped
+        let (submit_entry_tx, mut submit_entry_rx) = tokio::sync::mpsc::channel::<LogLeaf>(4);
+        let handle = tokio::spawn(async move {
+            while let Some(leaf) = submit_entry_rx.recv().await {
+                tracing::warn!(
+                    "ignoring record `{record_id}` submitted to a mirror",
+                    record_id = leaf.record_id
+                );
+            }
+        });
+
+        let svc = Self {
+            inner: Arc::new(inner),
+            submit_entry_tx,
+        };
+        Ok((svc, handle))
+    }
+
+    /// Returns whether the service is a read-only mirror of another registry.
+    pub fn is_mirror(&self) -> bool {
+        self.inner.operator_key.is_none()
+    }
+
+    /// Gets the current length of the registry log.
+    ///
+    /// The log may include records that are not yet part of a checkpoint.
+    pub async fn log_length(&self) -> RegistryLen {
+        self.inner.state.read().await.log.length() as RegistryLen
+    }
+
+    /// Commits stored records replicated from an upstream registry along with
+    /// the upstream checkpoint that includes them.
+    ///
+    /// The records must directly follow the current registry log; each is
+    /// validated as it is committed. The checkpoint must match the resulting
+    /// registry log and be signed by a key with permission to sign
+    /// checkpoints; it is verified before any record is committed.
+    pub(crate) async fn commit_mirrored(
+        &self,
+        leafs: Vec<LogLeaf>,
+        checkpoint: SerdeEnvelope<TimestampedCheckpoint>,
+    ) -> Result<(), CoreServiceError> {
+        self.inner.commit_mirrored(leafs, checkpoint).await?;
+        self.inner.update_snapshot().await;
+        Ok(())
+    }
+
     /// Constructs a log consistency proof between the given log tree roots.
     pub async fn log_consistency_proof(
         &self,
@@ -113,6 +301,7 @@ impl<Digest: SupportedDigest> CoreService<Digest> {
         log_length: RegistryLen,
         entries: &[RegistryIndex],
     ) -> Result<MapProofBundle<Digest, LogId, MapLeaf>, CoreServiceError> {
+        self.inner.rebuild_map_index(log_length).await?;
         let state = self.inner.state.read().await;
 
         let (map_root, map) = state
@@ -159,24 +348,67 @@ impl<Digest: SupportedDigest> CoreService<Digest> {
         self.inner.store.as_ref()
     }
 
+    /// Subscribes to updates produced by the service.
+    ///
+    /// A checkpoint update is sent only when the registry log changes.
+    pub fn subscribe(&self) -> broadcast::Receiver<CoreUpdate> {
+        self.inner.updates.subscribe()
+    }
+
+    /// Gets the current metrics of the queue of submitted package records.
+    pub fn commit_queue_metrics(&self) -> CommitQueueMetrics {
+        let queue = &self.inner.queue;
+        CommitQueueMetrics {
+            depth: queue.depth.load(Ordering::Relaxed),
+            deferred: queue.deferred.load(Ordering::Relaxed),
+            retries_total: queue.retries_total.load(Ordering::Relaxed),
+            failures_total: queue.failures_total.load(Ordering::Relaxed),
+        }
+    }
+
     /// Submits a package record to be processed.
     pub async fn submit_package_record(&self, log_id: LogId, record_id: RecordId) {
+        self.inner.queue.depth.fetch_add(1, Ordering::Relaxed);
         self.submit_entry_tx
             .send(LogLeaf { log_id, record_id })
             .await
             .unwrap()
     }
+
+    /// Submits an operator record to be processed.
+    pub async fn submit_operator_record(&self, record_id: RecordId) {
+        self.inner.queue.depth.fetch_add(1, Ordering::Relaxed);
+        self.submit_entry_tx
+            .send(LogLeaf {
+                log_id: LogId::operator_log::<Digest>(),
+                record_id,
+            })
+            .await
+            .unwrap()
+    }
 }
 
 struct Inner<Digest: SupportedDigest> {
-    // Operator signing key
-    operator_key: PrivateKey,
+    // Operator signing key; a mirror has no operator key
+    operator_key: Option<PrivateKey>,
+
+    // Key that signs checkpoints once the operator key is revoked or rotated.
+    replacement_key: Option<PrivateKey>,
 
     // DataStore persists transparency state.
     store: Box<dyn DataStore>,
 
     // In-memory transparency state.
     state: RwLock<State<Digest>>,
+
+    // Sender of updates to subscribers.
+    updates: broadcast::Sender<CoreUpdate>,
+
+    // Metrics of the queue of submitted package entries.
+    queue: CommitQueueCounters,
+
+    // The number of new entries after which a snapshot is stored.
+    snapshot_threshold: Option<RegistryLen>,
 }
 
 impl<Digest: SupportedDigest> Inner<Digest> {
@@ -185,16 +417,6 @@ impl<Digest: SupportedDigest> Inner<Digest> {
     async fn initialize(&mut self) -> Result<(), CoreServiceError> {
         tracing::debug!("Initializing CoreService");
 
-        let published = self.store.get_all_validated_records().await?.peekable();
-        pin_mut!(published);
-
-        // If there are no published records, initialize a new state
-        if published.as_mut().peek().await.is_none() {
-            tracing::debug!("No existing records; initializing new state");
-            return self.initialize_new().await;
-        }
-
-        // Reconstruct internal state from previously-stored data
         let mut checkpoints = self.store.get_all_checkpoints().await?;
         let mut checkpoints_by_len: HashMap<RegistryLen, Checkpoint> = Default::default();
         while let Some(checkpoint) = checkpoints.next().await {
@@ -202,7 +424,35 @@ impl<Digest: SupportedDigest> Inner<Digest> {
             checkpoints_by_len.insert(checkpoint.log_length, checkpoint);
         }
 
+        // Restore the state from the latest snapshot so that only the records
+        // after it need to be replayed
+        if let Some(snapshot) = self.store.get_latest_snapshot().await? {
+            let log_length = snapshot.log_length;
+            match State::restore(snapshot, &checkpoints_by_len) {
+                Ok(state) => {
+                    tracing::info!(
+                        "restored transparency state from snapshot at log length {log_length}"
+                    );
+                    *self.state.get_mut() = state;
+                }
+                Err(e) => {
+                    tracing::warn!("ignoring snapshot at log length {log_length}: {e}");
+                }
+            }
+        }
+
         let state = self.state.get_mut();
+        let start = state.log.length() as RegistryIndex;
+        let published = self.store.get_validated_records(start).await?.peekable();
+        pin_mut!(published);
+
+        // If there are no published records, initialize a new state
+        if start == 0 && published.as_mut().peek().await.is_none() {
+            tracing::debug!("No existing records; initializing new state");
+            return self.initialize_new().await;
+        }
+
+        // Reconstruct internal state from previously-stored data
         while let Some(entry) = published.next().await {
             state.push_entry(entry?);
             if let Some(stored_checkpoint) =
@@ -214,10 +464,25 @@ impl<Digest: SupportedDigest> Inner<Digest> {
             }
         }
 
+        // Every stored checkpoint should have been validated
+        if let Some(latest) = checkpoints_by_len.keys().max() {
+            if (state.log.length() as RegistryLen) < *latest {
+                return Err(CoreServiceError::InitializationFailure(format!(
+                    "the latest checkpoint at log length {latest} exceeds the {len} validated record(s)",
+                    len = state.log.length()
+                )));
+            }
+        }
+
         Ok(())
     }
 
     async fn initialize_new(&mut self) -> Result<(), CoreServiceError> {
+        // The log of a mirror starts with the upstream operator log's init record
+        let Some(operator_key) = &self.operator_key else {
+            return Ok(());
+        };
+
         let state = self.state.get_mut();
 
         // Construct operator init record
@@ -227,11 +492,10 @@ impl<Digest: SupportedDigest> Inner<Digest> {
             timestamp: SystemTime::now(),
             entries: vec![operator::OperatorEntry::Init {
                 hash_algorithm: Digest::ALGORITHM,
-                key: self.operator_key.public_key(),
+                key: operator_key.public_key(),
             }],
         };
-        let signed_init_record =
-            ProtoEnvelope::signed_contents(&self.operator_key, init_record).unwrap();
+        let signed_init_record = ProtoEnvelope::signed_contents(operator_key, init_record).unwrap();
         let log_id = LogId::operator_log::<Digest>();
         let record_id = RecordId::operator_record::<Digest>(&signed_init_record);
 
@@ -261,7 +525,9 @@ impl<Digest: SupportedDigest> Inner<Digest> {
     async fn process_state_updates(
         self: Arc<Self>,
         mut submit_entry_rx: mpsc::Receiver<LogLeaf>,
+        recovered: Vec<LogLeaf>,
         checkpoint_interval: Duration,
+        pending_record_ttl: Option<Duration>,
     ) {
         let mut checkpoint = self
             .store
@@ -274,74 +540,591 @@ impl<Digest: SupportedDigest> Inner<Digest> {
         let mut checkpoint_interval = tokio::time::interval(checkpoint_interval);
         checkpoint_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
 
+        let mut expiry_interval = pending_record_ttl.map(|ttl| {
+            let mut interval = tokio::time::interval(ttl.min(MAX_PENDING_RECORD_EXPIRY_INTERVAL));
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            (ttl, interval)
+        });
+
+        // Entries waiting to be processed after a failed commit, in submission order.
+        // Entries remain pending in the store, so they are recovered on restart.
+        let mut deferred = recovered
+            .into_iter()
+            .map(QueuedEntry::new)
+            .collect::<Vec<_>>();
+
         loop {
+            self.queue
+                .deferred
+                .store(deferred.len() as u64, Ordering::Relaxed);
+
+            let next_ready_at = deferred.iter().map(|e| e.ready_at).min();
+            let retry_tick = async move {
+                match next_ready_at {
+                    Some(ready_at) => tokio::time::sleep_until(ready_at).await,
+                    None => std::future::pending().await,
+                }
+            };
+
+            let expiry_tick = async {
+                match &mut expiry_interval {
+                    Some((ttl, interval)) => {
+                        interval.tick().await;
+                        *ttl
+                    }
+                    None => std::future::pending().await,
+                }
+            };
+
             tokio::select! {
                 entry = submit_entry_rx.recv() => match entry {
-                    Some(entry) => self.process_package_entry(&entry).await,
+                    Some(entry) => {
+                        // Drain the entries already submitted to commit them as a batch
+                        let mut entries = vec![QueuedEntry::new(entry)];
+                        while entries.len() < MAX_COMMIT_BATCH_SIZE {
+                            match submit_entry_rx.try_recv() {
+                                Ok(entry) => entries.push(QueuedEntry::new(entry)),
+                                Err(_) => break,
+                            }
+                        }
+
+                        self.process_queued_entries(entries, &mut deferred).await;
+                    }
                     None => break, // Channel closed
                 },
-                _ = checkpoint_interval.tick() => self.update_checkpoint(&mut checkpoint).await,
+                _ = retry_tick => {
+                    let now = Instant::now();
+                    let (mut ready, waiting) = std::mem::take(&mut deferred)
+                        .into_iter()
+                        .partition::<Vec<_>, _>(|e| e.ready_at <= now);
+                    deferred = waiting;
+
+                    while !ready.is_empty() {
+                        let rest = ready.split_off(ready.len().min(MAX_COMMIT_BATCH_SIZE));
+                        self.process_queued_entries(std::mem::replace(&mut ready, rest), &mut deferred)
+                            .await;
+                    }
+                }
+                _ = checkpoint_interval.tick() => {
+                    if self.update_checkpoint(&mut checkpoint).await {
+                        self.update_snapshot().await;
+                    }
+                }
+                ttl = expiry_tick => self.expire_pending_records(ttl).await,
+            }
+        }
+    }
+
+    // Rejects package records that have been pending for longer than the TTL
+    async fn expire_pending_records(&self, ttl: Duration) {
+        let Some(submitted_before) = SystemTime::now().checked_sub(ttl) else {
+            return;
+        };
+
+        let entries = match self
+            .store
+            .get_pending_package_records(submitted_before)
+            .await
+        {
+            Ok(entries) => entries,
+            Err(e) => {
+                tracing::error!("failed to get pending package records: {e}");
+                return;
+            }
+        };
+
+        for entry in entries {
+            let LogLeaf { log_id, record_id } = &entry;
+            match self
+                .store
+                .reject_package_record(log_id, record_id, PENDING_RECORD_EXPIRED_REASON)
+                .await
+            {
+                Ok(()) => {
+                    tracing::debug!("pending record `{record_id}` expired");
+
+                    // It is not an error if there are no subscribers
+                    let _ = self.updates.send(CoreUpdate::RecordRejected(entry));
+                }
+                // The record was processed since it was fetched
+                Err(DataStoreError::RecordNotPending(_)) => {}
+                Err(e) => {
+                    tracing::error!("failed to reject expired pending record `{record_id}`: {e}")
+                }
             }
         }
     }
 
-    // Processes a submitted package entry
-    async fn process_package_entry(&self, entry: &LogLeaf) {
-        tracing::debug!("Processing entry {entry:?}");
+    // Processes queued entries as a batch, except for entries of logs with a
+    // deferred entry, which are deferred to preserve the order of the log's entries
+    async fn process_queued_entries(
+        &self,
+        entries: Vec<QueuedEntry>,
+        deferred: &mut Vec<QueuedEntry>,
+    ) {
+        let operator_log_id = LogId::operator_log::<Digest>();
+        let mut batch = Vec::with_capacity(entries.len());
+        for mut entry in entries {
+            let blocking = deferred
+                .iter()
+                .filter(|e| e.leaf.log_id == entry.leaf.log_id)
+                .map(|e| e.ready_at)
+                .max();
+
+            match blocking {
+                Some(ready_at) => {
+                    entry.ready_at = entry.ready_at.max(ready_at);
+                    deferred.push(entry);
+                }
+                // Operator records are rare, so they are committed individually
+                None if entry.leaf.log_id == operator_log_id => {
+                    if let Some(entry) = self.process_operator_entry(entry).await {
+                        deferred.push(entry);
+                    }
+                }
+                None => batch.push(entry),
+            }
+        }
+
+        if !batch.is_empty() {
+            deferred.extend(self.process_package_entries(batch).await);
+        }
+    }
+
+    // Validates and commits a batch of submitted package entries, returning
+    // the entries if the commit should be retried
+    async fn process_package_entries(&self, entries: Vec<QueuedEntry>) -> Vec<QueuedEntry> {
+        tracing::debug!("Processing {count} entries", count = entries.len());
 
         let mut state = self.state.write().await;
-        let LogLeaf { log_id, record_id } = entry;
+        let leafs = entries.iter().map(|e| e.leaf.clone()).collect::<Vec<_>>();
 
-        // Validate and commit the package entry to the store
+        // Validate and commit the package entries to the store
         let registry_index = state.log.length() as RegistryIndex;
-        let commit_res = self
+        let results = match self
             .store
-            .commit_package_record(log_id, record_id, registry_index)
-            .await;
+            .commit_package_records(&leafs, registry_index)
+            .await
+        {
+            Ok(results) => results,
+            Err(e) => {
+                // No entry was committed, so the batch is retried as a whole
+                let mut retried = Vec::with_capacity(entries.len());
+                for entry in entries {
+                    retried.extend(self.retry_entry(entry, &e).await);
+                }
+                return retried;
+            }
+        };
 
-        if let Err(err) = commit_res {
-            match err {
-                DataStoreError::Rejection(_)
-                | DataStoreError::OperatorValidationFailed(_)
-                | DataStoreError::PackageValidationFailed(_) => {
+        let mut committed = Vec::with_capacity(leafs.len());
+        let mut retried = Vec::new();
+        for (entry, res) in entries.into_iter().zip(results) {
+            let leaf = entry.leaf.clone();
+            let record_id = &leaf.record_id;
+            match res {
+                Ok(()) => committed.push(leaf),
+                Err(
+                    err @ (DataStoreError::Rejection(_)
+                    | DataStoreError::OperatorValidationFailed(_)
+                    | DataStoreError::PackageValidationFailed(_)
+                    | DataStoreError::InvalidRecordContents { .. }),
+                ) => {
                     // The record failed to validate and was rejected; do not include it in the next checkpoint
                     tracing::debug!("record `{record_id}` rejected: {err:?}");
+
+                    // It is not an error if there are no subscribers
+                    let _ = self.updates.send(CoreUpdate::RecordRejected(leaf));
                 }
-                e => {
-                    // TODO: this should be made more robust with a proper reliable message
-                    // queue with retry logic
-                    tracing::error!("failed to validate package record `{record_id}`: {e}");
+                Err(DataStoreError::RecordNotPending(_)) => {
+                    // The record was already processed (e.g. it expired)
+                    tracing::debug!("record `{record_id}` is no longer pending");
+                }
+                Err(e) => {
+                    // The entry remains in the queue until it is retried or rejected
+                    retried.extend(self.retry_entry(entry, &e).await);
+                    continue;
                 }
             }
-            return;
+
+            self.queue.depth.fetch_sub(1, Ordering::Relaxed);
         }
 
-        state.push_entry(entry.clone());
+        state.extend_entries(committed);
+        retried
     }
 
-    // Store a checkpoint including the given new entries
-    async fn update_checkpoint(&self, checkpoint: &mut Checkpoint) {
-        {
+    // Validates and commits a submitted operator entry, returning the entry if
+    // the commit should be retried
+    async fn process_operator_entry(&self, entry: QueuedEntry) -> Option<QueuedEntry> {
+        tracing::debug!("Processing operator entry {leaf:?}", leaf = entry.leaf);
+
+        let mut state = self.state.write().await;
+        let LogLeaf { log_id, record_id } = &entry.leaf;
+
+        // Validate and commit the operator entry to the store
+        let registry_index = state.log.length() as RegistryIndex;
+        let result = match self.check_operator_record(log_id, record_id).await {
+            Ok(None) => {
+                self.store
+                    .commit_operator_record(log_id, record_id, registry_index)
+                    .await
+            }
+            Ok(Some(reason)) => self
+                .store
+                .reject_operator_record(log_id, record_id, &reason)
+                .await
+                .and(Err(DataStoreError::Rejection(reason))),
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(()) => state.push_entry(entry.leaf.clone()),
+            Err(
+                err @ (DataStoreError::Rejection(_)
+                | DataStoreError::OperatorValidationFailed(_)
+                | DataStoreError::InvalidRecordContents { .. }),
+            ) => {
+                // The record failed to validate and was rejected; do not include it in the next checkpoint
+                tracing::debug!("operator record `{record_id}` rejected: {err:?}");
+
+                // It is not an error if there are no subscribers
+                let _ = self
+                    .updates
+                    .send(CoreUpdate::RecordRejected(entry.leaf.clone()));
+            }
+            Err(DataStoreError::RecordNotPending(_)) => {
+                tracing::debug!("operator record `{record_id}` is no longer pending");
+            }
+            Err(e) => return self.retry_entry(entry, &e).await,
+        }
+
+        self.queue.depth.fetch_sub(1, Ordering::Relaxed);
+        None
+    }
+
+    // Returns the reason to reject the given operator record if it defines a
+    // namespace that already contains packages or if it would leave the
+    // service without a key permitted to sign checkpoints.
+    //
+    // Package logs are validated against the latest operator log state, so a
+    // namespace must be defined before any of its packages.
+    async fn check_operator_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<Option<String>, DataStoreError> {
+        let record = self.store.get_operator_record(log_id, record_id).await?;
+
+        // Records that fail to validate are rejected when committed
+        let mut state = self.store.get_operator_log_state(log_id).await?;
+        if state.validate(&record.envelope).is_ok() && self.find_signing_key(&state).is_none() {
+            return Ok(Some(
+                "the record would revoke the commit permission of the registry's signing key \
+                 without a replacement key configured"
+                    .to_string(),
+            ));
+        }
+
+        for entry in &record.envelope.as_ref().entries {
+            let namespace = match entry {
+                operator::OperatorEntry::DefineNamespace { namespace, .. }
+                | operator::OperatorEntry::ImportNamespace { namespace, .. } => namespace,
+                _ => continue,
+            };
+
+            if !self
+                .store
+                .get_package_ids(Some(namespace), None, 1)
+                .await?
+                .is_empty()
+            {
+                return Ok(Some(format!(
+                    "namespace `{namespace}` already contains packages"
+                )));
+            }
+        }
+
+        Ok(None)
+    }
+
+    // Returns the entry if its commit should be retried after the given
+    // error, otherwise rejecting its record
+    async fn retry_entry(&self, mut entry: QueuedEntry, e: &DataStoreError) -> Option<QueuedEntry> {
+        let LogLeaf { log_id, record_id } = &entry.leaf;
+        entry.attempts += 1;
+        if entry.attempts < MAX_COMMIT_ATTEMPTS {
+            let delay = commit_retry_delay(entry.attempts);
+            tracing::warn!(
+                "failed to commit record `{record_id}` (attempt {attempts}), retrying in {delay:?}: {e}",
+                attempts = entry.attempts
+            );
+            self.queue.retries_total.fetch_add(1, Ordering::Relaxed);
+            entry.ready_at = Instant::now() + delay;
+            return Some(entry);
+        }
+
+        tracing::error!(
+            "failed to commit record `{record_id}` after {attempts} attempts, rejecting it: {e}",
+            attempts = entry.attempts
+        );
+        self.queue.failures_total.fetch_add(1, Ordering::Relaxed);
+        self.queue.depth.fetch_sub(1, Ordering::Relaxed);
+
+        let result = if *log_id == LogId::operator_log::<Digest>() {
+            self.store
+                .reject_operator_record(log_id, record_id, COMMIT_FAILED_REASON)
+                .await
+        } else {
+            self.store
+                .reject_package_record(log_id, record_id, COMMIT_FAILED_REASON)
+                .await
+        };
+        match result {
+            // It is not an error if there are no subscribers
+            Ok(()) => {
+                let _ = self.updates.send(CoreUpdate::RecordRejected(entry.leaf));
+            }
+            // The record was processed since it was submitted
+            Err(DataStoreError::RecordNotPending(_)) => {}
+            // The record remains pending until it expires or the server restarts
+            Err(e) => tracing::error!("failed to reject record `{record_id}`: {e}"),
+        }
+
+        None
+    }
+
+    // Store a checkpoint including the given new entries, returning whether
+    // a new checkpoint was stored
+    async fn update_checkpoint(&self, checkpoint: &mut Checkpoint) -> bool {
+        let changed = {
             // Recalculate the checkpoint if necessary
             let mut state = self.state.write().await;
             if state.log.length() as RegistryLen != checkpoint.log_length {
                 *checkpoint = state.checkpoint();
                 tracing::debug!("Updating to checkpoint {checkpoint:?}");
+                true
+            } else {
+                false
+            }
+        };
+
+        match self.sign_and_store_checkpoint(checkpoint.clone()).await {
+            Ok(signed) => {
+                if changed {
+                    // It is not an error if there are no subscribers
+                    let _ = self.updates.send(CoreUpdate::Checkpoint(signed));
+                }
+                changed
+            }
+            Err(err) => {
+                tracing::error!("Error storing checkpoint {checkpoint:?}: {err:?}");
+                false
+            }
+        }
+    }
+
+    // Stores a snapshot of the state if the log has grown enough since the
+    // last snapshot; the state must be at a stored checkpoint
+    async fn update_snapshot(&self) {
+        let Some(threshold) = self.snapshot_threshold else {
+            return;
+        };
+
+        let snapshot = {
+            let state = self.state.read().await;
+            if (state.log.length() as RegistryLen) < state.snapshot_len + threshold {
+                return;
+            }
+
+            state.snapshot()
+        };
+
+        let log_length = snapshot.log_length;
+        match self.store.store_snapshot(&snapshot).await {
+            Ok(()) => {
+                tracing::debug!("stored snapshot at log length {log_length}");
+                self.state.write().await.snapshot_len = log_length;
+            }
+            Err(e) => tracing::error!("failed to store snapshot at log length {log_length}: {e}"),
+        }
+    }
+
+    // Rebuilds the maps of the checkpoints before the snapshot the state was
+    // restored from if the given log length is before it; the maps are
+    // only indexed for checkpoints after the snapshot on startup
+    async fn rebuild_map_index(&self, log_length: RegistryLen) -> Result<(), CoreServiceError> {
+        let restored_len = self.state.read().await.restored_len;
+        if log_length >= restored_len {
+            return Ok(());
+        }
+
+        let mut map_roots = HashMap::new();
+        let mut checkpoints = self.store.get_all_checkpoints().await?;
+        while let Some(checkpoint) = checkpoints.next().await {
+            let checkpoint = checkpoint?.checkpoint;
+            if checkpoint.log_length > 0 && checkpoint.log_length < restored_len {
+                map_roots.insert(checkpoint.log_length, checkpoint.map_root);
+            }
+        }
+
+        // Replay the records before the snapshot into the map only, as the
+        // log itself was restored from the snapshot
+        let mut map_index = HashMap::new();
+        let mut map = VerifiableMap::<Digest>::default();
+        let published = self
+            .store
+            .get_validated_records(0)
+            .await?
+            .take(restored_len as usize);
+        pin_mut!(published);
+        let mut len: RegistryLen = 0;
+        while let Some(entry) = published.next().await {
+            let LogLeaf { log_id, record_id } = entry?;
+            map = map.insert(log_id, MapLeaf { record_id });
+            len += 1;
+
+            if let Some(map_root) = map_roots.get(&len) {
+                let found = map.root().clone();
+                if AnyHash::from(found.clone()) != *map_root {
+                    return Err(CoreServiceError::IncorrectProof {
+                        root: map_root.clone(),
+                        found: found.into(),
+                    });
+                }
+
+                map_index.insert(len, (found, map.clone()));
             }
         }
 
-        if let Err(err) = self.sign_and_store_checkpoint(checkpoint.clone()).await {
-            tracing::error!("Error storing checkpoint {checkpoint:?}: {err:?}");
+        let mut state = self.state.write().await;
+        if state.restored_len == restored_len {
+            state.map_index.extend(map_index);
+            state.restored_len = 0;
         }
+
+        Ok(())
     }
 
-    async fn sign_and_store_checkpoint(&self, checkpoint: Checkpoint) -> anyhow::Result<()> {
+    // Commits replicated records followed by the upstream checkpoint
+    async fn commit_mirrored(
+        &self,
+        leafs: Vec<LogLeaf>,
+        signed: SerdeEnvelope<TimestampedCheckpoint>,
+    ) -> Result<(), CoreServiceError> {
+        let operator_log_id = LogId::operator_log::<Digest>();
+        let mut state = self.state.write().await;
+
+        // Verify the checkpoint against scratch copies of the registry log and
+        // operator log state so that nothing is committed if it is invalid
+        let checkpoint = state.checkpoint_with(&leafs);
+        if checkpoint != signed.as_ref().checkpoint {
+            return Err(CoreServiceError::CheckpointMismatch(checkpoint.log_length));
+        }
+
+        // The checkpoint must be signed by a key of the (replicated) operator log
+        let mut operator = self.store.get_operator_log_state(&operator_log_id).await?;
+        for LogLeaf { log_id, record_id } in &leafs {
+            if *log_id == operator_log_id {
+                let record = self.store.get_operator_record(log_id, record_id).await?;
+                operator
+                    .validate(&record.envelope)
+                    .map_err(DataStoreError::from)?;
+            }
+        }
+
+        let key_id = signed.key_id();
+        operator
+            .public_key(key_id)
+            .filter(|_| operator.key_has_permission(key_id, operator::Permission::Commit))
+            .and_then(|key| signed.verify(key).ok())
+            .ok_or_else(|| CoreServiceError::CheckpointSignatureInvalid(key_id.clone()))?;
+
+        // Each record is validated against the logs as of its registry index,
+        // as it was by the upstream registry
+        let start = state.log.length() as RegistryIndex;
+        for (i, leaf) in leafs.into_iter().enumerate() {
+            let LogLeaf { log_id, record_id } = &leaf;
+            if *log_id == operator_log_id {
+                self.store
+                    .commit_operator_record(log_id, record_id, start + i)
+                    .await?;
+            } else {
+                self.store
+                    .commit_package_record(log_id, record_id, start + i)
+                    .await?;
+            }
+
+            state.push_entry(leaf);
+        }
+
+        let checkpoint = state.checkpoint();
         let checkpoint_id = Hash::<Digest>::of(&checkpoint).into();
-        let timestamped = TimestampedCheckpoint::now(checkpoint.clone())?;
-        let signed = SerdeEnvelope::signed_contents(&self.operator_key, timestamped)?;
-        self.store.store_checkpoint(&checkpoint_id, signed).await?;
+        self.store
+            .store_checkpoint(&checkpoint_id, signed.clone())
+            .await?;
+        drop(state);
+
+        // It is not an error if there are no subscribers
+        let _ = self.updates.send(CoreUpdate::Checkpoint(signed));
         Ok(())
     }
+
+    // Gets the operator key, which a mirror does not have
+    fn operator_key(&self) -> Result<&PrivateKey, CoreServiceError> {
+        self.operator_key
+            .as_ref()
+            .ok_or(CoreServiceError::ReadOnlyMirror)
+    }
+
+    // Gets the first configured key with permission to sign checkpoints in
+    // the given operator log state
+    fn find_signing_key(&self, state: &operator::LogState) -> Option<&PrivateKey> {
+        self.operator_key
+            .iter()
+            .chain(&self.replacement_key)
+            .find(|key| {
+                state.key_has_permission(
+                    &key.public_key().fingerprint(),
+                    operator::Permission::Commit,
+                )
+            })
+    }
+
+    // Gets the configured key that currently has permission to sign checkpoints
+    async fn signing_key(&self) -> Result<&PrivateKey, CoreServiceError> {
+        let operator_key = self.operator_key()?;
+        let state = self
+            .store
+            .get_operator_log_state(&LogId::operator_log::<Digest>())
+            .await?;
+        self.find_signing_key(&state).ok_or_else(|| {
+            CoreServiceError::OperatorKeyNotAuthorized(operator_key.public_key().fingerprint())
+        })
+    }
+
+    async fn sign_and_store_checkpoint(
+        &self,
+        checkpoint: Checkpoint,
+    ) -> anyhow::Result<SerdeEnvelope<TimestampedCheckpoint>> {
+        // The operator key may have been revoked or rotated since the service started
+        let key = self.signing_key().await?;
+
+        let checkpoint_id = Hash::<Digest>::of(&checkpoint).into();
+        let timestamped = TimestampedCheckpoint::now(checkpoint.clone())?;
+        let signed = SerdeEnvelope::signed_contents(key, timestamped)?;
+        self.store
+            .store_checkpoint(&checkpoint_id, signed.clone())
+            .await?;
+        Ok(signed)
+    }
+}
+
+/// Gets the delay before retrying a commit that failed the given number of times.
+fn commit_retry_delay(attempts: u32) -> Duration {
+    INITIAL_COMMIT_RETRY_DELAY
+        .saturating_mul(2u32.saturating_pow(attempts.saturating_sub(1)))
+        .min(MAX_COMMIT_RETRY_DELAY)
 }
 
 type VerifiableMap<Digest> = Map<Digest, LogId, MapLeaf>;
@@ -357,15 +1140,140 @@ struct State<Digest: SupportedDigest> {
     map: VerifiableMap<Digest>,
     // Index verifiable map snapshots by log length (at checkpoints only)
     map_index: HashMap<RegistryLen, (Hash<Digest>, VerifiableMap<Digest>)>,
+    // The entries of the verifiable map, as the map only stores their hashes
+    map_entries: HashMap<LogId, RecordId>,
+
+    // The log length of the latest stored snapshot
+    snapshot_len: RegistryLen,
+    // The log length of the snapshot the state was restored from, until the
+    // maps of the checkpoints before it are rebuilt
+    restored_len: RegistryLen,
 }
 
 impl<Digest: SupportedDigest> State<Digest> {
+    // Restores the state from a snapshot, checking it against the stored
+    // checkpoint at the snapshot's log length
+    fn restore(
+        snapshot: TransparencySnapshot,
+        checkpoints: &HashMap<RegistryLen, Checkpoint>,
+    ) -> Result<Self, String> {
+        let TransparencySnapshot {
+            log_length,
+            log_tree,
+            map,
+        } = snapshot;
+
+        let stored_checkpoint = checkpoints
+            .get(&log_length)
+            .ok_or_else(|| "no checkpoint was stored at the snapshot's log length".to_string())?;
+
+        let log_tree = log_tree
+            .into_iter()
+            .map(Hash::<Digest>::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        let log = VecLog::from_parts(log_length, log_tree)
+            .ok_or_else(|| "the log tree does not match the log length".to_string())?;
+
+        let mut state = Self {
+            log,
+            // Leaf nodes are at the even indexes of the log tree
+            leaf_index: (0..log_length).map(|i| Node(i * 2)).collect(),
+            map: VerifiableMap::default().extend(map.iter().map(|leaf| {
+                (
+                    leaf.log_id.clone(),
+                    MapLeaf {
+                        record_id: leaf.record_id.clone(),
+                    },
+                )
+            })),
+            map_index: Default::default(),
+            map_entries: map
+                .into_iter()
+                .map(|LogLeaf { log_id, record_id }| (log_id, record_id))
+                .collect(),
+            snapshot_len: log_length,
+            restored_len: log_length,
+        };
+
+        if &state.checkpoint() != stored_checkpoint {
+            return Err("the snapshot is inconsistent with the stored checkpoint".to_string());
+        }
+
+        Ok(state)
+    }
+
+    fn snapshot(&self) -> TransparencySnapshot {
+        TransparencySnapshot {
+            log_length: self.log.length() as RegistryLen,
+            log_tree: self.log.as_ref().iter().map(Into::into).collect(),
+            map: self
+                .map_entries
+                .iter()
+                .map(|(log_id, record_id)| LogLeaf {
+                    log_id: log_id.clone(),
+                    record_id: record_id.clone(),
+                })
+                .collect(),
+        }
+    }
+
     fn push_entry(&mut self, log_leaf: LogLeaf) {
         let node = self.log.push(&log_leaf);
         self.leaf_index.push(node);
 
         let LogLeaf { log_id, record_id } = log_leaf;
-        self.map = self.map.insert(log_id, MapLeaf { record_id });
+        self.map = self.map.insert(
+            log_id.clone(),
+            MapLeaf {
+                record_id: record_id.clone(),
+            },
+        );
+        self.map_entries.insert(log_id, record_id);
+    }
+
+    fn extend_entries(&mut self, log_leafs: Vec<LogLeaf>) {
+        for log_leaf in &log_leafs {
+            let node = self.log.push(log_leaf);
+            self.leaf_index.push(node);
+        }
+
+        self.map = self.map.extend(log_leafs.iter().map(|leaf| {
+            (
+                leaf.log_id.clone(),
+                MapLeaf {
+                    record_id: leaf.record_id.clone(),
+                },
+            )
+        }));
+        self.map_entries.extend(
+            log_leafs
+                .into_iter()
+                .map(|LogLeaf { log_id, record_id }| (log_id, record_id)),
+        );
+    }
+
+    // Computes the checkpoint of the state after pushing the given entries
+    // without modifying the state
+    fn checkpoint_with(&self, log_leafs: &[LogLeaf]) -> Checkpoint {
+        let mut log = self.log.clone();
+        let mut map = self.map.clone();
+        for log_leaf in log_leafs {
+            log.push(log_leaf);
+            map = map.insert(
+                log_leaf.log_id.clone(),
+                MapLeaf {
+                    record_id: log_leaf.record_id.clone(),
+                },
+            );
+        }
+
+        let log_checkpoint = log.checkpoint();
+        Checkpoint {
+            log_length: log_checkpoint.length() as RegistryLen,
+            log_root: log_checkpoint.root().into(),
+            map_root: map.root().into(),
+        }
     }
 
     fn checkpoint(&mut self) -> Checkpoint {
@@ -403,4 +1311,797 @@ pub enum CoreServiceError {
     DataStore(#[from] DataStoreError),
     #[error("initialization failed: {0}")]
     InitializationFailure(String),
+    #[error("operator key `{0}` does not have permission to sign checkpoints")]
+    OperatorKeyNotAuthorized(KeyID),
+    #[error("the registry is a read-only mirror")]
+    ReadOnlyMirror,
+    #[error(
+        "the upstream checkpoint does not match the mirrored registry log at log length `{0}`"
+    )]
+    CheckpointMismatch(RegistryLen),
+    #[error("the upstream checkpoint is not validly signed by a key with permission to sign checkpoints (key `{0}`)")]
+    CheckpointSignatureInvalid(KeyID),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::datastore::{failing::FailingDataStore, MemoryDataStore, RecordStatus};
+    use std::collections::HashSet;
+    use warg_crypto::{hash::HashAlgorithm, signing::generate_p256_pair};
+    use warg_protocol::{
+        package::{PackageEntry, PackageRecord},
+        registry::PackageId,
+    };
+
+    /// Stores a pending record initializing the given package.
+    async fn store_init_record(store: &dyn DataStore, name: &str) -> LogLeaf {
+        let (public_key, signing_key) = generate_p256_pair();
+        let package_id = PackageId::new(name).unwrap();
+        let record = ProtoEnvelope::signed_contents(
+            &signing_key,
+            PackageRecord {
+                prev: None,
+                version: 0,
+                timestamp: SystemTime::now(),
+                entries: vec![PackageEntry::Init {
+                    hash_algorithm: HashAlgorithm::Sha256,
+                    key: public_key,
+                }],
+            },
+        )
+        .unwrap();
+        let log_id = LogId::package_log::<Sha256>(&package_id);
+        let record_id = RecordId::package_record::<Sha256>(&record);
+        store
+            .store_package_record(&log_id, &package_id, &record_id, &record, &HashSet::new())
+            .await
+            .unwrap();
+
+        LogLeaf { log_id, record_id }
+    }
+
+    #[test]
+    fn it_backs_off_commit_retries() {
+        assert_eq!(commit_retry_delay(1), INITIAL_COMMIT_RETRY_DELAY);
+        assert_eq!(commit_retry_delay(2), INITIAL_COMMIT_RETRY_DELAY * 2);
+        assert_eq!(commit_retry_delay(3), INITIAL_COMMIT_RETRY_DELAY * 4);
+        assert_eq!(commit_retry_delay(100), MAX_COMMIT_RETRY_DELAY);
+    }
+
+    #[tokio::test]
+    async fn it_restores_state_from_snapshot() {
+        let store = MemoryDataStore::default();
+        let mut state = State::<Sha256>::default();
+        for name in ["test:a", "test:b", "test:c"] {
+            state.push_entry(store_init_record(&store, name).await);
+        }
+        // A later record for an existing log replaces its map entry
+        let leaf = store_init_record(&store, "test:a").await;
+        state.push_entry(leaf.clone());
+
+        let checkpoint = state.checkpoint();
+        let checkpoints = HashMap::from([(checkpoint.log_length, checkpoint.clone())]);
+        let snapshot = state.snapshot();
+        assert_eq!(snapshot.map.len(), 3);
+        assert!(snapshot.map.contains(&leaf));
+
+        let mut restored = State::<Sha256>::restore(snapshot.clone(), &checkpoints).unwrap();
+        assert_eq!(restored.snapshot_len, checkpoint.log_length);
+        assert_eq!(restored.checkpoint(), checkpoint);
+
+        // The restored state continues the log
+        let leaf = store_init_record(&store, "test:d").await;
+        state.push_entry(leaf.clone());
+        restored.push_entry(leaf.clone());
+        assert_eq!(restored.checkpoint(), state.checkpoint());
+        assert_eq!(restored.leaf_index, state.leaf_index);
+
+        // A snapshot without a matching checkpoint is not restored
+        assert!(State::<Sha256>::restore(snapshot.clone(), &HashMap::new()).is_err());
+        let mut inconsistent = snapshot;
+        inconsistent.map.pop();
+        assert!(State::<Sha256>::restore(inconsistent, &checkpoints).is_err());
+    }
+
+    #[tokio::test]
+    async fn it_proves_map_inclusion_before_the_restored_snapshot() {
+        let operator_key = || {
+            PrivateKey::decode(
+                "ecdsa-p256:I+UlDo0HxyBBFeelhPPWmD+LnklOpqZDkrFP5VduASk=".to_string(),
+            )
+            .unwrap()
+        };
+
+        // Seed a store with a snapshot taken after two checkpoints
+        let store = Box::<MemoryDataStore>::default();
+        let mut state = State::<Sha256>::default();
+        let operator_log_id = LogId::operator_log::<Sha256>();
+        let init = ProtoEnvelope::signed_contents(
+            &operator_key(),
+            operator::OperatorRecord {
+                prev: None,
+                version: 0,
+                timestamp: SystemTime::now(),
+                entries: vec![operator::OperatorEntry::Init {
+                    hash_algorithm: HashAlgorithm::Sha256,
+                    key: operator_key().public_key(),
+                }],
+            },
+        )
+        .unwrap();
+        let init_id = RecordId::operator_record::<Sha256>(&init);
+        store
+            .store_operator_record(&operator_log_id, &init_id, &init)
+            .await
+            .unwrap();
+        store
+            .commit_operator_record(&operator_log_id, &init_id, 0)
+            .await
+            .unwrap();
+        state.push_entry(LogLeaf {
+            log_id: operator_log_id,
+            record_id: init_id,
+        });
+
+        let mut checkpoints = Vec::new();
+        for (index, name) in [(1, "test:a"), (2, "test:b")] {
+            let leaf = store_init_record(store.as_ref(), name).await;
+            store
+                .commit_package_record(&leaf.log_id, &leaf.record_id, index)
+                .await
+                .unwrap();
+            state.push_entry(leaf);
+
+            let checkpoint = state.checkpoint();
+            store
+                .store_checkpoint(
+                    &Hash::<Sha256>::of(&checkpoint).into(),
+                    SerdeEnvelope::signed_contents(
+                        &operator_key(),
+                        TimestampedCheckpoint::now(checkpoint.clone()).unwrap(),
+                    )
+                    .unwrap(),
+                )
+                .await
+                .unwrap();
+            checkpoints.push(checkpoint);
+        }
+        store.store_snapshot(&state.snapshot()).await.unwrap();
+
+        let (core, _) = CoreService::<Sha256>::start(
+            operator_key(),
+            None,
+            store,
+            Duration::from_secs(60),
+            None,
+            Some(100),
+        )
+        .await
+        .unwrap();
+        assert_eq!(core.inner.state.read().await.restored_len, 3);
+
+        // The map of the checkpoint before the snapshot is rebuilt
+        let bundle = core.map_inclusion_proofs(2, &[1]).await.unwrap();
+        let LogLeaf { log_id, record_id } = &core
+            .store()
+            .get_log_leafs_with_registry_index(&[1])
+            .await
+            .unwrap()[0];
+        let proof = &bundle.unbundle()[0];
+        assert_eq!(
+            AnyHash::from(proof.evaluate(
+                log_id,
+                &MapLeaf {
+                    record_id: record_id.clone()
+                }
+            )),
+            checkpoints[0].map_root
+        );
+        assert_eq!(core.inner.state.read().await.restored_len, 0);
+
+        // The package initialized after the checkpoint is not included
+        assert!(matches!(
+            core.map_inclusion_proofs(2, &[2]).await,
+            Err(CoreServiceError::PackageNotIncluded(_))
+        ));
+        core.map_inclusion_proofs(3, &[1, 2]).await.unwrap();
+        assert!(matches!(
+            core.map_inclusion_proofs(1, &[0]).await,
+            Err(CoreServiceError::CheckpointNotFound(1))
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_commits_records_in_batches() {
+        let store = Box::<MemoryDataStore>::default();
+        let a = store_init_record(store.as_ref(), "test:a").await;
+        let b = store_init_record(store.as_ref(), "test:b").await;
+        // A second initialization of the same package fails to validate
+        let invalid = store_init_record(store.as_ref(), "test:a").await;
+        let c = store_init_record(store.as_ref(), "test:c").await;
+
+        // The recovered records are committed as a single batch
+        let (_, operator_key) = generate_p256_pair();
+        let (core, _) = CoreService::<Sha256>::start(
+            operator_key,
+            None,
+            store,
+            Duration::from_millis(10),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let mut updates = core.subscribe();
+        let checkpoint = loop {
+            match tokio::time::timeout(Duration::from_secs(5), updates.recv())
+                .await
+                .unwrap()
+                .unwrap()
+            {
+                CoreUpdate::Checkpoint(checkpoint) => break checkpoint,
+                CoreUpdate::RecordRejected(leaf) => assert_eq!(leaf, invalid),
+            }
+        };
+        assert_eq!(checkpoint.as_ref().checkpoint.log_length, 4);
+
+        // Committed records are assigned consecutive registry indexes
+        for (leaf, registry_index) in [(&a, 1), (&b, 2), (&c, 3)] {
+            let record = core
+                .store()
+                .get_package_record(&leaf.log_id, &leaf.record_id)
+                .await
+                .unwrap();
+            assert_eq!(record.status, RecordStatus::Published);
+            assert_eq!(record.registry_index, Some(registry_index));
+        }
+
+        let record = core
+            .store()
+            .get_package_record(&invalid.log_id, &invalid.record_id)
+            .await
+            .unwrap();
+        assert!(matches!(record.status, RecordStatus::Rejected(_)));
+        assert_eq!(core.commit_queue_metrics(), CommitQueueMetrics::default());
+
+        // Extending the state is equivalent to pushing each entry
+        let mut pushed = State::<Sha256>::default();
+        let mut extended = State::<Sha256>::default();
+        for leaf in [&a, &b, &c] {
+            pushed.push_entry(leaf.clone());
+        }
+        extended.extend_entries(vec![a, b, c]);
+        assert_eq!(extended.checkpoint(), pushed.checkpoint());
+        assert_eq!(extended.leaf_index, pushed.leaf_index);
+        assert_eq!(extended.map_entries, pushed.map_entries);
+    }
+
+    #[tokio::test]
+    async fn it_resubmits_ready_records() {
+        let store = Box::<MemoryDataStore>::default();
+        let leaf = store_init_record(store.as_ref(), "test:recovered").await;
+
+        let (_, operator_key) = generate_p256_pair();
+        let (core, _) = CoreService::<Sha256>::start(
+            operator_key,
+            None,
+            store,
+            Duration::from_millis(10),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let mut updates = core.subscribe();
+        let update = tokio::time::timeout(Duration::from_secs(5), updates.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(update, CoreUpdate::Checkpoint(_)));
+
+        let record = core
+            .store()
+            .get_package_record(&leaf.log_id, &leaf.record_id)
+            .await
+            .unwrap();
+        assert_eq!(record.status, RecordStatus::Published);
+        assert_eq!(core.commit_queue_metrics(), CommitQueueMetrics::default());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn it_retries_failed_commits() {
+        for (failures, retries, status) in [
+            (2, 2, RecordStatus::Published),
+            (
+                MAX_COMMIT_ATTEMPTS,
+                MAX_COMMIT_ATTEMPTS - 1,
+                RecordStatus::Rejected(COMMIT_FAILED_REASON.to_string()),
+            ),
+        ] {
+            let store = Box::<FailingDataStore>::default();
+            store.commit_failures.store(failures, Ordering::Relaxed);
+            let (_, operator_key) = generate_p256_pair();
+            let (core, _) = CoreService::<Sha256>::start(
+                operator_key,
+                None,
+                store,
+                Duration::from_millis(10),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+
+            let leaf = store_init_record(core.store(), "test:retried").await;
+            core.submit_package_record(leaf.log_id.clone(), leaf.record_id.clone())
+                .await;
+
+            tokio::time::timeout(Duration::from_secs(60), async {
+                loop {
+                    let record = core
+                        .store()
+                        .get_package_record(&leaf.log_id, &leaf.record_id)
+                        .await
+                        .unwrap();
+                    if record.status == status {
+                        return;
+                    }
+
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+            })
+            .await
+            .unwrap();
+
+            let metrics = core.commit_queue_metrics();
+            assert_eq!(metrics.depth, 0);
+            assert_eq!(metrics.retries_total, retries as u64);
+            assert_eq!(
+                metrics.failures_total,
+                u64::from(failures == MAX_COMMIT_ATTEMPTS)
+            );
+        }
+    }
+
+    /// Submits an operator record with the given entry and waits for it to be
+    /// processed.
+    async fn publish_operator_entry(
+        core: &CoreService,
+        key: &PrivateKey,
+        entry: operator::OperatorEntry,
+    ) -> RecordStatus {
+        let log_id = LogId::operator_log::<Sha256>();
+        let state = core.store().get_operator_log_state(&log_id).await.unwrap();
+        let record = ProtoEnvelope::signed_contents(
+            key,
+            operator::OperatorRecord {
+                prev: state.head().as_ref().map(|head| head.digest.clone()),
+                version: 0,
+                timestamp: SystemTime::now(),
+                entries: vec![entry],
+            },
+        )
+        .unwrap();
+        let record_id = RecordId::operator_record::<Sha256>(&record);
+        core.store()
+            .store_operator_record(&log_id, &record_id, &record)
+            .await
+            .unwrap();
+        core.submit_operator_record(record_id.clone()).await;
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let status = core
+                    .store()
+                    .get_operator_record(&log_id, &record_id)
+                    .await
+                    .unwrap()
+                    .status;
+                if status != RecordStatus::Pending {
+                    return status;
+                }
+
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_commits_operator_records() {
+        let operator_key = PrivateKey::decode(
+            "ecdsa-p256:I+UlDo0HxyBBFeelhPPWmD+LnklOpqZDkrFP5VduASk=".to_string(),
+        )
+        .unwrap();
+        let (core, _) = CoreService::<Sha256>::start(
+            PrivateKey::decode(
+                "ecdsa-p256:I+UlDo0HxyBBFeelhPPWmD+LnklOpqZDkrFP5VduASk=".to_string(),
+            )
+            .unwrap(),
+            None,
+            Box::<MemoryDataStore>::default(),
+            Duration::from_millis(10),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        // Grant the commit permission to a second key
+        let (public_key, _) = generate_p256_pair();
+        let status = publish_operator_entry(
+            &core,
+            &operator_key,
+            operator::OperatorEntry::GrantFlat {
+                key: public_key.clone(),
+                permissions: vec![operator::Permission::Commit],
+            },
+        )
+        .await;
+        assert!(matches!(
+            status,
+            RecordStatus::Validated | RecordStatus::Published
+        ));
+
+        let log_id = LogId::operator_log::<Sha256>();
+        let state = core.store().get_operator_log_state(&log_id).await.unwrap();
+        assert!(state.key_has_permission(&public_key.fingerprint(), operator::Permission::Commit));
+
+        // A key without the commit permission cannot grant permissions
+        let (other_key, other_private_key) = generate_p256_pair();
+        let status = publish_operator_entry(
+            &core,
+            &other_private_key,
+            operator::OperatorEntry::GrantFlat {
+                key: other_key,
+                permissions: vec![operator::Permission::Commit],
+            },
+        )
+        .await;
+        assert!(matches!(status, RecordStatus::Rejected(_)));
+
+        // The service's key cannot be revoked or rotated without a replacement key
+        for entry in [
+            operator::OperatorEntry::RevokeFlat {
+                key_id: operator_key.public_key().fingerprint(),
+                permissions: vec![operator::Permission::Commit],
+            },
+            operator::OperatorEntry::RotateKey {
+                key_id: operator_key.public_key().fingerprint(),
+                key: public_key.clone(),
+            },
+        ] {
+            let status = publish_operator_entry(&core, &operator_key, entry).await;
+            assert!(matches!(status, RecordStatus::Rejected(_)));
+        }
+
+        let state = core.store().get_operator_log_state(&log_id).await.unwrap();
+        assert!(state.key_has_permission(
+            &operator_key.public_key().fingerprint(),
+            operator::Permission::Commit
+        ));
+        assert_eq!(
+            core.inner.signing_key().await.unwrap().public_key(),
+            operator_key.public_key()
+        );
+    }
+
+    #[tokio::test]
+    async fn it_signs_checkpoints_with_the_replacement_key() {
+        let (_, operator_key) = generate_p256_pair();
+        let (replacement_public_key, replacement_key) = generate_p256_pair();
+        let (core, _) = CoreService::<Sha256>::start(
+            PrivateKey::decode(operator_key.encode().to_string()).unwrap(),
+            Some(replacement_key),
+            Box::<MemoryDataStore>::default(),
+            Duration::from_millis(10),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        // The operator key cannot be revoked until the replacement key can sign checkpoints
+        let revoke = operator::OperatorEntry::RevokeFlat {
+            key_id: operator_key.public_key().fingerprint(),
+            permissions: vec![operator::Permission::Commit],
+        };
+        let status = publish_operator_entry(&core, &operator_key, revoke.clone()).await;
+        assert!(matches!(status, RecordStatus::Rejected(_)));
+
+        let status = publish_operator_entry(
+            &core,
+            &operator_key,
+            operator::OperatorEntry::GrantFlat {
+                key: replacement_public_key.clone(),
+                permissions: vec![operator::Permission::Commit],
+            },
+        )
+        .await;
+        assert!(!matches!(status, RecordStatus::Rejected(_)));
+
+        let status = publish_operator_entry(&core, &operator_key, revoke).await;
+        assert!(!matches!(status, RecordStatus::Rejected(_)));
+
+        // Checkpoints are now signed with the replacement key
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let checkpoint = core.store().get_latest_checkpoint().await.unwrap();
+                if checkpoint.key_id() == &replacement_public_key.fingerprint() {
+                    checkpoint.verify(&replacement_public_key).unwrap();
+                    return;
+                }
+
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_commits_namespace_definitions() {
+        let store = Box::<MemoryDataStore>::default();
+        let existing = store_init_record(store.as_ref(), "test:existing").await;
+
+        let operator_key = PrivateKey::decode(
+            "ecdsa-p256:I+UlDo0HxyBBFeelhPPWmD+LnklOpqZDkrFP5VduASk=".to_string(),
+        )
+        .unwrap();
+        let (core, _) = CoreService::<Sha256>::start(
+            PrivateKey::decode(
+                "ecdsa-p256:I+UlDo0HxyBBFeelhPPWmD+LnklOpqZDkrFP5VduASk=".to_string(),
+            )
+            .unwrap(),
+            None,
+            store,
+            Duration::from_millis(10),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let mut updates = core.subscribe();
+        let update = tokio::time::timeout(Duration::from_secs(5), updates.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(update, CoreUpdate::Checkpoint(_)));
+        let record = core
+            .store()
+            .get_package_record(&existing.log_id, &existing.record_id)
+            .await
+            .unwrap();
+        assert_eq!(record.status, RecordStatus::Published);
+
+        // A namespace that already contains packages cannot be defined
+        let status = publish_operator_entry(
+            &core,
+            &operator_key,
+            operator::OperatorEntry::DefineNamespace {
+                namespace: "test".to_string(),
+                owners: Vec::new(),
+            },
+        )
+        .await;
+        assert!(matches!(status, RecordStatus::Rejected(_)));
+
+        let status = publish_operator_entry(
+            &core,
+            &operator_key,
+            operator::OperatorEntry::DefineNamespace {
+                namespace: "wasi".to_string(),
+                owners: Vec::new(),
+            },
+        )
+        .await;
+        assert!(matches!(
+            status,
+            RecordStatus::Validated | RecordStatus::Published
+        ));
+
+        // Packages in the namespace can only be initialized by an owner
+        let leaf = store_init_record(core.store(), "wasi:http").await;
+        core.submit_package_record(leaf.log_id.clone(), leaf.record_id.clone())
+            .await;
+        let status = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let status = core
+                    .store()
+                    .get_package_record(&leaf.log_id, &leaf.record_id)
+                    .await
+                    .unwrap()
+                    .status;
+                if status != RecordStatus::Pending {
+                    return status;
+                }
+
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert!(matches!(status, RecordStatus::Rejected(_)));
+    }
+
+    #[tokio::test]
+    async fn it_verifies_mirrored_checkpoints() {
+        let operator_key = || {
+            PrivateKey::decode(
+                "ecdsa-p256:I+UlDo0HxyBBFeelhPPWmD+LnklOpqZDkrFP5VduASk=".to_string(),
+            )
+            .unwrap()
+        };
+        let (upstream, _) = CoreService::<Sha256>::start(
+            operator_key(),
+            None,
+            Box::<MemoryDataStore>::default(),
+            Duration::from_secs(60),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        let checkpoint = upstream.store().get_latest_checkpoint().await.unwrap();
+        let leafs = upstream
+            .store()
+            .get_log_leafs_with_registry_index(&[0])
+            .await
+            .unwrap();
+        let LogLeaf { log_id, record_id } = &leafs[0];
+        let record = upstream
+            .store()
+            .get_operator_record(log_id, record_id)
+            .await
+            .unwrap()
+            .envelope;
+
+        let (mirror, _) =
+            CoreService::<Sha256>::start_mirror(Box::<MemoryDataStore>::default(), None)
+                .await
+                .unwrap();
+        assert!(mirror.is_mirror());
+        assert!(matches!(
+            mirror.store().get_latest_checkpoint().await,
+            Err(DataStoreError::CheckpointNotFound(_))
+        ));
+        mirror
+            .store()
+            .store_operator_record(log_id, record_id, &record)
+            .await
+            .unwrap();
+
+        // A checkpoint signed by a key not in the operator log is rejected
+        let (_, other_key) = generate_p256_pair();
+        let forged =
+            SerdeEnvelope::signed_contents(&other_key, checkpoint.as_ref().clone()).unwrap();
+        assert!(matches!(
+            mirror.commit_mirrored(leafs.clone(), forged).await,
+            Err(CoreServiceError::CheckpointSignatureInvalid(_))
+        ));
+
+        // Nothing is committed when the checkpoint is rejected
+        assert_eq!(mirror.log_length().await, 0);
+        assert_eq!(
+            mirror
+                .store()
+                .get_operator_record(log_id, record_id)
+                .await
+                .unwrap()
+                .status,
+            RecordStatus::Pending
+        );
+
+        // A checkpoint that does not match the mirrored log is rejected
+        let mut tampered = checkpoint.as_ref().clone();
+        tampered.checkpoint.map_root = Hash::<Sha256>::default().into();
+        let tampered = SerdeEnvelope::signed_contents(&operator_key(), tampered).unwrap();
+        assert!(matches!(
+            mirror.commit_mirrored(leafs.clone(), tampered).await,
+            Err(CoreServiceError::CheckpointMismatch(1))
+        ));
+        assert!(mirror.store().get_latest_checkpoint().await.is_err());
+
+        assert_eq!(mirror.log_length().await, 0);
+
+        mirror
+            .commit_mirrored(leafs, checkpoint.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            mirror.store().get_latest_checkpoint().await.unwrap(),
+            checkpoint
+        );
+    }
+
+    #[tokio::test]
+    async fn it_expires_pending_records() {
+        let (_, operator_key) = generate_p256_pair();
+        let (core, _) = CoreService::<Sha256>::start(
+            operator_key,
+            None,
+            Box::<MemoryDataStore>::default(),
+            Duration::from_secs(60),
+            Some(Duration::from_millis(100)),
+            None,
+        )
+        .await
+        .unwrap();
+        let mut updates = core.subscribe();
+
+        // Records with all of their content present are not expired
+        let ready = store_init_record(core.store(), "test:ready").await;
+
+        // The record's timestamp is not when it was received
+        let (public_key, signing_key) = generate_p256_pair();
+        let package_id = PackageId::new("test:expired").unwrap();
+        let digest = HashAlgorithm::Sha256.digest(b"missing");
+        let record = ProtoEnvelope::signed_contents(
+            &signing_key,
+            PackageRecord {
+                prev: None,
+                version: 0,
+                timestamp: SystemTime::now() - Duration::from_secs(60 * 60),
+                entries: vec![
+                    PackageEntry::Init {
+                        hash_algorithm: HashAlgorithm::Sha256,
+                        key: public_key,
+                    },
+                    PackageEntry::Release {
+                        version: "1.0.0".parse().unwrap(),
+                        content: digest.clone(),
+                    },
+                ],
+            },
+        )
+        .unwrap();
+        let leaf = LogLeaf {
+            log_id: LogId::package_log::<Sha256>(&package_id),
+            record_id: RecordId::package_record::<Sha256>(&record),
+        };
+        core.store()
+            .store_package_record(
+                &leaf.log_id,
+                &package_id,
+                &leaf.record_id,
+                &record,
+                &HashSet::from([&digest]),
+            )
+            .await
+            .unwrap();
+        assert!(core
+            .store()
+            .get_pending_package_records(SystemTime::now() - Duration::from_secs(60))
+            .await
+            .unwrap()
+            .is_empty());
+
+        let update = tokio::time::timeout(Duration::from_secs(5), updates.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(update, CoreUpdate::RecordRejected(l) if l == leaf));
+
+        let record = core
+            .store()
+            .get_package_record(&leaf.log_id, &leaf.record_id)
+            .await
+            .unwrap();
+        assert_eq!(
+            record.status,
+            RecordStatus::Rejected(PENDING_RECORD_EXPIRED_REASON.to_string())
+        );
+
+        let record = core
+            .store()
+            .get_package_record(&ready.log_id, &ready.record_id)
+            .await
+            .unwrap();
+        assert_eq!(record.status, RecordStatus::Pending);
+    }
 }
@@ -0,0 +1,307 @@
+use std::{
+    collections::HashSet,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use thiserror::Error;
+use tokio::{task::JoinHandle, time::MissedTickBehavior};
+use warg_crypto::hash::AnyHash;
+
+use crate::{
+    contentstore::{ContentInfo, ContentStore, ContentStoreError},
+    datastore::DataStoreError,
+    services::CoreService,
+};
+
+/// Represents the result of a content garbage collection pass.
+#[derive(Debug, Default)]
+pub struct CollectionReport {
+    /// The number of content items found in the content store.
+    pub scanned: usize,
+    /// The unreferenced content that was collected.
+    ///
+    /// For a dry run, this is the content that would have been collected.
+    pub collected: Vec<ContentInfo>,
+    /// Whether or not the pass was a dry run.
+    pub dry_run: bool,
+}
+
+impl CollectionReport {
+    /// Gets the total size, in bytes, of the collected content.
+    pub fn collected_size(&self) -> u64 {
+        self.collected.iter().map(|info| info.size).sum()
+    }
+}
+
+/// Collects content that is not referenced by any validated package record.
+///
+/// Content of pending records is retained until the grace period elapses,
+/// after which the records are considered abandoned. Content that was
+/// modified within the grace period is always retained so that in-flight
+/// uploads are not collected.
+#[derive(Clone)]
+pub struct GarbageCollector {
+    core: CoreService,
+    content_store: Arc<dyn ContentStore>,
+    grace_period: Duration,
+    dry_run: bool,
+}
+
+impl GarbageCollector {
+    /// Creates a new garbage collector with the given grace period.
+    pub fn new(
+        core: CoreService,
+        content_store: Arc<dyn ContentStore>,
+        grace_period: Duration,
+    ) -> Self {
+        Self {
+            core,
+            content_store,
+            grace_period,
+            dry_run: false,
+        }
+    }
+
+    /// Sets whether or not collection only reports unreferenced content
+    /// without deleting it.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Performs a single garbage collection pass.
+    pub async fn collect(&self) -> Result<CollectionReport, GarbageCollectorError> {
+        let cutoff = SystemTime::now()
+            .checked_sub(self.grace_period)
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+
+        // List the content before getting the references so that content
+        // stored for a new record is never seen without its reference
+        let content = self.content_store.list_content().await?;
+        let referenced = self.core.store().get_referenced_content(cutoff).await?;
+
+        let scanned = content.len();
+        let collected = unreferenced_content(content, &referenced, cutoff);
+
+        if !self.dry_run {
+            for info in &collected {
+                tracing::debug!(
+                    "deleting unreferenced content `{digest}`",
+                    digest = info.digest
+                );
+                self.content_store.delete_content(&info.digest).await?;
+            }
+        }
+
+        Ok(CollectionReport {
+            scanned,
+            collected,
+            dry_run: self.dry_run,
+        })
+    }
+
+    /// Starts performing garbage collection passes at the given interval.
+    ///
+    /// The returned task runs until aborted.
+    pub fn start(self, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            loop {
+                interval.tick().await;
+
+                match self.collect().await {
+                    Ok(report) if report.dry_run => {
+                        for info in &report.collected {
+                            tracing::info!(
+                                "unreferenced content `{digest}` ({size} bytes) would be collected",
+                                digest = info.digest,
+                                size = info.size
+                            );
+                        }
+
+                        tracing::info!(
+                            "content garbage collection dry run: {count} of {scanned} item(s) ({size} bytes) would be collected",
+                            count = report.collected.len(),
+                            scanned = report.scanned,
+                            size = report.collected_size()
+                        );
+                    }
+                    Ok(report) => tracing::info!(
+                        "content garbage collection: collected {count} of {scanned} item(s) ({size} bytes)",
+                        count = report.collected.len(),
+                        scanned = report.scanned,
+                        size = report.collected_size()
+                    ),
+                    Err(e) => tracing::error!("content garbage collection failed: {e}"),
+                }
+            }
+        })
+    }
+}
+
+/// Selects the content that is not referenced and was last modified before
+/// the given cutoff.
+fn unreferenced_content(
+    content: Vec<ContentInfo>,
+    referenced: &HashSet<AnyHash>,
+    cutoff: SystemTime,
+) -> Vec<ContentInfo> {
+    content
+        .into_iter()
+        .filter(|info| !referenced.contains(&info.digest) && info.last_modified <= cutoff)
+        .collect()
+}
+
+#[derive(Debug, Error)]
+pub enum GarbageCollectorError {
+    #[error("failed to get referenced content: {0}")]
+    DataStore(#[from] DataStoreError),
+    #[error("failed to access content: {0}")]
+    ContentStore(#[from] ContentStoreError),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        contentstore::FileSystemContentStore,
+        datastore::{DataStore, MemoryDataStore},
+    };
+    use warg_crypto::{
+        hash::{HashAlgorithm, Sha256},
+        signing::{generate_p256_pair, PrivateKey},
+    };
+    use warg_protocol::{
+        package,
+        registry::{LogId, PackageId, RecordId},
+        ProtoEnvelope,
+    };
+
+    fn info(content: &[u8], last_modified: SystemTime) -> ContentInfo {
+        ContentInfo {
+            digest: HashAlgorithm::Sha256.digest(content),
+            size: content.len() as u64,
+            last_modified,
+        }
+    }
+
+    #[test]
+    fn it_selects_unreferenced_content() {
+        let now = SystemTime::now();
+        let cutoff = now - Duration::from_secs(60);
+        let referenced = info(b"referenced", cutoff - Duration::from_secs(60));
+        let unreferenced = info(b"unreferenced", cutoff - Duration::from_secs(60));
+        let recent = info(b"recent", now);
+
+        let collected = unreferenced_content(
+            vec![referenced.clone(), unreferenced.clone(), recent],
+            &HashSet::from([referenced.digest]),
+            cutoff,
+        );
+        assert_eq!(collected, [unreferenced]);
+    }
+
+    #[tokio::test]
+    async fn it_collects_unreferenced_content() {
+        let operator_key = PrivateKey::decode(
+            "ecdsa-p256:I+UlDo0HxyBBFeelhPPWmD+LnklOpqZDkrFP5VduASk=".to_string(),
+        )
+        .unwrap();
+        let (core, _) = CoreService::start(
+            operator_key,
+            None,
+            Box::<MemoryDataStore>::default(),
+            Duration::from_secs(60),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let content_store = Arc::new(FileSystemContentStore::new(
+            dir.path(),
+            "http://localhost".parse().unwrap(),
+        ));
+
+        let digest = HashAlgorithm::Sha256.digest(b"orphaned");
+        let path = dir.path().join("orphaned");
+        std::fs::write(&path, b"orphaned").unwrap();
+        content_store.store_content(&digest, &path).await.unwrap();
+
+        // Content within the grace period is retained
+        let gc = GarbageCollector::new(
+            core.clone(),
+            content_store.clone(),
+            Duration::from_secs(60 * 60),
+        );
+        let report = gc.collect().await.unwrap();
+        assert_eq!(report.scanned, 1);
+        assert!(report.collected.is_empty());
+
+        // A dry run reports the content without deleting it
+        let gc = GarbageCollector::new(core, content_store.clone(), Duration::ZERO);
+        let report = gc.clone().with_dry_run(true).collect().await.unwrap();
+        assert_eq!(report.collected.len(), 1);
+        assert_eq!(report.collected_size(), 8);
+        assert!(content_store.content_present(&digest).await.unwrap());
+
+        let report = gc.collect().await.unwrap();
+        assert_eq!(report.collected.len(), 1);
+        assert!(!content_store.content_present(&digest).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn it_references_content_of_recently_received_records() {
+        let store = MemoryDataStore::default();
+
+        // The record's timestamp is not when it was received
+        let (public_key, signing_key) = generate_p256_pair();
+        let package_id = PackageId::new("test:pending").unwrap();
+        let digest = HashAlgorithm::Sha256.digest(b"pending");
+        let record = ProtoEnvelope::signed_contents(
+            &signing_key,
+            package::PackageRecord {
+                prev: None,
+                version: 0,
+                timestamp: SystemTime::now() - Duration::from_secs(60 * 60),
+                entries: vec![
+                    package::PackageEntry::Init {
+                        hash_algorithm: HashAlgorithm::Sha256,
+                        key: public_key,
+                    },
+                    package::PackageEntry::Release {
+                        version: "1.0.0".parse().unwrap(),
+                        content: digest.clone(),
+                    },
+                ],
+            },
+        )
+        .unwrap();
+        store
+            .store_package_record(
+                &LogId::package_log::<Sha256>(&package_id),
+                &package_id,
+                &RecordId::package_record::<Sha256>(&record),
+                &record,
+                &HashSet::from([&digest]),
+            )
+            .await
+            .unwrap();
+
+        let referenced = store
+            .get_referenced_content(SystemTime::now() - Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert_eq!(referenced, HashSet::from([digest]));
+
+        let referenced = store
+            .get_referenced_content(SystemTime::now() + Duration::from_secs(1))
+            .await
+            .unwrap();
+        assert!(referenced.is_empty());
+    }
+}
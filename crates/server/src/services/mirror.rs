@@ -0,0 +1,878 @@
+use std::{borrow::Cow, collections::HashMap, path::PathBuf, sync::Arc, time::Duration};
+
+use futures::{pin_mut, StreamExt};
+use tempfile::NamedTempFile;
+use thiserror::Error;
+use tokio::{io::AsyncWriteExt, task::JoinHandle, time::MissedTickBehavior};
+use warg_api::v1::{
+    fetch::{FetchLogsRequest, FetchPackagesRequest},
+    proof::ConsistencyRequest,
+};
+use warg_client::api::{Client, ClientError};
+use warg_crypto::hash::{AnyHash, Sha256};
+use warg_protocol::{
+    operator, package,
+    registry::{LogId, LogLeaf, PackageId, RecordId, RegistryIndex, RegistryLen},
+    PublishedProtoEnvelope, Record as _,
+};
+
+use crate::{
+    contentstore::{ContentStore, ContentStoreError},
+    datastore::DataStoreError,
+    services::{CoreService, CoreServiceError},
+};
+
+/// The maximum number of records requested per log when fetching logs.
+const FETCH_LOGS_LIMIT: u16 = 100;
+
+/// Represents the result of synchronizing a mirror with its upstream
+/// registry.
+#[derive(Debug, Default)]
+pub struct SyncReport {
+    /// The log length of the upstream checkpoint that was mirrored.
+    pub log_length: RegistryLen,
+    /// The number of records replicated from the upstream registry.
+    pub records: usize,
+    /// The number of content items downloaded from the upstream registry.
+    pub contents: usize,
+}
+
+/// Replicates the records, content, and checkpoints of an upstream registry.
+///
+/// Every replicated record is validated against its log and every upstream
+/// checkpoint is checked for consistency with the mirrored checkpoint,
+/// recomputed from the replicated records, and verified against the keys of
+/// the replicated operator log. A mirror that fails to verify the upstream
+/// registry stops replicating it.
+#[derive(Clone)]
+pub struct Mirror {
+    core: CoreService,
+    content_store: Arc<dyn ContentStore>,
+    temp_dir: PathBuf,
+    client: Arc<Client>,
+}
+
+impl Mirror {
+    /// Creates a new mirror of the upstream registry of the given client.
+    ///
+    /// Downloaded content is written to the given temporary directory before
+    /// it is stored.
+    pub fn new(
+        core: CoreService,
+        content_store: Arc<dyn ContentStore>,
+        temp_dir: PathBuf,
+        client: Client,
+    ) -> Self {
+        Self {
+            core,
+            content_store,
+            temp_dir,
+            client: Arc::new(client),
+        }
+    }
+
+    /// Replicates the upstream registry up to its latest checkpoint.
+    pub async fn sync(&self) -> Result<SyncReport, MirrorError> {
+        let ts_checkpoint = self.client.latest_checkpoint().await?;
+        let checkpoint = &ts_checkpoint.as_ref().checkpoint;
+        let mut report = SyncReport {
+            log_length: checkpoint.log_length,
+            ..Default::default()
+        };
+
+        match self.core.store().get_latest_checkpoint().await {
+            Ok(mirrored) => {
+                let mirrored = &mirrored.as_ref().checkpoint;
+                if mirrored == checkpoint {
+                    return Ok(report);
+                }
+
+                if checkpoint.log_length <= mirrored.log_length {
+                    return Err(MirrorError::Inconsistent {
+                        mirrored: mirrored.log_length,
+                        upstream: checkpoint.log_length,
+                    });
+                }
+
+                self.client
+                    .prove_log_consistency(
+                        ConsistencyRequest {
+                            from: mirrored.log_length,
+                            to: checkpoint.log_length,
+                        },
+                        Cow::Borrowed(&mirrored.log_root),
+                        Cow::Borrowed(&checkpoint.log_root),
+                    )
+                    .await?;
+            }
+            // Nothing has been mirrored yet
+            Err(DataStoreError::CheckpointNotFound(_)) => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        // The fetched records must extend the mirrored log up to the checkpoint
+        let records = self.fetch_records(checkpoint.log_length).await?;
+        let start = self.core.log_length().await;
+        for (index, record) in (start..checkpoint.log_length).zip(&records) {
+            if record.registry_index() != index {
+                return Err(MirrorError::MissingRecord(index));
+            }
+        }
+
+        if start + records.len() != checkpoint.log_length {
+            return Err(MirrorError::MissingRecord(start + records.len()));
+        }
+
+        let mut leafs = Vec::with_capacity(records.len());
+        for record in records {
+            leafs.push(self.store_record(record, &mut report).await?);
+        }
+
+        report.records = leafs.len();
+        self.core.commit_mirrored(leafs, ts_checkpoint).await?;
+        Ok(report)
+    }
+
+    /// Starts synchronizing with the upstream registry at the given interval.
+    ///
+    /// The returned task runs until aborted or until the upstream registry
+    /// fails verification; other failures are retried at the next interval.
+    pub fn start(self, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            loop {
+                interval.tick().await;
+
+                match self.sync().await {
+                    Ok(report) if report.records == 0 => tracing::debug!(
+                        "mirror is up to date at log length {log_length}",
+                        log_length = report.log_length
+                    ),
+                    Ok(report) => tracing::info!(
+                        "mirrored {records} record(s) and {contents} content item(s) up to log length {log_length}",
+                        records = report.records,
+                        contents = report.contents,
+                        log_length = report.log_length
+                    ),
+                    Err(e) if e.is_verification_failure() => {
+                        tracing::error!(
+                            "stopped mirroring the upstream registry as it failed verification: {e}"
+                        );
+                        return;
+                    }
+                    Err(e) => tracing::error!("failed to mirror the upstream registry: {e}"),
+                }
+            }
+        })
+    }
+
+    // Fetches the upstream records that are not yet mirrored, validating
+    // each against its log, in registry log order
+    async fn fetch_records(
+        &self,
+        log_length: RegistryLen,
+    ) -> Result<Vec<FetchedRecord>, MirrorError> {
+        let store = self.core.store();
+        let mut operator = store
+            .get_operator_log_state(&LogId::operator_log::<Sha256>())
+            .await?;
+
+        let mut packages = HashMap::new();
+        for id in self.fetch_package_ids().await? {
+            let log_id = LogId::package_log::<Sha256>(&id);
+            let state = store.get_package_log_state(&log_id).await?;
+            packages.insert(log_id, (id, state));
+        }
+
+        // Namespaces defined by the fetched operator records, as they only
+        // restrict the package records committed after them
+        let mut namespace_indices = HashMap::new();
+        let mut records = Vec::new();
+        loop {
+            let last_known = packages
+                .iter()
+                .map(|(log_id, (_, state))| {
+                    (
+                        log_id.clone(),
+                        state.head().as_ref().map(|h| h.digest.clone()),
+                    )
+                })
+                .collect::<HashMap<_, _>>();
+
+            let response = self
+                .client
+                .fetch_logs(FetchLogsRequest {
+                    log_length,
+                    limit: Some(FETCH_LOGS_LIMIT),
+                    operator: operator.head().as_ref().map(|h| Cow::Borrowed(&h.digest)),
+                    packages: Cow::Owned(last_known),
+                })
+                .await?;
+
+            // Package records are only validated once the operator log is
+            // complete so that each is validated against the namespaces
+            // defined before it
+            let operator_complete = response.operator.len() < FETCH_LOGS_LIMIT as usize;
+
+            for record in response.operator {
+                let record: PublishedProtoEnvelope<operator::OperatorRecord> = record
+                    .try_into()
+                    .map_err(|e| MirrorError::InvalidRecord(format!("{e}")))?;
+                operator.validate(&record.envelope)?;
+
+                for entry in &record.envelope.as_ref().entries {
+                    if let operator::OperatorEntry::DefineNamespace { namespace, .. }
+                    | operator::OperatorEntry::ImportNamespace { namespace, .. } = entry
+                    {
+                        namespace_indices.insert(namespace.clone(), record.registry_index);
+                    }
+                }
+
+                records.push(FetchedRecord::Operator(record));
+            }
+
+            if !operator_complete {
+                // The package records will be fetched again with the next page
+                continue;
+            }
+
+            for (log_id, package_records) in response.packages {
+                let (id, state) = packages
+                    .get_mut(&log_id)
+                    .ok_or_else(|| MirrorError::UnknownLog(log_id.clone()))?;
+
+                for record in package_records {
+                    let record: PublishedProtoEnvelope<package::PackageRecord> = record
+                        .try_into()
+                        .map_err(|e| MirrorError::InvalidRecord(format!("{e}")))?;
+                    let restricted = namespace_indices
+                        .get(id.namespace())
+                        .map_or(true, |index| *index < record.registry_index);
+                    let result = if restricted {
+                        state.validate_with_operator(id, &operator, &record.envelope)
+                    } else {
+                        state.validate(&record.envelope)
+                    };
+                    result.map_err(|inner| MirrorError::PackageValidationFailed {
+                        id: id.clone(),
+                        inner,
+                    })?;
+                    records.push(FetchedRecord::Package(id.clone(), record));
+                }
+            }
+
+            if !response.more {
+                break;
+            }
+        }
+
+        records.sort_by_key(FetchedRecord::registry_index);
+        Ok(records)
+    }
+
+    // Fetches the identifiers of every package published to the upstream registry
+    async fn fetch_package_ids(&self) -> Result<Vec<PackageId>, MirrorError> {
+        let mut ids = Vec::new();
+        loop {
+            let response = self
+                .client
+                .fetch_packages(FetchPackagesRequest {
+                    namespace: None,
+                    since: ids.last().map(Cow::Borrowed),
+                    limit: None,
+                })
+                .await?;
+
+            ids.extend(response.packages);
+            if !response.more {
+                return Ok(ids);
+            }
+        }
+    }
+
+    // Stores a fetched record, if not already stored, along with any of its
+    // missing content, returning its log leaf
+    async fn store_record(
+        &self,
+        record: FetchedRecord,
+        report: &mut SyncReport,
+    ) -> Result<LogLeaf, MirrorError> {
+        let store = self.core.store();
+        match record {
+            FetchedRecord::Operator(record) => {
+                let log_id = LogId::operator_log::<Sha256>();
+                let record_id = RecordId::operator_record::<Sha256>(&record.envelope);
+                match store.get_operator_record(&log_id, &record_id).await {
+                    Ok(_) => {}
+                    Err(DataStoreError::LogNotFound(_) | DataStoreError::RecordNotFound(_)) => {
+                        store
+                            .store_operator_record(&log_id, &record_id, &record.envelope)
+                            .await?
+                    }
+                    Err(e) => return Err(e.into()),
+                }
+
+                Ok(LogLeaf { log_id, record_id })
+            }
+            FetchedRecord::Package(id, record) => {
+                let envelope = &record.envelope;
+                let log_id = LogId::package_log::<Sha256>(&id);
+                let record_id =
+                    RecordId::package_record_with(envelope.as_ref().hash_algorithm(), envelope);
+                match store.get_package_record(&log_id, &record_id).await {
+                    Ok(_) => {}
+                    Err(DataStoreError::LogNotFound(_) | DataStoreError::RecordNotFound(_)) => {
+                        let mut missing = envelope.as_ref().contents();
+                        for digest in missing.clone() {
+                            if self.content_store.content_present(digest).await? {
+                                missing.remove(digest);
+                            }
+                        }
+
+                        store
+                            .store_package_record(&log_id, &id, &record_id, envelope, &missing)
+                            .await?;
+                    }
+                    Err(e) => return Err(e.into()),
+                }
+
+                for digest in envelope.as_ref().contents() {
+                    if store
+                        .is_content_missing(&log_id, &record_id, digest)
+                        .await?
+                    {
+                        let size = self.download_content(&log_id, &record_id, digest).await?;
+                        store
+                            .set_content_present(&log_id, &record_id, digest, size, None)
+                            .await?;
+                        report.contents += 1;
+                    }
+                }
+
+                Ok(LogLeaf { log_id, record_id })
+            }
+        }
+    }
+
+    // Downloads content of a record from the upstream registry to the content
+    // store, returning the size of the content in bytes
+    async fn download_content(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        digest: &AnyHash,
+    ) -> Result<u64, MirrorError> {
+        tracing::debug!("downloading content `{digest}` of record `{record_id}`");
+
+        let tmp_path = NamedTempFile::new_in(&self.temp_dir)?.into_temp_path();
+        let mut file = tokio::fs::File::create(&tmp_path).await?;
+        let stream = self
+            .client
+            .download_content(log_id, record_id, digest, 0)
+            .await?;
+        pin_mut!(stream);
+
+        let mut hasher = digest.algorithm().hasher();
+        let mut size: u64 = 0;
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|e| MirrorError::ContentDownload {
+                digest: digest.clone(),
+                message: format!("{e}"),
+            })?;
+            size = size.saturating_add(chunk.len() as u64);
+            hasher.update(&chunk);
+            file.write_all(&chunk).await?;
+        }
+
+        file.flush().await?;
+
+        let found = hasher.finalize();
+        if &found != digest {
+            return Err(MirrorError::ContentDigestMismatch {
+                expected: digest.clone(),
+                found,
+            });
+        }
+
+        self.content_store.store_content(digest, &tmp_path).await?;
+        Ok(size)
+    }
+}
+
+/// Represents a record fetched from the upstream registry.
+enum FetchedRecord {
+    Operator(PublishedProtoEnvelope<operator::OperatorRecord>),
+    Package(PackageId, PublishedProtoEnvelope<package::PackageRecord>),
+}
+
+impl FetchedRecord {
+    fn registry_index(&self) -> RegistryIndex {
+        match self {
+            Self::Operator(record) => record.registry_index,
+            Self::Package(_, record) => record.registry_index,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum MirrorError {
+    #[error("failed to communicate with the upstream registry: {0}")]
+    Client(#[from] ClientError),
+    #[error("the upstream checkpoint at log length {upstream} is inconsistent with the mirrored checkpoint at log length {mirrored}")]
+    Inconsistent {
+        mirrored: RegistryLen,
+        upstream: RegistryLen,
+    },
+    #[error("the upstream registry did not return the record at registry index {0}")]
+    MissingRecord(RegistryIndex),
+    #[error("the upstream registry returned records of unknown log `{0}`")]
+    UnknownLog(LogId),
+    #[error("the upstream registry returned an invalid record: {0}")]
+    InvalidRecord(String),
+    #[error("the upstream operator record was invalid: {0}")]
+    OperatorValidationFailed(#[from] operator::ValidationError),
+    #[error("the upstream record of package `{id}` was invalid: {inner}")]
+    PackageValidationFailed {
+        id: PackageId,
+        inner: package::ValidationError,
+    },
+    #[error("failed to download content `{digest}`: {message}")]
+    ContentDownload { digest: AnyHash, message: String },
+    #[error("downloaded content digest `{found}` does not match expected digest `{expected}`")]
+    ContentDigestMismatch { expected: AnyHash, found: AnyHash },
+    #[error("failed to write downloaded content: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("failed to store mirrored content: {0}")]
+    ContentStore(#[from] ContentStoreError),
+    #[error("data store error: {0}")]
+    DataStore(#[from] DataStoreError),
+    #[error("failed to commit mirrored records: {0}")]
+    Core(#[from] CoreServiceError),
+}
+
+impl MirrorError {
+    /// Returns whether the error is a failure to verify the upstream
+    /// registry, as opposed to a failure to replicate it.
+    pub fn is_verification_failure(&self) -> bool {
+        match self {
+            Self::Inconsistent { .. }
+            | Self::InvalidRecord(_)
+            | Self::OperatorValidationFailed(_)
+            | Self::PackageValidationFailed { .. }
+            | Self::ContentDigestMismatch { .. } => true,
+            Self::Client(e) => matches!(
+                e,
+                ClientError::IncorrectConsistencyProof { .. } | ClientError::ConsistencyProof(_)
+            ),
+            Self::DataStore(e) | Self::Core(CoreServiceError::DataStore(e)) => matches!(
+                e,
+                DataStoreError::OperatorValidationFailed(_)
+                    | DataStoreError::PackageValidationFailed(_)
+            ),
+            Self::Core(e) => matches!(
+                e,
+                CoreServiceError::CheckpointMismatch(_)
+                    | CoreServiceError::CheckpointSignatureInvalid(_)
+            ),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        api::create_router,
+        contentstore::FileSystemContentStore,
+        datastore::{DataStore, MemoryDataStore},
+        services::CoreUpdate,
+    };
+    use axum::{routing::get, Json, Router};
+    use std::{collections::HashSet, net::TcpListener, time::SystemTime};
+    use warg_api::v1::paths;
+    use warg_crypto::{
+        hash::{Hash, HashAlgorithm},
+        signing::generate_p256_pair,
+    };
+    use warg_protocol::{
+        operator::{OperatorEntry, OperatorRecord},
+        package::{PackageEntry, PackageRecord},
+        registry::{Checkpoint, TimestampedCheckpoint},
+        ProtoEnvelope, SerdeEnvelope,
+    };
+
+    /// Serves the given registry, returning its URL and content store.
+    fn serve(core: CoreService, dir: &std::path::Path) -> (String, Arc<FileSystemContentStore>) {
+        serve_with(core, dir, Router::new())
+    }
+
+    /// Serves the given registry, with the routes of the given router taking
+    /// precedence, returning its URL and content store.
+    fn serve_with(
+        core: CoreService,
+        dir: &std::path::Path,
+        router: Router,
+    ) -> (String, Arc<FileSystemContentStore>) {
+        let (temp_dir, files_dir) = (dir.join("tmp"), dir.join("files"));
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        std::fs::create_dir_all(&files_dir).unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{addr}", addr = listener.local_addr().unwrap());
+        let content_store = Arc::new(FileSystemContentStore::new(files_dir, url.parse().unwrap()));
+        let router = router.fallback_service(create_router(
+            content_store.clone(),
+            core,
+            temp_dir,
+            None,
+            None,
+            None,
+            Vec::new(),
+        ));
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(router.into_make_service()),
+        );
+
+        (url, content_store)
+    }
+
+    #[tokio::test]
+    async fn it_mirrors_an_upstream_registry() {
+        // Serve an upstream registry
+        let dir = tempfile::tempdir().unwrap();
+        let (_, operator_key) = generate_p256_pair();
+        let (upstream, _) = CoreService::start(
+            operator_key,
+            None,
+            Box::<MemoryDataStore>::default(),
+            Duration::from_millis(10),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        let (url, content_store) = serve(upstream.clone(), dir.path());
+        let temp_dir = dir.path().join("tmp");
+
+        // Publish a package with content to the upstream registry
+        let digest = HashAlgorithm::Sha256.digest(b"content");
+        let path = temp_dir.join("content");
+        std::fs::write(&path, b"content").unwrap();
+        content_store.store_content(&digest, &path).await.unwrap();
+
+        let (public_key, signing_key) = generate_p256_pair();
+        let id = PackageId::new("test:mirrored").unwrap();
+        let record = ProtoEnvelope::signed_contents(
+            &signing_key,
+            PackageRecord {
+                prev: None,
+                version: 0,
+                timestamp: SystemTime::now(),
+                entries: vec![
+                    PackageEntry::Init {
+                        hash_algorithm: HashAlgorithm::Sha256,
+                        key: public_key,
+                    },
+                    PackageEntry::Release {
+                        version: "1.0.0".parse().unwrap(),
+                        content: digest.clone(),
+                    },
+                ],
+            },
+        )
+        .unwrap();
+        let log_id = LogId::package_log::<Sha256>(&id);
+        let record_id = RecordId::package_record::<Sha256>(&record);
+        upstream
+            .store()
+            .store_package_record(&log_id, &id, &record_id, &record, &HashSet::new())
+            .await
+            .unwrap();
+
+        let mut updates = upstream.subscribe();
+        upstream.submit_package_record(log_id, record_id).await;
+        let checkpoint = loop {
+            if let CoreUpdate::Checkpoint(checkpoint) =
+                tokio::time::timeout(Duration::from_secs(5), updates.recv())
+                    .await
+                    .unwrap()
+                    .unwrap()
+            {
+                break checkpoint;
+            }
+        };
+
+        // Mirror the upstream registry
+        let mirror_dir = tempfile::tempdir().unwrap();
+        let (core, _) = CoreService::start_mirror(Box::<MemoryDataStore>::default(), None)
+            .await
+            .unwrap();
+        let mirrored_content = Arc::new(FileSystemContentStore::new(
+            mirror_dir.path().join("files"),
+            "http://localhost".parse().unwrap(),
+        ));
+        std::fs::create_dir_all(mirror_dir.path().join("files")).unwrap();
+        let mirror = Mirror::new(
+            core.clone(),
+            mirrored_content.clone(),
+            mirror_dir.path().to_path_buf(),
+            Client::new(url.as_str()).unwrap(),
+        );
+
+        let report = mirror.sync().await.unwrap();
+        assert_eq!(report.log_length, 2);
+        assert_eq!(report.records, 2);
+        assert_eq!(report.contents, 1);
+        // The upstream registry may have signed the checkpoint again since
+        assert_eq!(
+            core.store()
+                .get_latest_checkpoint()
+                .await
+                .unwrap()
+                .into_contents()
+                .checkpoint,
+            checkpoint.into_contents().checkpoint
+        );
+        assert!(mirrored_content.content_present(&digest).await.unwrap());
+
+        // The mirror is up to date with the upstream registry
+        let report = mirror.sync().await.unwrap();
+        assert_eq!(report.records, 0);
+    }
+
+    #[tokio::test]
+    async fn it_mirrors_packages_published_before_their_namespace() {
+        // Seed an upstream registry with a namespace defined after a package
+        // was published to it
+        let store = Box::<MemoryDataStore>::default();
+        let (operator_public, operator_key) = generate_p256_pair();
+        let operator_log_id = LogId::operator_log::<Sha256>();
+        let init = ProtoEnvelope::signed_contents(
+            &operator_key,
+            OperatorRecord {
+                prev: None,
+                version: 0,
+                timestamp: SystemTime::now(),
+                entries: vec![OperatorEntry::Init {
+                    hash_algorithm: HashAlgorithm::Sha256,
+                    key: operator_public,
+                }],
+            },
+        )
+        .unwrap();
+        let init_id = RecordId::operator_record::<Sha256>(&init);
+        store
+            .store_operator_record(&operator_log_id, &init_id, &init)
+            .await
+            .unwrap();
+        store
+            .commit_operator_record(&operator_log_id, &init_id, 0)
+            .await
+            .unwrap();
+
+        let (public_key, signing_key) = generate_p256_pair();
+        let id = PackageId::new("test:early").unwrap();
+        let record = ProtoEnvelope::signed_contents(
+            &signing_key,
+            PackageRecord {
+                prev: None,
+                version: 0,
+                timestamp: SystemTime::now(),
+                entries: vec![PackageEntry::Init {
+                    hash_algorithm: HashAlgorithm::Sha256,
+                    key: public_key,
+                }],
+            },
+        )
+        .unwrap();
+        let log_id = LogId::package_log::<Sha256>(&id);
+        let record_id = RecordId::package_record::<Sha256>(&record);
+        store
+            .store_package_record(&log_id, &id, &record_id, &record, &HashSet::new())
+            .await
+            .unwrap();
+        store
+            .commit_package_record(&log_id, &record_id, 1)
+            .await
+            .unwrap();
+
+        // The package's key does not own the namespace
+        let (owner, _) = generate_p256_pair();
+        let define = ProtoEnvelope::signed_contents(
+            &operator_key,
+            OperatorRecord {
+                prev: Some(init_id),
+                version: 0,
+                timestamp: SystemTime::now(),
+                entries: vec![OperatorEntry::DefineNamespace {
+                    namespace: "test".to_string(),
+                    owners: vec![owner.fingerprint()],
+                }],
+            },
+        )
+        .unwrap();
+        let define_id = RecordId::operator_record::<Sha256>(&define);
+        store
+            .store_operator_record(&operator_log_id, &define_id, &define)
+            .await
+            .unwrap();
+        store
+            .commit_operator_record(&operator_log_id, &define_id, 2)
+            .await
+            .unwrap();
+
+        // The upstream registry signs a checkpoint of the seeded records
+        // after the initial "zero" checkpoint
+        let zero = Checkpoint {
+            log_root: Hash::<Sha256>::default().into(),
+            log_length: 0,
+            map_root: Hash::<Sha256>::default().into(),
+        };
+        store
+            .store_checkpoint(
+                &Hash::<Sha256>::of(&zero).into(),
+                SerdeEnvelope::signed_contents(
+                    &operator_key,
+                    TimestampedCheckpoint::now(zero).unwrap(),
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let (upstream, _) = CoreService::start(
+            operator_key,
+            None,
+            store,
+            Duration::from_millis(10),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        let (url, _) = serve(upstream.clone(), dir.path());
+        let checkpoint = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let checkpoint = upstream.store().get_latest_checkpoint().await.unwrap();
+                if checkpoint.as_ref().checkpoint.log_length == 3 {
+                    break checkpoint.into_contents().checkpoint;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        // The package record is validated against the operator log as of
+        // its registry index
+        let mirror_dir = tempfile::tempdir().unwrap();
+        let (core, _) = CoreService::start_mirror(Box::<MemoryDataStore>::default(), None)
+            .await
+            .unwrap();
+        let mirror = Mirror::new(
+            core.clone(),
+            Arc::new(FileSystemContentStore::new(
+                mirror_dir.path().to_path_buf(),
+                "http://localhost".parse().unwrap(),
+            )),
+            mirror_dir.path().to_path_buf(),
+            Client::new(url.as_str()).unwrap(),
+        );
+
+        let report = mirror.sync().await.unwrap();
+        assert_eq!(report.records, 3);
+        // The upstream registry may have signed the checkpoint again since
+        assert_eq!(
+            core.store()
+                .get_latest_checkpoint()
+                .await
+                .unwrap()
+                .into_contents()
+                .checkpoint,
+            checkpoint
+        );
+        let state = core.store().get_package_log_state(&log_id).await.unwrap();
+        assert_eq!(state.head().as_ref().map(|h| &h.digest), Some(&record_id));
+    }
+
+    #[tokio::test]
+    async fn it_stops_mirroring_an_upstream_registry_with_a_forged_checkpoint() {
+        let dir = tempfile::tempdir().unwrap();
+        let (_, operator_key) = generate_p256_pair();
+        let (upstream, _) = CoreService::start(
+            operator_key,
+            None,
+            Box::<MemoryDataStore>::default(),
+            Duration::from_millis(10),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        // Serve the upstream checkpoint signed by a key not in the operator log
+        let (_, forger_key) = generate_p256_pair();
+        let checkpoint = upstream
+            .store()
+            .get_latest_checkpoint()
+            .await
+            .unwrap()
+            .into_contents()
+            .checkpoint;
+        let forged = SerdeEnvelope::signed_contents(
+            &forger_key,
+            TimestampedCheckpoint::now(checkpoint).unwrap(),
+        )
+        .unwrap();
+        let (url, _) = serve_with(
+            upstream,
+            dir.path(),
+            Router::new().route(
+                &format!("/{path}", path = paths::fetch_checkpoint()),
+                get(move || async move { Json(forged) }),
+            ),
+        );
+
+        let mirror_dir = tempfile::tempdir().unwrap();
+        let (core, _) = CoreService::start_mirror(Box::<MemoryDataStore>::default(), None)
+            .await
+            .unwrap();
+        let mirror = Mirror::new(
+            core.clone(),
+            Arc::new(FileSystemContentStore::new(
+                mirror_dir.path().to_path_buf(),
+                "http://localhost".parse().unwrap(),
+            )),
+            mirror_dir.path().to_path_buf(),
+            Client::new(url.as_str()).unwrap(),
+        );
+
+        // Nothing is committed when the checkpoint fails verification
+        let e = mirror.sync().await.unwrap_err();
+        assert!(matches!(
+            e,
+            MirrorError::Core(CoreServiceError::CheckpointSignatureInvalid(_))
+        ));
+        assert!(e.is_verification_failure());
+        assert_eq!(core.log_length().await, 0);
+        assert!(matches!(
+            core.store().get_latest_checkpoint().await,
+            Err(DataStoreError::CheckpointNotFound(_))
+        ));
+
+        // The mirror stops replicating the upstream registry
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            mirror.start(Duration::from_millis(10)),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+    }
+}
@@ -1,3 +1,7 @@
 mod core;
+mod gc;
+mod mirror;
 
-pub use self::core::{CoreService, CoreServiceError};
+pub use self::core::{CommitQueueMetrics, CoreService, CoreServiceError, CoreUpdate};
+pub use self::gc::{CollectionReport, GarbageCollector, GarbageCollectorError};
+pub use self::mirror::{Mirror, MirrorError, SyncReport};
@@ -11,7 +11,7 @@ use super::{hash_branch, hash_empty, hash_leaf, Checkpoint, LogBuilder, LogData}
 
 /// A verifiable log where the node hashes are stored
 /// contiguously in memory by index.
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct VecLog<D, V>
 where
     D: SupportedDigest,
@@ -25,6 +25,21 @@ where
     _value: PhantomData<V>,
 }
 
+// Manually implemented so that the digest and value types need not be `Clone`
+impl<D, V> Clone for VecLog<D, V>
+where
+    D: SupportedDigest,
+    V: VisitBytes,
+{
+    fn clone(&self) -> Self {
+        Self {
+            length: self.length,
+            tree: self.tree.clone(),
+            _value: PhantomData,
+        }
+    }
+}
+
 /// Height is the number of child-edges between the node and leaves
 /// A leaf has height 0
 ///
@@ -39,6 +54,26 @@ where
         self.length
     }
 
+    /// Creates a log with the given number of entries from the node hashes
+    /// of its tree.
+    ///
+    /// The node hashes are those given by the log's `AsRef<[Hash<D>]>`
+    /// implementation.
+    ///
+    /// Returns `None` if the number of node hashes does not match the number
+    /// of entries.
+    pub fn from_parts(length: usize, tree: Vec<Hash<D>>) -> Option<Self> {
+        if tree.len() != (length * 2).saturating_sub(1) {
+            return None;
+        }
+
+        Some(Self {
+            length,
+            tree,
+            _value: PhantomData,
+        })
+    }
+
     fn get_digest(&self, node: Node) -> Hash<D> {
         self.tree[node.index()].clone()
     }
@@ -174,6 +209,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_log_from_parts() {
+        let mut log: VecLog<Sha256, u8> = VecLog::default();
+        assert!(VecLog::<Sha256, u8>::from_parts(0, vec![]).is_some());
+
+        for i in 0..10 {
+            log.push(&i);
+
+            let restored =
+                VecLog::<Sha256, u8>::from_parts(log.length(), log.as_ref().to_vec()).unwrap();
+            assert_eq!(restored.checkpoint(), log.checkpoint());
+        }
+
+        let mut restored =
+            VecLog::<Sha256, u8>::from_parts(log.length(), log.as_ref().to_vec()).unwrap();
+        log.push(&10);
+        restored.push(&10);
+        assert_eq!(restored.checkpoint(), log.checkpoint());
+
+        assert!(VecLog::<Sha256, u8>::from_parts(2, log.as_ref().to_vec()).is_none());
+    }
+
     #[test]
     fn test_log_modifications() {
         let data = [
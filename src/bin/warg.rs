@@ -4,7 +4,7 @@ use std::process::exit;
 use tracing_subscriber::EnvFilter;
 use warg_cli::commands::{
     ConfigCommand, DownloadCommand, InfoCommand, KeyCommand, PublishCommand, RunCommand,
-    UpdateCommand,
+    SearchCommand, UpdateCommand,
 };
 use warg_client::ClientError;
 
@@ -28,8 +28,9 @@ enum WargCli {
     Download(DownloadCommand),
     Update(UpdateCommand),
     #[clap(subcommand)]
-    Publish(PublishCommand),
+    Publish(Box<PublishCommand>),
     Run(RunCommand),
+    Search(SearchCommand),
 }
 
 #[tokio::main]
@@ -46,6 +47,7 @@ async fn main() -> Result<()> {
         WargCli::Update(cmd) => cmd.exec().await,
         WargCli::Publish(cmd) => cmd.exec().await,
         WargCli::Run(cmd) => cmd.exec().await,
+        WargCli::Search(cmd) => cmd.exec().await,
     } {
         if let Some(e) = e.downcast_ref::<ClientError>() {
             describe_client_error(e);
@@ -14,6 +14,7 @@ mod info;
 mod key;
 mod publish;
 mod run;
+mod search;
 mod update;
 
 use crate::keyring::get_signing_key;
@@ -24,6 +25,7 @@ pub use self::info::*;
 pub use self::key::*;
 pub use self::publish::*;
 pub use self::run::*;
+pub use self::search::*;
 pub use self::update::*;
 
 /// Common options for commands.
@@ -2,6 +2,7 @@ use anyhow::{bail, Context, Result};
 use clap::Args;
 use std::path::PathBuf;
 use warg_client::{Config, RegistryUrl};
+use warg_crypto::signing::PublicKey;
 
 /// Creates a new warg configuration file.
 #[derive(Args)]
@@ -18,6 +19,15 @@ pub struct ConfigCommand {
     #[clap(long, value_name = "CONTENT")]
     pub content_dir: Option<PathBuf>,
 
+    /// The public key of a witness trusted to cosign checkpoints.
+    #[clap(long = "witness-key", value_name = "PUBLIC_KEY")]
+    pub witness_keys: Vec<PublicKey>,
+
+    /// The number of trusted witness cosignatures required before a
+    /// checkpoint is accepted; defaults to the number of witness keys.
+    #[clap(long, value_name = "COUNT", requires = "witness_keys")]
+    pub witness_threshold: Option<usize>,
+
     /// Overwrite the existing configuration file.
     #[clap(long)]
     pub overwrite: bool,
@@ -44,6 +54,15 @@ impl ConfigCommand {
             );
         }
 
+        if let Some(threshold) = self.witness_threshold {
+            if threshold > self.witness_keys.len() {
+                bail!(
+                    "the witness threshold of {threshold} exceeds the number of witness keys ({len})",
+                    len = self.witness_keys.len()
+                );
+            }
+        }
+
         let default_url = self
             .registry
             .map(RegistryUrl::new)
@@ -60,6 +79,8 @@ impl ConfigCommand {
             default_url,
             registries_dir: self.registries_dir.map(|p| cwd.join(p)),
             content_dir: self.content_dir.map(|p| cwd.join(p)),
+            witness_keys: self.witness_keys,
+            witness_threshold: self.witness_threshold,
         };
 
         config.write_to_file(&path)?;
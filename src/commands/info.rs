@@ -3,7 +3,11 @@ use anyhow::Result;
 use clap::Args;
 use warg_client::storage::{PackageInfo, RegistryStorage};
 use warg_crypto::hash::AnyHash;
-use warg_protocol::{registry::PackageId, Version};
+use warg_protocol::{
+    package::{PackageMetadata, Permission},
+    registry::PackageId,
+    Version,
+};
 
 /// Display client storage information.
 #[derive(Args)]
@@ -46,15 +50,60 @@ impl InfoCommand {
 
     fn print_package_info(info: &PackageInfo) {
         println!("  id: {id}", id = info.id);
+        if let Some(metadata) = info.metadata() {
+            Self::print_metadata(metadata);
+        }
         println!("  versions:");
         info.state.releases().for_each(|r| {
             if let Some(content) = r.content() {
                 Self::print_release(&r.version, content);
             }
         });
+
+        let mut scoped = info.state.scoped_permissions().peekable();
+        if scoped.peek().is_some() {
+            println!("  scoped permissions:");
+            scoped.for_each(|(key_id, p)| {
+                println!(
+                    "    {key_id}: {permission} ({scope})",
+                    permission = p.permission,
+                    scope = p.scope
+                );
+            });
+        }
+
+        let mut thresholds = Permission::all()
+            .into_iter()
+            .map(|p| (p, info.state.threshold(p)))
+            .filter(|(_, threshold)| *threshold > 1)
+            .peekable();
+        if thresholds.peek().is_some() {
+            println!("  signature thresholds:");
+            thresholds.for_each(|(permission, threshold)| {
+                println!("    {permission}: {threshold}");
+            });
+        }
     }
 
     fn print_release(version: &Version, content: &AnyHash) {
         println!("    {version} ({content})");
     }
+
+    fn print_metadata(metadata: &PackageMetadata) {
+        if let Some(description) = &metadata.description {
+            println!("  description: {description}");
+        }
+        if let Some(license) = &metadata.license {
+            println!("  license: {license}");
+        }
+        if let Some(repository) = &metadata.repository {
+            println!("  repository: {repository}");
+        }
+        if !metadata.authors.is_empty() {
+            println!("  authors:");
+            for author in &metadata.authors {
+                println!("    {author}");
+            }
+        }
+    }
 }
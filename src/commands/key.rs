@@ -1,12 +1,14 @@
+use super::{publish::DEFAULT_WAIT_INTERVAL, CommonOptions};
 use crate::keyring::{delete_signing_key, get_signing_key, get_signing_key_entry, set_signing_key};
 use anyhow::{bail, Context, Result};
 use clap::{Args, Subcommand};
 use dialoguer::{theme::ColorfulTheme, Confirm};
 use keyring::{Entry, Error as KeyringError};
-use p256::ecdsa::SigningKey;
-use rand_core::OsRng;
-use warg_client::RegistryUrl;
-use warg_crypto::signing::PrivateKey;
+use warg_client::{
+    storage::{PublishEntry, PublishInfo, RegistryStorage as _},
+    RegistryUrl,
+};
+use warg_crypto::signing::{generate_pair, PrivateKey, SignatureAlgorithm};
 
 /// Manage signing keys for interacting with a registry.
 #[derive(Args)]
@@ -24,6 +26,7 @@ impl KeyCommand {
             KeySubcommand::Info(cmd) => cmd.exec().await,
             KeySubcommand::Set(cmd) => cmd.exec().await,
             KeySubcommand::Delete(cmd) => cmd.exec().await,
+            KeySubcommand::Rotate(cmd) => cmd.exec().await,
         }
     }
 }
@@ -39,6 +42,8 @@ pub enum KeySubcommand {
     Set(KeySetCommand),
     /// Deletes the signing key for a registry from the local keyring.
     Delete(KeyDeleteCommand),
+    /// Rotates the signing key for a registry in the local keyring.
+    Rotate(KeyRotateCommand),
 }
 
 #[derive(Args)]
@@ -85,6 +90,9 @@ impl std::fmt::Display for KeyringEntryArgs {
 pub struct KeyNewCommand {
     #[clap(flatten)]
     keyring_entry: KeyringEntryArgs,
+    /// The signature algorithm of the new key (`ecdsa-p256` or `ed25519`).
+    #[clap(long, value_name = "ALGORITHM", default_value = "ecdsa-p256")]
+    algorithm: SignatureAlgorithm,
 }
 
 impl KeyNewCommand {
@@ -111,7 +119,7 @@ impl KeyNewCommand {
             }
         }
 
-        let key = SigningKey::random(&mut OsRng).into();
+        let (_, key) = generate_pair(&self.algorithm);
         self.keyring_entry.set_entry(&key)?;
 
         Ok(())
@@ -197,3 +205,120 @@ impl KeyDeleteCommand {
         Ok(())
     }
 }
+
+/// Rotates the signing key for a registry in the local keyring.
+///
+/// A new key is generated and a key rotation is published for every package
+/// in client storage that the current key has permissions for.
+///
+/// The new key is kept as `<KEY_NAME>-rotated` until every rotation is
+/// confirmed, at which point it replaces the current key.
+#[derive(Args)]
+pub struct KeyRotateCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+}
+
+impl KeyRotateCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        if self.common.key_file.is_some() {
+            bail!("signing keys read from a file cannot be rotated");
+        }
+
+        let config = self.common.read_config()?;
+        let client = self.common.create_client(&config)?;
+        let url = client.url().clone();
+        let old_key = self.common.signing_key(&url)?;
+        let old_key_id = old_key.public_key().fingerprint();
+
+        client.update().await?;
+
+        let packages = client
+            .registry()
+            .load_packages()
+            .await?
+            .into_iter()
+            .filter(|info| info.state.key_has_permissions(&old_key_id))
+            .collect::<Vec<_>>();
+
+        if packages.is_empty() {
+            bail!("key ID `{old_key_id}` has no permissions for any package in client storage");
+        }
+
+        // The new key is stored under a separate name before any rotation is
+        // published so that it is never lost if the rotation is interrupted
+        let new_key_name = format!("{name}-rotated", name = self.common.key_name);
+        match get_signing_key_entry(&url, &new_key_name)?.get_password() {
+            Ok(_) => bail!(
+                "signing key `{new_key_name}` for registry `{url}` already exists from a previous rotation; use it to finish that rotation or delete it first"
+            ),
+            Err(KeyringError::NoEntry) => {}
+            Err(e) => bail!(
+                "failed to get signing key with name `{new_key_name}` of registry `{url}`: {e}"
+            ),
+        }
+
+        // The new key uses the same signature algorithm as the key being rotated
+        let (_, new_key) = generate_pair(&old_key.signature_algorithm());
+        let new_key_id = new_key.public_key().fingerprint();
+        set_signing_key(&url, &new_key_name, &new_key)?;
+
+        let mut failed = Vec::new();
+        for info in &packages {
+            let result = async {
+                let record_id = client
+                    .publish_with_info(
+                        &old_key,
+                        PublishInfo::new(
+                            info.id.clone(),
+                            None,
+                            vec![PublishEntry::RotateKey {
+                                key_id: old_key_id.clone(),
+                                key: new_key.public_key(),
+                            }],
+                        ),
+                    )
+                    .await?;
+
+                client
+                    .wait_for_publish(&info.id, &record_id, DEFAULT_WAIT_INTERVAL)
+                    .await
+            }
+            .await;
+
+            match result {
+                Ok(()) => println!(
+                    "rotated key ID `{old_key_id}` to `{new_key_id}` for package `{id}`",
+                    id = info.id
+                ),
+                Err(e) => {
+                    eprintln!("failed to rotate key for package `{id}`: {e}", id = info.id);
+                    failed.push(&info.id);
+                }
+            }
+        }
+
+        if failed.is_empty() {
+            set_signing_key(&url, &self.common.key_name, &new_key)?;
+            delete_signing_key(&url, &new_key_name)?;
+            println!(
+                "signing key `{name}` for registry `{url}` was rotated successfully",
+                name = self.common.key_name,
+            );
+            return Ok(());
+        }
+
+        // Some packages may now be controlled by the new key while others still
+        // require the old key, so keep both keys in the keyring
+        bail!(
+            "key rotation was incomplete: the new signing key was stored as `{new_key_name}` and the following packages may still use key ID `{old_key_id}`: {packages}",
+            packages = failed
+                .iter()
+                .map(|id| format!("`{id}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+}
@@ -11,16 +11,16 @@ use warg_client::{
     FileSystemClient,
 };
 use warg_crypto::{
-    hash::AnyHash,
+    hash::{AnyHash, HashAlgorithm},
     signing::{KeyID, PublicKey},
 };
 use warg_protocol::{
-    package::Permission,
+    package::{PackageMetadata, Permission},
     registry::{PackageId, RecordId},
-    Version,
+    Version, VersionReq,
 };
 
-const DEFAULT_WAIT_INTERVAL: Duration = Duration::from_secs(1);
+pub(super) const DEFAULT_WAIT_INTERVAL: Duration = Duration::from_secs(1);
 
 /// Used to enqueue a publish entry if there is a pending publish.
 /// Returns `Ok(None)` if the entry was enqueued or `Ok(Some(entry))` if there
@@ -44,10 +44,14 @@ where
 
             let entry = entry(client).await?;
 
-            if matches!(entry, PublishEntry::Init) && info.initializing() {
+            if matches!(entry, PublishEntry::Init { .. }) && info.initializing() {
                 bail!("there is already a pending initializing for package `{id}`");
             }
 
+            if !info.cosignatures.is_empty() {
+                bail!("the pending publish for package `{id}` has been co-signed and cannot be changed; use `publish abort` to abort the current publish");
+            }
+
             info.entries.push(entry);
             client.registry().store_publish(Some(&info)).await?;
             Ok(None)
@@ -56,6 +60,14 @@ where
     }
 }
 
+/// Formats the scope of a grant for display.
+fn scope_suffix(scope: Option<&VersionReq>) -> String {
+    match scope {
+        Some(scope) => format!(" for versions `{scope}`"),
+        None => String::new(),
+    }
+}
+
 /// Publish a package to a warg registry.
 #[derive(Subcommand)]
 pub enum PublishCommand {
@@ -65,16 +77,28 @@ pub enum PublishCommand {
     Release(PublishReleaseCommand),
     /// Yank a package version.
     Yank(PublishYankCommand),
+    /// Unyank a package version.
+    Unyank(PublishUnyankCommand),
     /// Grant permissions for the package.
     Grant(PublishGrantCommand),
     /// Revoke permissions for the package.
     Revoke(PublishRevokeCommand),
+    /// Set the metadata of the package.
+    Metadata(PublishMetadataCommand),
+    /// Set the signature threshold of permissions for the package.
+    Threshold(PublishThresholdCommand),
     /// Start a new pending publish.
     Start(PublishStartCommand),
     /// List the records in a pending publish.
     List(PublishListCommand),
     /// Abort a pending publish.
     Abort(PublishAbortCommand),
+    /// Co-sign a pending publish.
+    Cosign(PublishCosignCommand),
+    /// Export a pending publish to a file.
+    Export(PublishExportCommand),
+    /// Import a pending publish from a file.
+    Import(PublishImportCommand),
     /// Submit a pending publish.
     Submit(PublishSubmitCommand),
     /// Wait for a pending publish to complete.
@@ -88,11 +112,17 @@ impl PublishCommand {
             Self::Init(cmd) => cmd.exec().await,
             Self::Release(cmd) => cmd.exec().await,
             Self::Yank(cmd) => cmd.exec().await,
+            Self::Unyank(cmd) => cmd.exec().await,
             Self::Grant(cmd) => cmd.exec().await,
             Self::Revoke(cmd) => cmd.exec().await,
+            Self::Metadata(cmd) => cmd.exec().await,
+            Self::Threshold(cmd) => cmd.exec().await,
             Self::Start(cmd) => cmd.exec().await,
             Self::List(cmd) => cmd.exec().await,
             Self::Abort(cmd) => cmd.exec().await,
+            Self::Cosign(cmd) => cmd.exec().await,
+            Self::Export(cmd) => cmd.exec().await,
+            Self::Import(cmd) => cmd.exec().await,
             Self::Submit(cmd) => cmd.exec().await,
             Self::Wait(cmd) => cmd.exec().await,
         }
@@ -109,6 +139,9 @@ pub struct PublishInitCommand {
     /// The identifier of the package being initialized.
     #[clap(value_name = "PACKAGE")]
     pub id: PackageId,
+    /// The hash algorithm the package log will use.
+    #[clap(long, value_name = "ALGORITHM", default_value = "sha256")]
+    pub hash_algorithm: HashAlgorithm,
     /// Whether to wait for the publish to complete.
     #[clap(long)]
     pub no_wait: bool,
@@ -121,7 +154,9 @@ impl PublishInitCommand {
         let client = self.common.create_client(&config)?;
 
         match enqueue(&client, &self.id, |_| {
-            std::future::ready(Ok(PublishEntry::Init))
+            std::future::ready(Ok(PublishEntry::Init {
+                hash_algorithm: self.hash_algorithm,
+            }))
         })
         .await?
         {
@@ -130,11 +165,7 @@ impl PublishInitCommand {
                 let record_id = client
                     .publish_with_info(
                         &signing_key,
-                        PublishInfo {
-                            id: self.id.clone(),
-                            head: None,
-                            entries: vec![entry],
-                        },
+                        PublishInfo::new(self.id.clone(), None, vec![entry]),
                     )
                     .await?;
 
@@ -176,6 +207,9 @@ pub struct PublishReleaseCommand {
     /// The path to the package being published.
     #[clap(value_name = "PATH")]
     pub path: PathBuf,
+    /// The hash algorithm used for the content digest of the release.
+    #[clap(long, value_name = "ALGORITHM", default_value = "sha256")]
+    pub content_hash_algorithm: HashAlgorithm,
     /// Whether to wait for the publish to complete.
     #[clap(long)]
     pub no_wait: bool,
@@ -189,6 +223,7 @@ impl PublishReleaseCommand {
 
         let path = self.path.clone();
         let version = self.version.clone();
+        let algorithm = self.content_hash_algorithm;
         match enqueue(&client, &self.id, move |c| async move {
             let content = c
                 .content()
@@ -201,6 +236,7 @@ impl PublishReleaseCommand {
                         ))
                         .map_err(|e| anyhow!(e)),
                     ),
+                    algorithm,
                     None,
                 )
                 .await?;
@@ -214,11 +250,7 @@ impl PublishReleaseCommand {
                 let record_id = client
                     .publish_with_info(
                         &signing_key,
-                        PublishInfo {
-                            id: self.id.clone(),
-                            head: None,
-                            entries: vec![entry],
-                        },
+                        PublishInfo::new(self.id.clone(), None, vec![entry]),
                     )
                     .await?;
 
@@ -284,11 +316,7 @@ impl PublishYankCommand {
                 let record_id = client
                     .publish_with_info(
                         &signing_key,
-                        PublishInfo {
-                            id: self.id.clone(),
-                            head: None,
-                            entries: vec![entry],
-                        },
+                        PublishInfo::new(self.id.clone(), None, vec![entry]),
                     )
                     .await?;
 
@@ -319,6 +347,74 @@ impl PublishYankCommand {
     }
 }
 
+/// Unyank a previously yanked package release.
+///
+/// The unyank permission is not granted on init and must be granted explicitly.
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct PublishUnyankCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+    /// The identifier of the package being unyanked.
+    #[clap(long, short, value_name = "PACKAGE")]
+    pub id: PackageId,
+    /// The version of the package being unyanked.
+    #[clap(long, short, value_name = "VERSION")]
+    pub version: Version,
+    /// Whether to wait for the publish to complete.
+    #[clap(long)]
+    pub no_wait: bool,
+}
+
+impl PublishUnyankCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        let config = self.common.read_config()?;
+        let client = self.common.create_client(&config)?;
+
+        let version = self.version.clone();
+        match enqueue(&client, &self.id, move |_| async move {
+            Ok(PublishEntry::Unyank { version })
+        })
+        .await?
+        {
+            Some(entry) => {
+                let signing_key = self.common.signing_key(client.url())?;
+                let record_id = client
+                    .publish_with_info(
+                        &signing_key,
+                        PublishInfo::new(self.id.clone(), None, vec![entry]),
+                    )
+                    .await?;
+
+                if self.no_wait {
+                    println!("submitted record `{record_id}` for publishing");
+                } else {
+                    client
+                        .wait_for_publish(&self.id, &record_id, DEFAULT_WAIT_INTERVAL)
+                        .await?;
+
+                    println!(
+                        "unyanked version {version} of package `{id}`",
+                        version = self.version,
+                        id = self.id
+                    );
+                }
+            }
+            None => {
+                println!(
+                    "added unyank of version {version} for package `{id}` to pending publish",
+                    version = self.version,
+                    id = self.id
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
 /// Publish a package to a warg registry.
 #[derive(Args)]
 #[clap(disable_version_flag = true)]
@@ -339,6 +435,9 @@ pub struct PublishGrantCommand {
         default_value = "release,yank"
     )]
     pub permissions: Vec<Permission>,
+    /// Limit the permission(s) to versions matching the given requirement.
+    #[clap(long, value_name = "VERSION_REQ")]
+    pub scope: Option<VersionReq>,
     /// Whether to wait for the publish to complete.
     #[clap(long)]
     pub no_wait: bool,
@@ -354,6 +453,7 @@ impl PublishGrantCommand {
             Ok(PublishEntry::Grant {
                 key: self.public_key.clone(),
                 permissions: self.permissions.clone(),
+                scope: self.scope.clone(),
             })
         })
         .await?
@@ -363,11 +463,7 @@ impl PublishGrantCommand {
                 let record_id = client
                     .publish_with_info(
                         &signing_key,
-                        PublishInfo {
-                            id: self.id.clone(),
-                            head: None,
-                            entries: vec![entry],
-                        },
+                        PublishInfo::new(self.id.clone(), None, vec![entry]),
                     )
                     .await?;
 
@@ -379,8 +475,9 @@ impl PublishGrantCommand {
                         .await?;
 
                     println!(
-                        "granted ({permissions_str}) to key ID `{key_id}` for package `{id}`",
+                        "granted ({permissions_str}){scope_str} to key ID `{key_id}` for package `{id}`",
                         permissions_str = self.permissions.iter().join(","),
+                        scope_str = scope_suffix(self.scope.as_ref()),
                         key_id = self.public_key.fingerprint(),
                         id = self.id
                     );
@@ -388,8 +485,9 @@ impl PublishGrantCommand {
             }
             None => {
                 println!(
-                    "added grant of ({permissions_str}) to key ID `{key_id}` for package `{id}` to pending publish",
+                    "added grant of ({permissions_str}){scope_str} to key ID `{key_id}` for package `{id}` to pending publish",
                     permissions_str = self.permissions.iter().join(","),
+                    scope_str = scope_suffix(self.scope.as_ref()),
                     key_id = self.public_key.fingerprint(),
                     id = self.id
                 );
@@ -444,11 +542,7 @@ impl PublishRevokeCommand {
                 let record_id = client
                     .publish_with_info(
                         &signing_key,
-                        PublishInfo {
-                            id: self.id.clone(),
-                            head: None,
-                            entries: vec![entry],
-                        },
+                        PublishInfo::new(self.id.clone(), None, vec![entry]),
                     )
                     .await?;
 
@@ -481,6 +575,155 @@ impl PublishRevokeCommand {
     }
 }
 
+/// Set the metadata of a package.
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct PublishMetadataCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+    /// The identifier of the package.
+    #[clap(long, short, value_name = "PACKAGE")]
+    pub id: PackageId,
+    /// A short description of the package.
+    #[clap(long, value_name = "DESCRIPTION")]
+    pub description: Option<String>,
+    /// The SPDX license expression of the package.
+    #[clap(long, value_name = "LICENSE")]
+    pub license: Option<String>,
+    /// The URL of the package's source repository.
+    #[clap(long, value_name = "URL")]
+    pub repository: Option<String>,
+    /// An author of the package; may be specified multiple times.
+    #[clap(long = "author", value_name = "AUTHOR")]
+    pub authors: Vec<String>,
+    /// Whether to wait for the publish to complete.
+    #[clap(long)]
+    pub no_wait: bool,
+}
+
+impl PublishMetadataCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        let config = self.common.read_config()?;
+        let client = self.common.create_client(&config)?;
+
+        let metadata = PackageMetadata {
+            description: self.description,
+            license: self.license,
+            repository: self.repository,
+            authors: self.authors,
+        };
+
+        match enqueue(&client, &self.id, move |_| async move {
+            Ok(PublishEntry::Metadata { metadata })
+        })
+        .await?
+        {
+            Some(entry) => {
+                let signing_key = self.common.signing_key(client.url())?;
+                let record_id = client
+                    .publish_with_info(
+                        &signing_key,
+                        PublishInfo::new(self.id.clone(), None, vec![entry]),
+                    )
+                    .await?;
+
+                if self.no_wait {
+                    println!("submitted record `{record_id}` for publishing");
+                } else {
+                    client
+                        .wait_for_publish(&self.id, &record_id, DEFAULT_WAIT_INTERVAL)
+                        .await?;
+
+                    println!("set metadata of package `{id}`", id = self.id);
+                }
+            }
+            None => {
+                println!(
+                    "added metadata for package `{id}` to pending publish",
+                    id = self.id
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Set the signature threshold of permissions for the package.
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct PublishThresholdCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+    /// The identifier of the package.
+    #[clap(long, short, value_name = "PACKAGE")]
+    pub id: PackageId,
+    /// The permission(s) the threshold applies to.
+    #[clap(long = "permission", value_delimiter = ',', required = true)]
+    pub permissions: Vec<Permission>,
+    /// The number of distinct keys that must sign records using the permission(s).
+    #[clap(value_name = "THRESHOLD")]
+    pub threshold: u32,
+    /// Whether to wait for the publish to complete.
+    #[clap(long)]
+    pub no_wait: bool,
+}
+
+impl PublishThresholdCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        let config = self.common.read_config()?;
+        let client = self.common.create_client(&config)?;
+
+        match enqueue(&client, &self.id, |_| async {
+            Ok(PublishEntry::Threshold {
+                permissions: self.permissions.clone(),
+                threshold: self.threshold,
+            })
+        })
+        .await?
+        {
+            Some(entry) => {
+                let signing_key = self.common.signing_key(client.url())?;
+                let record_id = client
+                    .publish_with_info(
+                        &signing_key,
+                        PublishInfo::new(self.id.clone(), None, vec![entry]),
+                    )
+                    .await?;
+
+                if self.no_wait {
+                    println!("submitted record `{record_id}` for publishing");
+                } else {
+                    client
+                        .wait_for_publish(&self.id, &record_id, DEFAULT_WAIT_INTERVAL)
+                        .await?;
+
+                    println!(
+                        "set signature threshold of ({permissions_str}) to {threshold} for package `{id}`",
+                        permissions_str = self.permissions.iter().join(","),
+                        threshold = self.threshold,
+                        id = self.id
+                    );
+                }
+            }
+            None => {
+                println!(
+                    "added signature threshold of {threshold} for ({permissions_str}) of package `{id}` to pending publish",
+                    permissions_str = self.permissions.iter().join(","),
+                    threshold = self.threshold,
+                    id = self.id
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
 /// Start a new pending publish.
 #[derive(Args)]
 #[clap(disable_version_flag = true)]
@@ -502,11 +745,7 @@ impl PublishStartCommand {
         match client.registry().load_publish().await? {
             Some(info) => bail!("a publish is already in progress for package `{id}`; use `publish abort` to abort the current publish", id = info.id),
             None => {
-                client.registry().store_publish(Some(&PublishInfo {
-                    id: self.id.clone(),
-                    head: None,
-                    entries: Default::default(),
-                }))
+                client.registry().store_publish(Some(&PublishInfo::new(self.id.clone(), None, Default::default())))
                 .await?;
 
                 println!(
@@ -544,8 +783,8 @@ impl PublishListCommand {
                 for (i, entry) in info.entries.iter().enumerate() {
                     print!("record {i}: ");
                     match entry {
-                        PublishEntry::Init => {
-                            println!("initialize package");
+                        PublishEntry::Init { hash_algorithm } => {
+                            println!("initialize package using {hash_algorithm}");
                         }
                         PublishEntry::Release { version, content } => {
                             println!("release {version} with content digest `{content}`")
@@ -553,9 +792,17 @@ impl PublishListCommand {
                         PublishEntry::Yank { version } => {
                             println!("yank {version}")
                         }
-                        PublishEntry::Grant { key, permissions } => println!(
-                            "grant ({permissions_str}) to `{key_id}`",
+                        PublishEntry::Unyank { version } => {
+                            println!("unyank {version}")
+                        }
+                        PublishEntry::Grant {
+                            key,
+                            permissions,
+                            scope,
+                        } => println!(
+                            "grant ({permissions_str}){scope_str} to `{key_id}`",
                             permissions_str = permissions.iter().join(","),
+                            scope_str = scope_suffix(scope.as_ref()),
                             key_id = key.fingerprint(),
                         ),
                         PublishEntry::Revoke {
@@ -565,6 +812,27 @@ impl PublishListCommand {
                             "revoke ({permissions_str}) from `{key_id}`",
                             permissions_str = permissions.iter().join(","),
                         ),
+                        PublishEntry::RotateKey { key_id, key } => println!(
+                            "rotate `{key_id}` to `{new_key_id}`",
+                            new_key_id = key.fingerprint(),
+                        ),
+                        PublishEntry::Metadata { .. } => {
+                            println!("set package metadata")
+                        }
+                        PublishEntry::Threshold {
+                            permissions,
+                            threshold,
+                        } => println!(
+                            "set signature threshold of ({permissions_str}) to {threshold}",
+                            permissions_str = permissions.iter().join(","),
+                        ),
+                    }
+                }
+
+                if !info.cosignatures.is_empty() {
+                    println!("\nco-signed by:");
+                    for cosignature in &info.cosignatures {
+                        println!("  `{key_id}`", key_id = cosignature.key_id);
                     }
                 }
             }
@@ -604,6 +872,129 @@ impl PublishAbortCommand {
     }
 }
 
+/// Co-sign a pending publish.
+#[derive(Args)]
+pub struct PublishCosignCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+}
+
+impl PublishCosignCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        let config = self.common.read_config()?;
+        let client = self.common.create_client(&config)?;
+
+        match client.registry().load_publish().await? {
+            Some(info) => {
+                let signing_key = self.common.signing_key(client.url())?;
+                client.cosign(&signing_key).await?;
+
+                println!(
+                    "co-signed the pending publish for package `{id}` with key ID `{key_id}`",
+                    id = info.id,
+                    key_id = signing_key.public_key().fingerprint()
+                );
+            }
+            None => bail!("no pending publish to co-sign"),
+        }
+
+        Ok(())
+    }
+}
+
+/// Export a pending publish to a file.
+#[derive(Args)]
+pub struct PublishExportCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+    /// The path of the file to export to.
+    #[clap(value_name = "PATH")]
+    pub path: PathBuf,
+}
+
+impl PublishExportCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        let config = self.common.read_config()?;
+        let client = self.common.create_client(&config)?;
+
+        match client.registry().load_publish().await? {
+            Some(info) => {
+                let contents = serde_json::to_vec_pretty(&info)?;
+                std::fs::write(&self.path, contents).with_context(|| {
+                    format!(
+                        "failed to write pending publish to `{path}`",
+                        path = self.path.display()
+                    )
+                })?;
+
+                println!(
+                    "exported the pending publish for package `{id}` to `{path}`",
+                    id = info.id,
+                    path = self.path.display()
+                );
+            }
+            None => bail!("no pending publish to export"),
+        }
+
+        Ok(())
+    }
+}
+
+/// Import a pending publish from a file.
+#[derive(Args)]
+pub struct PublishImportCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+    /// The path of the file to import from.
+    #[clap(value_name = "PATH")]
+    pub path: PathBuf,
+    /// Replace any publish already in progress.
+    #[clap(long)]
+    pub force: bool,
+}
+
+impl PublishImportCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        let config = self.common.read_config()?;
+        let client = self.common.create_client(&config)?;
+
+        if !self.force {
+            if let Some(info) = client.registry().load_publish().await? {
+                bail!("a publish is already in progress for package `{id}`; use `--force` to replace it", id = info.id);
+            }
+        }
+
+        let contents = std::fs::read(&self.path).with_context(|| {
+            format!(
+                "failed to read pending publish from `{path}`",
+                path = self.path.display()
+            )
+        })?;
+        let info: PublishInfo = serde_json::from_slice(&contents).with_context(|| {
+            format!(
+                "failed to parse pending publish from `{path}`",
+                path = self.path.display()
+            )
+        })?;
+
+        client.registry().store_publish(Some(&info)).await?;
+
+        println!(
+            "imported the pending publish for package `{id}` from `{path}`",
+            id = info.id,
+            path = self.path.display()
+        );
+
+        Ok(())
+    }
+}
+
 /// Submit a pending publish.
 #[derive(Args)]
 pub struct PublishSubmitCommand {
@@ -640,7 +1031,7 @@ impl PublishSubmitCommand {
                     for entry in &info.entries {
                         let id = &info.id;
                         match entry {
-                            PublishEntry::Init => {
+                            PublishEntry::Init { .. } => {
                                 println!("published initialization of package `{id}`");
                             }
                             PublishEntry::Release { version, .. } => {
@@ -649,10 +1040,18 @@ impl PublishSubmitCommand {
                             PublishEntry::Yank { version } => {
                                 println!("yanked version {version} of package `{id}`")
                             }
-                            PublishEntry::Grant { key, permissions } => {
+                            PublishEntry::Unyank { version } => {
+                                println!("unyanked version {version} of package `{id}`")
+                            }
+                            PublishEntry::Grant {
+                                key,
+                                permissions,
+                                scope,
+                            } => {
                                 println!(
-                                    "granted ({permissions_str}) to `{key_id}`",
+                                    "granted ({permissions_str}){scope_str} to `{key_id}`",
                                     permissions_str = permissions.iter().join(","),
+                                    scope_str = scope_suffix(scope.as_ref()),
                                     key_id = key.fingerprint(),
                                 )
                             }
@@ -663,6 +1062,20 @@ impl PublishSubmitCommand {
                                 "revoked ({permissions_str}) from `{key_id}`",
                                 permissions_str = permissions.iter().join(","),
                             ),
+                            PublishEntry::RotateKey { key_id, key } => println!(
+                                "rotated `{key_id}` to `{new_key_id}`",
+                                new_key_id = key.fingerprint(),
+                            ),
+                            PublishEntry::Metadata { .. } => {
+                                println!("set metadata of package `{id}`")
+                            }
+                            PublishEntry::Threshold {
+                                permissions,
+                                threshold,
+                            } => println!(
+                                "set signature threshold of ({permissions_str}) to {threshold} for package `{id}`",
+                                permissions_str = permissions.iter().join(","),
+                            ),
                         }
                     }
                 }
@@ -0,0 +1,61 @@
+use super::CommonOptions;
+use anyhow::Result;
+use clap::Args;
+use std::borrow::Cow;
+use warg_api::v1::fetch::FetchPackagesRequest;
+use warg_client::{api, ClientError};
+
+/// Search for packages published to a registry.
+#[derive(Args)]
+pub struct SearchCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// Only list packages with namespaces starting with the given prefix.
+    #[clap(value_name = "NAMESPACE")]
+    pub namespace: Option<String>,
+}
+
+impl SearchCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        let config = self.common.read_config()?;
+        let url = self
+            .common
+            .registry
+            .as_deref()
+            .or(config.default_url.as_deref())
+            .ok_or(ClientError::NoDefaultUrl)?;
+        let client = api::Client::new(url)?;
+
+        let mut since = None;
+        let mut found = false;
+        loop {
+            let response = client
+                .fetch_packages(FetchPackagesRequest {
+                    namespace: self.namespace.as_deref().map(Cow::Borrowed),
+                    since: since.map(Cow::Owned),
+                    limit: None,
+                })
+                .await
+                .map_err(ClientError::Api)?;
+
+            for id in &response.packages {
+                println!("{id}");
+            }
+
+            found |= !response.packages.is_empty();
+            since = response.packages.last().cloned();
+            if !response.more || since.is_none() {
+                break;
+            }
+        }
+
+        if !found {
+            println!("no packages were found");
+        }
+
+        Ok(())
+    }
+}
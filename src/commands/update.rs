@@ -1,6 +1,7 @@
 use super::CommonOptions;
-use anyhow::Result;
+use anyhow::{bail, Result};
 use clap::Args;
+use futures::{pin_mut, StreamExt};
 
 /// Update all local package logs for a registry.
 #[derive(Args)]
@@ -8,6 +9,9 @@ pub struct UpdateCommand {
     /// The common command options.
     #[clap(flatten)]
     pub common: CommonOptions,
+    /// Keep updating package logs as the registry signs new checkpoints.
+    #[clap(long)]
+    pub watch: bool,
 }
 
 impl UpdateCommand {
@@ -16,8 +20,24 @@ impl UpdateCommand {
         let config = self.common.read_config()?;
         let client = self.common.create_client(&config)?;
 
-        println!("updating package logs to the latest available versions...");
-        client.update().await?;
-        Ok(())
+        if !self.watch {
+            println!("updating package logs to the latest available versions...");
+            client.update().await?;
+            return Ok(());
+        }
+
+        println!("watching for new registry checkpoints...");
+        let checkpoints = client.subscribe_checkpoints().await?;
+        pin_mut!(checkpoints);
+        while let Some(checkpoint) = checkpoints.next().await {
+            let checkpoint = checkpoint?;
+            client.update_to(&checkpoint).await?;
+            println!(
+                "updated package logs to checkpoint with log length {len}",
+                len = checkpoint.as_ref().checkpoint.log_length
+            );
+        }
+
+        bail!("the registry closed the checkpoint stream")
     }
 }
@@ -33,6 +33,7 @@ async fn client_incrementally_fetches() -> Result<()> {
         .content()
         .store_content(
             Box::pin(futures::stream::once(async move { Ok(bytes.into()) })),
+            Default::default(),
             None,
         )
         .await?;
@@ -43,11 +44,13 @@ async fn client_incrementally_fetches() -> Result<()> {
     let mut head = client
         .publish_with_info(
             &signing_key,
-            PublishInfo {
-                id: id.clone(),
-                head: None,
-                entries: vec![PublishEntry::Init],
-            },
+            PublishInfo::new(
+                id.clone(),
+                None,
+                vec![PublishEntry::Init {
+                    hash_algorithm: Default::default(),
+                }],
+            ),
         )
         .await?;
 
@@ -55,14 +58,14 @@ async fn client_incrementally_fetches() -> Result<()> {
         head = client
             .publish_with_info(
                 &signing_key,
-                PublishInfo {
-                    id: id.clone(),
-                    head: Some(head),
-                    entries: vec![PublishEntry::Release {
+                PublishInfo::new(
+                    id.clone(),
+                    Some(head),
+                    vec![PublishEntry::Release {
                         version: format!("0.{i}.0").parse().unwrap(),
                         content: digest.clone(),
                     }],
-                },
+                ),
             )
             .await?;
     }
@@ -0,0 +1,232 @@
+//! Tests for the filesystem storage backend.
+
+use super::{support::*, *};
+use anyhow::{Context, Result};
+use std::path::Path;
+use testresult::TestResult;
+use warg_client::api;
+use warg_crypto::signing::KeyID;
+use warg_protocol::registry::RegistryLen;
+use warg_server::datastore::{DataStore, FileSystemDataStore};
+
+async fn data_store(root: &Path) -> Result<Box<dyn DataStore>> {
+    Ok(Box::new(
+        FileSystemDataStore::open(root.join("data")).await?,
+    ))
+}
+
+async fn spawn_filesystem_server(
+    root: &Path,
+    content_base_url: Option<Url>,
+    authorized_keys: Option<Vec<(String, KeyID)>>,
+) -> Result<(ServerInstance, warg_client::Config)> {
+    spawn_server(
+        root,
+        content_base_url,
+        Some(data_store(root).await?),
+        authorized_keys,
+    )
+    .await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_starts_with_initial_checkpoint() -> Result<()> {
+    let (_server, config) = spawn_filesystem_server(&root().await?, None, None).await?;
+    test_initial_checkpoint(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_publishes_a_component() -> Result<()> {
+    let (_server, config) = spawn_filesystem_server(&root().await?, None, None).await?;
+    test_component_publishing(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_yanks_a_package() -> Result<()> {
+    let (_server, config) = spawn_filesystem_server(&root().await?, None, None).await?;
+    test_package_yanking(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_publishes_a_wit_package() -> Result<()> {
+    let (_server, config) = spawn_filesystem_server(&root().await?, None, None).await?;
+    test_wit_publishing(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_rejects_non_wasm_content() -> Result<()> {
+    let (_server, config) = spawn_filesystem_server(&root().await?, None, None).await?;
+    test_wasm_content_policy(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_rejects_unauthorized_signing_key() -> Result<()> {
+    let (_server, config) = spawn_filesystem_server(
+        &root().await?,
+        None,
+        Some(vec![(
+            "test".to_string(),
+            test_signing_key().public_key().fingerprint(),
+        )]),
+    )
+    .await?;
+
+    test_unauthorized_signing_key(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_rejects_unknown_signing_key() -> Result<()> {
+    let (_server, config) = spawn_filesystem_server(&root().await?, None, None).await?;
+    test_unknown_signing_key(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_accepts_ed25519_signing_key() -> Result<()> {
+    let (_server, config) = spawn_filesystem_server(&root().await?, None, None).await?;
+    test_ed25519_signing_key(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_rejects_invalid_signature() -> Result<()> {
+    let (_server, config) = spawn_filesystem_server(&root().await?, None, None).await?;
+    test_invalid_signature(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_fetches_historical_checkpoints() -> Result<()> {
+    let (_server, config) = spawn_filesystem_server(&root().await?, None, None).await?;
+    test_historical_checkpoint(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_lists_packages() -> Result<()> {
+    let (_server, config) = spawn_filesystem_server(&root().await?, None, None).await?;
+    test_package_listing(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_streams_checkpoints() -> Result<()> {
+    let (_server, config) = spawn_filesystem_server(&root().await?, None, None).await?;
+    test_checkpoint_subscription(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_downloads_content_ranges() -> Result<()> {
+    let (_server, config) = spawn_filesystem_server(&root().await?, None, None).await?;
+    test_content_download(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_uses_other_hash_algorithms() -> Result<()> {
+    let (_server, config) = spawn_filesystem_server(&root().await?, None, None).await?;
+    test_hash_algorithms(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_formats_custom_content_urls() -> Result<()> {
+    let (_server, config) = spawn_filesystem_server(
+        &root().await?,
+        Some("https://example.com".parse().unwrap()),
+        None,
+    )
+    .await?;
+    test_custom_content_url(&config).await
+}
+
+/// Ensures that the data of the filesystem data store survives a server restart.
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_persists_across_restarts() -> TestResult {
+    let root = root().await?;
+    let (server, config) = spawn_server(
+        &root,
+        None,
+        Some(data_store(&root).await?),
+        Some(vec![(
+            "test".to_string(),
+            test_signing_key().public_key().fingerprint(),
+        )]),
+    )
+    .await?;
+
+    // This should be the same set of tests as in `tests/memory/mod.rs`
+    test_initial_checkpoint(&config).await?;
+    test_component_publishing(&config).await?;
+    test_package_yanking(&config).await?;
+    test_wit_publishing(&config).await?;
+    test_wasm_content_policy(&config).await?;
+    test_unauthorized_signing_key(&config).await?;
+    // This is tested below where a different server is used that
+    // allows any signing key
+    //test_unknown_signing_key(&config).await?;
+    test_invalid_signature(&config).await?;
+
+    let mut packages = vec![
+        PackageId::new("test:component")?,
+        PackageId::new("test:yankee")?,
+        PackageId::new("test:wit-package")?,
+        PackageId::new("test:unauthorized-key")?,
+    ];
+
+    // There should be two log entries in the registry
+    let client = api::Client::new(config.default_url.as_ref().unwrap())?;
+    let ts_checkpoint = client.latest_checkpoint().await?;
+    assert_eq!(
+        ts_checkpoint.as_ref().checkpoint.log_length,
+        packages.len() as RegistryLen + 2, /* publishes + initial checkpoint + yank */
+        "expected {len} packages plus the initial checkpoint and yank",
+        len = packages.len()
+    );
+
+    drop(server);
+
+    // Restart the server and ensure the data is still there
+    let (server, config) = spawn_server(&root, None, Some(data_store(&root).await?), None).await?;
+
+    test_unknown_signing_key(&config).await?;
+    test_ed25519_signing_key(&config).await?;
+
+    packages.push(PackageId::new("test:unknown-key")?);
+    packages.push(PackageId::new("test:ed25519")?);
+
+    let client = api::Client::new(config.default_url.as_ref().unwrap())?;
+    let ts_checkpoint = client.latest_checkpoint().await?;
+    assert_eq!(
+        ts_checkpoint.as_ref().checkpoint.log_length,
+        packages.len() as RegistryLen + 2, /* publishes + initial checkpoint + yank*/
+        "expected {len} packages plus the initial checkpoint and yank",
+        len = packages.len()
+    );
+
+    // Delete the client cache to force a complete download of all packages below
+    fs::remove_dir_all(root.join("content"))?;
+    fs::remove_dir_all(root.join("registries"))?;
+
+    let client = create_client(&config)?;
+    client.upsert(packages.iter()).await?;
+
+    // Finally, after a restart, ensure the packages can be downloaded
+    for package in packages {
+        if package.name() == "yankee" {
+            continue;
+        }
+        client
+            .download(&package, &"0.1.0".parse()?)
+            .await?
+            .context("failed to resolve package")?;
+    }
+
+    // Restart the server for the custom content URL test
+    drop(client);
+    drop(server);
+    let (_server, config) = spawn_server(
+        &root,
+        Some("https://example.com".parse().unwrap()),
+        Some(data_store(&root).await?),
+        None,
+    )
+    .await?;
+
+    test_custom_content_url(&config).await?;
+
+    Ok(())
+}
@@ -77,8 +77,16 @@ async fn it_works_with_postgres() -> TestResult {
     let (server, config) = spawn_server(&root, None, Some(data_store()?), None).await?;
 
     test_unknown_signing_key(&config).await?;
+    test_ed25519_signing_key(&config).await?;
+    test_package_listing(&config).await?;
+    test_hash_algorithms(&config).await?;
 
     packages.push(PackageId::new("test:unknown-key")?);
+    packages.push(PackageId::new("test:ed25519")?);
+    packages.push(PackageId::new("test:hash-algorithms")?);
+    for id in ["list:a", "list:b", "listing:c", "other:d"] {
+        packages.push(PackageId::new(id)?);
+    }
 
     let client = api::Client::new(config.default_url.as_ref().unwrap())?;
     let ts_checkpoint = client.latest_checkpoint().await?;
@@ -1,5 +1,6 @@
 use self::support::*;
 use anyhow::{Context, Result};
+use futures::{pin_mut, StreamExt};
 use rand_core::OsRng;
 use reqwest::StatusCode;
 use std::{
@@ -9,15 +10,20 @@ use std::{
 };
 use url::Url;
 use warg_api::v1::{
+    fetch::{FetchError, FetchPackagesRequest, SubscribeEvent, SubscribeRequest},
     package::{ContentSource, PackageRecordState, PublishRecordRequest},
     paths,
 };
 use warg_client::{
     api,
-    storage::{PublishEntry, PublishInfo, RegistryStorage},
+    storage::{ContentStorage, PublishEntry, PublishInfo, RegistryStorage},
     ClientError, Config,
 };
-use warg_crypto::{hash::Sha256, signing::PrivateKey, Encode, Signable};
+use warg_crypto::{
+    hash::{HashAlgorithm, Sha256},
+    signing::{generate_ed25519_pair, PrivateKey},
+    Encode, Signable,
+};
 use warg_protocol::{
     package::{PackageEntry, PackageRecord, PACKAGE_RECORD_VERSION},
     registry::{LogId, PackageId},
@@ -27,6 +33,8 @@ use wit_component::DecodedWasm;
 
 mod support;
 
+#[cfg(feature = "filesystem")]
+mod filesystem;
 mod memory;
 #[cfg(feature = "postgres")]
 mod postgres;
@@ -129,13 +137,13 @@ async fn test_package_yanking(config: &Config) -> Result<()> {
     let record_id = client
         .publish_with_info(
             &signing_key,
-            PublishInfo {
-                id: id.clone(),
-                head: None,
-                entries: vec![PublishEntry::Yank {
+            PublishInfo::new(
+                id.clone(),
+                None,
+                vec![PublishEntry::Yank {
                     version: PACKAGE_VERSION.parse()?,
                 }],
-            },
+            ),
         )
         .await?;
     client
@@ -292,6 +300,36 @@ async fn test_unauthorized_signing_key(config: &Config) -> Result<()> {
     Ok(())
 }
 
+async fn test_ed25519_signing_key(config: &Config) -> Result<()> {
+    const PACKAGE_ID: &str = "test:ed25519";
+    const PACKAGE_VERSION: &str = "0.1.0";
+
+    let id = PackageId::new(PACKAGE_ID)?;
+    let client = create_client(config)?;
+    let (_, signing_key) = generate_ed25519_pair();
+    let digest = publish_component(
+        &client,
+        &id,
+        PACKAGE_VERSION,
+        "(component)",
+        true,
+        &signing_key,
+    )
+    .await?;
+
+    // Assert that the package can be downloaded
+    client.upsert([&id]).await?;
+    let download = client
+        .download(&id, &PACKAGE_VERSION.parse()?)
+        .await?
+        .context("failed to resolve package")?;
+
+    assert_eq!(download.digest, digest);
+    assert_eq!(download.version, PACKAGE_VERSION.parse()?);
+
+    Ok(())
+}
+
 async fn test_unknown_signing_key(config: &Config) -> Result<()> {
     const PACKAGE_ID: &str = "test:unknown-key";
     const PACKAGE_VERSION: &str = "0.1.0";
@@ -347,7 +385,7 @@ async fn test_invalid_signature(config: &Config) -> Result<()> {
             version: PACKAGE_RECORD_VERSION,
             timestamp: SystemTime::now(),
             entries: vec![PackageEntry::Init {
-                hash_algorithm: warg_crypto::hash::HashAlgorithm::Sha256,
+                hash_algorithm: HashAlgorithm::Sha256,
                 key: signing_key.public_key(),
             }],
         },
@@ -448,3 +486,316 @@ async fn test_custom_content_url(config: &Config) -> Result<()> {
 
     Ok(())
 }
+
+async fn test_package_listing(config: &Config) -> Result<()> {
+    let client = create_client(config)?;
+    let signing_key = test_signing_key();
+    let ids = ["list:a", "list:b", "listing:c", "other:d"]
+        .into_iter()
+        .map(PackageId::new)
+        .collect::<Result<Vec<_>>>()?;
+
+    for id in &ids {
+        publish_component(&client, id, "0.1.0", "(component)", true, &signing_key).await?;
+    }
+
+    let client = api::Client::new(config.default_url.as_ref().unwrap())?;
+
+    // Page through the packages in the `list` namespaces
+    let mut since = None;
+    let mut packages = Vec::new();
+    loop {
+        let response = client
+            .fetch_packages(FetchPackagesRequest {
+                namespace: Some(Cow::Borrowed("list")),
+                since: since.map(Cow::Owned),
+                limit: Some(1),
+            })
+            .await?;
+
+        // The last page is not empty as the number of packages is a multiple of the limit
+        assert_eq!(response.packages.len(), 1);
+        packages.extend(response.packages.iter().cloned());
+        since = response.packages.last().cloned();
+        if !response.more {
+            break;
+        }
+    }
+
+    assert_eq!(packages, ids[..3]);
+
+    // An invalid namespace prefix should be rejected
+    match client
+        .fetch_packages(FetchPackagesRequest {
+            namespace: Some(Cow::Borrowed("list%")),
+            ..Default::default()
+        })
+        .await
+    {
+        Err(api::ClientError::Fetch(FetchError::Message { status, .. })) => {
+            assert_eq!(status, StatusCode::BAD_REQUEST.as_u16());
+        }
+        _ => panic!("expected the request to be rejected"),
+    }
+
+    Ok(())
+}
+
+async fn test_historical_checkpoint(config: &Config) -> Result<()> {
+    let client = api::Client::new(config.default_url.as_ref().unwrap())?;
+    let initial = client.latest_checkpoint().await?;
+
+    test_component_publishing(config).await?;
+
+    let latest = client.latest_checkpoint().await?;
+    let log_length = latest.as_ref().checkpoint.log_length;
+    assert!(log_length > initial.as_ref().checkpoint.log_length);
+
+    // Both the initial and the latest checkpoints should be retrievable; an
+    // unchanged checkpoint is periodically signed again, so only compare contents
+    assert_eq!(
+        client
+            .checkpoint_at(initial.as_ref().checkpoint.log_length)
+            .await?
+            .as_ref()
+            .checkpoint,
+        initial.as_ref().checkpoint
+    );
+    assert_eq!(
+        client.checkpoint_at(log_length).await?.as_ref().checkpoint,
+        latest.as_ref().checkpoint
+    );
+
+    match client.checkpoint_at(log_length + 100).await {
+        Err(api::ClientError::Fetch(FetchError::CheckpointNotFound(len))) => {
+            assert_eq!(len, log_length + 100);
+        }
+        _ => panic!("expected the checkpoint to not be found"),
+    }
+
+    Ok(())
+}
+
+async fn test_checkpoint_subscription(config: &Config) -> Result<()> {
+    let client = api::Client::new(config.default_url.as_ref().unwrap())?;
+
+    // Subscribing to a record requires both the log and record identifiers
+    match client
+        .subscribe(SubscribeRequest {
+            log_id: Some(LogId::package_log::<Sha256>(&PackageId::new(
+                "test:component",
+            )?)),
+            record_id: None,
+        })
+        .await
+    {
+        Err(api::ClientError::Fetch(FetchError::Message { status, .. })) => {
+            assert_eq!(status, StatusCode::BAD_REQUEST.as_u16());
+        }
+        _ => panic!("expected a bad request"),
+    }
+
+    let events = client.subscribe(SubscribeRequest::default()).await?;
+    pin_mut!(events);
+
+    // The first event is the latest checkpoint; an unchanged checkpoint is
+    // periodically signed again, so only compare contents
+    let initial = match events.next().await.context("expected an event")?? {
+        SubscribeEvent::Checkpoint(checkpoint) => checkpoint,
+        _ => panic!("expected a checkpoint event"),
+    };
+    assert_eq!(
+        initial.as_ref().checkpoint,
+        client.latest_checkpoint().await?.as_ref().checkpoint
+    );
+
+    test_component_publishing(config).await?;
+
+    // Subsequent checkpoints should be pushed as they are signed
+    let latest = client.latest_checkpoint().await?;
+    loop {
+        match tokio::time::timeout(Duration::from_secs(10), events.next())
+            .await?
+            .context("expected an event")??
+        {
+            SubscribeEvent::Checkpoint(checkpoint) => {
+                let log_length = checkpoint.as_ref().checkpoint.log_length;
+                assert!(log_length > initial.as_ref().checkpoint.log_length);
+                if log_length == latest.as_ref().checkpoint.log_length {
+                    assert_eq!(checkpoint.as_ref().checkpoint, latest.as_ref().checkpoint);
+                    break;
+                }
+            }
+            _ => panic!("expected a checkpoint event"),
+        }
+    }
+
+    Ok(())
+}
+
+async fn test_content_download(config: &Config) -> Result<()> {
+    const PACKAGE_ID: &str = "test:content-download";
+    const PACKAGE_VERSION: &str = "0.1.0";
+
+    let id = PackageId::new(PACKAGE_ID)?;
+    let client = create_client(config)?;
+    let signing_key = test_signing_key();
+    let digest = publish_component(
+        &client,
+        &id,
+        PACKAGE_VERSION,
+        "(component (core module (func (export \"f\"))))",
+        true,
+        &signing_key,
+    )
+    .await?;
+
+    let path = client
+        .content()
+        .content_location(&digest)
+        .context("expected the content to be stored")?;
+    let bytes = fs::read(&path)?;
+    let etag = format!("\"{digest}\"");
+    let url = format!(
+        "{url}/content/{name}",
+        url = config.default_url.as_ref().unwrap(),
+        name = digest.to_string().replace(':', "-")
+    );
+
+    // Assert that the content's existence can be checked without downloading it
+    let http = reqwest::Client::new();
+    let response = http.head(&url).send().await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()["content-length"],
+        bytes.len().to_string()
+    );
+    assert_eq!(response.headers()["accept-ranges"], "bytes");
+    assert_eq!(response.headers()["etag"], etag.as_str());
+
+    // Assert that a range of the content can be downloaded
+    let response = http.get(&url).header("range", "bytes=1-").send().await?;
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(
+        response.headers()["content-range"],
+        format!(
+            "bytes 1-{last}/{len}",
+            last = bytes.len() - 1,
+            len = bytes.len()
+        )
+        .as_str()
+    );
+    assert_eq!(response.bytes().await?, &bytes[1..]);
+
+    let response = http
+        .get(&url)
+        .header("range", format!("bytes={len}-", len = bytes.len()))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+
+    // Assert that a range is ignored if the tag does not match
+    let response = http
+        .get(&url)
+        .header("range", "bytes=1-")
+        .header("if-range", "\"sha256:0000\"")
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.bytes().await?, bytes);
+
+    let response = http
+        .get(&url)
+        .header("if-none-match", etag.as_str())
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+    // Simulate an interrupted download and assert that the client resumes it
+    fs::remove_file(&path)?;
+    let partial = bytes[..4].to_vec();
+    let stream =
+        futures::stream::iter([Ok(partial.into()), Err(anyhow::anyhow!("connection reset"))]);
+    assert!(client
+        .content()
+        .resume_content(Box::pin(stream), &digest, 0)
+        .await
+        .is_err());
+    assert_eq!(client.content().partial_content_len(&digest), 4);
+
+    client.upsert([&id]).await?;
+    let download = client
+        .download(&id, &PACKAGE_VERSION.parse()?)
+        .await?
+        .context("failed to resolve package")?;
+    assert_eq!(download.digest, digest);
+    assert_eq!(fs::read(download.path)?, bytes);
+    assert_eq!(client.content().partial_content_len(&digest), 0);
+
+    Ok(())
+}
+
+async fn test_hash_algorithms(config: &Config) -> Result<()> {
+    const PACKAGE_ID: &str = "test:hash-algorithms";
+    const PACKAGE_VERSION: &str = "0.1.0";
+
+    let id = PackageId::new(PACKAGE_ID)?;
+    let client = create_client(config)?;
+    let signing_key = test_signing_key();
+
+    // Initialize a SHA-512 log and release content with a BLAKE3 digest
+    let bytes = wat::parse_str("(component)")?;
+    let digest = client
+        .content()
+        .store_content(
+            Box::pin(futures::stream::once({
+                let bytes = bytes.clone();
+                async move { Ok(bytes.into()) }
+            })),
+            HashAlgorithm::Blake3,
+            None,
+        )
+        .await?;
+    assert_eq!(digest, HashAlgorithm::Blake3.digest(&bytes));
+
+    let record_id = client
+        .publish_with_info(
+            &signing_key,
+            PublishInfo::new(
+                id.clone(),
+                None,
+                vec![
+                    PublishEntry::Init {
+                        hash_algorithm: HashAlgorithm::Sha512,
+                    },
+                    PublishEntry::Release {
+                        version: PACKAGE_VERSION.parse()?,
+                        content: digest.clone(),
+                    },
+                ],
+            ),
+        )
+        .await?;
+    assert_eq!(record_id.algorithm(), HashAlgorithm::Sha512);
+
+    client
+        .wait_for_publish(&id, &record_id, Duration::from_millis(100))
+        .await?;
+
+    // Remove the local content so that the client downloads and verifies it
+    fs::remove_file(
+        client
+            .content()
+            .content_location(&digest)
+            .context("expected the content to be stored")?,
+    )?;
+
+    client.upsert([&id]).await?;
+    let download = client
+        .download_exact(&id, &PACKAGE_VERSION.parse()?)
+        .await?;
+    assert_eq!(download.digest, digest);
+    assert_eq!(fs::read(download.path)?, bytes);
+
+    Ok(())
+}
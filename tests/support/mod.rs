@@ -156,6 +156,7 @@ pub async fn spawn_server(
         default_url: Some(format!("http://{addr}")),
         registries_dir: Some(root.join("registries")),
         content_dir: Some(root.join("content")),
+        ..Default::default()
     };
 
     Ok((instance, config))
@@ -173,13 +174,16 @@ pub async fn publish(
         .content()
         .store_content(
             Box::pin(futures::stream::once(async move { Ok(content.into()) })),
+            Default::default(),
             None,
         )
         .await?;
 
     let mut entries = Vec::with_capacity(2);
     if init {
-        entries.push(PublishEntry::Init);
+        entries.push(PublishEntry::Init {
+            hash_algorithm: Default::default(),
+        });
     }
     entries.push(PublishEntry::Release {
         version: version.parse().unwrap(),
@@ -187,14 +191,7 @@ pub async fn publish(
     });
 
     let record_id = client
-        .publish_with_info(
-            signing_key,
-            PublishInfo {
-                id: id.clone(),
-                head: None,
-                entries,
-            },
-        )
+        .publish_with_info(signing_key, PublishInfo::new(id.clone(), None, entries))
         .await?;
 
     client